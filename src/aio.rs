@@ -0,0 +1,177 @@
+//! Async SceneServer client (feature `async`).
+//!
+//! Mirrors [`crate::service::Service`] and [`crate::SceneLayer`] for REST
+//! backends without blocking the caller thread, so web servers and other
+//! async applications can fetch node pages and resources concurrently.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+use crate::defn::{NodePageDefinition, SceneDefinition};
+use crate::err::{I3SError, Result};
+use crate::node::{Node, NodePage};
+
+/// An async connection to a SceneServer REST endpoint.
+pub struct AsyncService {
+    base_url: String,
+    layer_id: u32,
+    client: reqwest::Client,
+    cache: DashMap<String, Arc<Vec<u8>>>,
+}
+
+impl AsyncService {
+    /// Connect to a SceneServer URL (e.g. `https://.../SceneServer`).
+    /// A URL ending in `/layers/{id}` selects that layer; a bare
+    /// SceneServer URL addresses layer 0.
+    ///
+    /// Unlike the blocking client, no probe request is issued; failures
+    /// surface on the first `get`.
+    pub fn connect(url: &str) -> Result<Self> {
+        let client = reqwest::Client::builder().build()?;
+        let (base_url, layer_id) = crate::service::split_layer_url(url);
+        Ok(Self {
+            base_url,
+            layer_id,
+            client,
+            cache: DashMap::new(),
+        })
+    }
+
+    /// The SceneServer base URL, without any layer path.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The id of the layer this connection addresses.
+    pub fn layer_id(&self) -> u32 {
+        self.layer_id
+    }
+
+    /// Fetch raw resource bytes by URL, consulting the in-memory cache.
+    pub async fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        if let Some(hit) = self.cache.get(uri) {
+            return Ok(Arc::clone(hit.value()));
+        }
+        let response = self.client.get(uri).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(I3SError::Http {
+                status: status.as_u16(),
+                uri: uri.to_string(),
+            });
+        }
+        let bytes = response.bytes().await?.to_vec();
+        let bytes = Arc::new(bytes);
+        self.cache.insert(uri.to_string(), Arc::clone(&bytes));
+        Ok(bytes)
+    }
+
+    /// Fetch and parse a node page by page index.
+    pub async fn get_node_page(&self, page_index: usize) -> Result<NodePage> {
+        let uri = format!(
+            "{}/layers/{}/nodepages/{page_index}",
+            self.base_url, self.layer_id
+        );
+        let bytes = self.get(&uri).await?;
+        serde_json::from_slice(&bytes).map_err(|e| I3SError::json(&uri, e))
+    }
+}
+
+/// An open hosted scene layer with async resource access.
+pub struct AsyncSceneLayer {
+    service: AsyncService,
+    defn: SceneDefinition,
+    pages: DashMap<usize, Arc<NodePage>>,
+}
+
+impl AsyncSceneLayer {
+    /// Connect to a SceneServer URL and fetch the layer document.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let service = AsyncService::connect(url)?;
+        let uri = format!("{}/layers/{}", service.base_url(), service.layer_id());
+        let bytes = service.get(&uri).await?;
+        let defn = SceneDefinition::from_slice(&bytes)?;
+        Ok(Self {
+            service,
+            defn,
+            pages: DashMap::new(),
+        })
+    }
+
+    /// The parsed `3dSceneLayer` document.
+    pub fn definition(&self) -> &SceneDefinition {
+        &self.defn
+    }
+
+    /// The underlying async service client.
+    pub fn service(&self) -> &AsyncService {
+        &self.service
+    }
+
+    fn node_pages(&self) -> Result<&NodePageDefinition> {
+        self.defn
+            .node_pages
+            .as_ref()
+            .ok_or_else(|| I3SError::MissingResource("nodePages definition".to_string()))
+    }
+
+    /// Fetch (and cache) the node page with the given page index.
+    pub async fn get_node_page(&self, page_index: usize) -> Result<Arc<NodePage>> {
+        if let Some(page) = self.pages.get(&page_index) {
+            return Ok(Arc::clone(page.value()));
+        }
+        let page = Arc::new(self.service.get_node_page(page_index).await?);
+        self.pages.insert(page_index, Arc::clone(&page));
+        Ok(page)
+    }
+
+    /// Fetch the node with the given index.
+    pub async fn get_node(&self, node_index: usize) -> Result<Arc<Node>> {
+        let pages = self.node_pages()?;
+        let page_index = pages.page_index(node_index)?;
+        let in_page = pages.index_in_page(node_index)?;
+        let page = self.get_node_page(page_index).await?;
+        page.nodes.get(in_page).map(Arc::clone).ok_or_else(|| {
+            I3SError::MissingResource(format!("node {node_index} in page {page_index}"))
+        })
+    }
+
+    /// Fetch the root node.
+    pub async fn root(&self) -> Result<Arc<Node>> {
+        let root_index = self.node_pages()?.root_index.unwrap_or(0);
+        self.get_node(root_index).await
+    }
+
+    /// Depth-first walk of the tree from the root. The callback returns
+    /// whether traversal should continue.
+    ///
+    /// Child node pages are fetched once per page rather than per node; pages
+    /// already in the cache are not refetched.
+    pub async fn traverse<F>(&self, mut callback: F) -> Result<()>
+    where
+        F: FnMut(&Arc<Node>) -> bool,
+    {
+        let pages_defn = self.node_pages()?.clone();
+        let mut stack = vec![self.root().await?];
+        while let Some(node) = stack.pop() {
+            if !callback(&node) {
+                return Ok(());
+            }
+            // Warm the distinct pages the children live in before resolving
+            // each child, so a page is requested at most once.
+            let mut seen = HashSet::new();
+            for &child in &node.children {
+                let page = pages_defn.page_index(child)?;
+                if seen.insert(page) {
+                    self.get_node_page(page).await?;
+                }
+            }
+            for &child in node.children.iter().rev() {
+                stack.push(self.get_node(child).await?);
+            }
+        }
+        Ok(())
+    }
+}
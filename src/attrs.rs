@@ -0,0 +1,264 @@
+//! Reading per-node attribute resources.
+//!
+//! Attribute resources store one column per node in the binary layout
+//! declared by `attributeStorageInfo`: a header (at least the value
+//! count), then the blocks named by `ordering`. [`read_attribute`]
+//! decodes a column into a typed [`AttributeColumn`]; columns the source
+//! keeps sorted — OBJECTID above all — support binary-searched lookup,
+//! so resolving a feature id to its row in a node costs O(log n)
+//! comparisons instead of a scan even on layers with millions of
+//! features. The found row indexes every other column of the same node.
+
+use crate::defn::AttributeStorageInfo;
+use crate::err::{I3SError, Result};
+
+/// One decoded attribute column of a node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeColumn {
+    Int32(Vec<i32>),
+    Float64(Vec<f64>),
+    /// UTF-8 strings; null terminators from the storage format are
+    /// stripped.
+    Strings(Vec<String>),
+}
+
+impl AttributeColumn {
+    /// Number of values (one per feature of the node).
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Int32(values) => values.len(),
+            Self::Float64(values) => values.len(),
+            Self::Strings(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the column is in ascending order, the precondition of the
+    /// `find_sorted_*` lookups. Check once per column, not per lookup.
+    pub fn is_sorted(&self) -> bool {
+        match self {
+            Self::Int32(values) => values.is_sorted(),
+            Self::Float64(values) => values.is_sorted_by(|a, b| a <= b),
+            Self::Strings(values) => values.is_sorted(),
+        }
+    }
+
+    /// Binary-search an ascending integer column for `value`.
+    ///
+    /// Returns the row index, or `None` if the value is absent or the
+    /// column holds another type. Unsorted columns give arbitrary
+    /// results — guard with [`is_sorted`](Self::is_sorted).
+    pub fn find_sorted_int(&self, value: i32) -> Option<usize> {
+        match self {
+            Self::Int32(values) => values.binary_search(&value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Binary-search an ascending float column for `value`.
+    pub fn find_sorted_float(&self, value: f64) -> Option<usize> {
+        match self {
+            Self::Float64(values) => values
+                .binary_search_by(|v| v.partial_cmp(&value).unwrap_or(std::cmp::Ordering::Less))
+                .ok(),
+            _ => None,
+        }
+    }
+
+    /// Binary-search an ascending string column for `value`.
+    pub fn find_sorted_str(&self, value: &str) -> Option<usize> {
+        match self {
+            Self::Strings(values) => values.binary_search_by(|v| v.as_str().cmp(value)).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Decode one attribute resource into a typed column.
+///
+/// The layout is driven by `info`: the header fields are consumed in
+/// declaration order, then the blocks listed in `ordering`. Value types
+/// match what [`attributeStorageInfo`](AttributeStorageInfo) can declare
+/// for mesh layers: `Int32`/`Oid32`, `Float64` (8-byte aligned after the
+/// header) and `String` (byte counts, then null-terminated UTF-8).
+pub fn read_attribute(bytes: &[u8], info: &AttributeStorageInfo) -> Result<AttributeColumn> {
+    let mut cursor = Cursor { bytes, offset: 0 };
+    let mut count = None;
+    for header in &info.header {
+        let value = cursor.read_u32(&info.key)?;
+        if header.property == "count" {
+            count = Some(value as usize);
+        }
+    }
+    let count = count.ok_or_else(|| {
+        I3SError::Validation(format!(
+            "attributeStorageInfo {} declares no count header",
+            info.key
+        ))
+    })?;
+    let values = info.attribute_values.as_ref().ok_or_else(|| {
+        I3SError::Validation(format!(
+            "attributeStorageInfo {} declares no attributeValues",
+            info.key
+        ))
+    })?;
+    match values.value_type.as_str() {
+        "Int32" | "Oid32" => {
+            let mut out = Vec::with_capacity(count.min(1 << 20));
+            for _ in 0..count {
+                out.push(cursor.read_u32(&info.key)? as i32);
+            }
+            Ok(AttributeColumn::Int32(out))
+        }
+        "Float64" => {
+            cursor.align8();
+            let mut out = Vec::with_capacity(count.min(1 << 20));
+            for _ in 0..count {
+                out.push(f64::from_le_bytes(cursor.read_array(&info.key)?));
+            }
+            Ok(AttributeColumn::Float64(out))
+        }
+        "String" => {
+            let mut sizes = Vec::with_capacity(count.min(1 << 20));
+            for _ in 0..count {
+                sizes.push(cursor.read_u32(&info.key)? as usize);
+            }
+            let mut out = Vec::with_capacity(sizes.len());
+            for size in sizes {
+                let raw = cursor.read_slice(size, &info.key)?;
+                let text = std::str::from_utf8(raw.strip_suffix(&[0]).unwrap_or(raw))
+                    .map_err(|_| {
+                        I3SError::Decode(format!("attribute {} holds invalid UTF-8", info.key))
+                    })?;
+                out.push(text.to_string());
+            }
+            Ok(AttributeColumn::Strings(out))
+        }
+        other => Err(I3SError::Decode(format!(
+            "unsupported attribute value type {other} for {}",
+            info.key
+        ))),
+    }
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Cursor<'_> {
+    fn read_slice(&mut self, len: usize, key: &str) -> Result<&[u8]> {
+        let end = self.offset.checked_add(len).filter(|&e| e <= self.bytes.len());
+        let end = end.ok_or_else(|| {
+            I3SError::Decode(format!(
+                "attribute {key} truncated at byte {}",
+                self.offset
+            ))
+        })?;
+        let out = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(out)
+    }
+
+    fn read_array<const N: usize>(&mut self, key: &str) -> Result<[u8; N]> {
+        Ok(self
+            .read_slice(N, key)?
+            .try_into()
+            .expect("slice length checked"))
+    }
+
+    fn read_u32(&mut self, key: &str) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_array(key)?))
+    }
+
+    /// Skip padding so the next read starts on an 8-byte boundary.
+    fn align8(&mut self) {
+        self.offset = self.offset.next_multiple_of(8).min(self.bytes.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defn::{AttributeHeader, AttributeValues};
+
+    fn info(value_type: &str, header: &[&str]) -> AttributeStorageInfo {
+        AttributeStorageInfo {
+            key: "f_0".to_string(),
+            name: "OBJECTID".to_string(),
+            header: header
+                .iter()
+                .map(|property| AttributeHeader {
+                    property: property.to_string(),
+                    value_type: "UInt32".to_string(),
+                })
+                .collect(),
+            ordering: vec!["attributeValues".to_string()],
+            attribute_values: Some(AttributeValues {
+                value_type: value_type.to_string(),
+                values_per_element: Some(1),
+                encoding: None,
+            }),
+            attribute_byte_counts: None,
+            object_ids: None,
+        }
+    }
+
+    #[test]
+    fn sorted_columns_support_binary_search() {
+        let mut bytes = 4u32.to_le_bytes().to_vec();
+        for id in [3i32, 8, 21, 40] {
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        let column = read_attribute(&bytes, &info("Oid32", &["count"])).unwrap();
+        assert_eq!(column.len(), 4);
+        assert!(column.is_sorted());
+        assert_eq!(column.find_sorted_int(21), Some(2));
+        assert_eq!(column.find_sorted_int(22), None);
+        assert_eq!(column.find_sorted_float(21.0), None);
+
+        let mut bytes = 3u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[0u8; 4]);
+        for value in [1.5f64, 2.5, 2.0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let column = read_attribute(&bytes, &info("Float64", &["count"])).unwrap();
+        assert_eq!(
+            column,
+            AttributeColumn::Float64(vec![1.5, 2.5, 2.0])
+        );
+        assert!(!column.is_sorted());
+    }
+
+    #[test]
+    fn string_columns_decode_counts_and_terminators() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&8u32.to_le_bytes()); // attributeValuesByteCount
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        bytes.extend_from_slice(b"ash\0oak\0");
+        let column = read_attribute(
+            &bytes,
+            &info("String", &["count", "attributeValuesByteCount"]),
+        )
+        .unwrap();
+        assert_eq!(
+            column,
+            AttributeColumn::Strings(vec!["ash".to_string(), "oak".to_string()])
+        );
+        assert!(column.is_sorted());
+        assert_eq!(column.find_sorted_str("oak"), Some(1));
+        assert_eq!(column.find_sorted_str("elm"), None);
+    }
+
+    #[test]
+    fn truncated_resources_are_rejected() {
+        let bytes = 9u32.to_le_bytes().to_vec();
+        let err = read_attribute(&bytes, &info("Int32", &["count"])).unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{err}");
+    }
+}
@@ -0,0 +1,232 @@
+//! Command-line inspection and export for I3S scene layers.
+//!
+//! Works against both `.slpk` paths and SceneServer URLs:
+//!
+//! ```text
+//! i3s-cli info <uri>
+//! i3s-cli validate <uri>
+//! i3s-cli tree <uri> [max-depth]
+//! i3s-cli extract-node <uri> <node-index> <out-dir>
+//! i3s-cli export <uri> <glb|tiles|obj> <out-path>
+//! ```
+
+use std::process::ExitCode;
+
+use i3s::export::gltf::{export_layer_glb, GltfExportOptions};
+use i3s::export::obj::{export_node_obj, export_subtree_obj, ObjExportOptions};
+use i3s::export::tiles3d::{export_tileset, TilesetExportOptions};
+use i3s::validate::{SceneLayerValidator, Severity};
+use i3s::SceneLayer;
+
+const USAGE: &str = "usage: i3s-cli [--config <file>] <info|validate|tree|extract-node|export> <uri> [args...]
+  info         <uri>
+  validate     <uri>
+  tree         <uri> [max-depth]
+  extract-node <uri> <node-index> <out-dir>
+  export       <uri> <glb|tiles|obj> <out-path>
+options:
+  --config <file>   settings profile (.toml or .json) applied to the
+                    connection, caches and export defaults";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("i3s-cli: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Pull `--config <file>` out of the argument list, returning the loaded
+/// profile (or the defaults) and the remaining positional arguments.
+fn take_config(args: &[String]) -> i3s::err::Result<(i3s::config::Config, Vec<String>)> {
+    let mut config = i3s::config::Config::default();
+    let mut rest = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--config" {
+            let path = iter.next().ok_or_else(|| {
+                i3s::I3SError::Validation("--config needs a file path".to_string())
+            })?;
+            config = i3s::config::Config::load(path)?;
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    Ok((config, rest))
+}
+
+fn run(args: &[String]) -> i3s::err::Result<ExitCode> {
+    let (config, args) = take_config(args)?;
+    let (command, rest) = match args.split_first() {
+        Some(split) => split,
+        None => {
+            eprintln!("{USAGE}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    let uri = match rest.first() {
+        Some(uri) => uri,
+        None => {
+            eprintln!("{USAGE}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    let layer = SceneLayer::from_uri_with(uri, &config)?;
+
+    match (command.as_str(), &rest[1..]) {
+        ("info", _) => info(&layer),
+        ("validate", _) => return validate(&layer),
+        ("tree", rest) => tree(&layer, rest.first().and_then(|d| d.parse().ok()))?,
+        ("extract-node", [index, out_dir]) => {
+            let index: usize = index
+                .parse()
+                .map_err(|_| i3s::I3SError::Validation(format!("bad node index: {index}")))?;
+            let nodes = layer.nodes()?;
+            let node = nodes.get(index)?;
+            let report = export_node_obj(&layer, &node, out_dir, &format!("node-{index}"))?;
+            println!(
+                "wrote node-{index}.obj ({} vertices, {} extra files)",
+                report.vertices,
+                report.files.len()
+            );
+        }
+        ("export", [format, out]) => export(&layer, format, out, &config)?,
+        _ => {
+            eprintln!("{USAGE}");
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn info(layer: &SceneLayer) {
+    let defn = layer.definition();
+    println!("name:        {}", layer.name().unwrap_or("<unnamed>"));
+    println!("layer type:  {:?}", layer.layer_type());
+    println!("profile:     {:?}", layer.profile());
+    if let Some(version) = &defn.store.version {
+        println!("version:     {version}");
+    }
+    if let Some(extent) = layer.extent() {
+        println!(
+            "extent:      [{}, {}] .. [{}, {}]",
+            extent.xmin, extent.ymin, extent.xmax, extent.ymax
+        );
+    }
+    println!("geometry definitions: {}", defn.geometry_definitions.len());
+    println!(
+        "texture sets:         {}",
+        defn.texture_set_definitions.len()
+    );
+    println!("material definitions: {}", defn.material_definitions.len());
+    println!("fields:               {}", defn.fields.len());
+}
+
+fn validate(layer: &SceneLayer) -> i3s::err::Result<ExitCode> {
+    let report = SceneLayerValidator {
+        check_buffer_sizes: true,
+    }
+    .validate(layer)?;
+    for finding in &report.findings {
+        eprintln!(
+            "{}: [{}] {}: {}",
+            match finding.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            },
+            finding.code,
+            finding.location,
+            finding.message
+        );
+    }
+    if report.is_conformant() {
+        println!("{} nodes ok", report.nodes_checked);
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!(
+            "{} nodes checked, {} finding(s)",
+            report.nodes_checked,
+            report.findings.len()
+        );
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn tree(layer: &SceneLayer, max_depth: Option<usize>) -> i3s::err::Result<()> {
+    let nodes = layer.nodes()?;
+    let root = nodes.root()?;
+    let mut stack = vec![(root, 0usize)];
+    while let Some((node, depth)) = stack.pop() {
+        let vertices = node
+            .mesh
+            .as_ref()
+            .and_then(|m| m.geometry.as_ref())
+            .map(|g| g.vertex_count)
+            .unwrap_or(0);
+        println!(
+            "{}{} {}[{} vertices]",
+            "  ".repeat(depth),
+            node.index,
+            if node.is_leaf() { "(leaf) " } else { "" },
+            vertices
+        );
+        if max_depth.is_none_or(|max| depth < max) {
+            for &child in node.children.iter().rev() {
+                stack.push((nodes.get(child)?, depth + 1));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn export(
+    layer: &SceneLayer,
+    format: &str,
+    out: &str,
+    config: &i3s::config::Config,
+) -> i3s::err::Result<()> {
+    match format {
+        "glb" => {
+            let options = GltfExportOptions {
+                export: config.export,
+                ..GltfExportOptions::default()
+            };
+            let report = export_layer_glb(layer, out, &options)?;
+            println!(
+                "wrote {out}: {} nodes, {} vertices, {} bytes",
+                report.nodes_exported, report.vertices, report.bytes_written
+            );
+        }
+        "tiles" => {
+            let options = TilesetExportOptions {
+                export: config.export,
+            };
+            let report = export_tileset(layer, out, &options)?;
+            println!(
+                "wrote {out}: {}/{} tiles with content, {} bytes",
+                report.tiles_with_content, report.tiles_total, report.bytes_written
+            );
+        }
+        "obj" => {
+            let root = layer.root()?;
+            let options = ObjExportOptions {
+                export: config.export,
+                texture_preference: config.textures,
+            };
+            let report = export_subtree_obj(layer, &root, out, "layer", &options)?;
+            println!(
+                "wrote {out}/layer.obj: {} nodes, {} vertices",
+                report.nodes_exported, report.vertices
+            );
+        }
+        other => {
+            return Err(i3s::I3SError::Validation(format!(
+                "unknown export format: {other} (expected glb, tiles, or obj)"
+            )))
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+//! Download budgets for metered sessions.
+//!
+//! An accidental full-layer traversal against a hosted service can pull
+//! gigabytes through a metered connection or a shared API key. A
+//! [`DownloadBudget`] caps a session by request count and/or delivered
+//! bytes; once a cap is hit every further fetch fails with
+//! [`I3SError::BudgetExhausted`] until the budget is topped up or reset.
+//!
+//! Attach one to a layer with
+//! [`SceneLayer::with_download_budget`](crate::SceneLayer::with_download_budget);
+//! all node page, geometry, texture and attribute fetches of that layer
+//! then draw from it. Counting happens at the resource-manager boundary,
+//! so repeated fetches served from the in-memory cache still count as
+//! requests (their bytes were already paid for once).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::err::{I3SError, Result};
+
+/// A byte/request quota shared by everything that holds it.
+#[derive(Debug, Default)]
+pub struct DownloadBudget {
+    max_bytes: Option<u64>,
+    max_requests: Option<u64>,
+    used_bytes: AtomicU64,
+    used_requests: AtomicU64,
+}
+
+impl DownloadBudget {
+    /// An unlimited budget; combine with the `max_*` constructors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cap the total delivered bytes.
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Cap the number of resource requests.
+    pub fn max_requests(mut self, requests: u64) -> Self {
+        self.max_requests = Some(requests);
+        self
+    }
+
+    /// Bytes drawn so far.
+    pub fn used_bytes(&self) -> u64 {
+        self.used_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Requests drawn so far.
+    pub fn used_requests(&self) -> u64 {
+        self.used_requests.load(Ordering::Relaxed)
+    }
+
+    /// Whether a cap has been reached.
+    pub fn is_exhausted(&self) -> bool {
+        self.max_requests
+            .is_some_and(|max| self.used_requests() >= max)
+            || self.max_bytes.is_some_and(|max| self.used_bytes() >= max)
+    }
+
+    /// Zero the counters, e.g. at the start of a new metering period.
+    pub fn reset(&self) {
+        self.used_bytes.store(0, Ordering::Relaxed);
+        self.used_requests.store(0, Ordering::Relaxed);
+    }
+
+    fn exhausted_error(&self) -> I3SError {
+        I3SError::BudgetExhausted {
+            used_bytes: self.used_bytes(),
+            used_requests: self.used_requests(),
+        }
+    }
+
+    /// Draw one request from the budget, before the fetch happens.
+    pub(crate) fn admit_request(&self) -> Result<()> {
+        if self.is_exhausted() {
+            return Err(self.exhausted_error());
+        }
+        self.used_requests.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Charge delivered bytes after a fetch.
+    ///
+    /// The fetch that crosses the byte cap still returns its data — sizes
+    /// are not known up front — and every fetch after it fails.
+    pub(crate) fn charge_bytes(&self, bytes: u64) {
+        self.used_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_cap_blocks_further_fetches() {
+        let budget = DownloadBudget::new().max_requests(2);
+        budget.admit_request().unwrap();
+        budget.admit_request().unwrap();
+        assert!(matches!(
+            budget.admit_request(),
+            Err(I3SError::BudgetExhausted {
+                used_requests: 2,
+                ..
+            })
+        ));
+        budget.reset();
+        budget.admit_request().unwrap();
+    }
+
+    #[test]
+    fn byte_cap_takes_effect_on_the_next_fetch() {
+        let budget = DownloadBudget::new().max_bytes(100);
+        budget.admit_request().unwrap();
+        budget.charge_bytes(250);
+        assert!(budget.is_exhausted());
+        assert!(budget.admit_request().is_err());
+        assert_eq!(budget.used_bytes(), 250);
+    }
+}
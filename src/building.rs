@@ -0,0 +1,161 @@
+//! Building scene layers: the layer document, sublayer enumeration, and
+//! opening sublayers as regular [`SceneLayer`]s.
+//!
+//! A building scene layer is a container: its `3dSceneLayer` document has no
+//! store of its own but declares a tree of sublayers (discipline/category
+//! groups plus the `Overview` and `FullModel` model groups). Each leaf
+//! sublayer is a complete 3D Object layer whose resources live under
+//! `sublayers/{id}/` in an SLPK or `layers/{id}` on a SceneServer.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::defn::{Extent, SpatialReference};
+use crate::err::{I3SError, Result};
+use crate::layer::SceneLayer;
+use crate::rm::{resource_manager_factory, Accessor, I3SFormat, ResourceManager, UriBuilder};
+
+/// One sublayer declaration, possibly a group with nested sublayers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sublayer {
+    pub id: u32,
+    pub name: String,
+    /// `"group"` for containers, otherwise the scene layer type of the leaf.
+    pub layer_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// `"Overview"` or `"FullModel"` on the two model groups.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_empty: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sublayers: Vec<Sublayer>,
+}
+
+impl Sublayer {
+    /// Whether this entry is a group rather than an openable layer.
+    pub fn is_group(&self) -> bool {
+        self.layer_type == "group"
+    }
+}
+
+/// The `3dSceneLayer` document of a building scene layer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildingSceneDefinition {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub layer_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_extent: Option<Extent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spatial_reference: Option<SpatialReference>,
+    #[serde(rename = "statisticsHRef", skip_serializing_if = "Option::is_none")]
+    pub statistics_href: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sublayers: Vec<Sublayer>,
+}
+
+/// An open building scene layer.
+pub struct BuildingSceneLayer {
+    rm: Arc<ResourceManager>,
+    defn: BuildingSceneDefinition,
+}
+
+impl BuildingSceneLayer {
+    /// Open a building scene layer from a `.slpk` path or SceneServer URL.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        let format = I3SFormat::from_uri(uri)?;
+        let rm = Arc::new(resource_manager_factory(format, uri)?);
+        let bytes = rm.get(&rm.scene_definition_uri())?;
+        let defn: BuildingSceneDefinition = serde_json::from_slice(&bytes)
+            .map_err(|e| I3SError::json(rm.scene_definition_uri(), e))?;
+        Ok(Self { rm, defn })
+    }
+
+    /// The parsed building scene layer document.
+    pub fn definition(&self) -> &BuildingSceneDefinition {
+        &self.defn
+    }
+
+    /// The top-level sublayer declarations (groups preserved).
+    pub fn sublayers(&self) -> &[Sublayer] {
+        &self.defn.sublayers
+    }
+
+    /// All openable (non-group) sublayers, groups flattened in order.
+    pub fn leaf_sublayers(&self) -> Vec<&Sublayer> {
+        fn walk<'a>(sublayers: &'a [Sublayer], out: &mut Vec<&'a Sublayer>) {
+            for s in sublayers {
+                if s.is_group() {
+                    walk(&s.sublayers, out);
+                } else {
+                    out.push(s);
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.defn.sublayers, &mut out);
+        out
+    }
+
+    /// The `Overview` sublayer, if the model declares one.
+    pub fn overview(&self) -> Option<&Sublayer> {
+        self.defn
+            .sublayers
+            .iter()
+            .find(|s| s.model_name.as_deref() == Some("Overview"))
+    }
+
+    /// The `FullModel` group, if the model declares one.
+    pub fn full_model(&self) -> Option<&Sublayer> {
+        self.defn
+            .sublayers
+            .iter()
+            .find(|s| s.model_name.as_deref() == Some("FullModel"))
+    }
+
+    /// Open a sublayer as its own [`SceneLayer`].
+    pub fn open_sublayer(&self, id: u32) -> Result<SceneLayer> {
+        let rm = Arc::new(ResourceManager::sublayer(Arc::clone(&self.rm), id));
+        SceneLayer::from_resource_manager(rm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sublayer_tree() {
+        let doc = serde_json::json!({
+            "id": 0,
+            "layerType": "Building",
+            "sublayers": [
+                { "id": 1, "name": "Overview", "layerType": "3DObject", "modelName": "Overview" },
+                { "id": 2, "name": "Full Model", "layerType": "group", "modelName": "FullModel",
+                  "sublayers": [
+                      { "id": 3, "name": "Architectural", "layerType": "group", "sublayers": [
+                          { "id": 4, "name": "Walls", "layerType": "3DObject" }
+                      ]}
+                  ]}
+            ]
+        });
+        let defn: BuildingSceneDefinition = serde_json::from_value(doc).unwrap();
+        assert_eq!(defn.sublayers.len(), 2);
+        assert!(defn.sublayers[1].is_group());
+        assert_eq!(defn.sublayers[1].sublayers[0].sublayers[0].id, 4);
+    }
+}
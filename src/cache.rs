@@ -0,0 +1,504 @@
+//! A second-level cache of decoded resources on disk.
+//!
+//! The raw resource caches in the backends avoid re-downloading bytes
+//! within one session; this cache sits beneath them and persists *decoded*
+//! geometry (and encoded textures) across sessions, so a repeat visit to
+//! the same layer skips gzip/Draco decode entirely. Entries are keyed by a
+//! layer key, node index, and the layer's store version, so republishing a
+//! layer invalidates its entries naturally.
+//!
+//! The geometry format is a plain little-endian dump of
+//! [`DecodedGeometry`]'s arrays with length prefixes — fast to write, fast
+//! to map back, and versioned by a format number so layout changes never
+//! misread stale files.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::decode::DecodedGeometry;
+use crate::err::{I3SError, Result};
+use crate::layer::SceneLayer;
+
+/// Limits for a layer's in-memory caches.
+///
+/// The defaults are unbounded, matching the previous behavior; set limits
+/// with [`SceneLayer::with_cache_config`](crate::SceneLayer::with_cache_config)
+/// so long traversals of huge layers evict least-recently-used entries
+/// instead of exhausting memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Parsed node pages kept in memory.
+    pub max_node_pages: Option<usize>,
+    /// Raw resource bytes (geometry, texture, attribute payloads) kept in
+    /// memory by the SLPK or service backend.
+    pub max_resource_bytes: Option<u64>,
+}
+
+/// A concurrent map with an optional total-weight budget and
+/// least-recently-used eviction.
+///
+/// Entries carry a weight chosen by the caller — bytes for resource
+/// payloads, 1 for counted entries like node pages. Inserting past the
+/// budget evicts the least recently used entries first; the entry being
+/// inserted is never evicted, so a single oversized payload still caches.
+pub struct BoundedCache<K, V> {
+    state: Mutex<BoundedState<K, V>>,
+}
+
+struct BoundedState<K, V> {
+    entries: HashMap<K, CacheEntry<V>>,
+    /// Recency index: tick of last use to key, oldest first.
+    order: BTreeMap<u64, K>,
+    total_weight: u64,
+    budget: Option<u64>,
+    tick: u64,
+}
+
+struct CacheEntry<V> {
+    value: Arc<V>,
+    weight: u64,
+    tick: u64,
+}
+
+impl<K: Eq + Hash + Clone, V> BoundedCache<K, V> {
+    /// An unbounded cache (plain memoization).
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(BoundedState {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                total_weight: 0,
+                budget: None,
+                tick: 0,
+            }),
+        }
+    }
+
+    /// Set or clear the weight budget, evicting down to it immediately.
+    pub fn set_budget(&self, budget: Option<u64>) {
+        let mut state = self.state.lock().expect("cache lock");
+        state.budget = budget;
+        state.evict_to_budget();
+    }
+
+    /// Fetch an entry and mark it most recently used.
+    pub fn get(&self, key: &K) -> Option<Arc<V>> {
+        let mut state = self.state.lock().expect("cache lock");
+        let state = &mut *state;
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state.entries.get_mut(key)?;
+        let value = Arc::clone(&entry.value);
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        state.order.remove(&old_tick);
+        state.order.insert(tick, key.clone());
+        Some(value)
+    }
+
+    /// Insert an entry (or return the existing one), evicting older
+    /// entries if the budget is now exceeded.
+    pub fn get_or_insert_with(
+        &self,
+        key: K,
+        weight: u64,
+        value: impl FnOnce() -> Arc<V>,
+    ) -> Arc<V> {
+        let mut state = self.state.lock().expect("cache lock");
+        let state = &mut *state;
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some(entry) = state.entries.get_mut(&key) {
+            let value = Arc::clone(&entry.value);
+            let old_tick = std::mem::replace(&mut entry.tick, tick);
+            state.order.remove(&old_tick);
+            state.order.insert(tick, key);
+            return value;
+        }
+        let value = value();
+        state.entries.insert(
+            key.clone(),
+            CacheEntry {
+                value: Arc::clone(&value),
+                weight,
+                tick,
+            },
+        );
+        state.order.insert(tick, key);
+        state.total_weight += weight;
+        state.evict_to_budget();
+        value
+    }
+
+    /// Insert an entry, replacing any existing one under the key.
+    pub fn insert(&self, key: K, weight: u64, value: Arc<V>) {
+        let mut state = self.state.lock().expect("cache lock");
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some(old) = state.entries.remove(&key) {
+            state.order.remove(&old.tick);
+            state.total_weight -= old.weight;
+        }
+        state.entries.insert(
+            key.clone(),
+            CacheEntry {
+                value,
+                weight,
+                tick,
+            },
+        );
+        state.order.insert(tick, key);
+        state.total_weight += weight;
+        state.evict_to_budget();
+    }
+
+    /// Whether the key is cached, without touching recency.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.state
+            .lock()
+            .expect("cache lock")
+            .entries
+            .contains_key(key)
+    }
+
+    /// Number of cached entries.
+    pub fn len(&self) -> usize {
+        self.state.lock().expect("cache lock").entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Combined weight of the cached entries.
+    pub fn total_weight(&self) -> u64 {
+        self.state.lock().expect("cache lock").total_weight
+    }
+
+    /// Drop every entry.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().expect("cache lock");
+        state.entries.clear();
+        state.order.clear();
+        state.total_weight = 0;
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> Default for BoundedCache<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> BoundedState<K, V> {
+    fn evict_to_budget(&mut self) {
+        let Some(budget) = self.budget else {
+            return;
+        };
+        // The newest entry (highest tick) is never evicted.
+        while self.total_weight > budget && self.entries.len() > 1 {
+            let Some((&tick, _)) = self.order.iter().next() else {
+                break;
+            };
+            let key = self.order.remove(&tick).expect("first entry exists");
+            if let Some(entry) = self.entries.remove(&key) {
+                self.total_weight -= entry.weight;
+            }
+        }
+    }
+}
+
+const GEOMETRY_MAGIC: &[u8; 4] = b"I3SD";
+const GEOMETRY_FORMAT_VERSION: u32 = 1;
+
+/// Prefix of a per-node texture entry that references a shared blob.
+const TEXTURE_REF_MAGIC: &[u8; 4] = b"I3ST";
+
+/// A content hash for deduplicating resource payloads (FNV-1a, 64-bit), as
+/// a fixed-width, filesystem-safe hex string.
+pub fn content_hash(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// A local directory holding decoded geometry and texture payloads.
+pub struct DecodedCache {
+    dir: PathBuf,
+}
+
+impl DecodedCache {
+    /// Use (and create) `dir` as the cache root.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// A stable cache key for a layer: its id, name, and store version.
+    pub fn layer_key(layer: &SceneLayer) -> String {
+        let defn = layer.definition();
+        sanitize(&format!(
+            "{}-{}-{}",
+            defn.id,
+            defn.name.as_deref().unwrap_or("layer"),
+            defn.store.version.as_deref().unwrap_or("0"),
+        ))
+    }
+
+    fn entry_path(&self, layer_key: &str, file: &str) -> PathBuf {
+        self.dir.join(sanitize(layer_key)).join(file)
+    }
+
+    /// Persist one node's decoded geometry.
+    pub fn store_geometry(
+        &self,
+        layer_key: &str,
+        node_index: usize,
+        geometry: &DecodedGeometry,
+    ) -> Result<()> {
+        let path = self.entry_path(layer_key, &format!("{node_index}.geom"));
+        std::fs::create_dir_all(path.parent().expect("entry path has a parent"))?;
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        out.write_all(GEOMETRY_MAGIC)?;
+        out.write_all(&GEOMETRY_FORMAT_VERSION.to_le_bytes())?;
+        out.write_all(&(geometry.vertex_count as u64).to_le_bytes())?;
+        write_array(&mut out, &geometry.positions, |v| v.to_le_bytes())?;
+        write_array(&mut out, &geometry.normals, |v| v.to_le_bytes())?;
+        write_array(&mut out, &geometry.uvs, |v| v.to_le_bytes())?;
+        write_array(&mut out, &geometry.colors, |v| v.to_le_bytes())?;
+        write_array(&mut out, &geometry.feature_ids, |v| v.to_le_bytes())?;
+        write_array(&mut out, &geometry.face_ranges, |v| v.to_le_bytes())?;
+        write_array(&mut out, &geometry.uv_regions, |v| v.to_le_bytes())?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Load one node's decoded geometry, if cached.
+    ///
+    /// Entries written by an older format version read back as `None` (a
+    /// cache miss), not an error.
+    pub fn load_geometry(
+        &self,
+        layer_key: &str,
+        node_index: usize,
+    ) -> Result<Option<DecodedGeometry>> {
+        let path = self.entry_path(layer_key, &format!("{node_index}.geom"));
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut input = std::io::BufReader::new(file);
+        let mut header = [0u8; 16];
+        input.read_exact(&mut header)?;
+        if &header[..4] != GEOMETRY_MAGIC {
+            return Err(I3SError::Decode(
+                "decoded cache entry has a bad magic".to_string(),
+            ));
+        }
+        let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        if version != GEOMETRY_FORMAT_VERSION {
+            return Ok(None);
+        }
+        let vertex_count = u64::from_le_bytes(header[8..].try_into().expect("8 bytes")) as usize;
+        Ok(Some(DecodedGeometry {
+            vertex_count,
+            positions: read_array(&mut input, f32::from_le_bytes)?,
+            normals: read_array(&mut input, f32::from_le_bytes)?,
+            uvs: read_array(&mut input, f32::from_le_bytes)?,
+            colors: read_array(&mut input, |b: [u8; 1]| b[0])?,
+            feature_ids: read_array(&mut input, u64::from_le_bytes)?,
+            face_ranges: read_array(&mut input, u32::from_le_bytes)?,
+            uv_regions: read_array(&mut input, u16::from_le_bytes)?,
+        }))
+    }
+
+    /// Persist an encoded texture payload.
+    ///
+    /// Payloads are content-addressed: the same bytes repeated across many
+    /// nodes (a common SLPK pattern) are stored once, and each per-node
+    /// entry is a small reference into the shared blob store.
+    pub fn store_texture(
+        &self,
+        layer_key: &str,
+        node_index: usize,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let hash = content_hash(bytes);
+        let blob = self.entry_path(layer_key, &format!("blobs/{hash}.tex"));
+        std::fs::create_dir_all(blob.parent().expect("entry path has a parent"))?;
+        if !blob.exists() {
+            std::fs::write(blob, bytes)?;
+        }
+        let path = self.entry_path(layer_key, &format!("{node_index}-{}.tex", sanitize(name)));
+        let mut reference = TEXTURE_REF_MAGIC.to_vec();
+        reference.extend_from_slice(hash.as_bytes());
+        std::fs::write(path, reference)?;
+        Ok(())
+    }
+
+    /// Load an encoded texture payload, if cached.
+    pub fn load_texture(
+        &self,
+        layer_key: &str,
+        node_index: usize,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        let path = self.entry_path(layer_key, &format!("{node_index}-{}.tex", sanitize(name)));
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        // Dereference a content-addressed entry; payloads written inline by
+        // older versions read back as-is. A pruned blob is a cache miss.
+        if bytes.starts_with(TEXTURE_REF_MAGIC) && bytes.len() == TEXTURE_REF_MAGIC.len() + 16 {
+            let hash = String::from_utf8_lossy(&bytes[TEXTURE_REF_MAGIC.len()..]).into_owned();
+            let blob = self.entry_path(layer_key, &format!("blobs/{hash}.tex"));
+            return match std::fs::read(blob) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            };
+        }
+        Ok(Some(bytes))
+    }
+
+    /// Drop all entries of one layer.
+    pub fn evict_layer(&self, layer_key: &str) -> Result<()> {
+        let dir = self.dir.join(sanitize(layer_key));
+        match std::fs::remove_dir_all(dir) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn sanitize(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn write_array<W: Write, T: Copy, const N: usize>(
+    out: &mut W,
+    values: &[T],
+    to_bytes: impl Fn(T) -> [u8; N],
+) -> Result<()> {
+    out.write_all(&(values.len() as u64).to_le_bytes())?;
+    for &v in values {
+        out.write_all(&to_bytes(v))?;
+    }
+    Ok(())
+}
+
+fn read_array<R: Read, T, const N: usize>(
+    input: &mut R,
+    from_bytes: impl Fn([u8; N]) -> T,
+) -> Result<Vec<T>> {
+    let mut len = [0u8; 8];
+    input.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len) as usize;
+    let mut values = Vec::with_capacity(len.min(1 << 20));
+    let mut buf = [0u8; N];
+    for _ in 0..len {
+        input.read_exact(&mut buf)?;
+        values.push(from_bytes(buf));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_cache_evicts_the_least_recently_used_entry() {
+        let cache: BoundedCache<&str, u32> = BoundedCache::new();
+        cache.set_budget(Some(10));
+        cache.insert("a", 4, Arc::new(1));
+        cache.insert("b", 4, Arc::new(2));
+
+        // Touching "a" makes "b" the eviction candidate.
+        assert_eq!(*cache.get(&"a").unwrap(), 1);
+        cache.insert("c", 4, Arc::new(3));
+        assert!(cache.contains_key(&"a"));
+        assert!(!cache.contains_key(&"b"));
+        assert!(cache.contains_key(&"c"));
+        assert_eq!(cache.total_weight(), 8);
+
+        // An entry heavier than the whole budget still caches: the
+        // newest insertion is never evicted.
+        cache.insert("d", 100, Arc::new(4));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(*cache.get(&"d").unwrap(), 4);
+
+        // Lifting the budget stops eviction entirely.
+        cache.set_budget(None);
+        cache.insert("e", 100, Arc::new(5));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn geometry_round_trips_through_the_cache() {
+        let dir = crate::testutil::TestDir::new("decoded-cache");
+        let cache = DecodedCache::open(&dir).unwrap();
+
+        let geometry = DecodedGeometry {
+            vertex_count: 3,
+            positions: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            normals: vec![0.0, 0.0, 1.0],
+            uvs: vec![0.5, 0.5],
+            colors: vec![255, 0, 0, 255],
+            feature_ids: vec![7],
+            face_ranges: vec![0, 2],
+            uv_regions: vec![0, 0, u16::MAX, u16::MAX],
+        };
+        cache.store_geometry("layer-a/1.8", 42, &geometry).unwrap();
+
+        assert!(cache.load_geometry("layer-a/1.8", 41).unwrap().is_none());
+        let loaded = cache.load_geometry("layer-a/1.8", 42).unwrap().unwrap();
+        assert_eq!(loaded.vertex_count, 3);
+        assert_eq!(loaded.positions, geometry.positions);
+        assert_eq!(loaded.uv_regions, geometry.uv_regions);
+
+        cache
+            .store_texture("layer-a/1.8", 42, "0", b"jpeg")
+            .unwrap();
+        assert_eq!(
+            cache.load_texture("layer-a/1.8", 42, "0").unwrap().unwrap(),
+            b"jpeg"
+        );
+
+        // The same payload stored for another node shares one blob.
+        cache
+            .store_texture("layer-a/1.8", 43, "0", b"jpeg")
+            .unwrap();
+        assert_eq!(
+            cache.load_texture("layer-a/1.8", 43, "0").unwrap().unwrap(),
+            b"jpeg"
+        );
+        let blobs = std::fs::read_dir(dir.join("layer-a_1.8").join("blobs"))
+            .unwrap()
+            .count();
+        assert_eq!(blobs, 1);
+
+        cache.evict_layer("layer-a/1.8").unwrap();
+        assert!(cache.load_geometry("layer-a/1.8", 42).unwrap().is_none());
+    }
+}
@@ -0,0 +1,181 @@
+//! Reading exploded layers from cloud object stores.
+//!
+//! Backed by the `object_store` crate, so SLPK contents extracted to an S3,
+//! Azure or GCS bucket (the same layout [`SceneLayerPackage::explode_to`]
+//! writes) can be read directly with
+//! `SceneLayer::from_uri("s3://bucket/layer")`. Credentials come from the
+//! usual provider environment variables; for anything beyond that,
+//! configure a store yourself and pass it to [`CloudStore::from_store`].
+//!
+//! The store speaks the blocking [`Accessor`] protocol by driving its async
+//! requests on a private single-threaded runtime, so it must not be used
+//! from inside another tokio runtime.
+//!
+//! [`SceneLayerPackage::explode_to`]: crate::slpk::SceneLayerPackage::explode_to
+
+use std::sync::Arc;
+
+use object_store::path::Path as StorePath;
+use object_store::ObjectStore;
+
+use crate::decode::maybe_ungzip;
+use crate::defn::ImageFormat;
+use crate::err::{I3SError, Result};
+use crate::rm::{Accessor, UriBuilder};
+
+/// An exploded layer hosted in a cloud object store.
+pub struct CloudStore {
+    store: Arc<dyn ObjectStore>,
+    prefix: StorePath,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl CloudStore {
+    /// Open a layer from a bucket URL such as `s3://bucket/layer`,
+    /// `gs://bucket/layer` or `az://account/container/layer`.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let parsed =
+            url::Url::parse(url).map_err(|_| I3SError::InvalidUri(url.to_string()))?;
+        let (store, prefix) = object_store::parse_url(&parsed)
+            .map_err(|e| I3SError::InvalidUri(format!("{url}: {e}")))?;
+        Ok(Self::from_store(Arc::from(store), prefix))
+    }
+
+    /// Wrap an already-configured store; `prefix` is the layer root inside
+    /// it (the directory holding `3dSceneLayer.json`).
+    pub fn from_store(store: Arc<dyn ObjectStore>, prefix: StorePath) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+        Self {
+            store,
+            prefix,
+            runtime,
+        }
+    }
+
+    fn entry(&self, uri: &str) -> StorePath {
+        let mut path = self.prefix.clone();
+        for part in uri.split('/') {
+            path = path.child(part);
+        }
+        path
+    }
+
+    fn fetch(&self, path: &StorePath) -> std::result::Result<Vec<u8>, object_store::Error> {
+        self.runtime.block_on(async {
+            let result = self.store.get(path).await?;
+            Ok(result.bytes().await?.to_vec())
+        })
+    }
+}
+
+impl Accessor for CloudStore {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        // As with local exploded folders, tolerate trees unpacked without
+        // decompressing by falling back to the gzipped entry name.
+        let bytes = match self.fetch(&self.entry(uri)) {
+            Ok(bytes) => bytes,
+            Err(object_store::Error::NotFound { .. }) => self
+                .fetch(&self.entry(&format!("{uri}.gz")))
+                .map_err(|_| I3SError::MissingResource(uri.to_string()))?,
+            Err(e) => return Err(I3SError::Decode(format!("object store: {e}"))),
+        };
+        Ok(Arc::new(maybe_ungzip(bytes)?))
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        let path = self.entry(uri);
+        match self.runtime.block_on(self.store.head(&path)) {
+            Ok(meta) => Ok(Some(meta.size as u64)),
+            Err(object_store::Error::NotFound { .. }) => {
+                Err(I3SError::MissingResource(uri.to_string()))
+            }
+            Err(e) => Err(I3SError::Decode(format!("object store: {e}"))),
+        }
+    }
+}
+
+impl UriBuilder for CloudStore {
+    fn scene_definition_uri(&self) -> String {
+        "3dSceneLayer.json".to_string()
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        format!("nodepages/{page_index}.json")
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        format!("nodes/{node_index}/geometries/{resource}.bin")
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String {
+        let ext = match format {
+            ImageFormat::Jpg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Dds => "bin.dds",
+            ImageFormat::Ktx2 => "ktx2",
+            ImageFormat::Basis => "basis",
+            ImageFormat::KtxEtc2 => "ktx",
+        };
+        format!("nodes/{node_index}/textures/{name}.{ext}")
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        format!("nodes/{node_index}/attributes/{key}/0.bin")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_store_serves_a_layer() {
+        let defn = serde_json::json!({
+            "id": 0,
+            "name": "cloud",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        });
+        let page = serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        });
+        let memory = Arc::new(object_store::memory::InMemory::new());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(async {
+                memory
+                    .put(
+                        &StorePath::from("layer/3dSceneLayer.json"),
+                        serde_json::to_vec(&defn).unwrap().into(),
+                    )
+                    .await?;
+                memory
+                    .put(
+                        &StorePath::from("layer/nodepages/0.json"),
+                        serde_json::to_vec(&page).unwrap().into(),
+                    )
+                    .await
+            })
+            .unwrap();
+        drop(runtime);
+
+        let store = CloudStore::from_store(memory, StorePath::from("layer"));
+        let layer = crate::layer::SceneLayer::from_backend(store).unwrap();
+        assert_eq!(layer.name(), Some("cloud"));
+        assert_eq!(layer.root().unwrap().index, 0);
+    }
+}
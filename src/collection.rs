@@ -0,0 +1,133 @@
+//! Managing several scene layers as one unit.
+
+#[cfg(any(feature = "http", feature = "slpk"))]
+use std::sync::Arc;
+
+#[cfg(feature = "http")]
+use serde::Deserialize;
+
+use crate::defn::Extent;
+#[cfg(any(feature = "http", feature = "slpk"))]
+use crate::err::Result;
+use crate::layer::SceneLayer;
+#[cfg(any(feature = "http", feature = "slpk"))]
+use crate::rm::ResourceManager;
+
+/// A set of open [`SceneLayer`]s displayed or processed together.
+///
+/// Layers opened from the same SceneServer share one HTTP client and
+/// response cache (they hold the same underlying [`ResourceManager`]);
+/// layers from a folder of SLPKs each own their archive handle.
+#[derive(Default)]
+pub struct SceneLayerCollection {
+    layers: Vec<SceneLayer>,
+}
+
+#[cfg(feature = "http")]
+#[derive(Deserialize)]
+struct ServiceRootLayer {
+    id: u32,
+}
+
+#[cfg(feature = "http")]
+#[derive(Deserialize)]
+struct ServiceRoot {
+    #[serde(default)]
+    layers: Vec<ServiceRootLayer>,
+}
+
+impl SceneLayerCollection {
+    /// An empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open every layer of a SceneServer.
+    ///
+    /// The root service document is fetched once and each declared layer is
+    /// opened over the same connection.
+    #[cfg(feature = "http")]
+    pub fn from_service(url: &str) -> Result<Self> {
+        use crate::rm::Accessor;
+
+        let service = crate::service::Service::connect(url)?;
+        let root_uri = format!("{}?f=json", service.base_url());
+        let bytes = service.get(&root_uri)?;
+        let root: ServiceRoot =
+            serde_json::from_slice(&bytes).map_err(|e| crate::err::I3SError::json(&root_uri, e))?;
+        let rm = Arc::new(ResourceManager::Service(service));
+        let mut layers = Vec::with_capacity(root.layers.len());
+        for layer in &root.layers {
+            let scoped = Arc::new(ResourceManager::sublayer(Arc::clone(&rm), layer.id));
+            layers.push(SceneLayer::from_resource_manager(scoped)?);
+        }
+        Ok(Self { layers })
+    }
+
+    /// Open every `.slpk` file directly inside a directory.
+    #[cfg(feature = "slpk")]
+    pub fn from_slpk_dir(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let mut layers = Vec::new();
+        let mut entries: Vec<_> = std::fs::read_dir(path)?
+            .collect::<std::io::Result<Vec<_>>>()?
+            .into_iter()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "slpk"))
+            .collect();
+        entries.sort();
+        for path in entries {
+            let package = crate::slpk::SceneLayerPackage::open(&path)?;
+            let rm = Arc::new(ResourceManager::Slpk(package));
+            layers.push(SceneLayer::from_resource_manager(rm)?);
+        }
+        Ok(Self { layers })
+    }
+
+    /// Add an already-open layer.
+    pub fn push(&mut self, layer: SceneLayer) {
+        self.layers.push(layer);
+    }
+
+    /// The layers of the collection.
+    pub fn layers(&self) -> &[SceneLayer] {
+        &self.layers
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, SceneLayer> {
+        self.layers.iter()
+    }
+
+    /// The union of the extents of all layers that declare one.
+    pub fn combined_extent(&self) -> Option<Extent> {
+        let mut combined: Option<Extent> = None;
+        for extent in self.layers.iter().filter_map(|l| l.extent()) {
+            combined = Some(match combined {
+                None => extent,
+                Some(acc) => Extent {
+                    xmin: acc.xmin.min(extent.xmin),
+                    ymin: acc.ymin.min(extent.ymin),
+                    xmax: acc.xmax.max(extent.xmax),
+                    ymax: acc.ymax.max(extent.ymax),
+                },
+            });
+        }
+        combined
+    }
+}
+
+impl<'a> IntoIterator for &'a SceneLayerCollection {
+    type Item = &'a SceneLayer;
+    type IntoIter = std::slice::Iter<'a, SceneLayer>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.layers.iter()
+    }
+}
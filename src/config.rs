@@ -0,0 +1,199 @@
+//! Declarative configuration profiles for pipelines.
+//!
+//! A [`Config`] gathers the tuning knobs that otherwise live in scattered
+//! option structs — cache budgets, transport retries, texture preferences,
+//! export defaults — into one document that can be loaded from a TOML or
+//! JSON file. Teams check the profile into their pipeline repository and
+//! pass it to [`SceneLayer::from_uri_with`](crate::SceneLayer::from_uri_with)
+//! or `i3s-cli --config`, so every run uses the same settings without
+//! recompiling.
+//!
+//! Every section and field is optional and defaults to the crate's built-in
+//! behavior; unknown keys are rejected so typos fail loudly instead of
+//! silently reverting to defaults.
+//!
+//! ```toml
+//! [cache]
+//! max_node_pages = 64
+//! max_resource_bytes = 268435456
+//!
+//! [network]
+//! retries = 5
+//! backoff_ms = 500
+//!
+//! [textures]
+//! supports_basis = true
+//!
+//! [export]
+//! up_axis = "y-up"
+//! position_decimals = 4
+//! ```
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::cache::CacheConfig;
+use crate::decode::TexturePreference;
+use crate::err::{I3SError, Result};
+use crate::export::ExportOptions;
+
+/// A full settings profile, deserialized from TOML or JSON.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// In-memory cache budgets, applied via
+    /// [`SceneLayer::with_cache_config`](crate::SceneLayer::with_cache_config).
+    pub cache: CacheConfig,
+    /// Transport tuning for hosted services.
+    pub network: NetworkConfig,
+    /// Worker counts for parallel stages.
+    pub concurrency: ConcurrencyConfig,
+    /// Texture formats the consuming client can handle.
+    pub textures: TexturePreference,
+    /// Defaults for the exporters.
+    pub export: ExportOptions,
+}
+
+/// Transport settings, mirroring [`ServiceOptions`](crate::service::ServiceOptions)
+/// with plain numbers so they deserialize from config files.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Per-request timeout in seconds.
+    pub timeout_secs: u64,
+    /// Extra attempts after a retryable failure.
+    pub retries: u32,
+    /// Delay in milliseconds before the first retry; doubled after every
+    /// further attempt.
+    pub backoff_ms: u64,
+    /// Directory for the persistent response cache; absent disables it.
+    pub disk_cache: Option<std::path::PathBuf>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        // Keep in step with `ServiceOptions::default`.
+        Self {
+            timeout_secs: 30,
+            retries: 3,
+            backoff_ms: 250,
+            disk_cache: None,
+        }
+    }
+}
+
+/// Worker counts for stages that fan out across threads.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConcurrencyConfig {
+    /// Threads for deep validation and other worker pools; absent uses
+    /// the machine's available parallelism.
+    pub workers: Option<usize>,
+}
+
+impl Config {
+    /// Load a profile from a `.toml` or `.json` file, picked by extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| I3SError::from(e).with_uri(&path.to_string_lossy()))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml_str(&text),
+            Some("json") => Self::from_json_str(&text),
+            _ => Err(I3SError::Validation(format!(
+                "config file must end in .toml or .json: {}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Parse a profile from TOML text.
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| I3SError::Validation(format!("invalid config: {e}")))
+    }
+
+    /// Parse a profile from JSON text.
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        serde_json::from_str(text)
+            .map_err(|e| I3SError::Validation(format!("invalid config: {e}")))
+    }
+
+    /// The transport options this profile asks for.
+    #[cfg(feature = "http")]
+    pub fn service_options(&self) -> crate::service::ServiceOptions {
+        let mut options = crate::service::ServiceOptions::default()
+            .timeout(std::time::Duration::from_secs(self.network.timeout_secs))
+            .retries(self.network.retries)
+            .backoff(std::time::Duration::from_millis(self.network.backoff_ms));
+        if let Some(dir) = &self.network.disk_cache {
+            options = options.disk_cache(dir);
+        }
+        options
+    }
+
+    /// The worker count to use, resolving the machine default.
+    pub fn workers(&self) -> usize {
+        self.concurrency.workers.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_parse_from_toml_and_json_with_defaults() {
+        let config = Config::from_toml_str(
+            r#"
+            [cache]
+            max_node_pages = 64
+
+            [network]
+            retries = 5
+            backoff_ms = 500
+
+            [textures]
+            supports_basis = true
+
+            [export]
+            up_axis = "y-up"
+            position_decimals = 4
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.cache.max_node_pages, Some(64));
+        assert_eq!(config.cache.max_resource_bytes, None);
+        assert_eq!(config.network.retries, 5);
+        assert_eq!(config.network.timeout_secs, 30);
+        assert!(config.textures.supports_basis);
+        assert!(!config.textures.supports_dds);
+        assert_eq!(config.export.up_axis, crate::export::UpAxis::YUp);
+        assert_eq!(config.export.position_decimals, Some(4));
+        assert_eq!(config.export.uv_decimals, Some(6));
+
+        let config = Config::from_json_str(
+            r#"{"concurrency": {"workers": 2}, "export": {"unit_scale": 0.3048}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.workers(), 2);
+        assert_eq!(config.export.unit_scale, 0.3048);
+
+        // Empty documents are the built-in defaults.
+        assert_eq!(
+            Config::from_toml_str("").unwrap().network.backoff_ms,
+            NetworkConfig::default().backoff_ms
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let err = Config::from_toml_str("[cache]\nmax_node_page = 1\n").unwrap_err();
+        assert!(matches!(err, I3SError::Validation(_)), "{err:?}");
+        assert!(Config::from_json_str(r#"{"netwrok": {}}"#).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+//! Coordinate reference system helpers.
+//!
+//! Layers declare their CRS as well-known IDs on the scene definition's
+//! `spatialReference`. This module resolves those IDs; the feature-gated
+//! [`reproject`] module (feature `proj`) transforms decoded coordinates
+//! into arbitrary target systems through PROJ.
+
+#[cfg(feature = "proj")]
+pub mod reproject;
+
+use crate::defn::SceneDefinition;
+
+/// The horizontal EPSG code a layer declares, preferring `latestWkid`
+/// over the possibly deprecated `wkid`. `None` when the definition
+/// carries no spatial reference.
+pub fn layer_epsg(defn: &SceneDefinition) -> Option<u32> {
+    let sr = defn.spatial_reference.as_ref()?;
+    sr.latest_wkid.or(sr.wkid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defn::SpatialReference;
+
+    #[test]
+    fn layer_epsg_prefers_latest_wkid() {
+        let mut defn = crate::defn::SceneDefinition::builder(
+            crate::defn::LayerType::IntegratedMesh,
+            crate::defn::Profile::MeshPyramids,
+        )
+        .spatial_reference(SpatialReference {
+            wkid: Some(102100),
+            latest_wkid: Some(3857),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+        assert_eq!(layer_epsg(&defn), Some(3857));
+
+        defn.spatial_reference = Some(SpatialReference {
+            wkid: Some(4326),
+            ..Default::default()
+        });
+        assert_eq!(layer_epsg(&defn), Some(4326));
+
+        defn.spatial_reference = None;
+        assert_eq!(layer_epsg(&defn), None);
+    }
+}
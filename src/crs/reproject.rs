@@ -0,0 +1,166 @@
+//! Reprojection of decoded coordinates through PROJ (feature `proj`).
+//!
+//! A [`Reprojector`] wraps one PROJ transformation between two EPSG
+//! systems and applies it to the coordinate shapes the crate decodes:
+//! interleaved vertex position arrays, OBB centers and layer extents.
+//! Transforms are horizontal only — heights pass through unchanged, since
+//! vertical datum shifts depend on height-model information PROJ cannot
+//! derive from an EPSG pair alone.
+
+use proj::Proj;
+
+use crate::defn::{Extent, SceneDefinition};
+use crate::err::{I3SError, Result};
+use crate::obb::OrientedBoundingBox;
+
+/// Transforms coordinates from one EPSG system into another.
+///
+/// PROJ transformation objects are not thread-safe; build one
+/// `Reprojector` per thread when reprojecting in parallel.
+pub struct Reprojector {
+    transform: Proj,
+    source: u32,
+    target: u32,
+}
+
+impl std::fmt::Debug for Reprojector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Reprojector")
+            .field("source", &self.source)
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+impl Reprojector {
+    /// Build a transform from `source_epsg` into `target_epsg`.
+    pub fn new(source_epsg: u32, target_epsg: u32) -> Result<Self> {
+        let transform = Proj::new_known_crs(
+            &format!("EPSG:{source_epsg}"),
+            &format!("EPSG:{target_epsg}"),
+            None,
+        )
+        .map_err(|e| {
+            I3SError::Decode(format!(
+                "cannot build EPSG:{source_epsg} -> EPSG:{target_epsg} transform: {e}"
+            ))
+        })?;
+        Ok(Self {
+            transform,
+            source: source_epsg,
+            target: target_epsg,
+        })
+    }
+
+    /// Build a transform from the horizontal CRS `defn` declares into
+    /// `target_epsg`.
+    pub fn for_definition(defn: &SceneDefinition, target_epsg: u32) -> Result<Self> {
+        let source = super::layer_epsg(defn).ok_or_else(|| {
+            I3SError::Validation("layer declares no horizontal spatial reference".to_string())
+        })?;
+        Self::new(source, target_epsg)
+    }
+
+    /// The EPSG code coordinates are transformed from.
+    pub fn source_epsg(&self) -> u32 {
+        self.source
+    }
+
+    /// The EPSG code coordinates are transformed into.
+    pub fn target_epsg(&self) -> u32 {
+        self.target
+    }
+
+    /// Transform one x/y/z position in place. The height is untouched.
+    pub fn transform_position(&self, position: &mut [f64; 3]) -> Result<()> {
+        let (x, y) = self
+            .transform
+            .convert((position[0], position[1]))
+            .map_err(|e| {
+                I3SError::Decode(format!(
+                    "EPSG:{} -> EPSG:{} failed at ({}, {}): {e}",
+                    self.source, self.target, position[0], position[1]
+                ))
+            })?;
+        position[0] = x;
+        position[1] = y;
+        Ok(())
+    }
+
+    /// Transform interleaved x/y/z positions in place — the shape of
+    /// decoded point cloud positions and of mesh positions once shifted
+    /// out of their node-relative frame.
+    pub fn transform_positions(&self, positions: &mut [f64]) -> Result<()> {
+        for chunk in positions.chunks_exact_mut(3) {
+            let mut position = [chunk[0], chunk[1], chunk[2]];
+            self.transform_position(&mut position)?;
+            (chunk[0], chunk[1]) = (position[0], position[1]);
+        }
+        Ok(())
+    }
+
+    /// Transform an OBB center in place. Half sizes and orientation are
+    /// kept: they are meter-scaled in the box's own frame, which is a fair
+    /// approximation for culling as long as the target CRS is also
+    /// meter-based near the center.
+    pub fn transform_obb(&self, obb: &mut OrientedBoundingBox) -> Result<()> {
+        self.transform_position(&mut obb.center)
+    }
+
+    /// Transform an extent by reprojecting its four corners and returning
+    /// their axis-aligned hull. Curved transforms can bulge edges past the
+    /// corner hull; pad the result when a strict cover is required.
+    pub fn transform_extent(&self, extent: &Extent) -> Result<Extent> {
+        let mut corners = [
+            [extent.xmin, extent.ymin, 0.0],
+            [extent.xmax, extent.ymin, 0.0],
+            [extent.xmin, extent.ymax, 0.0],
+            [extent.xmax, extent.ymax, 0.0],
+        ];
+        for corner in &mut corners {
+            self.transform_position(corner)?;
+        }
+        Ok(Extent {
+            xmin: corners.iter().map(|c| c[0]).fold(f64::INFINITY, f64::min),
+            ymin: corners.iter().map(|c| c[1]).fold(f64::INFINITY, f64::min),
+            xmax: corners
+                .iter()
+                .map(|c| c[0])
+                .fold(f64::NEG_INFINITY, f64::max),
+            ymax: corners
+                .iter()
+                .map(|c| c[1])
+                .fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wgs84_to_web_mercator_round_trips() {
+        let forward = Reprojector::new(4326, 3857).unwrap();
+        let mut position = [10.0, 0.0, 25.0];
+        forward.transform_position(&mut position).unwrap();
+        // 10 degrees of longitude on the equator in Web Mercator meters.
+        assert!((position[0] - 1_113_194.9).abs() < 1.0);
+        assert!(position[1].abs() < 1e-6);
+        assert_eq!(position[2], 25.0);
+
+        let back = Reprojector::new(3857, 4326).unwrap();
+        back.transform_position(&mut position).unwrap();
+        assert!((position[0] - 10.0).abs() < 1e-9);
+
+        let extent = Extent {
+            xmin: -10.0,
+            ymin: -5.0,
+            xmax: 10.0,
+            ymax: 5.0,
+        };
+        let projected = forward.transform_extent(&extent).unwrap();
+        assert!(projected.xmin < 0.0 && projected.xmax > 0.0);
+        assert!((projected.xmax + projected.xmin).abs() < 1e-6);
+    }
+}
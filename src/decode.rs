@@ -0,0 +1,1196 @@
+//! Decoding of node geometry, material and texture resources.
+
+use std::sync::Arc;
+
+use crate::err::{I3SError, Result};
+
+use crate::defn::{
+    GeometryAttribute, GeometryBuffer, GeometryDefinition, ImageFormat, MaterialDefinition,
+    Profile, TextureSetDefinition,
+};
+
+/// Geometry of a node decoded into typed arrays.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodedGeometry {
+    pub vertex_count: usize,
+    /// Interleaved x/y/z positions, relative to the node OBB center.
+    pub positions: Vec<f32>,
+    pub normals: Vec<f32>,
+    pub uvs: Vec<f32>,
+    /// Interleaved r/g/b/a vertex colors.
+    pub colors: Vec<u8>,
+    pub feature_ids: Vec<u64>,
+    /// Interleaved min/max vertex index per feature.
+    pub face_ranges: Vec<u32>,
+    /// Interleaved per-vertex `[umin, vmin, umax, vmax]` atlas sub-regions,
+    /// normalized to `u16::MAX`. Empty when the layer is not atlased.
+    pub uv_regions: Vec<u16>,
+}
+
+/// How to treat the texture set `atlas` flag versus actual uvRegion data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AtlasHandling {
+    /// Use uv regions only when the `atlas` flag says so; contradictions are
+    /// reported on the [`AtlasDiagnostic`].
+    #[default]
+    HonorFlag,
+    /// Treat geometry as atlased whenever uv regions are present.
+    ForceEnable,
+    /// Ignore uv regions entirely.
+    ForceDisable,
+}
+
+/// The atlas decision for one decoded geometry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasDiagnostic {
+    /// The `atlas` flag of the texture set definition, if declared.
+    pub atlas_flag: Option<bool>,
+    /// Whether the geometry buffer carries uvRegion data.
+    pub has_uv_regions: bool,
+    /// Flag and data disagree (`atlas` true without regions, or regions
+    /// present with `atlas` absent/false). Texturing is likely distorted
+    /// on one of the two interpretations.
+    pub contradiction: bool,
+    /// Whether uv regions will be applied.
+    pub use_regions: bool,
+}
+
+/// Decide whether to apply uv regions, honoring `handling`.
+pub fn resolve_atlas(
+    atlas_flag: Option<bool>,
+    has_uv_regions: bool,
+    handling: AtlasHandling,
+) -> AtlasDiagnostic {
+    let flagged = atlas_flag.unwrap_or(false);
+    let contradiction = flagged != has_uv_regions;
+    let use_regions = match handling {
+        AtlasHandling::HonorFlag => flagged && has_uv_regions,
+        AtlasHandling::ForceEnable => has_uv_regions,
+        AtlasHandling::ForceDisable => false,
+    };
+    AtlasDiagnostic {
+        atlas_flag,
+        has_uv_regions,
+        contradiction,
+        use_regions,
+    }
+}
+
+/// A texture chosen from a texture set definition.
+#[derive(Debug, Clone)]
+pub struct SelectedTexture {
+    /// Resource name of the texture (e.g. `"0"`, `"0_0_1"`).
+    pub name: String,
+    pub format: ImageFormat,
+}
+
+/// Material of a node resolved against the layer definitions.
+#[derive(Debug, Clone)]
+pub struct DecodedMaterial {
+    pub base_color_factor: [f64; 4],
+    pub double_sided: bool,
+    pub alpha_mode: Option<String>,
+    pub texture: Option<SelectedTexture>,
+}
+
+/// A transform applied to positions as they are decoded — reprojection,
+/// RTC shifts, unit scaling — so conversion pipelines do not need a second
+/// full pass over millions of vertices. Implemented automatically for
+/// thread-safe closures.
+pub trait VertexTransform: Send + Sync {
+    /// Transform one position in place. Mesh positions are node-relative;
+    /// point cloud positions are in the layer CRS.
+    fn transform(&self, position: &mut [f64; 3]);
+}
+
+impl<F: Fn(&mut [f64; 3]) + Send + Sync> VertexTransform for F {
+    fn transform(&self, position: &mut [f64; 3]) {
+        self(position)
+    }
+}
+
+fn transform_f32_positions(transform: &dyn VertexTransform, positions: &mut [f32]) {
+    for chunk in positions.chunks_exact_mut(3) {
+        let mut position = [
+            f64::from(chunk[0]),
+            f64::from(chunk[1]),
+            f64::from(chunk[2]),
+        ];
+        transform.transform(&mut position);
+        (chunk[0], chunk[1], chunk[2]) = (
+            position[0] as f32,
+            position[1] as f32,
+            position[2] as f32,
+        );
+    }
+}
+
+fn transform_f64_positions(transform: &dyn VertexTransform, positions: &mut [f64]) {
+    for chunk in positions.chunks_exact_mut(3) {
+        let mut position = [chunk[0], chunk[1], chunk[2]];
+        transform.transform(&mut position);
+        (chunk[0], chunk[1], chunk[2]) = (position[0], position[1], position[2]);
+    }
+}
+
+/// Decodes one compressed geometry encoding (a `compressedAttributes`
+/// buffer) into typed arrays — a Draco implementation, or a vendor codec
+/// from a future spec version. Implemented automatically for thread-safe
+/// closures.
+pub trait GeometryCodec: Send + Sync {
+    /// Decode `bytes` into typed arrays. `attributes` is the declared
+    /// attribute list of the compressed buffer, in buffer order.
+    fn decode(
+        &self,
+        bytes: &[u8],
+        attributes: &[String],
+        vertex_count: usize,
+        feature_count: usize,
+    ) -> Result<DecodedGeometry>;
+}
+
+impl<F> GeometryCodec for F
+where
+    F: Fn(&[u8], &[String], usize, usize) -> Result<DecodedGeometry> + Send + Sync,
+{
+    fn decode(
+        &self,
+        bytes: &[u8],
+        attributes: &[String],
+        vertex_count: usize,
+        feature_count: usize,
+    ) -> Result<DecodedGeometry> {
+        self(bytes, attributes, vertex_count, feature_count)
+    }
+}
+
+/// Geometry codecs keyed by `compressedAttributes.encoding` value.
+///
+/// [`ResourceDecoder::decode_geometry`] dispatches compressed buffers
+/// through the registry, so encodings beyond what the crate ships —
+/// vendor codecs, future spec versions — can be handled without forking.
+#[derive(Default)]
+pub struct GeometryCodecRegistry {
+    codecs: std::collections::HashMap<String, Box<dyn GeometryCodec>>,
+}
+
+impl std::fmt::Debug for GeometryCodecRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeometryCodecRegistry")
+            .field("encodings", &self.codecs.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl GeometryCodecRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install a codec for `encoding`, replacing any previous one.
+    pub fn register(&mut self, encoding: impl Into<String>, codec: impl GeometryCodec + 'static) {
+        self.codecs.insert(encoding.into(), Box::new(codec));
+    }
+
+    /// Whether a codec is installed for `encoding`.
+    pub fn supports(&self, encoding: &str) -> bool {
+        self.codecs.contains_key(encoding)
+    }
+
+    fn get(&self, encoding: &str) -> Option<&dyn GeometryCodec> {
+        self.codecs.get(encoding).map(Box::as_ref)
+    }
+}
+
+/// Decodes node resources according to the layer profile.
+pub struct ResourceDecoder {
+    profile: Profile,
+    transform: Option<std::sync::Arc<dyn VertexTransform>>,
+    codecs: std::sync::Arc<GeometryCodecRegistry>,
+}
+
+impl std::fmt::Debug for ResourceDecoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceDecoder")
+            .field("profile", &self.profile)
+            .field("transform", &self.transform.is_some())
+            .field("codecs", &self.codecs)
+            .finish()
+    }
+}
+
+impl ResourceDecoder {
+    /// A decoder for `profile`. Total over every profile: decode methods
+    /// a profile does not support return
+    /// [`I3SError::UnsupportedProfile`] instead of this constructor
+    /// rejecting the layer up front.
+    pub fn new(profile: Profile) -> Self {
+        Self {
+            profile,
+            transform: None,
+            codecs: std::sync::Arc::default(),
+        }
+    }
+
+    /// The profile this decoder was built for.
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+
+    /// Apply `transform` to every position this decoder produces.
+    pub fn with_vertex_transform(mut self, transform: impl VertexTransform + 'static) -> Self {
+        self.transform = Some(std::sync::Arc::new(transform));
+        self
+    }
+
+    /// Dispatch compressed geometry buffers through `codecs`.
+    pub fn with_geometry_codecs(mut self, codecs: std::sync::Arc<GeometryCodecRegistry>) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// Decode an uncompressed geometry buffer into typed arrays.
+    ///
+    /// `vertex_count` and `feature_count` come from the node's mesh object.
+    pub fn decode_geometry(
+        &self,
+        bytes: &[u8],
+        definition: &GeometryDefinition,
+        vertex_count: usize,
+        feature_count: usize,
+    ) -> Result<DecodedGeometry> {
+        if self.profile != Profile::MeshPyramids {
+            return Err(I3SError::UnsupportedProfile(self.profile));
+        }
+        let buffer = definition
+            .geometry_buffers
+            .first()
+            .ok_or_else(|| I3SError::Decode("geometry definition has no buffers".to_string()))?;
+        let mut geometry = if let Some(compressed) = &buffer.compressed_attributes {
+            let codec = self.codecs.get(&compressed.encoding).ok_or_else(|| {
+                I3SError::Decode(format!(
+                    "no geometry codec registered for encoding {:?}; register one \
+                     on the layer's GeometryCodecRegistry",
+                    compressed.encoding
+                ))
+            })?;
+            codec.decode(bytes, &compressed.attributes, vertex_count, feature_count)?
+        } else {
+            decode_uncompressed(bytes, buffer, vertex_count, feature_count)?
+        };
+        if let Some(transform) = &self.transform {
+            transform_f32_positions(transform.as_ref(), &mut geometry.positions);
+        }
+        Ok(geometry)
+    }
+
+    /// Decode a geometry buffer and resolve atlas handling against the
+    /// node's texture set definition.
+    ///
+    /// When the resolution says regions are not to be used (or the flag and
+    /// the data contradict each other under
+    /// [`AtlasHandling::HonorFlag`]), the decoded uv regions are dropped so
+    /// downstream texturing cannot silently mix interpretations; the
+    /// returned diagnostic says what was found.
+    pub fn decode_geometry_with_atlas(
+        &self,
+        bytes: &[u8],
+        definition: &GeometryDefinition,
+        vertex_count: usize,
+        feature_count: usize,
+        texture_set: Option<&TextureSetDefinition>,
+        handling: AtlasHandling,
+    ) -> Result<(DecodedGeometry, AtlasDiagnostic)> {
+        let mut geometry = self.decode_geometry(bytes, definition, vertex_count, feature_count)?;
+        let diagnostic = resolve_atlas(
+            texture_set.and_then(|set| set.atlas),
+            !geometry.uv_regions.is_empty(),
+            handling,
+        );
+        if !diagnostic.use_regions {
+            geometry.uv_regions.clear();
+        }
+        Ok((geometry, diagnostic))
+    }
+
+    /// Decode PCSL point resources into typed arrays.
+    ///
+    /// `buffers` maps attribute keys (`"position"`, `"rgb"`, `"intensity"`,
+    /// `"classCode"`, `"flags"`) to raw resource bytes; unknown keys are
+    /// ignored. See [`crate::pointcloud`] for the per-buffer formats.
+    pub fn decode_points(
+        &self,
+        buffers: &[(&str, &[u8])],
+        point_count: usize,
+    ) -> Result<crate::pointcloud::DecodedPoints> {
+        if self.profile != Profile::PointClouds {
+            return Err(I3SError::UnsupportedProfile(self.profile));
+        }
+        let mut points = crate::pointcloud::DecodedPoints {
+            count: point_count,
+            ..Default::default()
+        };
+        for (key, bytes) in buffers {
+            match *key {
+                "position" => {
+                    points.positions = crate::pointcloud::decode_positions(bytes, point_count)?;
+                }
+                "rgb" => points.rgb = crate::pointcloud::decode_rgb(bytes, point_count)?,
+                "intensity" => {
+                    points.intensity = crate::pointcloud::decode_intensity(bytes, point_count)?;
+                }
+                "classCode" => {
+                    points.class_codes = crate::pointcloud::decode_bytes(bytes, point_count)?;
+                }
+                "flags" => points.flags = crate::pointcloud::decode_bytes(bytes, point_count)?,
+                _ => {}
+            }
+        }
+        if let Some(transform) = &self.transform {
+            transform_f64_positions(transform.as_ref(), &mut points.positions);
+        }
+        Ok(points)
+    }
+}
+
+/// Binding of a resolved vertex attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeBinding {
+    /// One element per vertex.
+    PerVertex,
+    /// One element per feature.
+    PerFeature,
+}
+
+/// One attribute of a resolved buffer layout, in buffer order.
+#[derive(Debug, Clone)]
+pub struct AttributeLayout {
+    /// Attribute name as it appears in the geometry definition
+    /// (`position`, `normal`, `uv0`, ...).
+    pub name: &'static str,
+    /// Declared component value type, e.g. `Float32` or `UInt64`.
+    pub value_type: String,
+    /// Components per element (3 for positions, 2 for UVs, ...).
+    pub components: usize,
+    /// Size of one component in bytes.
+    pub component_bytes: usize,
+    /// Whether elements are counted per vertex or per feature.
+    pub binding: AttributeBinding,
+}
+
+impl AttributeLayout {
+    /// Size of one element (all components) in bytes.
+    pub fn element_bytes(&self) -> usize {
+        self.components * self.component_bytes
+    }
+}
+
+/// One attribute placed at concrete byte offsets for known counts.
+#[derive(Debug, Clone)]
+pub struct ResolvedAttribute {
+    pub name: &'static str,
+    /// Byte offset of the attribute block from the start of the resource.
+    pub offset: usize,
+    /// Length of the attribute block in bytes.
+    pub length: usize,
+}
+
+/// Layout of one geometry buffer.
+///
+/// I3S buffers are planar: each attribute occupies a contiguous block, so
+/// offsets depend on the vertex and feature counts of the node and are only
+/// concrete after [`BufferLayout::resolve`].
+#[derive(Debug, Clone)]
+pub enum BufferLayout {
+    /// A plain little-endian buffer with the given attribute blocks in order.
+    Uncompressed {
+        /// Byte offset of the first attribute block.
+        offset: usize,
+        attributes: Vec<AttributeLayout>,
+    },
+    /// An encoded buffer (e.g. Draco) whose byte layout is opaque.
+    Compressed {
+        encoding: String,
+        attributes: Vec<String>,
+    },
+}
+
+impl BufferLayout {
+    /// Concrete byte offsets and lengths for a node with the given counts.
+    ///
+    /// Fails for compressed buffers, which have no fixed byte layout.
+    pub fn resolve(
+        &self,
+        vertex_count: usize,
+        feature_count: usize,
+    ) -> Result<Vec<ResolvedAttribute>> {
+        match self {
+            Self::Uncompressed { offset, attributes } => {
+                let mut out = Vec::with_capacity(attributes.len());
+                let mut offset = *offset;
+                for attr in attributes {
+                    let count = match attr.binding {
+                        AttributeBinding::PerVertex => vertex_count,
+                        AttributeBinding::PerFeature => feature_count,
+                    };
+                    let length = attr.element_bytes() * count;
+                    out.push(ResolvedAttribute {
+                        name: attr.name,
+                        offset,
+                        length,
+                    });
+                    offset += length;
+                }
+                Ok(out)
+            }
+            Self::Compressed { encoding, .. } => Err(I3SError::Decode(format!(
+                "{encoding}-compressed buffers have no fixed byte layout"
+            ))),
+        }
+    }
+}
+
+/// Fully resolved vertex layout of one geometry definition.
+#[derive(Debug, Clone)]
+pub struct GeometryLayout {
+    pub topology: Option<String>,
+    /// One layout per geometry buffer, in definition order.
+    pub buffers: Vec<BufferLayout>,
+}
+
+/// Resolve the vertex layout of a geometry definition.
+pub fn geometry_layout(definition: &GeometryDefinition) -> Result<GeometryLayout> {
+    let mut buffers = Vec::with_capacity(definition.geometry_buffers.len());
+    for buffer in &definition.geometry_buffers {
+        if let Some(compressed) = &buffer.compressed_attributes {
+            buffers.push(BufferLayout::Compressed {
+                encoding: compressed.encoding.clone(),
+                attributes: compressed.attributes.clone(),
+            });
+            continue;
+        }
+        // Mirrors the block order of `decode_uncompressed`.
+        let declared: [(&'static str, &Option<GeometryAttribute>, AttributeBinding); 7] = [
+            ("position", &buffer.position, AttributeBinding::PerVertex),
+            ("normal", &buffer.normal, AttributeBinding::PerVertex),
+            ("uv0", &buffer.uv0, AttributeBinding::PerVertex),
+            ("color", &buffer.color, AttributeBinding::PerVertex),
+            ("uvRegion", &buffer.uv_region, AttributeBinding::PerVertex),
+            ("featureId", &buffer.feature_id, AttributeBinding::PerFeature),
+            ("faceRange", &buffer.face_range, AttributeBinding::PerFeature),
+        ];
+        let mut attributes = Vec::new();
+        for (name, attr, binding) in declared {
+            if let Some(attr) = attr {
+                attributes.push(AttributeLayout {
+                    name,
+                    value_type: attr.value_type.clone(),
+                    components: attr.component,
+                    component_bytes: component_size(&attr.value_type)?,
+                    binding,
+                });
+            }
+        }
+        buffers.push(BufferLayout::Uncompressed {
+            offset: buffer.offset.unwrap_or(0),
+            attributes,
+        });
+    }
+    Ok(GeometryLayout {
+        topology: definition.topology.clone(),
+        buffers,
+    })
+}
+
+fn component_size(value_type: &str) -> Result<usize> {
+    match value_type {
+        "Int8" | "UInt8" => Ok(1),
+        "Int16" | "UInt16" => Ok(2),
+        "Int32" | "UInt32" | "Float32" => Ok(4),
+        "Int64" | "UInt64" | "Float64" => Ok(8),
+        other => Err(I3SError::Decode(format!(
+            "unknown attribute value type: {other}"
+        ))),
+    }
+}
+
+pub(crate) fn attribute_len(attr: &GeometryAttribute, count: usize) -> Result<usize> {
+    Ok(component_size(&attr.value_type)? * attr.component * count)
+}
+
+fn read_f32s(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+fn decode_uncompressed(
+    bytes: &[u8],
+    buffer: &GeometryBuffer,
+    vertex_count: usize,
+    feature_count: usize,
+) -> Result<DecodedGeometry> {
+    let mut out = DecodedGeometry {
+        vertex_count,
+        ..Default::default()
+    };
+    let mut offset = buffer.offset.unwrap_or(0);
+    let mut take = |len: usize| -> Result<&[u8]> {
+        let end = offset + len;
+        let slice = bytes.get(offset..end).ok_or_else(|| {
+            I3SError::Decode(format!(
+                "geometry buffer too short: need {end}, have {}",
+                bytes.len()
+            ))
+            .with_context(crate::err::ErrorContext {
+                byte_offset: Some(offset),
+                ..Default::default()
+            })
+        })?;
+        offset = end;
+        Ok(slice)
+    };
+
+    if let Some(attr) = &buffer.position {
+        out.positions = read_f32s(take(attribute_len(attr, vertex_count)?)?);
+    }
+    if let Some(attr) = &buffer.normal {
+        out.normals = read_f32s(take(attribute_len(attr, vertex_count)?)?);
+    }
+    if let Some(attr) = &buffer.uv0 {
+        out.uvs = read_f32s(take(attribute_len(attr, vertex_count)?)?);
+    }
+    if let Some(attr) = &buffer.color {
+        out.colors = take(attribute_len(attr, vertex_count)?)?.to_vec();
+    }
+    if let Some(attr) = &buffer.uv_region {
+        out.uv_regions = take(attribute_len(attr, vertex_count)?)?
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+    }
+    if let Some(attr) = &buffer.feature_id {
+        let raw = take(attribute_len(attr, feature_count)?)?;
+        out.feature_ids = match component_size(&attr.value_type)? {
+            4 => raw
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as u64)
+                .collect(),
+            8 => raw
+                .chunks_exact(8)
+                .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+            n => {
+                return Err(I3SError::Decode(format!(
+                    "unsupported featureId width: {n}"
+                )))
+            }
+        };
+    }
+    if let Some(attr) = &buffer.face_range {
+        out.face_ranges = take(attribute_len(attr, feature_count)?)?
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+    }
+    Ok(out)
+}
+
+/// What texture formats (and sizes) the consuming client can handle.
+///
+/// Passed to [`decode_material`] so the best texture is chosen per node
+/// instead of whatever the layer happens to list first. The default assumes a
+/// plain CPU consumer: only JPEG/PNG are accepted.
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TexturePreference {
+    /// The client can consume ETC2-compressed KTX textures directly.
+    pub supports_etc2: bool,
+    /// The client can transcode Basis/KTX2 textures.
+    pub supports_basis: bool,
+    /// The client can consume DDS (S3TC) textures directly.
+    pub supports_dds: bool,
+    /// Advisory upper edge length in pixels; recorded on the selection so
+    /// callers can downsample after decode. Encoded sizes are not known
+    /// before download, so this does not filter formats.
+    pub max_texture_size: Option<u32>,
+}
+
+impl TexturePreference {
+    /// Whether the client can use a texture in the given encoded format.
+    pub fn supports(&self, format: ImageFormat) -> bool {
+        match format {
+            ImageFormat::Jpg | ImageFormat::Png => true,
+            ImageFormat::Dds => self.supports_dds,
+            ImageFormat::Ktx2 | ImageFormat::Basis => self.supports_basis,
+            ImageFormat::KtxEtc2 => self.supports_etc2,
+        }
+    }
+
+    /// Rank a format for selection; higher is better. GPU-ready compressed
+    /// formats beat plain images when the client supports them.
+    fn rank(&self, format: ImageFormat) -> Option<u8> {
+        if !self.supports(format) {
+            return None;
+        }
+        Some(match format {
+            ImageFormat::Ktx2 | ImageFormat::Basis => 4,
+            ImageFormat::KtxEtc2 => 3,
+            ImageFormat::Dds => 2,
+            ImageFormat::Jpg | ImageFormat::Png => 1,
+        })
+    }
+}
+
+/// Resolve a node material, picking the best texture the client supports.
+pub fn decode_material(
+    material: &MaterialDefinition,
+    texture_set: Option<&TextureSetDefinition>,
+    preference: &TexturePreference,
+) -> Result<DecodedMaterial> {
+    let pbr = material.pbr_metallic_roughness.clone().unwrap_or_default();
+    let texture = match (pbr.base_color_texture.as_ref(), texture_set) {
+        (Some(_), Some(set)) => {
+            let format = set
+                .formats
+                .iter()
+                .filter_map(|f| preference.rank(f.format).map(|rank| (rank, f)))
+                .max_by_key(|(rank, _)| *rank)
+                .map(|(_, f)| f)
+                .ok_or_else(|| {
+                    I3SError::Decode(format!(
+                        "no supported texture format among {:?}",
+                        set.formats.iter().map(|f| f.format).collect::<Vec<_>>()
+                    ))
+                })?;
+            Some(SelectedTexture {
+                name: format.name.clone(),
+                format: format.format,
+            })
+        }
+        _ => None,
+    };
+    Ok(DecodedMaterial {
+        base_color_factor: pbr.base_color_factor.unwrap_or([1.0, 1.0, 1.0, 1.0]),
+        double_sided: material.double_sided.unwrap_or(false),
+        alpha_mode: material.alpha_mode.clone(),
+        texture,
+    })
+}
+
+/// A texture decoded into 8-bit RGBA pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major r/g/b/a pixels, `width * height * 4` bytes.
+    pub rgba: Vec<u8>,
+}
+
+/// Decodes one encoded texture format into pixels — for example a
+/// proprietary GPU transcoder for Basis, or an `image`-crate adapter for
+/// JPEG. Implemented automatically for thread-safe closures.
+pub trait TextureDecoder: Send + Sync {
+    fn decode(&self, bytes: &[u8]) -> Result<DecodedImage>;
+}
+
+impl<F: Fn(&[u8]) -> Result<DecodedImage> + Send + Sync> TextureDecoder for F {
+    fn decode(&self, bytes: &[u8]) -> Result<DecodedImage> {
+        self(bytes)
+    }
+}
+
+/// Texture decoders keyed by encoded [`ImageFormat`].
+///
+/// [`decode_material_image`] consults installed decoders before any
+/// built-in handling, so clients can extend the texture pipeline — or
+/// override it — without forking. The crate itself ships no pixel
+/// decoders (it deliberately pulls in no image dependency), so an empty
+/// registry decodes nothing.
+#[derive(Default)]
+pub struct TextureDecoderRegistry {
+    decoders: std::collections::HashMap<ImageFormat, Box<dyn TextureDecoder>>,
+}
+
+impl std::fmt::Debug for TextureDecoderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextureDecoderRegistry")
+            .field("formats", &self.decoders.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl TextureDecoderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install a decoder for `format`, replacing any previous one.
+    pub fn register(&mut self, format: ImageFormat, decoder: impl TextureDecoder + 'static) {
+        self.decoders.insert(format, Box::new(decoder));
+    }
+
+    /// Whether a decoder is installed for `format`.
+    pub fn supports(&self, format: ImageFormat) -> bool {
+        self.decoders.contains_key(&format)
+    }
+}
+
+/// Decode an encoded texture into pixels.
+///
+/// Decoders installed in `registry` take precedence; formats without one
+/// fall through to the built-in handling, which today is empty — the
+/// crate has no image dependency — and reports the format as undecodable.
+pub fn decode_material_image(
+    registry: &TextureDecoderRegistry,
+    format: ImageFormat,
+    bytes: &[u8],
+) -> Result<DecodedImage> {
+    if let Some(decoder) = registry.decoders.get(&format) {
+        return decoder.decode(bytes);
+    }
+    Err(I3SError::Decode(format!(
+        "no texture decoder registered for {format:?}"
+    )))
+}
+
+/// What a cached transcode was produced for; part of the cache key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TranscodeTarget {
+    /// Plain 8-bit RGBA pixels.
+    Rgba8,
+    /// BC7 blocks for desktop GPUs.
+    Bc7,
+    /// ETC2 blocks for mobile GPUs.
+    Etc2,
+    /// 4x4 ASTC blocks.
+    Astc4x4,
+}
+
+/// The output of one texture transcode, as stored in a [`TranscodeCache`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscodedTexture {
+    pub width: u32,
+    pub height: u32,
+    /// What `data` is encoded as.
+    pub target: TranscodeTarget,
+    pub data: Vec<u8>,
+}
+
+impl From<DecodedImage> for TranscodedTexture {
+    fn from(image: DecodedImage) -> Self {
+        Self {
+            width: image.width,
+            height: image.height,
+            target: TranscodeTarget::Rgba8,
+            data: image.rgba,
+        }
+    }
+}
+
+/// In-memory cache of transcoded textures, keyed by resource URI and
+/// [`TranscodeTarget`].
+///
+/// Transcoding KTX2/Basis is the expensive step of the texture pipeline;
+/// a viewer revisiting nodes as the camera orbits pays it repeatedly
+/// unless the result is kept. The cache is thread-safe and evicts
+/// least-recently-used entries against an optional byte budget (weights
+/// are the transcoded payload sizes), like the layer's resource caches.
+#[derive(Default)]
+pub struct TranscodeCache {
+    cache: crate::cache::BoundedCache<(String, TranscodeTarget), TranscodedTexture>,
+}
+
+impl TranscodeCache {
+    /// An unbounded cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A cache evicting least-recently-used entries past `max_bytes`.
+    pub fn with_budget(max_bytes: u64) -> Self {
+        let cache = Self::new();
+        cache.cache.set_budget(Some(max_bytes));
+        cache
+    }
+
+    /// Change or lift the byte budget.
+    pub fn set_budget(&self, max_bytes: Option<u64>) {
+        self.cache.set_budget(max_bytes);
+    }
+
+    /// The transcode of `uri` for `target`, running (and keeping)
+    /// `transcode` only on a miss.
+    pub fn get_or_transcode(
+        &self,
+        uri: &str,
+        target: TranscodeTarget,
+        transcode: impl FnOnce() -> Result<TranscodedTexture>,
+    ) -> Result<Arc<TranscodedTexture>> {
+        let key = (uri.to_string(), target);
+        if let Some(hit) = self.cache.get(&key) {
+            return Ok(hit);
+        }
+        let texture = Arc::new(transcode()?);
+        self.cache
+            .insert(key, texture.data.len() as u64, Arc::clone(&texture));
+        Ok(texture)
+    }
+
+    /// Decode `bytes` to RGBA through `registry`, reusing a cached
+    /// result for `uri` when one exists.
+    pub fn decode_rgba(
+        &self,
+        registry: &TextureDecoderRegistry,
+        format: ImageFormat,
+        bytes: &[u8],
+        uri: &str,
+    ) -> Result<Arc<TranscodedTexture>> {
+        self.get_or_transcode(uri, TranscodeTarget::Rgba8, || {
+            decode_material_image(registry, format, bytes).map(TranscodedTexture::from)
+        })
+    }
+
+    /// Number of cached transcodes.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Total bytes of cached transcoded payloads.
+    pub fn total_bytes(&self) -> u64 {
+        self.cache.total_weight()
+    }
+}
+
+/// Transparently decompress a gzip-wrapped resource.
+pub(crate) fn maybe_ungzip(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
+        use std::io::Read;
+        let mut out = Vec::new();
+        flate2::read::GzDecoder::new(&bytes[..])
+            .read_to_end(&mut out)
+            .map_err(|e| I3SError::Decode(format!("gzip decode failed: {e}")))?;
+        Ok(out)
+    } else {
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defn::GeometryBuffer;
+
+    fn float_attr(component: usize) -> GeometryAttribute {
+        GeometryAttribute {
+            value_type: "Float32".to_string(),
+            component,
+            encoding: None,
+            binding: Some("per-vertex".to_string()),
+        }
+    }
+
+    #[test]
+    fn decoders_build_for_every_profile() {
+        // Opening a layer of any profile must not abort; unsupported
+        // decode paths surface as typed errors instead.
+        let decoder = ResourceDecoder::new(Profile::Points);
+        let definition = GeometryDefinition {
+            topology: None,
+            geometry_buffers: Vec::new(),
+        };
+        let err = decoder.decode_geometry(&[], &definition, 0, 0).unwrap_err();
+        assert!(matches!(err, I3SError::UnsupportedProfile(Profile::Points)));
+        let err = decoder.decode_points(&[], 0).unwrap_err();
+        assert!(matches!(err, I3SError::UnsupportedProfile(Profile::Points)));
+    }
+
+    #[test]
+    fn decode_positions_only() {
+        let buffer = GeometryBuffer {
+            position: Some(float_attr(3)),
+            ..Default::default()
+        };
+        let verts: Vec<f32> = vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        let bytes: Vec<u8> = verts.iter().flat_map(|f| f.to_le_bytes()).collect();
+        let decoded = decode_uncompressed(&bytes, &buffer, 2, 0).unwrap();
+        assert_eq!(decoded.positions, verts);
+        assert!(decoded.normals.is_empty());
+    }
+
+    #[test]
+    fn vertex_transform_applies_during_decode() {
+        let definition = GeometryDefinition {
+            topology: Some("triangle".to_string()),
+            geometry_buffers: vec![GeometryBuffer {
+                position: Some(float_attr(3)),
+                ..Default::default()
+            }],
+        };
+        let verts: Vec<f32> = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let bytes: Vec<u8> = verts.iter().flat_map(|f| f.to_le_bytes()).collect();
+
+        // An RTC shift plus unit scale, as a conversion pipeline would use.
+        let decoder = ResourceDecoder::new(Profile::MeshPyramids).with_vertex_transform(
+            |position: &mut [f64; 3]| {
+                for value in position.iter_mut() {
+                    *value = *value * 2.0 + 10.0;
+                }
+            },
+        );
+        let decoded = decoder.decode_geometry(&bytes, &definition, 2, 0).unwrap();
+        assert_eq!(decoded.positions, vec![12.0, 14.0, 16.0, 18.0, 20.0, 22.0]);
+
+        // Without a transform the decoder leaves positions untouched.
+        let plain = ResourceDecoder::new(Profile::MeshPyramids)
+            .decode_geometry(&bytes, &definition, 2, 0)
+            .unwrap();
+        assert_eq!(plain.positions, verts);
+    }
+
+    #[test]
+    fn layout_resolves_offsets_in_decode_order() {
+        let definition = GeometryDefinition {
+            topology: Some("triangle".to_string()),
+            geometry_buffers: vec![GeometryBuffer {
+                position: Some(float_attr(3)),
+                normal: Some(float_attr(3)),
+                feature_id: Some(GeometryAttribute {
+                    value_type: "UInt64".to_string(),
+                    component: 1,
+                    encoding: None,
+                    binding: Some("per-feature".to_string()),
+                }),
+                ..Default::default()
+            }],
+        };
+        let layout = geometry_layout(&definition).unwrap();
+        let resolved = layout.buffers[0].resolve(2, 1).unwrap();
+        let blocks: Vec<(&str, usize, usize)> = resolved
+            .iter()
+            .map(|block| (block.name, block.offset, block.length))
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                ("position", 0, 24),
+                ("normal", 24, 24),
+                ("featureId", 48, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn compressed_layout_has_no_byte_offsets() {
+        let definition = GeometryDefinition {
+            topology: None,
+            geometry_buffers: vec![GeometryBuffer {
+                compressed_attributes: Some(crate::defn::CompressedAttributes {
+                    encoding: "draco".to_string(),
+                    attributes: vec!["position".to_string()],
+                }),
+                ..Default::default()
+            }],
+        };
+        let layout = geometry_layout(&definition).unwrap();
+        assert!(matches!(&layout.buffers[0], BufferLayout::Compressed { encoding, .. } if encoding == "draco"));
+        assert!(layout.buffers[0].resolve(10, 0).is_err());
+    }
+
+    #[test]
+    fn short_buffer_is_an_error() {
+        let buffer = GeometryBuffer {
+            position: Some(float_attr(3)),
+            ..Default::default()
+        };
+        assert!(decode_uncompressed(&[0u8; 4], &buffer, 2, 0).is_err());
+    }
+
+    #[test]
+    fn atlas_contradictions_are_reported() {
+        let flag_without_regions = resolve_atlas(Some(true), false, AtlasHandling::HonorFlag);
+        assert!(flag_without_regions.contradiction);
+        assert!(!flag_without_regions.use_regions);
+
+        let regions_without_flag = resolve_atlas(None, true, AtlasHandling::HonorFlag);
+        assert!(regions_without_flag.contradiction);
+        assert!(!regions_without_flag.use_regions);
+
+        let forced = resolve_atlas(None, true, AtlasHandling::ForceEnable);
+        assert!(forced.use_regions);
+
+        let agreed = resolve_atlas(Some(true), true, AtlasHandling::HonorFlag);
+        assert!(!agreed.contradiction);
+        assert!(agreed.use_regions);
+    }
+
+    #[test]
+    fn registered_geometry_codecs_decode_compressed_buffers() {
+        let definition = GeometryDefinition {
+            topology: Some("triangle".to_string()),
+            geometry_buffers: vec![GeometryBuffer {
+                compressed_attributes: Some(crate::defn::CompressedAttributes {
+                    encoding: "vendor-lz".to_string(),
+                    attributes: vec!["position".to_string()],
+                }),
+                ..Default::default()
+            }],
+        };
+        let plain = ResourceDecoder::new(Profile::MeshPyramids);
+        let err = plain.decode_geometry(&[], &definition, 1, 0).unwrap_err();
+        assert!(err.to_string().contains("vendor-lz"));
+
+        // A stand-in codec: every vertex at the origin.
+        let mut registry = GeometryCodecRegistry::new();
+        registry.register(
+            "vendor-lz",
+            |_bytes: &[u8], attributes: &[String], vertex_count: usize, _features: usize| {
+                assert_eq!(attributes, ["position".to_string()]);
+                Ok(DecodedGeometry {
+                    vertex_count,
+                    positions: vec![0.0; vertex_count * 3],
+                    ..Default::default()
+                })
+            },
+        );
+        let decoder = ResourceDecoder::new(Profile::MeshPyramids)
+            .with_geometry_codecs(std::sync::Arc::new(registry));
+        let decoded = decoder.decode_geometry(&[], &definition, 2, 0).unwrap();
+        assert_eq!(decoded.positions, vec![0.0; 6]);
+    }
+
+    #[test]
+    fn registered_texture_decoders_take_precedence() {
+        let mut registry = TextureDecoderRegistry::new();
+        assert!(!registry.supports(ImageFormat::Png));
+        assert!(decode_material_image(&registry, ImageFormat::Png, &[]).is_err());
+
+        // A stand-in for a real transcoder: one white pixel, any input.
+        registry.register(ImageFormat::Png, |_bytes: &[u8]| {
+            Ok(DecodedImage {
+                width: 1,
+                height: 1,
+                rgba: vec![255; 4],
+            })
+        });
+        assert!(registry.supports(ImageFormat::Png));
+        let image = decode_material_image(&registry, ImageFormat::Png, &[1, 2, 3]).unwrap();
+        assert_eq!((image.width, image.height), (1, 1));
+        assert_eq!(image.rgba, vec![255; 4]);
+        assert!(decode_material_image(&registry, ImageFormat::Dds, &[]).is_err());
+    }
+
+    #[test]
+    fn texture_selection_honors_preference() {
+        use crate::defn::{MaterialTexture, PbrMetallicRoughness, TextureFormat};
+        let material = MaterialDefinition {
+            pbr_metallic_roughness: Some(PbrMetallicRoughness {
+                base_color_texture: Some(MaterialTexture {
+                    texture_set_definition_id: 0,
+                    tex_coord: None,
+                    factor: None,
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let set = TextureSetDefinition {
+            formats: vec![
+                TextureFormat {
+                    name: "0".to_string(),
+                    format: ImageFormat::Jpg,
+                },
+                TextureFormat {
+                    name: "0_0_1".to_string(),
+                    format: ImageFormat::Ktx2,
+                },
+            ],
+            atlas: None,
+        };
+
+        let plain = decode_material(&material, Some(&set), &TexturePreference::default()).unwrap();
+        assert_eq!(plain.texture.unwrap().format, ImageFormat::Jpg);
+
+        let gpu = TexturePreference {
+            supports_basis: true,
+            ..Default::default()
+        };
+        let picked = decode_material(&material, Some(&set), &gpu).unwrap();
+        assert_eq!(picked.texture.unwrap().format, ImageFormat::Ktx2);
+    }
+
+    #[test]
+    fn transcode_cache_runs_each_transcode_once_per_target() {
+        let runs = std::sync::atomic::AtomicUsize::new(0);
+        let transcode = |target: TranscodeTarget| {
+            runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(TranscodedTexture {
+                width: 2,
+                height: 2,
+                target,
+                data: vec![0; 16],
+            })
+        };
+
+        let cache = TranscodeCache::new();
+        let uri = "nodes/3/textures/0_0_1";
+        let first = cache
+            .get_or_transcode(uri, TranscodeTarget::Rgba8, || {
+                transcode(TranscodeTarget::Rgba8)
+            })
+            .unwrap();
+        let again = cache
+            .get_or_transcode(uri, TranscodeTarget::Rgba8, || {
+                transcode(TranscodeTarget::Rgba8)
+            })
+            .unwrap();
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(first, again);
+
+        // Another target for the same resource is its own entry.
+        cache
+            .get_or_transcode(uri, TranscodeTarget::Bc7, || {
+                transcode(TranscodeTarget::Bc7)
+            })
+            .unwrap();
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.total_bytes(), 32);
+
+        // A failed transcode caches nothing.
+        let err = cache.get_or_transcode("nodes/9/textures/0", TranscodeTarget::Rgba8, || {
+            Err(I3SError::Decode("bad ktx2".to_string()))
+        });
+        assert!(err.is_err());
+        assert_eq!(cache.len(), 2);
+
+        // The RGBA path goes through the decoder registry.
+        let mut registry = TextureDecoderRegistry::new();
+        registry.register(ImageFormat::Ktx2, |_: &[u8]| {
+            Ok(DecodedImage {
+                width: 1,
+                height: 1,
+                rgba: vec![1, 2, 3, 4],
+            })
+        });
+        let rgba = cache
+            .decode_rgba(&registry, ImageFormat::Ktx2, b"ktx2", "nodes/4/textures/0_0_1")
+            .unwrap();
+        assert_eq!(rgba.target, TranscodeTarget::Rgba8);
+        assert_eq!(rgba.data, vec![1, 2, 3, 4]);
+
+        // A tight budget evicts the least-recently-used entries.
+        cache.set_budget(Some(16));
+        cache
+            .get_or_transcode("nodes/5/textures/0", TranscodeTarget::Rgba8, || {
+                transcode(TranscodeTarget::Rgba8)
+            })
+            .unwrap();
+        assert!(cache.total_bytes() <= 16);
+    }
+}
@@ -0,0 +1,659 @@
+//! Typed representation of the `3dSceneLayer` document and its sub-definitions.
+
+use serde::{Deserialize, Serialize};
+
+use crate::err::{I3SError, Result};
+
+/// The kind of scene layer, from the `layerType` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LayerType {
+    IntegratedMesh,
+    #[serde(rename = "3DObject")]
+    Object3D,
+    PointCloud,
+    Point,
+    Building,
+}
+
+/// The store profile, which determines how node resources are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    MeshPyramids,
+    #[serde(rename = "pointclouds")]
+    PointClouds,
+    Points,
+    Building,
+}
+
+/// Layer extent in the horizontal CRS of the layer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Extent {
+    pub xmin: f64,
+    pub ymin: f64,
+    pub xmax: f64,
+    pub ymax: f64,
+}
+
+/// Spatial reference of the layer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SpatialReference {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wkid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_wkid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vcs_wkid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_vcs_wkid: Option<u32>,
+}
+
+/// The `store` object of a scene layer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Store {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub profile: Profile,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extent: Option<[f64; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normal_reference_frame: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lod_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lod_model: Option<String>,
+}
+
+/// Layer-level node page layout, from the `nodePages` property.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodePageDefinition {
+    pub nodes_per_page: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lod_selection_metric_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_index: Option<usize>,
+}
+
+impl NodePageDefinition {
+    fn nodes_per_page_checked(&self) -> Result<usize> {
+        if self.nodes_per_page == 0 {
+            Err(I3SError::Validation("nodesPerPage is zero".to_string()))
+        } else {
+            Ok(self.nodes_per_page)
+        }
+    }
+
+    /// The node page holding `node_index`.
+    pub fn page_index(&self, node_index: usize) -> Result<usize> {
+        Ok(node_index / self.nodes_per_page_checked()?)
+    }
+
+    /// The position of `node_index` within its node page.
+    pub fn index_in_page(&self, node_index: usize) -> Result<usize> {
+        Ok(node_index % self.nodes_per_page_checked()?)
+    }
+
+    /// The node indices covered by the page with the given page index.
+    pub fn page_range(&self, page_index: usize) -> Result<std::ops::Range<usize>> {
+        let per_page = self.nodes_per_page_checked()?;
+        let start = page_index.checked_mul(per_page).ok_or_else(|| {
+            I3SError::Validation(format!("node page index {page_index} overflows"))
+        })?;
+        let end = start.checked_add(per_page).ok_or_else(|| {
+            I3SError::Validation(format!("node page index {page_index} overflows"))
+        })?;
+        Ok(start..end)
+    }
+}
+
+/// One vertex attribute declaration inside a geometry buffer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeometryAttribute {
+    #[serde(rename = "type")]
+    pub value_type: String,
+    pub component: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binding: Option<String>,
+}
+
+/// Draco (or other) compressed vertex attribute declaration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressedAttributes {
+    pub encoding: String,
+    pub attributes: Vec<String>,
+}
+
+/// One buffer layout of a geometry definition. Buffer 0 is uncompressed,
+/// buffer 1 (when present) is the compressed variant.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeometryBuffer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<GeometryAttribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normal: Option<GeometryAttribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv0: Option<GeometryAttribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<GeometryAttribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_id: Option<GeometryAttribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub face_range: Option<GeometryAttribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv_region: Option<GeometryAttribute>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressed_attributes: Option<CompressedAttributes>,
+}
+
+/// One entry of `geometryDefinitions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeometryDefinition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology: Option<String>,
+    pub geometry_buffers: Vec<GeometryBuffer>,
+}
+
+/// Encoded image format of a texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Jpg,
+    Png,
+    Dds,
+    Ktx2,
+    Basis,
+    #[serde(rename = "ktx-etc2")]
+    KtxEtc2,
+}
+
+/// One texture format inside a texture set definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureFormat {
+    pub name: String,
+    pub format: ImageFormat,
+}
+
+impl TextureFormat {
+    /// Check the resource name against the SLPK naming convention.
+    ///
+    /// Texture resource names are digit groups separated by underscores
+    /// (`"0"`, `"0_0_1"`); anything else produces paths readers will not
+    /// find.
+    pub fn validate(&self) -> Result<()> {
+        let well_formed = !self.name.is_empty()
+            && self
+                .name
+                .split('_')
+                .all(|group| !group.is_empty() && group.bytes().all(|b| b.is_ascii_digit()));
+        if well_formed {
+            Ok(())
+        } else {
+            Err(I3SError::Validation(format!(
+                "texture name {:?} is not digit groups separated by underscores",
+                self.name
+            )))
+        }
+    }
+}
+
+/// One entry of `textureSetDefinitions`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureSetDefinition {
+    pub formats: Vec<TextureFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atlas: Option<bool>,
+}
+
+/// PBR metallic-roughness parameters of a material.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PbrMetallicRoughness {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_color_factor: Option<[f64; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_color_texture: Option<MaterialTexture>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metallic_factor: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roughness_factor: Option<f64>,
+}
+
+/// A reference from a material to a texture set definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialTexture {
+    pub texture_set_definition_id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tex_coord: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factor: Option<f64>,
+}
+
+/// One entry of `materialDefinitions`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialDefinition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pbr_metallic_roughness: Option<PbrMetallicRoughness>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normal_texture: Option<MaterialTexture>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alpha_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alpha_cutoff: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub double_sided: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cull_face: Option<String>,
+}
+
+/// One attribute field of the layer schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+/// Header entry of an attribute storage declaration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeHeader {
+    pub property: String,
+    pub value_type: String,
+}
+
+/// Per-value layout of an attribute storage declaration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValues {
+    pub value_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values_per_element: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+/// One entry of `attributeStorageInfo`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeStorageInfo {
+    pub key: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub header: Vec<AttributeHeader>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ordering: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute_values: Option<AttributeValues>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute_byte_counts: Option<AttributeValues>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_ids: Option<AttributeValues>,
+}
+
+/// The `heightModelInfo` object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeightModelInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height_model: Option<String>,
+    #[serde(rename = "vertCRS", skip_serializing_if = "Option::is_none")]
+    pub vert_crs: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height_unit: Option<String>,
+}
+
+/// The `serviceUpdateTimeStamp` object: when the hosted service last
+/// rebuilt the layer's cache. SLPK archives carry no update metadata.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceUpdateTimeStamp {
+    /// Milliseconds since the Unix epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_update: Option<u64>,
+}
+
+/// The `editingInfo` object of an editable hosted layer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditingInfo {
+    /// Milliseconds since the Unix epoch of the last applied edit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_edit_date: Option<u64>,
+}
+
+/// The top-level `3dSceneLayer` document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneDefinition {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub layer_type: LayerType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copyright_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spatial_reference: Option<SpatialReference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_extent: Option<Extent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height_model_info: Option<HeightModelInfo>,
+    pub store: Store,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_pages: Option<NodePageDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub geometry_definitions: Vec<GeometryDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub texture_set_definitions: Vec<TextureSetDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub material_definitions: Vec<MaterialDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attribute_storage_info: Vec<AttributeStorageInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drawing_info: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_update_time_stamp: Option<ServiceUpdateTimeStamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editing_info: Option<EditingInfo>,
+}
+
+impl SceneDefinition {
+    /// Parse a `3dSceneLayer` document from raw JSON bytes.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes).map_err(|e| I3SError::json("3dSceneLayer.json", e))
+    }
+
+    /// The store profile of the layer.
+    pub fn profile(&self) -> Profile {
+        self.store.profile
+    }
+
+    /// Start building a definition from scratch.
+    pub fn builder(layer_type: LayerType, profile: Profile) -> SceneDefinitionBuilder {
+        SceneDefinitionBuilder::new(layer_type, profile)
+    }
+}
+
+/// Fluent construction of a spec-compliant [`SceneDefinition`].
+///
+/// [`build`](Self::build) fills in the required store fields, and validates
+/// cross-references (material texture set ids, geometry buffer layouts)
+/// before handing out a definition, so broken documents are caught at
+/// authoring time rather than by a consumer.
+pub struct SceneDefinitionBuilder {
+    defn: SceneDefinition,
+}
+
+impl SceneDefinitionBuilder {
+    pub fn new(layer_type: LayerType, profile: Profile) -> Self {
+        Self {
+            defn: SceneDefinition {
+                id: 0,
+                name: None,
+                version: None,
+                layer_type,
+                alias: None,
+                description: None,
+                copyright_text: None,
+                capabilities: None,
+                spatial_reference: None,
+                full_extent: None,
+                height_model_info: None,
+                store: Store {
+                    id: None,
+                    profile,
+                    version: Some("1.8".to_string()),
+                    extent: None,
+                    normal_reference_frame: None,
+                    lod_type: None,
+                    lod_model: None,
+                },
+                node_pages: Some(NodePageDefinition {
+                    nodes_per_page: 64,
+                    lod_selection_metric_type: Some("maxScreenThresholdSQ".to_string()),
+                    root_index: Some(0),
+                }),
+                geometry_definitions: Vec::new(),
+                texture_set_definitions: Vec::new(),
+                material_definitions: Vec::new(),
+                fields: Vec::new(),
+                attribute_storage_info: Vec::new(),
+                drawing_info: None,
+                service_update_time_stamp: None,
+                editing_info: None,
+            },
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.defn.name = Some(name.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.defn.description = Some(description.into());
+        self
+    }
+
+    pub fn copyright_text(mut self, text: impl Into<String>) -> Self {
+        self.defn.copyright_text = Some(text.into());
+        self
+    }
+
+    pub fn spatial_reference(mut self, sr: SpatialReference) -> Self {
+        self.defn.spatial_reference = Some(sr);
+        self
+    }
+
+    pub fn full_extent(mut self, extent: Extent) -> Self {
+        self.defn.full_extent = Some(extent);
+        self
+    }
+
+    pub fn height_model_info(mut self, info: HeightModelInfo) -> Self {
+        self.defn.height_model_info = Some(info);
+        self
+    }
+
+    pub fn nodes_per_page(mut self, nodes_per_page: usize) -> Self {
+        if let Some(pages) = &mut self.defn.node_pages {
+            pages.nodes_per_page = nodes_per_page;
+        }
+        self
+    }
+
+    pub fn geometry_definition(mut self, definition: GeometryDefinition) -> Self {
+        self.defn.geometry_definitions.push(definition);
+        self
+    }
+
+    pub fn texture_set_definition(mut self, definition: TextureSetDefinition) -> Self {
+        self.defn.texture_set_definitions.push(definition);
+        self
+    }
+
+    pub fn material_definition(mut self, definition: MaterialDefinition) -> Self {
+        self.defn.material_definitions.push(definition);
+        self
+    }
+
+    pub fn field(mut self, field: Field) -> Self {
+        self.defn.fields.push(field);
+        self
+    }
+
+    pub fn attribute_storage_info(mut self, info: AttributeStorageInfo) -> Self {
+        self.defn.attribute_storage_info.push(info);
+        self
+    }
+
+    fn check_texture_ref(&self, texture: &MaterialTexture, what: &str) -> Result<()> {
+        let count = self.defn.texture_set_definitions.len();
+        if texture.texture_set_definition_id >= count {
+            return Err(I3SError::Validation(format!(
+                "{what} references textureSetDefinition {} but only {count} are defined",
+                texture.texture_set_definition_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validate cross-references and hand out the definition.
+    pub fn build(self) -> Result<SceneDefinition> {
+        let pages = self
+            .defn
+            .node_pages
+            .as_ref()
+            .expect("builder always sets nodePages");
+        if pages.nodes_per_page == 0 {
+            return Err(I3SError::Validation(
+                "nodesPerPage must be greater than zero".to_string(),
+            ));
+        }
+        for (i, material) in self.defn.material_definitions.iter().enumerate() {
+            if let Some(pbr) = &material.pbr_metallic_roughness {
+                if let Some(texture) = &pbr.base_color_texture {
+                    self.check_texture_ref(texture, &format!("material {i} baseColorTexture"))?;
+                }
+            }
+            if let Some(texture) = &material.normal_texture {
+                self.check_texture_ref(texture, &format!("material {i} normalTexture"))?;
+            }
+        }
+        for (i, geometry) in self.defn.geometry_definitions.iter().enumerate() {
+            let Some(first) = geometry.geometry_buffers.first() else {
+                return Err(I3SError::Validation(format!(
+                    "geometry definition {i} declares no buffers"
+                )));
+            };
+            if first.position.is_none() {
+                return Err(I3SError::Validation(format!(
+                    "geometry definition {i} buffer 0 has no position attribute"
+                )));
+            }
+        }
+        for (i, set) in self.defn.texture_set_definitions.iter().enumerate() {
+            if set.formats.is_empty() {
+                return Err(I3SError::Validation(format!(
+                    "texture set definition {i} declares no formats"
+                )));
+            }
+        }
+        Ok(self.defn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_arithmetic_is_checked() {
+        let pages = NodePageDefinition {
+            nodes_per_page: 64,
+            lod_selection_metric_type: None,
+            root_index: None,
+        };
+        assert_eq!(pages.page_index(65).unwrap(), 1);
+        assert_eq!(pages.index_in_page(65).unwrap(), 1);
+        assert_eq!(pages.page_range(1).unwrap(), 64..128);
+
+        let broken = NodePageDefinition {
+            nodes_per_page: 0,
+            lod_selection_metric_type: None,
+            root_index: None,
+        };
+        assert!(broken.page_index(1).is_err());
+        assert!(pages.page_range(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn update_timestamps_parse_when_present() {
+        let defn = SceneDefinition::from_slice(
+            br#"{
+                "id": 0,
+                "layerType": "IntegratedMesh",
+                "store": { "profile": "meshpyramids" },
+                "serviceUpdateTimeStamp": { "lastUpdate": 1700000000000 },
+                "editingInfo": { "lastEditDate": 1690000000000 }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            defn.service_update_time_stamp.unwrap().last_update,
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(
+            defn.editing_info.unwrap().last_edit_date,
+            Some(1_690_000_000_000)
+        );
+
+        let bare = SceneDefinition::from_slice(
+            br#"{"id": 0, "layerType": "IntegratedMesh", "store": {"profile": "meshpyramids"}}"#,
+        )
+        .unwrap();
+        assert!(bare.service_update_time_stamp.is_none());
+        assert!(bare.editing_info.is_none());
+    }
+
+    #[test]
+    fn builder_produces_valid_definition() {
+        let defn = SceneDefinition::builder(LayerType::IntegratedMesh, Profile::MeshPyramids)
+            .name("campus")
+            .nodes_per_page(16)
+            .build()
+            .unwrap();
+        assert_eq!(defn.name.as_deref(), Some("campus"));
+        assert_eq!(defn.node_pages.unwrap().nodes_per_page, 16);
+    }
+
+    #[test]
+    fn builder_rejects_dangling_texture_reference() {
+        let material = MaterialDefinition {
+            pbr_metallic_roughness: Some(PbrMetallicRoughness {
+                base_color_texture: Some(MaterialTexture {
+                    texture_set_definition_id: 3,
+                    tex_coord: None,
+                    factor: None,
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let err = SceneDefinition::builder(LayerType::Object3D, Profile::MeshPyramids)
+            .material_definition(material)
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("textureSetDefinition 3"));
+    }
+}
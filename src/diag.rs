@@ -0,0 +1,287 @@
+//! Opt-in diagnostics for slow resource fetches and decodes.
+//!
+//! A single oversized texture or a slow origin can stall an entire
+//! traversal without anything obviously failing. The [`SlowResourceDetector`]
+//! wraps fetch/decode work, records every operation that exceeds a
+//! threshold, and optionally forwards each finding to a handler as it
+//! happens.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::err::Result;
+use crate::rm::Accessor;
+
+/// One operation that exceeded the configured threshold.
+#[derive(Debug, Clone)]
+pub struct SlowResource {
+    /// URI (or description) of the resource involved.
+    pub uri: String,
+    /// What was being done: `"fetch"`, `"decode"`, ...
+    pub operation: &'static str,
+    pub elapsed: Duration,
+}
+
+type SlowHandler = Box<dyn Fn(&SlowResource) + Send + Sync>;
+
+/// Flags resources whose fetch or decode exceeds a threshold.
+pub struct SlowResourceDetector {
+    threshold: Duration,
+    flagged: Mutex<Vec<SlowResource>>,
+    handler: Option<SlowHandler>,
+}
+
+impl SlowResourceDetector {
+    /// Record operations slower than `threshold`.
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            flagged: Mutex::new(Vec::new()),
+            handler: None,
+        }
+    }
+
+    /// Additionally invoke `handler` for each slow operation as it is found.
+    pub fn with_handler(
+        threshold: Duration,
+        handler: impl Fn(&SlowResource) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            threshold,
+            flagged: Mutex::new(Vec::new()),
+            handler: Some(Box::new(handler)),
+        }
+    }
+
+    /// Time `work`, flagging it when it exceeds the threshold.
+    pub fn observe<T>(&self, uri: &str, operation: &'static str, work: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let out = work();
+        let elapsed = start.elapsed();
+        if elapsed >= self.threshold {
+            let record = SlowResource {
+                uri: uri.to_string(),
+                operation,
+                elapsed,
+            };
+            if let Some(handler) = &self.handler {
+                handler(&record);
+            }
+            self.flagged
+                .lock()
+                .expect("detector lock poisoned")
+                .push(record);
+        }
+        out
+    }
+
+    /// Fetch a resource while watching its timing.
+    pub fn get<A: Accessor>(&self, accessor: &A, uri: &str) -> Result<std::sync::Arc<Vec<u8>>> {
+        self.observe(uri, "fetch", || accessor.get(uri))
+    }
+
+    /// All operations flagged so far.
+    pub fn flagged(&self) -> Vec<SlowResource> {
+        self.flagged.lock().expect("detector lock poisoned").clone()
+    }
+}
+
+/// How many of the slowest fetches a session keeps.
+const SLOWEST_TRACKED: usize = 8;
+
+/// How many error messages a session keeps verbatim.
+const ERROR_SAMPLES: usize = 16;
+
+/// Accumulated fetch statistics of one layer session.
+///
+/// Enabled with
+/// [`SceneLayer::with_session_recording`](crate::SceneLayer::with_session_recording);
+/// every fetch that goes through the layer's backend is counted here.
+/// Snapshot the totals at any point with [`SessionStats::report`].
+#[derive(Default)]
+pub struct SessionStats {
+    requests: AtomicU64,
+    bytes: AtomicU64,
+    errors: AtomicU64,
+    uris: Mutex<HashSet<String>>,
+    error_samples: Mutex<Vec<String>>,
+    slowest: Mutex<Vec<(String, Duration)>>,
+}
+
+impl SessionStats {
+    pub(crate) fn record(
+        &self,
+        uri: &str,
+        elapsed: Duration,
+        outcome: &Result<Arc<Vec<u8>>>,
+    ) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.uris
+            .lock()
+            .expect("session lock poisoned")
+            .insert(uri.to_string());
+        match outcome {
+            Ok(bytes) => {
+                self.bytes.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+            }
+            Err(err) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                let mut samples = self.error_samples.lock().expect("session lock poisoned");
+                if samples.len() < ERROR_SAMPLES {
+                    samples.push(format!("{uri}: {err}"));
+                }
+            }
+        }
+        let mut slowest = self.slowest.lock().expect("session lock poisoned");
+        slowest.push((uri.to_string(), elapsed));
+        slowest.sort_by_key(|entry| std::cmp::Reverse(entry.1));
+        slowest.truncate(SLOWEST_TRACKED);
+    }
+
+    /// A snapshot of everything recorded so far.
+    pub fn report(&self) -> SessionReport {
+        let resources_fetched = self.requests.load(Ordering::Relaxed);
+        let unique_resources = self.uris.lock().expect("session lock poisoned").len() as u64;
+        // The backends memoize fetched resources by URI, so a repeated
+        // request never reaches the wire (or the archive) again.
+        let cache_hits = resources_fetched - unique_resources;
+        SessionReport {
+            resources_fetched,
+            unique_resources,
+            cache_hits,
+            cache_hit_rate: if resources_fetched == 0 {
+                0.0
+            } else {
+                cache_hits as f64 / resources_fetched as f64
+            },
+            bytes_fetched: self.bytes.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            error_samples: self
+                .error_samples
+                .lock()
+                .expect("session lock poisoned")
+                .clone(),
+            slowest: self
+                .slowest
+                .lock()
+                .expect("session lock poisoned")
+                .iter()
+                .map(|(uri, elapsed)| SlowFetch {
+                    uri: uri.clone(),
+                    millis: elapsed.as_secs_f64() * 1000.0,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A serializable summary of one layer session, for attaching to batch-job
+/// logs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionReport {
+    /// Fetches issued through the layer, including repeats.
+    pub resources_fetched: u64,
+    /// Distinct URIs fetched.
+    pub unique_resources: u64,
+    /// Repeated fetches answered by the backend's in-memory cache.
+    pub cache_hits: u64,
+    /// `cache_hits / resources_fetched`, or zero for an idle session.
+    pub cache_hit_rate: f64,
+    /// Payload bytes of all successful fetches.
+    pub bytes_fetched: u64,
+    pub errors: u64,
+    /// The first few error messages, verbatim.
+    pub error_samples: Vec<String>,
+    /// The slowest fetches, slowest first.
+    pub slowest: Vec<SlowFetch>,
+}
+
+/// One entry of [`SessionReport::slowest`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowFetch {
+    pub uri: String,
+    pub millis: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_only_slow_operations() {
+        let detector = SlowResourceDetector::new(Duration::from_millis(5));
+        detector.observe("fast", "decode", || ());
+        detector.observe("slow", "decode", || {
+            std::thread::sleep(Duration::from_millis(10))
+        });
+        let flagged = detector.flagged();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].uri, "slow");
+        assert!(flagged[0].elapsed >= Duration::from_millis(5));
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn session_report_counts_fetches_hits_and_errors() {
+        use crate::rm::UriBuilder;
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("diag");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path)
+            .unwrap()
+            .with_session_recording();
+        layer.root().unwrap();
+        let rm = layer.resource_manager();
+        let uri = rm.geometry_uri(0, 0);
+        rm.get(&uri).unwrap();
+        rm.get(&uri).unwrap();
+        assert!(rm.get("nodes/9/geometries/0").is_err());
+
+        let report = layer.session_report().unwrap();
+        assert_eq!(report.resources_fetched, 4);
+        assert_eq!(report.unique_resources, 3);
+        assert_eq!(report.cache_hits, 1);
+        assert_eq!(report.cache_hit_rate, 0.25);
+        assert!(report.bytes_fetched >= 3);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.error_samples.len(), 1);
+        assert_eq!(report.slowest.len(), 4);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["resourcesFetched"], 4);
+        assert_eq!(json["cacheHits"], 1);
+        assert!(json["slowest"][0]["millis"].is_f64());
+    }
+}
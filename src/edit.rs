@@ -0,0 +1,516 @@
+//! Editing the `3dSceneLayer` document of an existing layer.
+//!
+//! [`SceneLayer::edit_definition`] hands out a [`DefinitionEditor`] that
+//! works on a copy of the parsed definition. Presentation-level properties
+//! (name, copyright, extent, drawing info, ...) can be changed fluently and
+//! the result written back into the layer's SLPK or saved as a new package;
+//! node pages and resources are copied byte for byte.
+
+use crate::defn::{Extent, SceneDefinition};
+use crate::layer::SceneLayer;
+
+/// A pending set of edits to a layer's scene definition.
+pub struct DefinitionEditor<'a> {
+    #[cfg_attr(not(feature = "slpk"), allow(dead_code))]
+    layer: &'a SceneLayer,
+    defn: SceneDefinition,
+    #[cfg(feature = "slpk")]
+    added_fields: Vec<AddedField>,
+    #[cfg(feature = "slpk")]
+    dropped_keys: Vec<String>,
+}
+
+impl<'a> DefinitionEditor<'a> {
+    pub(crate) fn new(layer: &'a SceneLayer) -> Self {
+        Self {
+            layer,
+            defn: layer.definition().clone(),
+            #[cfg(feature = "slpk")]
+            added_fields: Vec::new(),
+            #[cfg(feature = "slpk")]
+            dropped_keys: Vec::new(),
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.defn.name = Some(name.into());
+        self
+    }
+
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.defn.alias = Some(alias.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.defn.description = Some(description.into());
+        self
+    }
+
+    pub fn copyright_text(mut self, text: impl Into<String>) -> Self {
+        self.defn.copyright_text = Some(text.into());
+        self
+    }
+
+    pub fn full_extent(mut self, extent: Extent) -> Self {
+        self.defn.full_extent = Some(extent);
+        self
+    }
+
+    pub fn capabilities(mut self, capabilities: Vec<String>) -> Self {
+        self.defn.capabilities = Some(capabilities);
+        self
+    }
+
+    pub fn drawing_info(mut self, drawing_info: serde_json::Value) -> Self {
+        self.defn.drawing_info = Some(drawing_info);
+        self
+    }
+
+    /// The definition with all edits applied so far.
+    pub fn definition(&self) -> &SceneDefinition {
+        &self.defn
+    }
+
+    /// Hand out the edited definition without persisting anything.
+    pub fn into_definition(self) -> SceneDefinition {
+        self.defn
+    }
+}
+
+/// Computed values of one attribute field, for the features of one node.
+#[cfg(feature = "slpk")]
+#[derive(Debug, Clone)]
+pub enum FieldValues {
+    Int32(Vec<i32>),
+    Float64(Vec<f64>),
+    Strings(Vec<String>),
+}
+
+#[cfg(feature = "slpk")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StorageKind {
+    Int32,
+    Float64,
+    String,
+}
+
+/// Per-node value provider of an added field.
+#[cfg(feature = "slpk")]
+type ValueProvider = Box<dyn Fn(&crate::node::Node) -> crate::err::Result<FieldValues>>;
+
+#[cfg(feature = "slpk")]
+struct AddedField {
+    key: String,
+    kind: StorageKind,
+    values: ValueProvider,
+}
+
+#[cfg(feature = "slpk")]
+impl StorageKind {
+    fn for_field_type(field_type: &str) -> crate::err::Result<Self> {
+        match field_type {
+            "esriFieldTypeOID" | "esriFieldTypeInteger" | "esriFieldTypeSmallInteger" => {
+                Ok(Self::Int32)
+            }
+            "esriFieldTypeDouble" | "esriFieldTypeSingle" => Ok(Self::Float64),
+            "esriFieldTypeString" => Ok(Self::String),
+            other => Err(crate::err::I3SError::Validation(format!(
+                "cannot derive attribute storage for field type {other}"
+            ))),
+        }
+    }
+
+    /// The `attributeStorageInfo` entry describing resources of this kind.
+    fn storage_info(self, key: &str, name: &str) -> crate::defn::AttributeStorageInfo {
+        use crate::defn::{AttributeHeader, AttributeStorageInfo, AttributeValues};
+        let count = AttributeHeader {
+            property: "count".to_string(),
+            value_type: "UInt32".to_string(),
+        };
+        match self {
+            Self::Int32 | Self::Float64 => AttributeStorageInfo {
+                key: key.to_string(),
+                name: name.to_string(),
+                header: vec![count],
+                ordering: vec!["attributeValues".to_string()],
+                attribute_values: Some(AttributeValues {
+                    value_type: if self == Self::Int32 { "Int32" } else { "Float64" }
+                        .to_string(),
+                    values_per_element: Some(1),
+                    encoding: None,
+                }),
+                attribute_byte_counts: None,
+                object_ids: None,
+            },
+            Self::String => AttributeStorageInfo {
+                key: key.to_string(),
+                name: name.to_string(),
+                header: vec![
+                    count,
+                    AttributeHeader {
+                        property: "attributeValuesByteCount".to_string(),
+                        value_type: "UInt32".to_string(),
+                    },
+                ],
+                ordering: vec![
+                    "attributeByteCounts".to_string(),
+                    "attributeValues".to_string(),
+                ],
+                attribute_values: Some(AttributeValues {
+                    value_type: "String".to_string(),
+                    values_per_element: Some(1),
+                    encoding: Some("UTF-8".to_string()),
+                }),
+                attribute_byte_counts: Some(AttributeValues {
+                    value_type: "UInt32".to_string(),
+                    values_per_element: Some(1),
+                    encoding: None,
+                }),
+                object_ids: None,
+            },
+        }
+    }
+}
+
+/// Encode computed values in the binary layout the storage info declares:
+/// a `u32` count header, then the value block (doubles padded to 8-byte
+/// alignment, strings as byte counts plus null-terminated UTF-8).
+#[cfg(feature = "slpk")]
+fn encode_field_values(kind: StorageKind, values: &FieldValues) -> crate::err::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    match (kind, values) {
+        (StorageKind::Int32, FieldValues::Int32(values)) => {
+            out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            for value in values {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        (StorageKind::Float64, FieldValues::Float64(values)) => {
+            out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            out.extend_from_slice(&[0u8; 4]);
+            for value in values {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        (StorageKind::String, FieldValues::Strings(values)) => {
+            out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            let total: usize = values.iter().map(|v| v.len() + 1).sum();
+            out.extend_from_slice(&(total as u32).to_le_bytes());
+            for value in values {
+                out.extend_from_slice(&((value.len() + 1) as u32).to_le_bytes());
+            }
+            for value in values {
+                out.extend_from_slice(value.as_bytes());
+                out.push(0);
+            }
+        }
+        _ => {
+            return Err(crate::err::I3SError::Validation(
+                "computed values do not match the declared field type".to_string(),
+            ))
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "slpk")]
+impl DefinitionEditor<'_> {
+    /// Add an attribute field computed per node, enriching the schema with
+    /// derived values (height, area, ...). `values` is called once per node
+    /// that carries attribute resources and must return one value per
+    /// feature, matching the field's declared type. The field, its
+    /// `attributeStorageInfo` entry and the per-node resources are written
+    /// on save.
+    pub fn add_field<F>(mut self, field: crate::defn::Field, values: F) -> crate::err::Result<Self>
+    where
+        F: Fn(&crate::node::Node) -> crate::err::Result<FieldValues> + 'static,
+    {
+        let kind = StorageKind::for_field_type(&field.field_type)?;
+        let next = self
+            .defn
+            .attribute_storage_info
+            .iter()
+            .filter_map(|info| info.key.strip_prefix("f_")?.parse::<usize>().ok())
+            .max()
+            .map_or(0, |max| max + 1);
+        let key = format!("f_{next}");
+        self.defn
+            .attribute_storage_info
+            .push(kind.storage_info(&key, &field.name));
+        self.defn.fields.push(field);
+        self.added_fields.push(AddedField {
+            key,
+            kind,
+            values: Box::new(values),
+        });
+        Ok(self)
+    }
+
+    /// Drop an attribute field by name, removing it from `fields`, from
+    /// `attributeStorageInfo` and (on save) its per-node resources.
+    pub fn drop_field(mut self, name: &str) -> crate::err::Result<Self> {
+        let position = self
+            .defn
+            .attribute_storage_info
+            .iter()
+            .position(|info| info.name == name)
+            .ok_or_else(|| {
+                crate::err::I3SError::Validation(format!(
+                    "no attribute storage is declared for field {name:?}"
+                ))
+            })?;
+        let info = self.defn.attribute_storage_info.remove(position);
+        self.defn.fields.retain(|field| field.name != name);
+        self.dropped_keys.push(info.key);
+        Ok(self)
+    }
+
+    /// Write a copy of the layer's package with the edited definition.
+    ///
+    /// Every entry except `3dSceneLayer.json.gz` (and the regenerated
+    /// `metadata.json`) is copied byte for byte, so node pages, geometry,
+    /// textures and attributes are untouched. Requires an SLPK-backed
+    /// layer.
+    pub fn save_as(&self, path: impl AsRef<std::path::Path>) -> crate::err::Result<()> {
+        let package = self.package()?;
+        let mut writer = crate::slpk::writer::SlpkWriter::create(path)?;
+        for name in package.entries() {
+            if name == "3dSceneLayer.json.gz" || name == "metadata.json" {
+                continue;
+            }
+            if self
+                .dropped_keys
+                .iter()
+                .any(|key| name.contains(&format!("/attributes/{key}/")))
+            {
+                continue;
+            }
+            if let Some(bytes) = package.raw_entry(&name)? {
+                writer.write_raw(&name, &bytes)?;
+            }
+        }
+        self.write_added_attributes(&mut writer)?;
+        writer.write_scene_definition(&self.defn)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Compute and write the resources of fields added with
+    /// [`add_field`](Self::add_field), for every node that carries
+    /// attribute resources.
+    fn write_added_attributes(
+        &self,
+        writer: &mut crate::slpk::writer::SlpkWriter<std::fs::File>,
+    ) -> crate::err::Result<()> {
+        if self.added_fields.is_empty() {
+            return Ok(());
+        }
+        let nodes = self.layer.nodes()?;
+        let mut stack = vec![nodes.root()?];
+        while let Some(node) = stack.pop() {
+            if let Some(attribute) = node.mesh.as_ref().and_then(|m| m.attribute.as_ref()) {
+                for added in &self.added_fields {
+                    let values = (added.values)(&node)?;
+                    let bytes = encode_field_values(added.kind, &values)?;
+                    writer.write_attribute(attribute.resource, &added.key, &bytes)?;
+                }
+            }
+            stack.extend(nodes.get_many(&node.children)?);
+        }
+        Ok(())
+    }
+
+    /// Replace the layer's SLPK on disk with the edited definition.
+    ///
+    /// The package is rewritten next to the original and renamed over it,
+    /// so a crash mid-save leaves the original intact. Packages opened
+    /// before the swap — this layer's included — keep serving a consistent
+    /// snapshot of the original archive, so a preview server can run
+    /// against the same file while it is edited; reopen the path to
+    /// observe the edits.
+    pub fn save_in_place(&self) -> crate::err::Result<()> {
+        let package = self.package()?;
+        let path = package.path().ok_or_else(|| {
+            crate::err::I3SError::Validation(
+                "in-place save needs a file-backed package".to_string(),
+            )
+        })?;
+        let tmp = path.with_extension("slpk.tmp");
+        self.save_as(&tmp)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    fn package(&self) -> crate::err::Result<&crate::slpk::SceneLayerPackage> {
+        self.layer
+            .resource_manager()
+            .slpk_package()
+            .ok_or_else(|| {
+                crate::err::I3SError::Validation(
+                    "the definition can only be saved for an SLPK-backed layer".to_string(),
+                )
+            })
+    }
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use crate::slpk::writer::SlpkWriter;
+
+    #[test]
+    fn edited_definition_round_trips_through_packages() {
+        let dir = crate::testutil::TestDir::new("edit");
+        let path = dir.join("layer.slpk");
+        let copy = dir.join("edited.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": "before",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        layer
+            .edit_definition()
+            .name("after")
+            .copyright_text("(c) survey team")
+            .full_extent(crate::defn::Extent {
+                xmin: -1.0,
+                ymin: -1.0,
+                xmax: 1.0,
+                ymax: 1.0,
+            })
+            .drawing_info(serde_json::json!({ "renderer": { "type": "simple" } }))
+            .save_as(&copy)
+            .unwrap();
+
+        // The open layer is untouched; the copy carries the edits and all
+        // of the original resources.
+        assert_eq!(layer.name(), Some("before"));
+        let edited = crate::layer::SceneLayer::open_slpk(&copy).unwrap();
+        assert_eq!(edited.name(), Some("after"));
+        assert_eq!(
+            edited.definition().copyright_text.as_deref(),
+            Some("(c) survey team")
+        );
+        assert_eq!(
+            edited.definition().drawing_info.as_ref().unwrap()["renderer"]["type"],
+            "simple"
+        );
+        assert_eq!(edited.root().unwrap().index, 0);
+        let package = crate::slpk::SceneLayerPackage::open(&copy).unwrap();
+        let geometry = crate::rm::Accessor::get(
+            &package,
+            &crate::rm::UriBuilder::geometry_uri(&package, 0, 0),
+        )
+        .unwrap();
+        assert_eq!(&*geometry, &vec![1u8, 2, 3]);
+
+        // In-place save rewrites the original package.
+        layer.edit_definition().name("rewritten").save_in_place().unwrap();
+        let reopened = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        assert_eq!(reopened.name(), Some("rewritten"));
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&copy).ok();
+    }
+
+    #[test]
+    fn fields_can_be_added_and_dropped_with_resources() {
+        let dir = crate::testutil::TestDir::new("schema");
+        let path = dir.join("layer.slpk");
+        let copy = dir.join("enriched.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "3DObject",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "fields": [{ "name": "NAME", "type": "esriFieldTypeString" }],
+            "attributeStorageInfo": [{ "key": "f_0", "name": "NAME" }]
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 12.5],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                },
+                "mesh": {
+                    "geometry": { "definition": 0, "resource": 0, "vertexCount": 3,
+                                  "featureCount": 2 },
+                    "attribute": { "resource": 0 }
+                }
+            }]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_attribute(0, "f_0", b"legacy names").unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        layer
+            .edit_definition()
+            .add_field(
+                crate::defn::Field {
+                    name: "height".to_string(),
+                    field_type: "esriFieldTypeDouble".to_string(),
+                    alias: None,
+                },
+                |node| {
+                    let top = node.obb.center[2] + f64::from(node.obb.half_size[2]);
+                    Ok(super::FieldValues::Float64(vec![top, top]))
+                },
+            )
+            .unwrap()
+            .drop_field("NAME")
+            .unwrap()
+            .save_as(&copy)
+            .unwrap();
+
+        let enriched = crate::layer::SceneLayer::open_slpk(&copy).unwrap();
+        let fields = &enriched.definition().fields;
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].name, "height");
+        let storage = &enriched.definition().attribute_storage_info;
+        assert_eq!(storage.len(), 1);
+        assert_eq!(storage[0].key, "f_1");
+
+        let package = crate::slpk::SceneLayerPackage::open(&copy).unwrap();
+        let uri = crate::rm::UriBuilder::attribute_uri(&package, 0, "f_1");
+        let bytes = crate::rm::Accessor::get(&package, &uri).unwrap();
+        assert_eq!(u32::from_le_bytes(bytes[0..4].try_into().unwrap()), 2);
+        let first = f64::from_le_bytes(bytes[8..16].try_into().unwrap());
+        assert_eq!(first, 13.5);
+        // The dropped field's resource is gone from the copy.
+        let dropped = crate::rm::UriBuilder::attribute_uri(&package, 0, "f_0");
+        assert!(crate::rm::Accessor::get(&package, &dropped).is_err());
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&copy).ok();
+    }
+}
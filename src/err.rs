@@ -0,0 +1,260 @@
+//! Error types for the crate.
+
+use std::fmt;
+
+use crate::defn::{LayerType, Profile};
+
+/// Convenience alias used across the public API.
+pub type Result<T> = std::result::Result<T, I3SError>;
+
+/// Errors that can occur while reading or decoding an I3S layer.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum I3SError {
+    /// An underlying I/O failure (file access, archive read, ...).
+    Io(std::io::Error),
+    /// An HTTP request completed with a non-success status.
+    Http { status: u16, uri: String },
+    /// An HTTP request could not be performed at all.
+    #[cfg(feature = "http")]
+    Request(reqwest::Error),
+    /// A ZIP archive could not be read.
+    #[cfg(feature = "slpk")]
+    Zip(zip::result::ZipError),
+    /// A JSON resource could not be deserialized.
+    Json {
+        uri: String,
+        source: serde_json::Error,
+    },
+    /// A resource referenced by the layer does not exist.
+    MissingResource(String),
+    /// A binary resource could not be decoded.
+    Decode(String),
+    /// The layer profile is not supported by the requested operation.
+    UnsupportedProfile(Profile),
+    /// The layer type does not match what an operation requires.
+    UnsupportedLayerType { got: LayerType, expected: LayerType },
+    /// A URI could not be interpreted as an I3S source.
+    InvalidUri(String),
+    /// A constructed or patched definition failed validation.
+    Validation(String),
+    /// A session download budget ran out.
+    BudgetExhausted { used_bytes: u64, used_requests: u64 },
+    /// An error annotated with what was being worked on when it occurred.
+    Contextual {
+        context: Box<ErrorContext>,
+        source: Box<I3SError>,
+    },
+}
+
+/// What a failing operation was working on, attached to errors via
+/// [`I3SError::with_context`] so failures deep in a decode or fetch still
+/// name the node and resource they belong to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Index of the node whose resource failed.
+    pub node_index: Option<usize>,
+    /// Resource kind, e.g. `"geometry"`, `"texture"`, `"node page"`.
+    pub resource: Option<&'static str>,
+    /// URI of the failing resource.
+    pub uri: Option<String>,
+    /// Byte offset inside the resource, for layout errors.
+    pub byte_offset: Option<usize>,
+}
+
+impl ErrorContext {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Fill unset fields from `outer`; inner (more specific) values win.
+    fn merge_from(&mut self, outer: ErrorContext) {
+        self.node_index = self.node_index.or(outer.node_index);
+        self.resource = self.resource.or(outer.resource);
+        self.uri = self.uri.take().or(outer.uri);
+        self.byte_offset = self.byte_offset.or(outer.byte_offset);
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(resource) = self.resource {
+            parts.push(resource.to_string());
+        }
+        if let Some(node) = self.node_index {
+            parts.push(format!("node {node}"));
+        }
+        if let Some(uri) = &self.uri {
+            parts.push(format!("uri {uri}"));
+        }
+        if let Some(offset) = self.byte_offset {
+            parts.push(format!("byte {offset}"));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl I3SError {
+    /// Build a [`I3SError::Json`] carrying the URI of the offending resource.
+    pub(crate) fn json(uri: impl Into<String>, source: serde_json::Error) -> Self {
+        Self::Json {
+            uri: uri.into(),
+            source,
+        }
+    }
+
+    /// Annotate this error with `context`. Wrapping an already annotated
+    /// error merges instead of nesting; fields set closer to the failure
+    /// win.
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        if context.is_empty() {
+            return self;
+        }
+        match self {
+            Self::Contextual {
+                context: mut inner,
+                source,
+            } => {
+                inner.merge_from(context);
+                Self::Contextual {
+                    context: inner,
+                    source,
+                }
+            }
+            source => Self::Contextual {
+                context: Box::new(context),
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// The attached context, if any.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::Contextual { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error itself, with any context layer stripped.
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::Contextual { source, .. } => source,
+            other => other,
+        }
+    }
+
+    /// Attach `uri` as context unless the error already names one.
+    pub(crate) fn with_uri(self, uri: &str) -> Self {
+        let named = matches!(
+            self.root_cause(),
+            Self::MissingResource(_) | Self::Http { .. } | Self::Json { .. } | Self::InvalidUri(_)
+        ) || self.context().is_some_and(|c| c.uri.is_some());
+        if named {
+            self
+        } else {
+            self.with_context(ErrorContext {
+                uri: Some(uri.to_string()),
+                ..Default::default()
+            })
+        }
+    }
+}
+
+impl fmt::Display for I3SError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io error: {e}"),
+            Self::Http { status, uri } => write!(f, "http status {status} for {uri}"),
+            #[cfg(feature = "http")]
+            Self::Request(e) => write!(f, "http request failed: {e}"),
+            #[cfg(feature = "slpk")]
+            Self::Zip(e) => write!(f, "zip error: {e}"),
+            Self::Json { uri, source } => write!(f, "invalid json in {uri}: {source}"),
+            Self::MissingResource(uri) => write!(f, "missing resource: {uri}"),
+            Self::Decode(msg) => write!(f, "decode error: {msg}"),
+            Self::UnsupportedProfile(p) => write!(f, "unsupported profile: {p:?}"),
+            Self::UnsupportedLayerType { got, expected } => {
+                write!(f, "layer is {got:?}, expected {expected:?}")
+            }
+            Self::InvalidUri(uri) => write!(f, "invalid uri: {uri}"),
+            Self::Validation(msg) => write!(f, "invalid definition: {msg}"),
+            Self::BudgetExhausted {
+                used_bytes,
+                used_requests,
+            } => write!(
+                f,
+                "download budget exhausted after {used_requests} requests / {used_bytes} bytes"
+            ),
+            Self::Contextual { context, source } => write!(f, "{source} ({context})"),
+        }
+    }
+}
+
+impl std::error::Error for I3SError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            #[cfg(feature = "http")]
+            Self::Request(e) => Some(e),
+            #[cfg(feature = "slpk")]
+            Self::Zip(e) => Some(e),
+            Self::Json { source, .. } => Some(source),
+            Self::Contextual { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for I3SError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(feature = "http")]
+impl From<reqwest::Error> for I3SError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+#[cfg(feature = "slpk")]
+impl From<zip::result::ZipError> for I3SError {
+    fn from(e: zip::result::ZipError) -> Self {
+        Self::Zip(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_layers_merge_and_display() {
+        let inner = I3SError::Decode("buffer too short".to_string()).with_context(ErrorContext {
+            byte_offset: Some(24),
+            ..Default::default()
+        });
+        // The outer layer fills in what the inner one left unset.
+        let annotated = inner.with_context(ErrorContext {
+            node_index: Some(7),
+            resource: Some("geometry"),
+            uri: Some("nodes/7/geometries/0".to_string()),
+            ..Default::default()
+        });
+        let context = annotated.context().unwrap();
+        assert_eq!(context.node_index, Some(7));
+        assert_eq!(context.byte_offset, Some(24));
+        assert!(matches!(annotated.root_cause(), I3SError::Decode(_)));
+        let message = annotated.to_string();
+        assert!(message.contains("buffer too short"));
+        assert!(message.contains("node 7"));
+        assert!(message.contains("byte 24"));
+
+        // Errors that already name their resource are left alone.
+        let missing = I3SError::MissingResource("nodes/1".to_string()).with_uri("nodes/1");
+        assert!(missing.context().is_none());
+    }
+}
@@ -0,0 +1,156 @@
+//! Exporting the node bounding hierarchy as a flat binary BVH.
+//!
+//! Emits only the tree topology and oriented bounding boxes — no geometry
+//! decode — in a fixed-stride little-endian layout a GPU culling compute
+//! shader can consume directly:
+//!
+//! ```text
+//! header: magic "I3SB" | version u32 (1) | node count u32 | root center 3 x f64
+//! record: node index u32 | parent slot i32 (-1 for the root)
+//!         | 3 x 4 transform, 12 x f32, column-major:
+//!           the three rotated half-axis vectors, then the box center
+//!           relative to the root center
+//! ```
+//!
+//! Records are in pre-order, so a node's parent always precedes it; the
+//! parent field is the record slot, not the I3S node index. Centers are
+//! root-relative `f32`, matching the relative-to-center trick of the glTF
+//! exporter so large projected coordinates survive the `f64` to `f32`
+//! narrowing.
+
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::obb::{rotate, OrientedBoundingBox};
+
+/// Magic bytes at the start of a BVH blob.
+pub const BVH_MAGIC: &[u8; 4] = b"I3SB";
+/// Current version of the record layout.
+pub const BVH_VERSION: u32 = 1;
+
+/// What [`export_bvh`] produced.
+#[derive(Debug, Clone, Copy)]
+pub struct BvhExportReport {
+    /// Records written.
+    pub nodes: usize,
+    /// Size of the blob in bytes.
+    pub bytes: usize,
+}
+
+/// The 3x4 column-major transform of a box, relative to `origin`.
+fn obb_transform(obb: &OrientedBoundingBox, origin: [f64; 3]) -> [f32; 12] {
+    let axes = [
+        rotate(obb.quaternion, [obb.half_size[0] as f64, 0.0, 0.0]),
+        rotate(obb.quaternion, [0.0, obb.half_size[1] as f64, 0.0]),
+        rotate(obb.quaternion, [0.0, 0.0, obb.half_size[2] as f64]),
+    ];
+    let mut out = [0.0f32; 12];
+    for (column, axis) in axes.iter().enumerate() {
+        for row in 0..3 {
+            out[column * 3 + row] = axis[row] as f32;
+        }
+    }
+    for row in 0..3 {
+        out[9 + row] = (obb.center[row] - origin[row]) as f32;
+    }
+    out
+}
+
+/// Serialize the layer's node tree as a flat binary BVH.
+pub fn export_bvh(layer: &SceneLayer) -> Result<(Vec<u8>, BvhExportReport)> {
+    let nodes = layer.nodes()?;
+    let root = nodes.root()?;
+    let origin = root.obb.center;
+
+    // Pre-order walk recording (node, parent record slot).
+    let mut records: Vec<(u32, i32, [f32; 12])> = Vec::new();
+    let mut stack: Vec<(std::sync::Arc<crate::node::Node>, i32)> = vec![(root, -1)];
+    while let Some((node, parent_slot)) = stack.pop() {
+        let slot = records.len() as i32;
+        records.push((node.index as u32, parent_slot, obb_transform(&node.obb, origin)));
+        for child in nodes.get_many(&node.children)?.into_iter().rev() {
+            stack.push((child, slot));
+        }
+    }
+
+    let mut out = Vec::with_capacity(4 + 4 + 4 + 24 + records.len() * 56);
+    out.extend_from_slice(BVH_MAGIC);
+    out.extend_from_slice(&BVH_VERSION.to_le_bytes());
+    out.extend_from_slice(&(records.len() as u32).to_le_bytes());
+    for value in origin {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    for (index, parent, transform) in &records {
+        out.extend_from_slice(&index.to_le_bytes());
+        out.extend_from_slice(&parent.to_le_bytes());
+        for value in transform {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    let report = BvhExportReport {
+        nodes: records.len(),
+        bytes: out.len(),
+    };
+    Ok((out, report))
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::slpk::writer::SlpkWriter;
+
+    #[test]
+    fn bvh_records_follow_preorder_with_parent_slots() {
+        let dir = crate::testutil::TestDir::new("bvh");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let obb = |x: f64| {
+            serde_json::json!({
+                "center": [x, 0.0, 0.0],
+                "halfSize": [1.0, 2.0, 3.0],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            })
+        };
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "children": [1, 2], "obb": obb(100.0) },
+                { "index": 1, "parentIndex": 0, "obb": obb(90.0) },
+                { "index": 2, "parentIndex": 0, "obb": obb(110.0) }
+            ]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(path.to_str().unwrap()).unwrap();
+        let (blob, report) = export_bvh(&layer).unwrap();
+        assert_eq!(report.nodes, 3);
+        assert_eq!(blob.len(), report.bytes);
+        assert_eq!(&blob[0..4], BVH_MAGIC);
+        assert_eq!(u32::from_le_bytes(blob[8..12].try_into().unwrap()), 3);
+        let origin = f64::from_le_bytes(blob[12..20].try_into().unwrap());
+        assert_eq!(origin, 100.0);
+
+        let record = |slot: usize| {
+            let start = 36 + slot * 56;
+            let index = u32::from_le_bytes(blob[start..start + 4].try_into().unwrap());
+            let parent = i32::from_le_bytes(blob[start + 4..start + 8].try_into().unwrap());
+            let center_x =
+                f32::from_le_bytes(blob[start + 8 + 36..start + 8 + 40].try_into().unwrap());
+            (index, parent, center_x)
+        };
+        assert_eq!(record(0), (0, -1, 0.0));
+        assert_eq!(record(1), (1, 0, -10.0));
+        assert_eq!(record(2), (2, 0, 10.0));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,492 @@
+//! Whole-layer glTF (GLB) export.
+//!
+//! Traverses the tree at a chosen LOD cut, decodes the selected node
+//! geometries, and writes one binary glTF scene. Each I3S node becomes a
+//! glTF node whose translation is its OBB center relative to the root
+//! center (recorded in the scene extras as `rtcCenter`), so coordinates
+//! stay small. Axes and units are written as stored in the layer.
+
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::node::Node;
+use std::sync::Arc;
+
+use super::ExportOptions;
+
+/// Which nodes of the tree form the exported LOD cut.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum LodSelection {
+    /// Export leaf nodes only (full detail).
+    #[default]
+    Leaves,
+    /// Descend until a node's `lodThreshold` is at or below the value, then
+    /// export that node and skip its subtree.
+    MaxThreshold(f64),
+}
+
+/// Options for [`export_layer_glb`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GltfExportOptions {
+    pub export: ExportOptions,
+    pub selection: LodSelection,
+}
+
+/// Summary of a finished export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GltfExportReport {
+    pub nodes_exported: usize,
+    pub vertices: usize,
+    pub bytes_written: usize,
+}
+
+fn select_nodes(layer: &SceneLayer, selection: LodSelection) -> Result<Vec<Arc<Node>>> {
+    let nodes = layer.nodes()?;
+    let mut selected = Vec::new();
+    let mut stack = vec![nodes.root()?];
+    while let Some(node) = stack.pop() {
+        let take = match selection {
+            LodSelection::Leaves => node.is_leaf(),
+            LodSelection::MaxThreshold(t) => {
+                node.is_leaf() || node.lod_threshold.is_some_and(|lod| lod <= t)
+            }
+        };
+        if take {
+            if node.mesh.is_some() {
+                selected.push(node);
+            }
+        } else {
+            for &child in node.children.iter().rev() {
+                stack.push(nodes.get(child)?);
+            }
+        }
+    }
+    Ok(selected)
+}
+
+fn pad_to_4(bytes: &mut Vec<u8>, fill: u8) {
+    while !bytes.len().is_multiple_of(4) {
+        bytes.push(fill);
+    }
+}
+
+/// Quantize positions to 16-bit grid steps around zero, returning the step
+/// size the containing node must apply as its scale.
+fn quantize_positions(values: &[f32]) -> (Vec<i16>, f64) {
+    let max_abs = values.iter().fold(0.0f32, |max, v| max.max(v.abs()));
+    let step = if max_abs > 0.0 {
+        f64::from(max_abs) / f64::from(i16::MAX)
+    } else {
+        1.0
+    };
+    let quantized = values
+        .iter()
+        .map(|v| (f64::from(*v) / step).round() as i16)
+        .collect();
+    (quantized, step)
+}
+
+fn min_max(values: &[f32], stride: usize) -> (Vec<f32>, Vec<f32>) {
+    let mut min = vec![f32::INFINITY; stride];
+    let mut max = vec![f32::NEG_INFINITY; stride];
+    for chunk in values.chunks_exact(stride) {
+        for (i, v) in chunk.iter().enumerate() {
+            min[i] = min[i].min(*v);
+            max[i] = max[i].max(*v);
+        }
+    }
+    (min, max)
+}
+
+fn glb_container(json_chunk: &mut Vec<u8>, bin: &mut Vec<u8>) -> Vec<u8> {
+    pad_to_4(json_chunk, b' ');
+    pad_to_4(bin, 0);
+    let total = 12 + 8 + json_chunk.len() + 8 + bin.len();
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(b"glTF");
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(total as u32).to_le_bytes());
+    out.extend_from_slice(&(json_chunk.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"JSON");
+    out.extend_from_slice(json_chunk);
+    out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"BIN\0");
+    out.extend_from_slice(bin);
+    out
+}
+
+/// Build a standalone GLB holding a single decoded geometry as one mesh,
+/// optionally placed under a translated root node.
+pub(crate) fn single_mesh_glb(
+    geometry: &crate::decode::DecodedGeometry,
+    translation: Option<[f64; 3]>,
+) -> Result<Vec<u8>> {
+    let mut bin = Vec::new();
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut attributes = serde_json::Map::new();
+    for (semantic, values, stride) in [
+        ("POSITION", &geometry.positions, 3usize),
+        ("NORMAL", &geometry.normals, 3),
+        ("TEXCOORD_0", &geometry.uvs, 2),
+    ] {
+        if values.is_empty() {
+            continue;
+        }
+        let byte_offset = bin.len();
+        for v in values {
+            bin.extend_from_slice(&v.to_le_bytes());
+        }
+        pad_to_4(&mut bin, 0);
+        buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": byte_offset,
+            "byteLength": values.len() * 4,
+            "target": 34962,
+        }));
+        let mut accessor = json!({
+            "bufferView": buffer_views.len() - 1,
+            "componentType": 5126,
+            "count": values.len() / stride,
+            "type": if stride == 3 { "VEC3" } else { "VEC2" },
+        });
+        if semantic == "POSITION" {
+            let (min, max) = min_max(values, stride);
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        accessors.push(accessor);
+        attributes.insert(semantic.to_string(), json!(accessors.len() - 1));
+    }
+
+    let document = json!({
+        "asset": { "version": "2.0", "generator": "i3s" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [match translation {
+            Some(t) => json!({ "mesh": 0, "translation": t }),
+            None => json!({ "mesh": 0 }),
+        }],
+        "meshes": [{ "primitives": [{ "attributes": attributes, "mode": 4 }] }],
+        "accessors": accessors,
+        "bufferViews": buffer_views,
+        "buffers": [{ "byteLength": bin.len() }],
+    });
+    let mut json_chunk = serde_json::to_vec(&document)
+        .map_err(|e| crate::err::I3SError::json("glTF document", e))?;
+    Ok(glb_container(&mut json_chunk, &mut bin))
+}
+
+/// Export the layer as a single `.glb` file at the chosen LOD cut.
+pub fn export_layer_glb(
+    layer: &SceneLayer,
+    path: impl AsRef<Path>,
+    options: &GltfExportOptions,
+) -> Result<GltfExportReport> {
+    let selected = select_nodes(layer, options.selection)?;
+    let root_center = layer.root()?.obb.center;
+
+    let mut bin = Vec::new();
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut meshes = Vec::new();
+    let mut gltf_nodes = Vec::new();
+    let mut total_vertices = 0usize;
+
+    for node in &selected {
+        let Some(mut geometry) = layer.node_geometry(node)? else {
+            continue;
+        };
+        options.export.convert_geometry(&mut geometry);
+        options.export.quantize_geometry(&mut geometry);
+        if geometry.positions.is_empty() {
+            continue;
+        }
+        total_vertices += geometry.vertex_count;
+
+        let mut attributes = serde_json::Map::new();
+        let mut node_scale = None;
+        for (semantic, values, stride) in [
+            ("POSITION", &geometry.positions, 3usize),
+            ("NORMAL", &geometry.normals, 3),
+            ("TEXCOORD_0", &geometry.uvs, 2),
+        ] {
+            if values.is_empty() {
+                continue;
+            }
+            let byte_offset = bin.len();
+            // UVs stay float even when quantizing: normalized storage
+            // cannot represent coordinates outside [0, 1].
+            let accessor = if options.export.khr_mesh_quantization && stride == 3 {
+                let quantized = if semantic == "POSITION" {
+                    let (quantized, step) = quantize_positions(values);
+                    node_scale = Some(step);
+                    quantized
+                } else {
+                    values
+                        .iter()
+                        .map(|v| (f64::from(v.clamp(-1.0, 1.0)) * f64::from(i16::MAX)).round() as i16)
+                        .collect()
+                };
+                // Two padding bytes per vertex keep the stride a multiple
+                // of 4, as the spec requires of vertex attributes.
+                for vertex in quantized.chunks_exact(3) {
+                    for v in vertex {
+                        bin.extend_from_slice(&v.to_le_bytes());
+                    }
+                    bin.extend_from_slice(&[0, 0]);
+                }
+                buffer_views.push(json!({
+                    "buffer": 0,
+                    "byteOffset": byte_offset,
+                    "byteLength": quantized.len() / 3 * 8,
+                    "byteStride": 8,
+                    "target": 34962,
+                }));
+                let mut accessor = json!({
+                    "bufferView": buffer_views.len() - 1,
+                    "componentType": 5122,
+                    "normalized": semantic == "NORMAL",
+                    "count": quantized.len() / 3,
+                    "type": "VEC3",
+                });
+                if semantic == "POSITION" {
+                    accessor["min"] = json!((0..3)
+                        .map(|axis| quantized.iter().skip(axis).step_by(3).min().copied().unwrap_or(0))
+                        .collect::<Vec<_>>());
+                    accessor["max"] = json!((0..3)
+                        .map(|axis| quantized.iter().skip(axis).step_by(3).max().copied().unwrap_or(0))
+                        .collect::<Vec<_>>());
+                }
+                accessor
+            } else {
+                for v in values {
+                    bin.extend_from_slice(&v.to_le_bytes());
+                }
+                pad_to_4(&mut bin, 0);
+                buffer_views.push(json!({
+                    "buffer": 0,
+                    "byteOffset": byte_offset,
+                    "byteLength": values.len() * 4,
+                    "target": 34962,
+                }));
+                let mut accessor = json!({
+                    "bufferView": buffer_views.len() - 1,
+                    "componentType": 5126,
+                    "count": values.len() / stride,
+                    "type": if stride == 3 { "VEC3" } else { "VEC2" },
+                });
+                if semantic == "POSITION" {
+                    let (min, max) = min_max(values, stride);
+                    accessor["min"] = json!(min);
+                    accessor["max"] = json!(max);
+                }
+                accessor
+            };
+            accessors.push(accessor);
+            attributes.insert(semantic.to_string(), json!(accessors.len() - 1));
+        }
+
+        meshes.push(json!({
+            "name": format!("node-{}", node.index),
+            "primitives": [{ "attributes": attributes, "mode": 4 }],
+        }));
+        let mut gltf_node = json!({
+            "name": format!("node-{}", node.index),
+            "mesh": meshes.len() - 1,
+            "translation": options.export.convert_point([
+                node.obb.center[0] - root_center[0],
+                node.obb.center[1] - root_center[1],
+                node.obb.center[2] - root_center[2],
+            ]),
+        });
+        if let Some(step) = node_scale {
+            // Undo the position quantization step when the node is placed.
+            gltf_node["scale"] = json!([step, step, step]);
+        }
+        gltf_nodes.push(gltf_node);
+    }
+
+    let mut document = json!({
+        "asset": { "version": "2.0", "generator": "i3s" },
+        "scene": 0,
+        "scenes": [{
+            "nodes": (0..gltf_nodes.len()).collect::<Vec<_>>(),
+            "extras": { "rtcCenter": options.export.convert_point(root_center) },
+        }],
+        "nodes": gltf_nodes,
+        "meshes": meshes,
+        "accessors": accessors,
+        "bufferViews": buffer_views,
+        "buffers": [{ "byteLength": bin.len() }],
+    });
+    if options.export.khr_mesh_quantization {
+        document["extensionsUsed"] = json!(["KHR_mesh_quantization"]);
+    }
+
+    let mut json_chunk = serde_json::to_vec(&document)
+        .map_err(|e| crate::err::I3SError::json("glTF document", e))?;
+    let glb = glb_container(&mut json_chunk, &mut bin);
+    if !options.export.dry_run {
+        std::fs::write(path, &glb)?;
+    }
+
+    Ok(GltfExportReport {
+        nodes_exported: gltf_nodes.len(),
+        vertices: total_vertices,
+        bytes_written: glb.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn exports_leaf_geometry_to_glb() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("gltf");
+        let slpk_path = dir.join("layer.slpk");
+        let glb_path = dir.join("layer.glb");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }]
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [10.0, 20.0, 30.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                },
+                "mesh": {
+                    "geometry": { "definition": 0, "resource": 0, "vertexCount": 3 }
+                }
+            }]
+        }))
+        .unwrap();
+        let positions: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+
+        let mut writer = SlpkWriter::create(&slpk_path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, &positions).unwrap();
+        writer.finish().unwrap();
+
+        let layer = SceneLayer::from_uri(slpk_path.to_str().unwrap()).unwrap();
+        let report = export_layer_glb(&layer, &glb_path, &GltfExportOptions::default()).unwrap();
+        assert_eq!(report.nodes_exported, 1);
+        assert_eq!(report.vertices, 3);
+
+        let glb = std::fs::read(&glb_path).unwrap();
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(glb.len(), report.bytes_written);
+
+        // A dry run produces the same report without touching the disk.
+        let dry = GltfExportOptions {
+            export: crate::export::ExportOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let dry_path = dir.join("never-written.glb");
+        let dry_report = export_layer_glb(&layer, &dry_path, &dry).unwrap();
+        assert_eq!(dry_report, report);
+        assert!(!dry_path.exists());
+
+        std::fs::remove_file(&slpk_path).ok();
+        std::fs::remove_file(&glb_path).ok();
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn quantized_exports_store_short_positions() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("gltf-quantized");
+        let slpk_path = dir.join("layer.slpk");
+        let glb_path = dir.join("layer.glb");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }]
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                },
+                "mesh": {
+                    "geometry": { "definition": 0, "resource": 0, "vertexCount": 3 }
+                }
+            }]
+        }))
+        .unwrap();
+        let positions: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+
+        let mut writer = SlpkWriter::create(&slpk_path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, &positions).unwrap();
+        writer.finish().unwrap();
+
+        let layer = SceneLayer::from_uri(slpk_path.to_str().unwrap()).unwrap();
+        let options = GltfExportOptions {
+            export: crate::export::ExportOptions {
+                khr_mesh_quantization: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        export_layer_glb(&layer, &glb_path, &options).unwrap();
+
+        let glb = std::fs::read(&glb_path).unwrap();
+        let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        let document: serde_json::Value = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+
+        assert_eq!(
+            document["extensionsUsed"],
+            serde_json::json!(["KHR_mesh_quantization"])
+        );
+        let accessor = &document["accessors"][0];
+        assert_eq!(accessor["componentType"], 5122);
+        assert_eq!(accessor["max"], serde_json::json!([32767, 32767, 0]));
+        assert_eq!(document["bufferViews"][0]["byteStride"], 8);
+        // The node scale undoes the quantization: one grid step per unit.
+        let step = document["nodes"][0]["scale"][0].as_f64().unwrap();
+        assert!((step * 32767.0 - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,272 @@
+//! Exporting decoded layer content to other formats.
+//!
+//! Exporters share [`ExportOptions`], which controls coordinate quantization
+//! so that repeated exports of the same data are byte-identical and diffable:
+//! floats are rounded to a fixed number of decimals before being written, and
+//! glTF output can store vertex data quantized under `KHR_mesh_quantization`.
+
+pub mod bvh;
+pub mod gltf;
+pub mod obj;
+pub mod tiles3d;
+
+use crate::decode::DecodedGeometry;
+
+/// Which axis points up in exported coordinates.
+///
+/// I3S data is Z-up; glTF viewers and most DCC tools expect Y-up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UpAxis {
+    /// Keep the source Z-up axes.
+    #[default]
+    ZUp,
+    /// Rotate into Y-up: `(x, y, z)` becomes `(x, z, -y)`.
+    YUp,
+}
+
+/// Linear units of exported coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthUnit {
+    #[default]
+    Meter,
+    /// International foot (0.3048 m).
+    Foot,
+    /// US survey foot (1200/3937 m).
+    UsSurveyFoot,
+}
+
+impl LengthUnit {
+    /// Length of one unit in meters.
+    pub fn meters_per_unit(self) -> f64 {
+        match self {
+            Self::Meter => 1.0,
+            Self::Foot => 0.3048,
+            Self::UsSurveyFoot => 1200.0 / 3937.0,
+        }
+    }
+
+    /// The multiplier that converts `from` values into `to` values.
+    pub fn factor(from: Self, to: Self) -> f64 {
+        from.meters_per_unit() / to.meters_per_unit()
+    }
+
+    /// Parse a `heightModelInfo` unit string.
+    pub fn from_height_unit(unit: &str) -> Option<Self> {
+        match unit.to_ascii_lowercase().as_str() {
+            "meter" | "meters" | "metre" | "metres" => Some(Self::Meter),
+            "foot" | "feet" | "international-foot" => Some(Self::Foot),
+            "us-foot" | "us-feet" | "us-survey-foot" => Some(Self::UsSurveyFoot),
+            _ => None,
+        }
+    }
+}
+
+/// Options shared by all exporters.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportOptions {
+    /// Round positions to this many decimal places before writing.
+    /// `None` writes full-precision values.
+    pub position_decimals: Option<u8>,
+    /// Round texture coordinates to this many decimal places.
+    pub uv_decimals: Option<u8>,
+    /// Normalize and round normals to this many decimal places.
+    pub normal_decimals: Option<u8>,
+    /// Store glTF vertex data quantized under `KHR_mesh_quantization`:
+    /// positions as 16-bit grid steps undone by a node scale, normals as
+    /// normalized 16-bit integers. Texture coordinates stay float.
+    pub khr_mesh_quantization: bool,
+    /// Up axis of the exported coordinates.
+    pub up_axis: UpAxis,
+    /// Multiplier applied to all lengths (positions, translations,
+    /// bounding volumes, geometric errors); build one with
+    /// [`LengthUnit::factor`].
+    pub unit_scale: f64,
+    /// Walk the full export pipeline — selection, decode, encode — but
+    /// write nothing. The returned report carries the sizes and counts
+    /// the real run would produce, for estimating disk needs up front.
+    pub dry_run: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            // Millimeter precision: deterministic and lossless for the value
+            // ranges of OBB-relative coordinates.
+            position_decimals: Some(3),
+            uv_decimals: Some(6),
+            normal_decimals: Some(6),
+            khr_mesh_quantization: false,
+            up_axis: UpAxis::default(),
+            unit_scale: 1.0,
+            dry_run: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Options that write full-precision values (output is still
+    /// deterministic, but not stable under decode round-trips).
+    pub fn full_precision() -> Self {
+        Self {
+            position_decimals: None,
+            uv_decimals: None,
+            normal_decimals: None,
+            ..Self::default()
+        }
+    }
+
+    /// Convert a point or offset from source axes/units into the export's.
+    pub fn convert_point(&self, p: [f64; 3]) -> [f64; 3] {
+        let p = [
+            p[0] * self.unit_scale,
+            p[1] * self.unit_scale,
+            p[2] * self.unit_scale,
+        ];
+        match self.up_axis {
+            UpAxis::ZUp => p,
+            UpAxis::YUp => [p[0], p[2], -p[1]],
+        }
+    }
+
+    /// Swizzle a direction into the export's axes, without unit scaling.
+    pub fn convert_direction(&self, v: [f64; 3]) -> [f64; 3] {
+        match self.up_axis {
+            UpAxis::ZUp => v,
+            UpAxis::YUp => [v[0], v[2], -v[1]],
+        }
+    }
+
+    /// Apply the configured axis and unit conversion to a decoded geometry
+    /// in place: positions are scaled and swizzled, normals swizzled.
+    /// Exporters call this before quantization so decimals apply to output
+    /// units.
+    pub fn convert_geometry(&self, geometry: &mut DecodedGeometry) {
+        if self.up_axis == UpAxis::ZUp && self.unit_scale == 1.0 {
+            return;
+        }
+        for p in geometry.positions.chunks_exact_mut(3) {
+            let converted =
+                self.convert_point([f64::from(p[0]), f64::from(p[1]), f64::from(p[2])]);
+            (p[0], p[1], p[2]) = (
+                converted[0] as f32,
+                converted[1] as f32,
+                converted[2] as f32,
+            );
+        }
+        for n in geometry.normals.chunks_exact_mut(3) {
+            let converted =
+                self.convert_direction([f64::from(n[0]), f64::from(n[1]), f64::from(n[2])]);
+            (n[0], n[1], n[2]) = (
+                converted[0] as f32,
+                converted[1] as f32,
+                converted[2] as f32,
+            );
+        }
+    }
+
+    /// Apply the configured quantization to a decoded geometry in place.
+    pub fn quantize_geometry(&self, geometry: &mut DecodedGeometry) {
+        if let Some(decimals) = self.position_decimals {
+            quantize_f32s(&mut geometry.positions, decimals);
+        }
+        if let Some(decimals) = self.uv_decimals {
+            quantize_f32s(&mut geometry.uvs, decimals);
+        }
+        if let Some(decimals) = self.normal_decimals {
+            quantize_f32s(&mut geometry.normals, decimals);
+        }
+    }
+}
+
+/// Round a value to `decimals` decimal places, mapping negative zero to zero
+/// so quantized output never depends on the sign of a rounded-away fraction.
+pub fn quantize_f64(value: f64, decimals: u8) -> f64 {
+    let scale = 10f64.powi(i32::from(decimals));
+    let rounded = (value * scale).round() / scale;
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+/// Single-precision variant of [`quantize_f64`].
+pub fn quantize_f32(value: f32, decimals: u8) -> f32 {
+    quantize_f64(f64::from(value), decimals) as f32
+}
+
+fn quantize_f32s(values: &mut [f32], decimals: u8) {
+    for v in values.iter_mut() {
+        *v = quantize_f32(*v, decimals);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantization_is_idempotent() {
+        let v = quantize_f64(1.23456789, 3);
+        assert_eq!(v, 1.235);
+        assert_eq!(quantize_f64(v, 3), v);
+    }
+
+    #[test]
+    fn negative_zero_is_normalized() {
+        let v = quantize_f64(-0.0001, 3);
+        assert_eq!(v.to_bits(), 0f64.to_bits());
+    }
+
+    #[test]
+    fn y_up_conversion_swizzles_points_and_directions() {
+        let options = ExportOptions {
+            up_axis: UpAxis::YUp,
+            unit_scale: LengthUnit::factor(LengthUnit::Meter, LengthUnit::Foot),
+            ..ExportOptions::default()
+        };
+        let p = options.convert_point([0.3048, 0.6096, 0.9144]);
+        assert!((p[0] - 1.0).abs() < 1e-12);
+        assert!((p[1] - 3.0).abs() < 1e-12);
+        assert!((p[2] + 2.0).abs() < 1e-12);
+        // Directions are swizzled but never scaled.
+        assert_eq!(options.convert_direction([0.0, 1.0, 0.0]), [0.0, 0.0, -1.0]);
+
+        let mut geometry = DecodedGeometry {
+            vertex_count: 1,
+            positions: vec![1.0, 2.0, 3.0],
+            normals: vec![0.0, 0.0, 1.0],
+            ..Default::default()
+        };
+        let y_up_only = ExportOptions {
+            up_axis: UpAxis::YUp,
+            ..ExportOptions::default()
+        };
+        y_up_only.convert_geometry(&mut geometry);
+        assert_eq!(geometry.positions, vec![1.0, 3.0, -2.0]);
+        assert_eq!(geometry.normals, vec![0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn height_unit_strings_parse() {
+        assert_eq!(LengthUnit::from_height_unit("Meter"), Some(LengthUnit::Meter));
+        assert_eq!(LengthUnit::from_height_unit("us-foot"), Some(LengthUnit::UsSurveyFoot));
+        assert_eq!(LengthUnit::from_height_unit("furlong"), None);
+    }
+
+    #[test]
+    fn geometry_quantization_applies_to_all_channels() {
+        let mut geometry = DecodedGeometry {
+            vertex_count: 1,
+            positions: vec![1.000_49, 2.0, 3.0],
+            uvs: vec![0.123_456_9, 0.5],
+            normals: vec![0.6, 0.0, 0.8],
+            ..Default::default()
+        };
+        ExportOptions::default().quantize_geometry(&mut geometry);
+        assert_eq!(geometry.positions[0], 1.0);
+        assert_eq!(geometry.uvs[0], 0.123_457);
+    }
+}
@@ -0,0 +1,409 @@
+//! OBJ/MTL export of node meshes.
+//!
+//! A quick-inspection path: dump one node, or all leaves under a node, as a
+//! Wavefront OBJ with an accompanying MTL and texture files, for loading
+//! into Blender or MeshLab. Positions are written absolute (node-relative
+//! values offset by the OBB center) and faces are emitted as consecutive
+//! triangles, matching the triangle-soup layout of decoded I3S geometry.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::decode::{decode_material, TexturePreference};
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::node::Node;
+use crate::rm::{Accessor, UriBuilder};
+
+use super::ExportOptions;
+
+/// Options for the OBJ export functions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ObjExportOptions {
+    pub export: ExportOptions,
+    /// Texture formats the inspecting tool can open. The default accepts
+    /// JPEG/PNG only, which is what mesh viewers reliably load.
+    pub texture_preference: TexturePreference,
+}
+
+/// Summary of a finished OBJ export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjExportReport {
+    pub nodes_exported: usize,
+    pub vertices: usize,
+    /// File names written next to the OBJ (the MTL and any textures).
+    pub files: Vec<String>,
+    /// Total size of the OBJ, MTL and texture files (the size they would
+    /// have, on a dry run).
+    pub bytes_written: usize,
+}
+
+struct ObjWriter {
+    obj: String,
+    mtl: String,
+    vertex_base: usize,
+    /// Texture files already written, by payload content hash, so a texture
+    /// repeated across nodes lands on disk once.
+    textures: std::collections::HashMap<String, String>,
+    report: ObjExportReport,
+}
+
+impl ObjWriter {
+    fn new(stem: &str) -> Self {
+        Self {
+            obj: format!("mtllib {stem}.mtl\n"),
+            mtl: String::new(),
+            vertex_base: 1,
+            textures: std::collections::HashMap::new(),
+            report: ObjExportReport {
+                nodes_exported: 0,
+                vertices: 0,
+                files: Vec::new(),
+                bytes_written: 0,
+            },
+        }
+    }
+
+    fn append_node(
+        &mut self,
+        layer: &SceneLayer,
+        node: &Node,
+        dir: &Path,
+        options: &ObjExportOptions,
+    ) -> Result<()> {
+        let Some(mut geometry) = layer.node_geometry(node)? else {
+            return Ok(());
+        };
+        options.export.convert_geometry(&mut geometry);
+        options.export.quantize_geometry(&mut geometry);
+        if geometry.positions.is_empty() {
+            return Ok(());
+        }
+
+        let material_name = self.append_material(layer, node, dir, options)?;
+        let _ = writeln!(self.obj, "o node-{}", node.index);
+        if let Some(name) = &material_name {
+            let _ = writeln!(self.obj, "usemtl {name}");
+        }
+
+        let center = options.export.convert_point(node.obb.center);
+        for p in geometry.positions.chunks_exact(3) {
+            let _ = writeln!(
+                self.obj,
+                "v {} {} {}",
+                f64::from(p[0]) + center[0],
+                f64::from(p[1]) + center[1],
+                f64::from(p[2]) + center[2],
+            );
+        }
+        for uv in geometry.uvs.chunks_exact(2) {
+            // OBJ's texture origin is bottom-left; I3S uses top-left.
+            let _ = writeln!(self.obj, "vt {} {}", uv[0], 1.0 - uv[1]);
+        }
+        for n in geometry.normals.chunks_exact(3) {
+            let _ = writeln!(self.obj, "vn {} {} {}", n[0], n[1], n[2]);
+        }
+
+        let has_uvs = !geometry.uvs.is_empty();
+        let has_normals = !geometry.normals.is_empty();
+        let vertex_count = geometry.positions.len() / 3;
+        for tri in (0..vertex_count.saturating_sub(2)).step_by(3) {
+            let mut line = String::from("f");
+            for corner in 0..3 {
+                let index = self.vertex_base + tri + corner;
+                match (has_uvs, has_normals) {
+                    (true, true) => {
+                        let _ = write!(line, " {index}/{index}/{index}");
+                    }
+                    (true, false) => {
+                        let _ = write!(line, " {index}/{index}");
+                    }
+                    (false, true) => {
+                        let _ = write!(line, " {index}//{index}");
+                    }
+                    (false, false) => {
+                        let _ = write!(line, " {index}");
+                    }
+                }
+            }
+            let _ = writeln!(self.obj, "{line}");
+        }
+
+        self.vertex_base += vertex_count;
+        self.report.nodes_exported += 1;
+        self.report.vertices += vertex_count;
+        Ok(())
+    }
+
+    /// Write the node material into the MTL (fetching its texture next to
+    /// the OBJ, unless the same payload was already written for another
+    /// node), returning the material name, if the node has one.
+    fn append_material(
+        &mut self,
+        layer: &SceneLayer,
+        node: &Node,
+        dir: &Path,
+        options: &ObjExportOptions,
+    ) -> Result<Option<String>> {
+        let Some(material) = node.mesh.as_ref().and_then(|m| m.material.as_ref()) else {
+            return Ok(None);
+        };
+        let defn = layer.definition();
+        let Some(definition) = defn.material_definitions.get(material.definition) else {
+            return Ok(None);
+        };
+        let texture_set = definition
+            .pbr_metallic_roughness
+            .as_ref()
+            .and_then(|pbr| pbr.base_color_texture.as_ref())
+            .and_then(|t| {
+                defn.texture_set_definitions
+                    .get(t.texture_set_definition_id)
+            });
+        let decoded = decode_material(definition, texture_set, &options.texture_preference)?;
+
+        let name = format!("node-{}", node.index);
+        let _ = writeln!(self.mtl, "newmtl {name}");
+        let [r, g, b, _] = decoded.base_color_factor;
+        let _ = writeln!(self.mtl, "Kd {r} {g} {b}");
+        if let Some(texture) = &decoded.texture {
+            let rm = layer.resource_manager();
+            let uri = rm.texture_uri(material.resource, &texture.name, texture.format);
+            let bytes = rm.get(&uri)?;
+            let ext = match texture.format {
+                crate::defn::ImageFormat::Jpg => "jpg",
+                crate::defn::ImageFormat::Png => "png",
+                crate::defn::ImageFormat::Dds => "dds",
+                crate::defn::ImageFormat::Ktx2 => "ktx2",
+                crate::defn::ImageFormat::Basis => "basis",
+                crate::defn::ImageFormat::KtxEtc2 => "ktx",
+            };
+            let hash = crate::cache::content_hash(&bytes);
+            let file = match self.textures.entry(hash) {
+                std::collections::hash_map::Entry::Occupied(entry) => entry.get().clone(),
+                std::collections::hash_map::Entry::Vacant(entry) => {
+                    let file = format!("tex-{}.{ext}", entry.key());
+                    if !options.export.dry_run {
+                        std::fs::write(dir.join(&file), &*bytes)?;
+                    }
+                    self.report.files.push(file.clone());
+                    self.report.bytes_written += bytes.len();
+                    entry.insert(file).clone()
+                }
+            };
+            let _ = writeln!(self.mtl, "map_Kd {file}");
+        }
+        let _ = writeln!(self.mtl);
+        Ok(Some(name))
+    }
+
+    fn finish(mut self, dir: &Path, stem: &str, options: &ObjExportOptions) -> Result<ObjExportReport> {
+        let mtl = format!("{stem}.mtl");
+        if !options.export.dry_run {
+            std::fs::write(dir.join(format!("{stem}.obj")), self.obj.as_bytes())?;
+            std::fs::write(dir.join(&mtl), self.mtl.as_bytes())?;
+        }
+        self.report.bytes_written += self.obj.len() + self.mtl.len();
+        self.report.files.push(mtl);
+        Ok(self.report)
+    }
+}
+
+/// Dump one node's mesh as `dir/{stem}.obj` plus MTL/textures.
+pub fn export_node_obj(
+    layer: &SceneLayer,
+    node: &Node,
+    dir: impl AsRef<Path>,
+    stem: &str,
+) -> Result<ObjExportReport> {
+    let dir = dir.as_ref();
+    let options = ObjExportOptions::default();
+    std::fs::create_dir_all(dir)?;
+    let mut writer = ObjWriter::new(stem);
+    writer.append_node(layer, node, dir, &options)?;
+    writer.finish(dir, stem, &options)
+}
+
+/// Dump every leaf under `node` into one `dir/{stem}.obj`, with one OBJ
+/// object (`o node-{index}`) per leaf.
+pub fn export_subtree_obj(
+    layer: &SceneLayer,
+    node: &Arc<Node>,
+    dir: impl AsRef<Path>,
+    stem: &str,
+    options: &ObjExportOptions,
+) -> Result<ObjExportReport> {
+    let dir = dir.as_ref();
+    if !options.export.dry_run {
+        std::fs::create_dir_all(dir)?;
+    }
+    let nodes = layer.nodes()?;
+    let mut writer = ObjWriter::new(stem);
+    let mut stack = vec![Arc::clone(node)];
+    while let Some(node) = stack.pop() {
+        if node.is_leaf() {
+            writer.append_node(layer, &node, dir, options)?;
+        } else {
+            for &child in node.children.iter().rev() {
+                stack.push(nodes.get(child)?);
+            }
+        }
+    }
+    writer.finish(dir, stem, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn writes_obj_with_absolute_positions() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("obj");
+        let slpk_path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }]
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [100.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                },
+                "mesh": {
+                    "geometry": { "definition": 0, "resource": 0, "vertexCount": 3 }
+                }
+            }]
+        }))
+        .unwrap();
+        let positions: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+
+        let mut writer = SlpkWriter::create(&slpk_path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, &positions).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(slpk_path.to_str().unwrap()).unwrap();
+        let root = layer.root().unwrap();
+        let report = export_node_obj(&layer, &root, &dir, "root").unwrap();
+        assert_eq!(report.nodes_exported, 1);
+        assert_eq!(report.vertices, 3);
+
+        let obj = std::fs::read_to_string(dir.join("root.obj")).unwrap();
+        assert!(obj.contains("v 100 0 0"));
+        assert!(obj.contains("v 101 0 0"));
+        assert!(obj.contains("f 1 2 3"));
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn identical_textures_are_written_once() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("obj-dedup");
+        let slpk_path = dir.join("layer.slpk");
+
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }],
+            "materialDefinitions": [{
+                "pbrMetallicRoughness": {
+                    "baseColorTexture": { "textureSetDefinitionId": 0 }
+                }
+            }],
+            "textureSetDefinitions": [{
+                "formats": [{ "name": "0", "format": "jpg" }]
+            }]
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "obb": obb, "children": [1, 2] },
+                {
+                    "index": 1, "obb": obb, "parentIndex": 0,
+                    "mesh": {
+                        "geometry": { "definition": 0, "resource": 1, "vertexCount": 3 },
+                        "material": { "definition": 0, "resource": 1 }
+                    }
+                },
+                {
+                    "index": 2, "obb": obb, "parentIndex": 0,
+                    "mesh": {
+                        "geometry": { "definition": 0, "resource": 2, "vertexCount": 3 },
+                        "material": { "definition": 0, "resource": 2 }
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+        let positions: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+
+        let mut writer = SlpkWriter::create(&slpk_path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(1, 1, &positions).unwrap();
+        writer.write_geometry(2, 2, &positions).unwrap();
+        let jpeg = b"shared-jpeg-payload";
+        writer
+            .write_texture(1, "0", crate::defn::ImageFormat::Jpg, jpeg)
+            .unwrap();
+        writer
+            .write_texture(2, "0", crate::defn::ImageFormat::Jpg, jpeg)
+            .unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&slpk_path).unwrap();
+        let root = layer.root().unwrap();
+        let report =
+            export_subtree_obj(&layer, &root, &dir, "scene", &ObjExportOptions::default())
+                .unwrap();
+        assert_eq!(report.nodes_exported, 2);
+
+        // One shared texture file, referenced by both materials.
+        let textures: Vec<_> = report
+            .files
+            .iter()
+            .filter(|f| f.ends_with(".jpg"))
+            .collect();
+        assert_eq!(textures.len(), 1);
+        let mtl = std::fs::read_to_string(dir.join("scene.mtl")).unwrap();
+        assert_eq!(
+            mtl.matches(&format!("map_Kd {}", textures[0])).count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Conversion to Cesium 3D Tiles.
+//!
+//! Walks the full node tree and writes a `tileset.json` plus one GLB per
+//! node with mesh content, so the same SLPK or REST source can be served in
+//! both formats. Node OBBs map to 3D Tiles `boundingVolume.box` entries and
+//! `lodThreshold` becomes `geometricError`; leaves get a geometric error of
+//! zero. Tiles use `REPLACE` refinement, matching the I3S mesh pyramid.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::node::{Node, NodeArray};
+use crate::obb::{rotate, OrientedBoundingBox};
+
+use super::gltf::single_mesh_glb;
+use super::ExportOptions;
+
+/// Options for [`export_tileset`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TilesetExportOptions {
+    pub export: ExportOptions,
+}
+
+/// Summary of a finished tileset conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TilesetExportReport {
+    /// Tiles written with GLB content.
+    pub tiles_with_content: usize,
+    /// Total tiles in the tileset, including content-less interior tiles.
+    pub tiles_total: usize,
+    pub bytes_written: usize,
+}
+
+/// An OBB as the 12-number 3D Tiles `boundingVolume.box`: center followed by
+/// the three half-axes.
+fn bounding_volume_box(obb: &OrientedBoundingBox, export: &ExportOptions) -> [f64; 12] {
+    let center = export.convert_point(obb.center);
+    let axes = [
+        export.convert_point(rotate(obb.quaternion, [f64::from(obb.half_size[0]), 0.0, 0.0])),
+        export.convert_point(rotate(obb.quaternion, [0.0, f64::from(obb.half_size[1]), 0.0])),
+        export.convert_point(rotate(obb.quaternion, [0.0, 0.0, f64::from(obb.half_size[2])])),
+    ];
+    [
+        center[0], center[1], center[2], axes[0][0], axes[0][1], axes[0][2], axes[1][0],
+        axes[1][1], axes[1][2], axes[2][0], axes[2][1], axes[2][2],
+    ]
+}
+
+fn geometric_error(node: &Node) -> f64 {
+    if node.is_leaf() {
+        0.0
+    } else {
+        node.lod_threshold.unwrap_or(0.0)
+    }
+}
+
+struct TilesetBuilder<'a> {
+    layer: &'a SceneLayer,
+    options: &'a TilesetExportOptions,
+    content_dir: std::path::PathBuf,
+    report: TilesetExportReport,
+}
+
+impl TilesetBuilder<'_> {
+    fn build_tile(&mut self, nodes: &mut NodeArray, node: &Arc<Node>) -> Result<serde_json::Value> {
+        self.report.tiles_total += 1;
+        let mut tile = json!({
+            "boundingVolume": { "box": bounding_volume_box(&node.obb, &self.options.export) },
+            "geometricError": geometric_error(node) * self.options.export.unit_scale,
+            "refine": "REPLACE",
+        });
+
+        if let Some(mut geometry) = self.layer.node_geometry(node)? {
+            self.options.export.convert_geometry(&mut geometry);
+            self.options.export.quantize_geometry(&mut geometry);
+            if !geometry.positions.is_empty() {
+                let glb = single_mesh_glb(
+                    &geometry,
+                    Some(self.options.export.convert_point(node.obb.center)),
+                )?;
+                let name = format!("{}.glb", node.index);
+                if !self.options.export.dry_run {
+                    std::fs::write(self.content_dir.join(&name), &glb)?;
+                }
+                tile["content"] = json!({ "uri": format!("content/{name}") });
+                self.report.tiles_with_content += 1;
+                self.report.bytes_written += glb.len();
+            }
+        }
+
+        let mut children = Vec::with_capacity(node.children.len());
+        for &child in &node.children {
+            let child = nodes.get(child)?;
+            children.push(self.build_tile(nodes, &child)?);
+        }
+        if !children.is_empty() {
+            tile["children"] = json!(children);
+        }
+        Ok(tile)
+    }
+}
+
+/// Convert the layer to a 3D Tiles tileset rooted at `dir`.
+///
+/// Writes `dir/tileset.json` and `dir/content/{node}.glb` for every node
+/// with decodable geometry.
+pub fn export_tileset(
+    layer: &SceneLayer,
+    dir: impl AsRef<Path>,
+    options: &TilesetExportOptions,
+) -> Result<TilesetExportReport> {
+    let dir = dir.as_ref();
+    let content_dir = dir.join("content");
+    if !options.export.dry_run {
+        std::fs::create_dir_all(&content_dir)?;
+    }
+
+    let mut nodes = layer.nodes()?;
+    let root = nodes.root()?;
+    let mut builder = TilesetBuilder {
+        layer,
+        options,
+        content_dir,
+        report: TilesetExportReport {
+            tiles_with_content: 0,
+            tiles_total: 0,
+            bytes_written: 0,
+        },
+    };
+    let root_tile = builder.build_tile(&mut nodes, &root)?;
+
+    let tileset = json!({
+        "asset": { "version": "1.1" },
+        "geometricError": geometric_error(&root).max(1.0) * 2.0 * options.export.unit_scale,
+        "root": root_tile,
+    });
+    let bytes = serde_json::to_vec_pretty(&tileset)
+        .map_err(|e| crate::err::I3SError::json("tileset.json", e))?;
+    if !options.export.dry_run {
+        std::fs::write(dir.join("tileset.json"), &bytes)?;
+    }
+
+    let mut report = builder.report;
+    report.bytes_written += bytes.len();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_obb_maps_to_axis_aligned_box() {
+        let obb: OrientedBoundingBox = serde_json::from_value(serde_json::json!({
+            "center": [1.0, 2.0, 3.0],
+            "halfSize": [4.0, 5.0, 6.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        }))
+        .unwrap();
+        let bv = bounding_volume_box(&obb, &ExportOptions::default());
+        assert_eq!(&bv[..3], &[1.0, 2.0, 3.0]);
+        assert_eq!(&bv[3..6], &[4.0, 0.0, 0.0]);
+        assert_eq!(&bv[6..9], &[0.0, 5.0, 0.0]);
+        assert_eq!(&bv[9..], &[0.0, 0.0, 6.0]);
+
+        let y_up = ExportOptions {
+            up_axis: crate::export::UpAxis::YUp,
+            unit_scale: 2.0,
+            ..ExportOptions::default()
+        };
+        let bv = bounding_volume_box(&obb, &y_up);
+        assert_eq!(&bv[..3], &[2.0, 6.0, -4.0]);
+        assert_eq!(&bv[3..6], &[8.0, 0.0, 0.0]);
+        assert_eq!(&bv[6..9], &[0.0, 0.0, -10.0]);
+        assert_eq!(&bv[9..], &[0.0, 12.0, 0.0]);
+    }
+}
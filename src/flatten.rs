@@ -0,0 +1,193 @@
+//! Flattening a LOD cut into ECS-friendly parallel arrays.
+//!
+//! [`NodeArray::select_lod`](crate::node::NodeArray::select_lod) hands back
+//! `Arc<Node>`s, which is the right shape for traversal but the wrong one
+//! for bulk insertion into an ECS world or a GPU-driven renderer: those
+//! want one tightly-packed array per component. [`FlatScene`] is that
+//! struct-of-arrays form — element `i` of every array describes the same
+//! node, every element is plain old data, and the whole cut is built with
+//! a handful of `Vec` allocations instead of one per node.
+
+use std::sync::Arc;
+
+use crate::err::Result;
+use crate::node::{Camera, Node, NodeArray};
+
+/// Material id of a node whose mesh has no material.
+pub const NO_MATERIAL: u32 = u32::MAX;
+
+/// Handle to a node's geometry resource, small enough to live in a
+/// component or an indirect-draw record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeometryHandle {
+    /// Index into the layer's `geometryDefinitions`.
+    pub definition: u32,
+    /// The geometry resource id, as passed to
+    /// [`geometry_uri`](crate::rm::UriBuilder::geometry_uri).
+    pub resource: u32,
+    pub vertex_count: u32,
+}
+
+/// A LOD cut in struct-of-arrays form.
+///
+/// All arrays have the same length; element `i` of each describes the
+/// node at `node_indices[i]`. Nodes without a geometry are left out, so
+/// every element is drawable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlatScene {
+    pub node_indices: Vec<usize>,
+    /// World-space bounding-box center per node — the translation to
+    /// place its node-relative geometry under.
+    pub centers: Vec<[f64; 3]>,
+    /// Bounding-box orientation quaternion (x, y, z, w) per node.
+    pub rotations: Vec<[f64; 4]>,
+    /// Bounding-box half extents per node, for culling components.
+    pub half_sizes: Vec<[f32; 3]>,
+    pub geometries: Vec<GeometryHandle>,
+    /// Material definition index per node, [`NO_MATERIAL`] when the mesh
+    /// has none.
+    pub material_ids: Vec<u32>,
+}
+
+impl FlatScene {
+    /// An empty scene with room for `capacity` nodes in every array.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            node_indices: Vec::with_capacity(capacity),
+            centers: Vec::with_capacity(capacity),
+            rotations: Vec::with_capacity(capacity),
+            half_sizes: Vec::with_capacity(capacity),
+            geometries: Vec::with_capacity(capacity),
+            material_ids: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Flatten an already-selected cut, e.g. the output of
+    /// [`select_lod`](crate::node::NodeArray::select_lod) or
+    /// [`select_visible`](crate::node::NodeArray::select_visible).
+    pub fn from_nodes(nodes: &[Arc<Node>]) -> Self {
+        let mut scene = Self::with_capacity(nodes.len());
+        for node in nodes {
+            scene.push(node);
+        }
+        scene
+    }
+
+    /// Append one node to every array; nodes without a geometry are
+    /// skipped. Returns whether the node was appended.
+    pub fn push(&mut self, node: &Node) -> bool {
+        let Some(mesh) = &node.mesh else {
+            return false;
+        };
+        let Some(geometry) = &mesh.geometry else {
+            return false;
+        };
+        self.node_indices.push(node.index);
+        self.centers.push(node.obb.center);
+        self.rotations.push(node.obb.quaternion);
+        self.half_sizes.push(node.obb.half_size);
+        self.geometries.push(GeometryHandle {
+            definition: geometry.definition as u32,
+            resource: geometry.resource as u32,
+            vertex_count: geometry.vertex_count as u32,
+        });
+        self.material_ids.push(
+            mesh.material
+                .as_ref()
+                .map_or(NO_MATERIAL, |material| material.definition as u32),
+        );
+        true
+    }
+
+    /// How many drawable nodes the scene holds.
+    pub fn len(&self) -> usize {
+        self.node_indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node_indices.is_empty()
+    }
+}
+
+/// Select the LOD cut for `camera` and flatten it in one step.
+pub fn flatten_lod(
+    nodes: &mut NodeArray,
+    camera: &Camera,
+    max_screen_error: f64,
+) -> Result<FlatScene> {
+    Ok(FlatScene::from_nodes(&nodes.select_lod(
+        camera,
+        max_screen_error,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::NodePage;
+
+    #[test]
+    fn arrays_stay_aligned_and_skip_undrawable_nodes() {
+        let page: NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                {
+                    // No mesh: skipped.
+                    "index": 0,
+                    "obb": {
+                        "center": [0.0, 0.0, 0.0],
+                        "halfSize": [1.0, 1.0, 1.0],
+                        "quaternion": [0.0, 0.0, 0.0, 1.0]
+                    },
+                    "children": [1, 2]
+                },
+                {
+                    "index": 1,
+                    "obb": {
+                        "center": [10.0, 20.0, 30.0],
+                        "halfSize": [1.0, 2.0, 3.0],
+                        "quaternion": [0.0, 0.0, 0.0, 1.0]
+                    },
+                    "parentIndex": 0,
+                    "mesh": {
+                        "geometry": { "definition": 0, "resource": 1, "vertexCount": 36 },
+                        "material": { "definition": 4, "resource": 1 }
+                    }
+                },
+                {
+                    "index": 2,
+                    "obb": {
+                        "center": [-10.0, 0.0, 5.0],
+                        "halfSize": [4.0, 4.0, 4.0],
+                        "quaternion": [0.0, 0.0, 1.0, 0.0]
+                    },
+                    "parentIndex": 0,
+                    "mesh": {
+                        // Untextured: sentinel material id.
+                        "geometry": { "definition": 1, "resource": 2, "vertexCount": 9 }
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+
+        let scene = FlatScene::from_nodes(&page.nodes);
+        assert_eq!(scene.len(), 2);
+        assert_eq!(scene.node_indices, vec![1, 2]);
+        assert_eq!(scene.centers[0], [10.0, 20.0, 30.0]);
+        assert_eq!(scene.rotations[1], [0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(scene.half_sizes[0], [1.0, 2.0, 3.0]);
+        assert_eq!(
+            scene.geometries[0],
+            GeometryHandle {
+                definition: 0,
+                resource: 1,
+                vertex_count: 36
+            }
+        );
+        assert_eq!(scene.material_ids, vec![4, NO_MATERIAL]);
+
+        let mut scene = FlatScene::with_capacity(1);
+        assert!(!scene.push(&page.nodes[0]));
+        assert!(scene.is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+//! Reading exploded scene layer folders from disk.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use dashmap::DashMap;
+
+use crate::decode::maybe_ungzip;
+use crate::defn::ImageFormat;
+use crate::err::{I3SError, Result};
+use crate::rm::{Accessor, UriBuilder};
+
+struct CachedEntry {
+    bytes: Arc<Vec<u8>>,
+    /// Modification time of the file when it was read.
+    mtime: Option<SystemTime>,
+}
+
+/// An exploded scene layer directory (decompressed `3dSceneLayer.json`,
+/// `nodepages/`, `nodes/...`), as produced by
+/// [`SceneLayerPackage::explode_to`](crate::slpk::SceneLayerPackage::explode_to)
+/// or a static hosting pipeline.
+pub struct ExplodedFolder {
+    root: PathBuf,
+    cache: Arc<DashMap<String, CachedEntry>>,
+}
+
+impl ExplodedFolder {
+    /// Open an exploded layer directory from disk.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        if !root.join("3dSceneLayer.json").is_file()
+            && !root.join("3dSceneLayer.json.gz").is_file()
+        {
+            return Err(I3SError::InvalidUri(format!(
+                "{} has no 3dSceneLayer.json",
+                root.display()
+            )));
+        }
+        Ok(Self {
+            root,
+            cache: Arc::new(DashMap::new()),
+        })
+    }
+
+    /// Root directory of the layer.
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Start a background watcher that evicts cached resources whose files
+    /// change on disk, polling modification times every `poll_interval`.
+    ///
+    /// Enables live-preview workflows: an authoring tool can regenerate
+    /// node pages or geometry in place and readers pick up the new bytes on
+    /// their next fetch. The watcher stops when the returned handle drops.
+    pub fn watch(&self, poll_interval: Duration) -> FolderWatcher {
+        FolderWatcher::spawn(self.root.clone(), Arc::clone(&self.cache), poll_interval)
+    }
+
+    /// Drop all cached resource bytes.
+    pub fn invalidate(&self) {
+        self.cache.clear();
+    }
+}
+
+/// A running change watcher for an [`ExplodedFolder`].
+pub struct FolderWatcher {
+    stop: Arc<AtomicBool>,
+    invalidations: Arc<AtomicU64>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl FolderWatcher {
+    fn spawn(
+        root: PathBuf,
+        cache: Arc<DashMap<String, CachedEntry>>,
+        poll_interval: Duration,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let invalidations = Arc::new(AtomicU64::new(0));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            let invalidations = Arc::clone(&invalidations);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let stale: Vec<String> = cache
+                        .iter()
+                        .filter(|entry| {
+                            let on_disk = fs::metadata(root.join(entry.key()))
+                                .and_then(|m| m.modified())
+                                .ok();
+                            on_disk != entry.value().mtime
+                        })
+                        .map(|entry| entry.key().clone())
+                        .collect();
+                    for uri in stale {
+                        cache.remove(&uri);
+                        invalidations.fetch_add(1, Ordering::Relaxed);
+                    }
+                    std::thread::sleep(poll_interval);
+                }
+            })
+        };
+        Self {
+            stop,
+            invalidations,
+            thread: Some(thread),
+        }
+    }
+
+    /// How many cached resources have been evicted since the watcher
+    /// started.
+    pub fn invalidations(&self) -> u64 {
+        self.invalidations.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for FolderWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Accessor for ExplodedFolder {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        if let Some(hit) = self.cache.get(uri) {
+            return Ok(Arc::clone(&hit.value().bytes));
+        }
+        let path = self.root.join(uri);
+        // Tolerate folders unpacked without decompressing: fall back to the
+        // gzipped entry name and inflate on the fly.
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                fs::read(self.root.join(format!("{uri}.gz")))
+                    .map_err(|_| I3SError::MissingResource(uri.to_string()))?
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let bytes = Arc::new(maybe_ungzip(bytes)?);
+        self.cache.insert(
+            uri.to_string(),
+            CachedEntry {
+                bytes: Arc::clone(&bytes),
+                mtime,
+            },
+        );
+        Ok(bytes)
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        let metadata = fs::metadata(self.root.join(uri))
+            .map_err(|_| I3SError::MissingResource(uri.to_string()))?;
+        Ok(Some(metadata.len()))
+    }
+}
+
+impl UriBuilder for ExplodedFolder {
+    fn scene_definition_uri(&self) -> String {
+        "3dSceneLayer.json".to_string()
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        format!("nodepages/{page_index}.json")
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        format!("nodes/{node_index}/geometries/{resource}.bin")
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String {
+        let ext = match format {
+            ImageFormat::Jpg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Dds => "bin.dds",
+            ImageFormat::Ktx2 => "ktx2",
+            ImageFormat::Basis => "basis",
+            ImageFormat::KtxEtc2 => "ktx",
+        };
+        format!("nodes/{node_index}/textures/{name}.{ext}")
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        format!("nodes/{node_index}/attributes/{key}/0.bin")
+    }
+}
+
+#[cfg(test)]
+mod watch_tests {
+    use super::*;
+
+    #[test]
+    fn watcher_evicts_changed_resources() {
+        let dir = crate::testutil::TestDir::new("watch");
+        std::fs::write(
+            dir.join("3dSceneLayer.json"),
+            serde_json::to_vec(&serde_json::json!({
+                "id": 0,
+                "name": "before",
+                "layerType": "IntegratedMesh",
+                "store": { "profile": "meshpyramids" }
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+
+        let folder = ExplodedFolder::open(&dir).unwrap();
+        let watcher = folder.watch(Duration::from_millis(10));
+        let before = folder.get("3dSceneLayer.json").unwrap();
+        assert!(String::from_utf8_lossy(&before).contains("before"));
+
+        std::fs::write(dir.join("3dSceneLayer.json"), br#"{"id":0,"name":"after"}"#).unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while watcher.invalidations() == 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(watcher.invalidations() > 0);
+        let after = folder.get("3dSceneLayer.json").unwrap();
+        assert!(String::from_utf8_lossy(&after).contains("after"));
+
+        drop(watcher);
+    }
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::slpk::writer::SlpkWriter;
+
+    #[test]
+    fn exploded_layer_opens_via_from_uri() {
+        let dir = crate::testutil::TestDir::new("folder");
+        let path = dir.join("layer.slpk");
+        let out = dir.join("exploded");
+        std::fs::remove_dir_all(&out).ok();
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": "exploded",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        let package = crate::slpk::SceneLayerPackage::open(&path).unwrap();
+        package.explode_to(&out).unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(out.to_str().unwrap()).unwrap();
+        assert_eq!(layer.name(), Some("exploded"));
+        let root = layer.root().unwrap();
+        assert_eq!(root.index, 0);
+        let folder = ExplodedFolder::open(&out).unwrap();
+        let geometry = folder.get(&folder.geometry_uri(0, 0)).unwrap();
+        assert_eq!(&*geometry, &vec![1u8, 2, 3]);
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_dir_all(&out).ok();
+    }
+}
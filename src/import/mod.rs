@@ -0,0 +1,3 @@
+//! Importing scene layers from other formats.
+
+pub mod tiles3d;
@@ -0,0 +1,534 @@
+//! Conversion from Cesium 3D Tiles.
+//!
+//! Reads a `tileset.json` plus its per-tile GLB (or b3dm-wrapped GLB)
+//! content and writes an equivalent SLPK via [`SlpkWriter`]. Bounding
+//! volume boxes become node OBBs, `geometricError` becomes `lodThreshold`,
+//! and the first mesh primitive of each content file becomes the node
+//! geometry, with positions re-based onto the node OBB center. Tiles
+//! without content become interior nodes without a mesh. Textured and
+//! draco-compressed content is out of scope for now; accessors must be
+//! uncompressed `float` data.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::defn::{
+    GeometryAttribute, GeometryBuffer, GeometryDefinition, LayerType, Profile, SceneDefinition,
+};
+use crate::err::{I3SError, Result};
+use crate::node::{Mesh, MeshGeometry, Node, NodePage};
+use crate::slpk::writer::{SlpkWriter, SlpkWriterStats};
+
+/// Summary of a finished tileset import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TilesetImportReport {
+    /// Nodes written to the package.
+    pub nodes: usize,
+    /// Nodes that carry geometry.
+    pub nodes_with_geometry: usize,
+    pub vertices: usize,
+}
+
+/// Vertex streams pulled out of one content file.
+struct ImportedGeometry {
+    positions: Vec<f32>,
+    normals: Vec<f32>,
+    uvs: Vec<f32>,
+}
+
+/// One node assembled from a tile, pending page assignment.
+struct PendingNode {
+    node: Node,
+    geometry: Option<(usize, Vec<u8>)>,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| I3SError::Decode("content truncated".to_string()))
+}
+
+/// Strip a b3dm wrapper down to the embedded GLB, or pass GLB through.
+fn content_glb(bytes: &[u8]) -> Result<&[u8]> {
+    if bytes.starts_with(b"glTF") {
+        return Ok(bytes);
+    }
+    if bytes.starts_with(b"b3dm") {
+        let ft_json = read_u32(bytes, 12)? as usize;
+        let ft_bin = read_u32(bytes, 16)? as usize;
+        let bt_json = read_u32(bytes, 20)? as usize;
+        let bt_bin = read_u32(bytes, 24)? as usize;
+        let start = 28 + ft_json + ft_bin + bt_json + bt_bin;
+        return bytes
+            .get(start..)
+            .filter(|glb| glb.starts_with(b"glTF"))
+            .ok_or_else(|| I3SError::Decode("b3dm does not embed a GLB".to_string()));
+    }
+    Err(I3SError::Decode(
+        "tile content is neither GLB nor b3dm".to_string(),
+    ))
+}
+
+fn glb_chunks(glb: &[u8]) -> Result<(Value, &[u8])> {
+    let mut offset = 12;
+    let mut document = None;
+    let mut bin: &[u8] = &[];
+    while offset + 8 <= glb.len() {
+        let len = read_u32(glb, offset)? as usize;
+        let kind = &glb[offset + 4..offset + 8];
+        let chunk = glb
+            .get(offset + 8..offset + 8 + len)
+            .ok_or_else(|| I3SError::Decode("GLB chunk truncated".to_string()))?;
+        match kind {
+            b"JSON" => {
+                document = Some(
+                    serde_json::from_slice(chunk)
+                        .map_err(|e| I3SError::json("GLB json chunk", e))?,
+                )
+            }
+            b"BIN\0" => bin = chunk,
+            _ => {}
+        }
+        offset += 8 + len;
+    }
+    document
+        .map(|doc| (doc, bin))
+        .ok_or_else(|| I3SError::Decode("GLB has no JSON chunk".to_string()))
+}
+
+fn accessor_f32s(document: &Value, bin: &[u8], accessor_index: &Value) -> Result<Vec<f32>> {
+    let Some(index) = accessor_index.as_u64() else {
+        return Ok(Vec::new());
+    };
+    let accessor = &document["accessors"][index as usize];
+    if accessor["componentType"].as_u64() != Some(5126) {
+        return Err(I3SError::Decode(
+            "only float accessors are supported by the tileset importer".to_string(),
+        ));
+    }
+    let components = match accessor["type"].as_str() {
+        Some("VEC3") => 3,
+        Some("VEC2") => 2,
+        Some("SCALAR") => 1,
+        other => {
+            return Err(I3SError::Decode(format!(
+                "unsupported accessor type {other:?}"
+            )))
+        }
+    };
+    let count = accessor["count"].as_u64().unwrap_or(0) as usize;
+    let view = &document["bufferViews"][accessor["bufferView"].as_u64().unwrap_or(0) as usize];
+    let base = view["byteOffset"].as_u64().unwrap_or(0) as usize
+        + accessor["byteOffset"].as_u64().unwrap_or(0) as usize;
+    // Interleaved views declare a byteStride wider than one element.
+    let element = components * 4;
+    let stride = view["byteStride"].as_u64().map_or(element, |s| s as usize);
+    if stride < element {
+        return Err(I3SError::Decode(format!(
+            "bufferView byteStride {stride} is narrower than the {element}-byte element"
+        )));
+    }
+    let mut out = Vec::with_capacity(count * components);
+    for index in 0..count {
+        let start = base + index * stride;
+        let slice = bin
+            .get(start..start + element)
+            .ok_or_else(|| I3SError::Decode("accessor outside the GLB binary chunk".to_string()))?;
+        out.extend(
+            slice
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])),
+        );
+    }
+    Ok(out)
+}
+
+/// Extract the first mesh primitive of a GLB, applying the containing glTF
+/// node's translation to positions.
+fn import_glb(bytes: &[u8]) -> Result<ImportedGeometry> {
+    let (document, bin) = glb_chunks(content_glb(bytes)?)?;
+    let node = &document["nodes"][0];
+    let mesh = node["mesh"].as_u64().unwrap_or(0) as usize;
+    let primitive = &document["meshes"][mesh]["primitives"][0];
+    let attributes = &primitive["attributes"];
+
+    let mut positions = accessor_f32s(&document, bin, &attributes["POSITION"])?;
+    if let Some(t) = node["translation"].as_array() {
+        let t: Vec<f64> = t.iter().filter_map(Value::as_f64).collect();
+        if t.len() == 3 {
+            for (i, p) in positions.iter_mut().enumerate() {
+                *p = ((f64::from(*p)) + t[i % 3]) as f32;
+            }
+        }
+    }
+    Ok(ImportedGeometry {
+        positions,
+        normals: accessor_f32s(&document, bin, &attributes["NORMAL"])?,
+        uvs: accessor_f32s(&document, bin, &attributes["TEXCOORD_0"])?,
+    })
+}
+
+/// Quaternion (x, y, z, w) for the rotation whose columns are the given
+/// orthonormal axes.
+fn quaternion_from_axes(axes: [[f64; 3]; 3]) -> [f64; 4] {
+    let m = |r: usize, c: usize| axes[c][r];
+    let trace = m(0, 0) + m(1, 1) + m(2, 2);
+    if trace > 0.0 {
+        let s = 0.5 / (trace + 1.0).sqrt();
+        [
+            (m(2, 1) - m(1, 2)) * s,
+            (m(0, 2) - m(2, 0)) * s,
+            (m(1, 0) - m(0, 1)) * s,
+            0.25 / s,
+        ]
+    } else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+        let s = 2.0 * (1.0 + m(0, 0) - m(1, 1) - m(2, 2)).sqrt();
+        [
+            0.25 * s,
+            (m(0, 1) + m(1, 0)) / s,
+            (m(0, 2) + m(2, 0)) / s,
+            (m(2, 1) - m(1, 2)) / s,
+        ]
+    } else if m(1, 1) > m(2, 2) {
+        let s = 2.0 * (1.0 + m(1, 1) - m(0, 0) - m(2, 2)).sqrt();
+        [
+            (m(0, 1) + m(1, 0)) / s,
+            0.25 * s,
+            (m(1, 2) + m(2, 1)) / s,
+            (m(0, 2) - m(2, 0)) / s,
+        ]
+    } else {
+        let s = 2.0 * (1.0 + m(2, 2) - m(0, 0) - m(1, 1)).sqrt();
+        [
+            (m(0, 2) + m(2, 0)) / s,
+            (m(1, 2) + m(2, 1)) / s,
+            0.25 * s,
+            (m(1, 0) - m(0, 1)) / s,
+        ]
+    }
+}
+
+fn obb_from_box(bv: &[f64]) -> Result<crate::obb::OrientedBoundingBox> {
+    if bv.len() != 12 {
+        return Err(I3SError::Decode(
+            "boundingVolume.box must have 12 numbers".to_string(),
+        ));
+    }
+    let mut half_size = [0f32; 3];
+    let mut axes = [[0f64; 3]; 3];
+    for i in 0..3 {
+        let axis = [bv[3 + i * 3], bv[4 + i * 3], bv[5 + i * 3]];
+        let len = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+        half_size[i] = len as f32;
+        axes[i] = if len > 0.0 {
+            [axis[0] / len, axis[1] / len, axis[2] / len]
+        } else {
+            let mut unit = [0.0; 3];
+            unit[i] = 1.0;
+            unit
+        };
+    }
+    Ok(crate::obb::OrientedBoundingBox {
+        center: [bv[0], bv[1], bv[2]],
+        half_size,
+        quaternion: quaternion_from_axes(axes),
+    })
+}
+
+fn float_attribute(component: usize) -> GeometryAttribute {
+    GeometryAttribute {
+        value_type: "Float32".to_string(),
+        component,
+        encoding: None,
+        binding: Some("per-vertex".to_string()),
+    }
+}
+
+/// Index of the geometry definition matching the attribute combination,
+/// adding one when first seen.
+fn definition_for(
+    definitions: &mut Vec<GeometryDefinition>,
+    has_normals: bool,
+    has_uvs: bool,
+) -> usize {
+    let matches = |buffer: &GeometryBuffer| {
+        buffer.normal.is_some() == has_normals && buffer.uv0.is_some() == has_uvs
+    };
+    if let Some(i) = definitions
+        .iter()
+        .position(|d| d.geometry_buffers.first().is_some_and(matches))
+    {
+        return i;
+    }
+    definitions.push(GeometryDefinition {
+        topology: Some("triangle".to_string()),
+        geometry_buffers: vec![GeometryBuffer {
+            offset: None,
+            position: Some(float_attribute(3)),
+            normal: has_normals.then(|| float_attribute(3)),
+            uv0: has_uvs.then(|| float_attribute(2)),
+            color: None,
+            feature_id: None,
+            face_range: None,
+            uv_region: None,
+            compressed_attributes: None,
+        }],
+    });
+    definitions.len() - 1
+}
+
+struct TilesetImporter {
+    base_dir: std::path::PathBuf,
+    definitions: Vec<GeometryDefinition>,
+    nodes: Vec<PendingNode>,
+    report: TilesetImportReport,
+}
+
+impl TilesetImporter {
+    fn import_tile(&mut self, tile: &Value, parent: Option<usize>) -> Result<usize> {
+        let index = self.nodes.len();
+        let obb = obb_from_box(
+            tile["boundingVolume"]["box"]
+                .as_array()
+                .map(|a| a.iter().filter_map(Value::as_f64).collect::<Vec<_>>())
+                .as_deref()
+                .unwrap_or(&[]),
+        )?;
+        let geometric_error = tile["geometricError"].as_f64().unwrap_or(0.0);
+
+        let mut geometry = None;
+        let mut mesh = None;
+        if let Some(uri) = tile["content"]["uri"].as_str() {
+            let imported = import_glb(&std::fs::read(self.base_dir.join(uri))?)?;
+            if !imported.positions.is_empty() {
+                let vertex_count = imported.positions.len() / 3;
+                let definition = definition_for(
+                    &mut self.definitions,
+                    !imported.normals.is_empty(),
+                    !imported.uvs.is_empty(),
+                );
+                let mut bytes = Vec::new();
+                for p in imported.positions.chunks_exact(3) {
+                    for (i, v) in p.iter().enumerate() {
+                        let rebased = (f64::from(*v) - obb.center[i]) as f32;
+                        bytes.extend_from_slice(&rebased.to_le_bytes());
+                    }
+                }
+                for v in imported.normals.iter().chain(&imported.uvs) {
+                    bytes.extend_from_slice(&v.to_le_bytes());
+                }
+                mesh = Some(Mesh {
+                    material: None,
+                    geometry: Some(MeshGeometry {
+                        definition,
+                        resource: index,
+                        vertex_count,
+                        feature_count: None,
+                    }),
+                    attribute: None,
+                });
+                geometry = Some((index, bytes));
+                self.report.nodes_with_geometry += 1;
+                self.report.vertices += vertex_count;
+            }
+        }
+
+        self.nodes.push(PendingNode {
+            node: Node {
+                index,
+                parent_index: parent,
+                children: Vec::new(),
+                obb,
+                lod_threshold: (geometric_error > 0.0).then_some(geometric_error),
+                mesh,
+                extras: Default::default(),
+            },
+            geometry,
+        });
+        self.report.nodes += 1;
+
+        if let Some(children) = tile["children"].as_array() {
+            for child in children {
+                let child_index = self.import_tile(child, Some(index))?;
+                self.nodes[index].node.children.push(child_index);
+            }
+        }
+        Ok(index)
+    }
+}
+
+/// Build an SLPK from a 3D Tiles tileset.
+pub fn import_tileset(
+    tileset_path: impl AsRef<Path>,
+    slpk_path: impl AsRef<Path>,
+) -> Result<TilesetImportReport> {
+    let (report, _) = import_tileset_into(tileset_path.as_ref(), SlpkWriter::create(slpk_path)?)?;
+    Ok(report)
+}
+
+/// Walk the full import pipeline — content decode, node assembly, package
+/// layout — without writing anything, returning the report the real run
+/// would produce together with the disk space it would need.
+pub fn import_tileset_dry_run(
+    tileset_path: impl AsRef<Path>,
+) -> Result<(TilesetImportReport, SlpkWriterStats)> {
+    import_tileset_into(tileset_path.as_ref(), SlpkWriter::dry_run())
+}
+
+fn import_tileset_into<W: std::io::Write + std::io::Seek>(
+    tileset_path: &Path,
+    mut writer: SlpkWriter<W>,
+) -> Result<(TilesetImportReport, SlpkWriterStats)> {
+    let tileset: Value = serde_json::from_slice(&std::fs::read(tileset_path)?)
+        .map_err(|e| I3SError::json(tileset_path.to_string_lossy(), e))?;
+    let base_dir = tileset_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+
+    let mut importer = TilesetImporter {
+        base_dir,
+        definitions: Vec::new(),
+        nodes: Vec::new(),
+        report: TilesetImportReport {
+            nodes: 0,
+            nodes_with_geometry: 0,
+            vertices: 0,
+        },
+    };
+    importer.import_tile(&tileset["root"], None)?;
+
+    const NODES_PER_PAGE: usize = 64;
+    let mut builder = SceneDefinition::builder(LayerType::IntegratedMesh, Profile::MeshPyramids)
+        .nodes_per_page(NODES_PER_PAGE);
+    for definition in importer.definitions {
+        builder = builder.geometry_definition(definition);
+    }
+    let defn = builder.build()?;
+
+    writer.write_scene_definition(&defn)?;
+    let mut pages = Vec::new();
+    let mut geometries = Vec::new();
+    for pending in importer.nodes {
+        if pages.len() * NODES_PER_PAGE == pending.node.index {
+            pages.push(NodePage { nodes: Vec::new() });
+        }
+        pages
+            .last_mut()
+            .expect("page pushed above")
+            .nodes
+            .push(std::sync::Arc::new(pending.node));
+        if let Some(geometry) = pending.geometry {
+            geometries.push(geometry);
+        }
+    }
+    for (page_index, page) in pages.iter().enumerate() {
+        writer.write_node_page(page_index, page)?;
+    }
+    for (node_index, bytes) in geometries {
+        writer.write_geometry(node_index, node_index, &bytes)?;
+    }
+    let stats = writer.finish()?;
+    Ok((importer.report, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tiles3d::{export_tileset, TilesetExportOptions};
+    use crate::layer::SceneLayer;
+
+    #[test]
+    fn exported_tileset_round_trips_to_slpk() {
+        let dir = crate::testutil::TestDir::new("tiles3d-import");
+        let source_slpk = dir.join("source.slpk");
+        let tileset_dir = dir.join("tiles");
+        let imported_slpk = dir.join("imported.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }]
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [10.0, 20.0, 30.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                },
+                "mesh": {
+                    "geometry": { "definition": 0, "resource": 0, "vertexCount": 3 }
+                }
+            }]
+        }))
+        .unwrap();
+        let positions = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let bytes: Vec<u8> = positions.iter().flat_map(|f| f.to_le_bytes()).collect();
+
+        let mut writer = SlpkWriter::create(&source_slpk).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, &bytes).unwrap();
+        writer.finish().unwrap();
+
+        let source = SceneLayer::from_uri(source_slpk.to_str().unwrap()).unwrap();
+        export_tileset(
+            &source,
+            &tileset_dir,
+            &TilesetExportOptions {
+                export: crate::export::ExportOptions::full_precision(),
+            },
+        )
+        .unwrap();
+
+        let report = import_tileset(tileset_dir.join("tileset.json"), &imported_slpk).unwrap();
+        assert_eq!(report.nodes, 1);
+        assert_eq!(report.nodes_with_geometry, 1);
+        assert_eq!(report.vertices, 3);
+
+        let imported = SceneLayer::from_uri(imported_slpk.to_str().unwrap()).unwrap();
+        let root = imported.root().unwrap();
+        assert_eq!(root.obb.center, [10.0, 20.0, 30.0]);
+        let geometry = imported.node_geometry(&root).unwrap().unwrap();
+        assert_eq!(geometry.positions, positions);
+    }
+
+    #[test]
+    fn interleaved_accessors_honor_the_byte_stride() {
+        // Two vertices of interleaved position + normal, 24 bytes each.
+        let vertices = [
+            ([1.0f32, 2.0, 3.0], [0.0f32, 0.0, 1.0]),
+            ([4.0, 5.0, 6.0], [0.0, 1.0, 0.0]),
+        ];
+        let mut bin = Vec::new();
+        for (position, normal) in &vertices {
+            for v in position.iter().chain(normal) {
+                bin.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        let document = serde_json::json!({
+            "accessors": [
+                { "bufferView": 0, "byteOffset": 0, "componentType": 5126,
+                  "count": 2, "type": "VEC3" },
+                { "bufferView": 0, "byteOffset": 12, "componentType": 5126,
+                  "count": 2, "type": "VEC3" }
+            ],
+            "bufferViews": [{ "buffer": 0, "byteLength": 48, "byteStride": 24 }]
+        });
+        let positions = accessor_f32s(&document, &bin, &serde_json::json!(0)).unwrap();
+        assert_eq!(positions, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let normals = accessor_f32s(&document, &bin, &serde_json::json!(1)).unwrap();
+        assert_eq!(normals, [0.0, 0.0, 1.0, 0.0, 1.0, 0.0]);
+    }
+}
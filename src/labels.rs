@@ -0,0 +1,197 @@
+//! Display labels for attribute fields.
+//!
+//! The layer schema carries machine-oriented field keys with optional
+//! aliases; hosted layers usually also have a portal item whose popup
+//! configuration localizes display names and coded-value domain labels.
+//! [`FieldLabels`] starts from the schema aliases and merges portal item
+//! metadata over them, so exported tables and popups can show display
+//! names instead of raw field keys.
+
+use std::collections::HashMap;
+
+use crate::defn::SceneDefinition;
+#[cfg(feature = "http")]
+use crate::err::Result;
+
+/// Display names and domain labels for the fields of one layer.
+#[derive(Debug, Clone, Default)]
+pub struct FieldLabels {
+    labels: HashMap<String, String>,
+    domains: HashMap<String, HashMap<String, String>>,
+}
+
+impl FieldLabels {
+    /// Labels from the layer schema alone: every declared alias becomes
+    /// the field's display name.
+    pub fn from_definition(defn: &SceneDefinition) -> Self {
+        let mut labels = Self::default();
+        for field in &defn.fields {
+            if let Some(alias) = &field.alias {
+                labels.labels.insert(field.name.clone(), alias.clone());
+            }
+        }
+        labels
+    }
+
+    /// Merge field metadata from a portal item's data document (the
+    /// `sharing/rest/content/items/{id}/data` JSON). Popup `fieldInfos`
+    /// labels and `layerDefinition` aliases and coded-value domains are
+    /// taken from every layer entry; portal values override what the
+    /// schema declared, since the portal item is where localization
+    /// happens.
+    pub fn merge_portal_item(&mut self, item: &serde_json::Value) {
+        let layers = item
+            .get("layers")
+            .and_then(|layers| layers.as_array())
+            .map(|layers| layers.iter().collect::<Vec<_>>())
+            .unwrap_or_else(|| vec![item]);
+        for layer in layers {
+            for field in json_array(layer, &["layerDefinition", "fields"]) {
+                let Some(name) = field.get("name").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                if let Some(alias) = field.get("alias").and_then(|v| v.as_str()) {
+                    self.labels.insert(name.to_string(), alias.to_string());
+                }
+                for coded in json_array(field, &["domain", "codedValues"]) {
+                    let (Some(code), Some(label)) = (
+                        coded.get("code"),
+                        coded.get("name").and_then(|v| v.as_str()),
+                    ) else {
+                        continue;
+                    };
+                    // Codes may be numbers or strings; store their
+                    // canonical string form.
+                    let code = match code.as_str() {
+                        Some(code) => code.to_string(),
+                        None => code.to_string(),
+                    };
+                    self.domains
+                        .entry(name.to_string())
+                        .or_default()
+                        .insert(code, label.to_string());
+                }
+            }
+            // Popup labels carry the localization and win over aliases.
+            for info in json_array(layer, &["popupInfo", "fieldInfos"]) {
+                if let (Some(name), Some(label)) = (
+                    info.get("fieldName").and_then(|v| v.as_str()),
+                    info.get("label").and_then(|v| v.as_str()),
+                ) {
+                    self.labels.insert(name.to_string(), label.to_string());
+                }
+            }
+        }
+    }
+
+    /// Fetch a portal item's data document and merge its labels.
+    ///
+    /// `portal_url` is the sharing root (e.g.
+    /// `https://www.arcgis.com/sharing/rest`), `item_id` the layer's
+    /// portal item.
+    #[cfg(feature = "http")]
+    pub fn fetch_portal_item(&mut self, portal_url: &str, item_id: &str) -> Result<()> {
+        let uri = format!(
+            "{}/content/items/{item_id}/data?f=json",
+            portal_url.trim_end_matches('/')
+        );
+        let response = reqwest::blocking::get(&uri)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(crate::err::I3SError::Http {
+                status: status.as_u16(),
+                uri,
+            });
+        }
+        let item: serde_json::Value = response
+            .json()
+            .map_err(crate::err::I3SError::Request)?;
+        self.merge_portal_item(&item);
+        Ok(())
+    }
+
+    /// The display name for a field key: the merged label when one is
+    /// known, otherwise the key itself.
+    pub fn display_name<'a>(&'a self, field: &'a str) -> &'a str {
+        self.labels.get(field).map_or(field, String::as_str)
+    }
+
+    /// The label of a coded domain value, if the field has one.
+    pub fn domain_label(&self, field: &str, code: &str) -> Option<&str> {
+        self.domains
+            .get(field)
+            .and_then(|codes| codes.get(code))
+            .map(String::as_str)
+    }
+}
+
+fn json_array<'a>(value: &'a serde_json::Value, path: &[&str]) -> Vec<&'a serde_json::Value> {
+    let mut current = value;
+    for key in path {
+        match current.get(key) {
+            Some(next) => current = next,
+            None => return Vec::new(),
+        }
+    }
+    current
+        .as_array()
+        .map(|values| values.iter().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn portal_labels_override_schema_aliases() {
+        let defn: SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "3DObject",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "fields": [
+                { "name": "BLDG_USE", "type": "esriFieldTypeString", "alias": "Use" },
+                { "name": "HEIGHT_M", "type": "esriFieldTypeDouble" }
+            ]
+        }))
+        .unwrap();
+        let mut labels = FieldLabels::from_definition(&defn);
+        assert_eq!(labels.display_name("BLDG_USE"), "Use");
+        assert_eq!(labels.display_name("HEIGHT_M"), "HEIGHT_M");
+
+        labels.merge_portal_item(&serde_json::json!({
+            "layers": [{
+                "popupInfo": {
+                    "fieldInfos": [
+                        { "fieldName": "BLDG_USE", "label": "Gebäudenutzung" }
+                    ]
+                },
+                "layerDefinition": {
+                    "fields": [{
+                        "name": "BLDG_USE",
+                        "alias": "Building use",
+                        "domain": {
+                            "type": "codedValue",
+                            "codedValues": [
+                                { "name": "Wohnen", "code": 1 },
+                                { "name": "Büro", "code": 2 }
+                            ]
+                        }
+                    }, {
+                        "name": "HEIGHT_M",
+                        "alias": "Height (m)"
+                    }]
+                }
+            }]
+        }));
+
+        // The localized popup label wins over both aliases.
+        assert_eq!(labels.display_name("BLDG_USE"), "Gebäudenutzung");
+        // A field without popup info falls back to the portal alias.
+        assert_eq!(labels.display_name("HEIGHT_M"), "Height (m)");
+        assert_eq!(labels.domain_label("BLDG_USE", "1"), Some("Wohnen"));
+        assert_eq!(labels.domain_label("BLDG_USE", "3"), None);
+        assert_eq!(labels.domain_label("HEIGHT_M", "1"), None);
+    }
+}
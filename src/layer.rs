@@ -0,0 +1,702 @@
+//! The high-level `SceneLayer` entry point.
+
+use std::sync::Arc;
+
+use crate::decode::{DecodedGeometry, GeometryLayout, ResourceDecoder};
+use crate::defn::{Extent, LayerType, Profile, SceneDefinition};
+use crate::err::{I3SError, Result};
+use crate::node::{Node, NodeArray};
+use crate::rm::{resource_manager_factory, Accessor, I3SFormat, ResourceManager, UriBuilder};
+
+/// An open I3S scene layer, backed by an SLPK archive or a SceneServer.
+pub struct SceneLayer {
+    rm: Arc<ResourceManager>,
+    defn: SceneDefinition,
+    /// Node pages fetched so far, shared by every [`NodeArray`] view.
+    node_pages: Arc<crate::cache::BoundedCache<usize, crate::node::NodePage>>,
+    stats: Option<Arc<crate::diag::SessionStats>>,
+}
+
+impl SceneLayer {
+    /// Open a layer from a `.slpk` path, a SceneServer URL or an exploded
+    /// layer directory.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        let format = I3SFormat::from_uri(uri)?;
+        let rm = Arc::new(resource_manager_factory(format, uri)?);
+        Self::from_resource_manager(rm)
+    }
+
+    /// Open a layer like [`from_uri`](Self::from_uri), applying a settings
+    /// profile.
+    ///
+    /// The profile's network section shapes the service connection and its
+    /// cache section bounds the layer's in-memory caches; the texture and
+    /// export sections are for the caller to pass on to decoders and
+    /// exporters.
+    pub fn from_uri_with(uri: &str, config: &crate::config::Config) -> Result<Self> {
+        let format = I3SFormat::from_uri(uri)?;
+        let layer = match format {
+            #[cfg(feature = "http")]
+            I3SFormat::Rest => {
+                let service = crate::service::Service::connect_with_options(
+                    uri,
+                    crate::service::Auth::None,
+                    config.service_options(),
+                )?;
+                Self::from_resource_manager(Arc::new(ResourceManager::Service(service)))?
+            }
+            _ => Self::from_resource_manager(Arc::new(resource_manager_factory(format, uri)?))?,
+        };
+        Ok(layer.with_cache_config(config.cache))
+    }
+
+    /// Open a layer from a local `.slpk` archive without format guessing.
+    #[cfg(feature = "slpk")]
+    pub fn open_slpk(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let uri = path.as_ref().to_string_lossy().into_owned();
+        let rm = Arc::new(resource_manager_factory(I3SFormat::Slpk, &uri)?);
+        Self::from_resource_manager(rm)
+    }
+
+    /// Open a layer from a SceneServer layer URL without format guessing.
+    #[cfg(feature = "http")]
+    pub fn open_service(url: &str) -> Result<Self> {
+        let rm = Arc::new(resource_manager_factory(I3SFormat::Rest, url)?);
+        Self::from_resource_manager(rm)
+    }
+
+    /// Open a hosted layer with credentials applied to every request.
+    #[cfg(feature = "http")]
+    pub fn open_service_with(url: &str, auth: crate::service::Auth) -> Result<Self> {
+        let service = crate::service::Service::connect_with(url, auth)?;
+        Self::from_resource_manager(Arc::new(ResourceManager::Service(service)))
+    }
+
+    /// Open one layer of a multi-layer SceneServer by id; enumerate the
+    /// ids with [`Service::list_layers`](crate::service::Service::list_layers).
+    #[cfg(feature = "http")]
+    pub fn open_layer(base_url: &str, id: u32) -> Result<Self> {
+        let service = crate::service::Service::builder(base_url)
+            .layer_id(id)
+            .connect()?;
+        Self::from_resource_manager(Arc::new(ResourceManager::Service(service)))
+    }
+
+    /// Open a layer from a user-provided storage backend.
+    ///
+    /// Any thread-safe `Accessor + UriBuilder` works, so resources can come
+    /// from a database, an object store or a proprietary cache without
+    /// changes to this crate.
+    pub fn from_backend(backend: impl crate::rm::Backend + 'static) -> Result<Self> {
+        Self::from_resource_manager(Arc::new(ResourceManager::custom(backend)))
+    }
+
+    /// Open a layer from an exploded directory without format guessing.
+    pub fn open_folder(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let uri = path.as_ref().to_string_lossy().into_owned();
+        let rm = Arc::new(resource_manager_factory(I3SFormat::Folder, &uri)?);
+        Self::from_resource_manager(rm)
+    }
+
+    pub(crate) fn from_resource_manager(rm: Arc<ResourceManager>) -> Result<Self> {
+        let bytes = rm.get(&rm.scene_definition_uri())?;
+        let defn = SceneDefinition::from_slice(&bytes)?;
+        Ok(Self {
+            rm,
+            defn,
+            node_pages: Arc::new(crate::cache::BoundedCache::new()),
+            stats: None,
+        })
+    }
+
+    /// Meter every fetch of this layer against a shared download budget.
+    ///
+    /// Once the budget's caps are hit, node page, geometry, texture and
+    /// attribute fetches fail with [`I3SError::BudgetExhausted`] until the
+    /// budget is reset.
+    pub fn with_download_budget(mut self, budget: Arc<crate::budget::DownloadBudget>) -> Self {
+        self.rm = Arc::new(ResourceManager::budgeted(Arc::clone(&self.rm), budget));
+        self
+    }
+
+    /// Bound this layer's in-memory caches per
+    /// [`CacheConfig`](crate::cache::CacheConfig).
+    ///
+    /// `max_node_pages` caps the node page cache shared by all
+    /// [`NodeArray`] views and `max_resource_bytes` caps the backend's
+    /// raw byte cache, both with least-recently-used eviction. Limits
+    /// apply immediately and for the rest of the layer's lifetime.
+    pub fn with_cache_config(self, config: crate::cache::CacheConfig) -> Self {
+        self.apply_cache_config(config);
+        self
+    }
+
+    pub(crate) fn apply_cache_config(&self, config: crate::cache::CacheConfig) {
+        self.node_pages
+            .set_budget(config.max_node_pages.map(|pages| pages as u64));
+        self.rm.set_resource_cache_budget(config.max_resource_bytes);
+    }
+
+    /// Count every fetch of this layer into session statistics.
+    ///
+    /// Recording starts from this call; the definition fetch that opened
+    /// the layer is not included. Snapshot the totals at any point with
+    /// [`session_report`](Self::session_report).
+    pub fn with_session_recording(mut self) -> Self {
+        let stats = Arc::new(crate::diag::SessionStats::default());
+        self.rm = Arc::new(ResourceManager::recorded(
+            Arc::clone(&self.rm),
+            Arc::clone(&stats),
+        ));
+        self.stats = Some(stats);
+        self
+    }
+
+    /// A serializable summary of the session so far — fetches, bytes,
+    /// cache hits, errors and the slowest URIs — or `None` unless
+    /// [`with_session_recording`](Self::with_session_recording) was called.
+    pub fn session_report(&self) -> Option<crate::diag::SessionReport> {
+        self.stats.as_ref().map(|stats| stats.report())
+    }
+
+    pub(crate) fn resource_manager(&self) -> &Arc<ResourceManager> {
+        &self.rm
+    }
+
+    /// The parsed `3dSceneLayer` document.
+    pub fn definition(&self) -> &SceneDefinition {
+        &self.defn
+    }
+
+    /// The layer name, if declared.
+    pub fn name(&self) -> Option<&str> {
+        self.defn.name.as_deref()
+    }
+
+    /// The layer type.
+    pub fn layer_type(&self) -> LayerType {
+        self.defn.layer_type
+    }
+
+    /// The store profile.
+    pub fn profile(&self) -> Profile {
+        self.defn.store.profile
+    }
+
+    /// The layer extent, if declared.
+    pub fn extent(&self) -> Option<Extent> {
+        self.defn.full_extent
+    }
+
+    /// When the service last updated this layer, if it says.
+    ///
+    /// The later of the definition's `serviceUpdateTimeStamp.lastUpdate`
+    /// and `editingInfo.lastEditDate`, which hosted services stamp when
+    /// the cache is rebuilt or an edit lands. `None` for SLPK archives
+    /// and services that report neither; sync logic wanting coverage of
+    /// those too should fall back to [`fingerprint`](Self::fingerprint).
+    pub fn last_updated(&self) -> Option<std::time::SystemTime> {
+        let stamps = [
+            self.defn
+                .service_update_time_stamp
+                .and_then(|s| s.last_update),
+            self.defn.editing_info.and_then(|e| e.last_edit_date),
+        ];
+        stamps
+            .into_iter()
+            .flatten()
+            .max()
+            .map(|ms| std::time::UNIX_EPOCH + std::time::Duration::from_millis(ms))
+    }
+
+    /// A stable fingerprint of the layer for change detection.
+    ///
+    /// Hashes the scene definition together with the node page count and
+    /// the sizes of a small sample of node pages (first, middle, last).
+    /// Catalog crawlers can store the value and skip re-harvesting hosted
+    /// layers whose fingerprint has not moved, without walking the tree:
+    /// page-count discovery is a doubling-then-binary search over size
+    /// probes, so the whole computation takes O(log pages) requests. The
+    /// flip side of sampling is that an edit confined to unsampled
+    /// resources goes unnoticed until it also touches the definition or a
+    /// sampled page.
+    pub fn fingerprint(&self) -> Result<String> {
+        let mut material = serde_json::to_vec(&self.defn)
+            .map_err(|e| I3SError::Validation(format!("definition not serializable: {e}")))?;
+        let page_count = self.node_page_count()?;
+        material.extend_from_slice(&(page_count as u64).to_le_bytes());
+        if page_count > 0 {
+            for page in [0, page_count / 2, page_count - 1] {
+                let uri = self.rm.node_page_uri(page);
+                let size = match self.rm.size(&uri)? {
+                    Some(size) => size,
+                    // Backends without size probes pay one page fetch.
+                    None => self.rm.get(&uri)?.len() as u64,
+                };
+                material.extend_from_slice(&(page as u64).to_le_bytes());
+                material.extend_from_slice(&size.to_le_bytes());
+            }
+        }
+        Ok(crate::cache::content_hash(&material))
+    }
+
+    /// Whether a node page resource exists, preferring size probes over
+    /// payload fetches.
+    fn node_page_exists(&self, page_index: usize) -> Result<bool> {
+        let uri = self.rm.node_page_uri(page_index);
+        let probed = match self.rm.size(&uri) {
+            Ok(Some(_)) => return Ok(true),
+            Ok(None) => self.rm.get(&uri).map(|_| ()),
+            Err(e) => Err(e),
+        };
+        match probed {
+            Ok(()) => Ok(true),
+            Err(I3SError::MissingResource(_)) | Err(I3SError::Http { status: 404, .. }) => {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Count node pages by doubling until one is absent, then binary
+    /// searching the boundary.
+    fn node_page_count(&self) -> Result<usize> {
+        if !self.node_page_exists(0)? {
+            return Ok(0);
+        }
+        let mut absent = 1;
+        while self.node_page_exists(absent)? {
+            absent *= 2;
+        }
+        let mut present = absent / 2;
+        while present + 1 < absent {
+            let mid = present + (absent - present) / 2;
+            if self.node_page_exists(mid)? {
+                present = mid;
+            } else {
+                absent = mid;
+            }
+        }
+        Ok(absent)
+    }
+
+    /// Clip the layer to `extent` and write a self-contained `.slpk` at
+    /// `path`, returning the extracted package opened as a layer.
+    ///
+    /// Keeps the subtree of nodes whose bounding volumes touch the
+    /// extent, renumbers them densely and copies only their resources;
+    /// see [`extract_extent`](crate::slpk::split::extract_extent) for
+    /// the details.
+    #[cfg(feature = "slpk")]
+    pub fn extract(
+        &self,
+        extent: &Extent,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<SceneLayer> {
+        crate::slpk::split::extract_extent(self, extent, path.as_ref())?;
+        Self::open_slpk(path)
+    }
+
+    /// Start editing the layer's `3dSceneLayer` document.
+    ///
+    /// The editor works on a copy; nothing changes until one of its save
+    /// methods is called, and this layer keeps serving the definition it
+    /// was opened with.
+    pub fn edit_definition(&self) -> crate::edit::DefinitionEditor<'_> {
+        crate::edit::DefinitionEditor::new(self)
+    }
+
+    /// A view over the node tree.
+    ///
+    /// All views of one layer share its node page cache, so pages fetched
+    /// through any of them (or through [`root`](Self::root)) are fetched
+    /// once for the layer's lifetime.
+    pub fn nodes(&self) -> Result<NodeArray> {
+        let node_pages = self
+            .defn
+            .node_pages
+            .as_ref()
+            .ok_or_else(|| I3SError::MissingResource("nodePages definition".to_string()))?;
+        Ok(NodeArray::new(
+            Arc::clone(&self.rm),
+            node_pages,
+            Arc::clone(&self.node_pages),
+        ))
+    }
+
+    /// Fetch the root node.
+    pub fn root(&self) -> Result<Arc<Node>> {
+        self.nodes()?.root()
+    }
+
+    /// Fetch one node page as the source provides it.
+    ///
+    /// Returns the decompressed JSON bytes alongside the typed parse.
+    /// The shared node page cache is bypassed in both directions —
+    /// nothing is read from it and nothing inserted — so diagnostic
+    /// tools and differs see exactly what the backend holds right now,
+    /// including fields the typed structs drop. For regular tree access
+    /// use [`nodes`](Self::nodes), which caches.
+    pub fn node_page_raw(
+        &self,
+        page_index: usize,
+    ) -> Result<(Arc<Vec<u8>>, crate::node::NodePage)> {
+        let uri = self.rm.node_page_uri(page_index);
+        let bytes = self.rm.get(&uri).map_err(|e| {
+            e.with_context(crate::err::ErrorContext {
+                resource: Some("node page"),
+                uri: Some(uri.clone()),
+                ..Default::default()
+            })
+        })?;
+        let page = serde_json::from_slice(&bytes).map_err(|e| I3SError::json(&uri, e))?;
+        Ok((bytes, page))
+    }
+
+    /// Resolve the vertex layout of one entry of `geometryDefinitions`.
+    ///
+    /// The returned layout describes attribute order, component types and
+    /// element sizes for every buffer of the definition; byte offsets become
+    /// concrete once resolved against a node's vertex and feature counts.
+    /// Intended for custom decoders and GPU upload paths.
+    pub fn geometry_layout(&self, definition_index: usize) -> Result<GeometryLayout> {
+        let definition = self
+            .defn
+            .geometry_definitions
+            .get(definition_index)
+            .ok_or_else(|| {
+                I3SError::MissingResource(format!("geometry definition {definition_index}"))
+            })?;
+        crate::decode::geometry_layout(definition)
+    }
+
+    /// Fetch and decode the geometry of a node.
+    ///
+    /// Returns `Ok(None)` for nodes without a geometry resource (typical
+    /// for interior nodes of object layers).
+    pub fn node_geometry(&self, node: &Node) -> Result<Option<DecodedGeometry>> {
+        self.node_geometry_with(node, &ResourceDecoder::new(self.defn.store.profile))
+    }
+
+    /// Fetch a node's geometry and decode it with a caller-configured
+    /// decoder (e.g. one carrying a
+    /// [`VertexTransform`](crate::decode::VertexTransform)).
+    pub fn node_geometry_with(
+        &self,
+        node: &Node,
+        decoder: &ResourceDecoder,
+    ) -> Result<Option<DecodedGeometry>> {
+        let Some(geometry) = node.mesh.as_ref().and_then(|m| m.geometry.as_ref()) else {
+            return Ok(None);
+        };
+        let definition = self
+            .defn
+            .geometry_definitions
+            .get(geometry.definition)
+            .ok_or_else(|| {
+                I3SError::MissingResource(format!(
+                    "geometry definition {} referenced by node {}",
+                    geometry.definition, node.index
+                ))
+            })?;
+        let uri = self.rm.geometry_uri(node.index, geometry.resource);
+        let context = || crate::err::ErrorContext {
+            node_index: Some(node.index),
+            resource: Some("geometry"),
+            uri: Some(uri.clone()),
+            ..Default::default()
+        };
+        let bytes = self.rm.get(&uri).map_err(|e| e.with_context(context()))?;
+        decoder
+            .decode_geometry(
+                &bytes,
+                definition,
+                geometry.vertex_count,
+                geometry.feature_count.unwrap_or(0),
+            )
+            .map(Some)
+            .map_err(|e| e.with_context(context()))
+    }
+
+    /// Fetch and decode one attribute column of a node by storage key
+    /// (e.g. `"f_0"`).
+    ///
+    /// Columns the source keeps sorted — OBJECTID in particular —
+    /// support the binary-searched lookups on
+    /// [`AttributeColumn`](crate::attrs::AttributeColumn), so resolving
+    /// a feature id to its row does not scan the node's features.
+    pub fn node_attribute(
+        &self,
+        node: &Node,
+        key: &str,
+    ) -> Result<crate::attrs::AttributeColumn> {
+        let Some(attribute) = node.mesh.as_ref().and_then(|m| m.attribute.as_ref()) else {
+            return Err(I3SError::MissingResource(format!(
+                "attribute resources of node {}",
+                node.index
+            )));
+        };
+        let info = self
+            .defn
+            .attribute_storage_info
+            .iter()
+            .find(|info| info.key == key)
+            .ok_or_else(|| I3SError::MissingResource(format!("attributeStorageInfo {key}")))?;
+        let uri = self.rm.attribute_uri(attribute.resource, key);
+        let context = || crate::err::ErrorContext {
+            node_index: Some(node.index),
+            resource: Some("attribute"),
+            uri: Some(uri.clone()),
+            ..Default::default()
+        };
+        let bytes = self.rm.get(&uri).map_err(|e| e.with_context(context()))?;
+        crate::attrs::read_attribute(&bytes, info).map_err(|e| e.with_context(context()))
+    }
+
+    /// Fetch and classify everything decodable on a node.
+    ///
+    /// Uses the profile's default decoder and texture preference; see
+    /// [`node_content_with`](Self::node_content_with).
+    pub fn node_content(&self, node: &Node) -> Result<NodeContent> {
+        self.node_content_with(
+            node,
+            &ResourceDecoder::new(self.defn.store.profile),
+            &crate::decode::TexturePreference::default(),
+        )
+    }
+
+    /// Fetch and classify a node's content with a caller-configured
+    /// decoder and texture preference.
+    ///
+    /// Unlike reaching into `node.mesh` directly, the gaps that are
+    /// normal in real layers come back as variants instead of errors:
+    /// interior nodes without a mesh are [`NodeContent::Empty`], and
+    /// leaves whose material reference is absent, dangling or limited to
+    /// unsupported texture encodings are [`NodeContent::GeometryOnly`].
+    /// Errors are reserved for failures fetching or decoding resources
+    /// the node actually has.
+    pub fn node_content_with(
+        &self,
+        node: &Node,
+        decoder: &ResourceDecoder,
+        preference: &crate::decode::TexturePreference,
+    ) -> Result<NodeContent> {
+        let Some(geometry) = self.node_geometry_with(node, decoder)? else {
+            return Ok(NodeContent::Empty);
+        };
+        let Some(material) = node.mesh.as_ref().and_then(|m| m.material.as_ref()) else {
+            return Ok(NodeContent::GeometryOnly(geometry));
+        };
+        // A dangling definition index is a data defect, but one that only
+        // costs the material — keep the geometry usable.
+        let Some(definition) = self.defn.material_definitions.get(material.definition) else {
+            return Ok(NodeContent::GeometryOnly(geometry));
+        };
+        let texture_set = definition
+            .pbr_metallic_roughness
+            .as_ref()
+            .and_then(|pbr| pbr.base_color_texture.as_ref())
+            .and_then(|t| {
+                self.defn
+                    .texture_set_definitions
+                    .get(t.texture_set_definition_id)
+            });
+        match crate::decode::decode_material(definition, texture_set, preference) {
+            Ok(decoded) => Ok(NodeContent::Full {
+                geometry,
+                material: decoded,
+                material_resource: material.resource,
+            }),
+            // The only decode failure is a texture offered solely in
+            // encodings the client rejects; fall back to untextured.
+            Err(_) => Ok(NodeContent::GeometryOnly(geometry)),
+        }
+    }
+}
+
+/// The decodable content of one node, classified.
+///
+/// Interior nodes often carry no mesh and some leaves have geometry but
+/// no material; exporters driven by a traversal match on this instead of
+/// special-casing those gaps at every call site.
+#[derive(Debug)]
+pub enum NodeContent {
+    /// No mesh, or a mesh without a geometry resource.
+    Empty,
+    /// Geometry without a usable material; consumers apply their default
+    /// material.
+    GeometryOnly(DecodedGeometry),
+    /// Geometry plus the resolved material. When the material selects a
+    /// texture, fetch its bytes with
+    /// [`UriBuilder::texture_uri`](crate::rm::UriBuilder::texture_uri)
+    /// and `material_resource`.
+    Full {
+        geometry: DecodedGeometry,
+        material: crate::decode::DecodedMaterial,
+        material_resource: usize,
+    },
+}
+
+impl NodeContent {
+    /// The decoded geometry, for callers indifferent to materials.
+    pub fn geometry(&self) -> Option<&DecodedGeometry> {
+        match self {
+            Self::Empty => None,
+            Self::GeometryOnly(geometry) | Self::Full { geometry, .. } => Some(geometry),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use crate::slpk::writer::SlpkWriter;
+    use crate::testutil::{unit_obb, write_test_layer};
+
+    fn write_layer(path: &std::path::Path, pages: usize, pad: &[u8]) {
+        let nodes: Vec<_> = (0..pages)
+            .map(|index| serde_json::json!({ "index": index, "obb": unit_obb() }))
+            .collect();
+        write_test_layer(
+            path,
+            serde_json::json!({
+                "id": 0,
+                "layerType": "IntegratedMesh",
+                "store": { "profile": "meshpyramids" },
+                "nodePages": { "nodesPerPage": 1 }
+            }),
+            &nodes,
+            &[(0, 0, pad.to_vec())],
+        );
+    }
+
+    #[test]
+    fn node_content_classifies_empty_and_untextured_nodes() {
+        let dir = crate::testutil::TestDir::new("node-content");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 4 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }],
+            "materialDefinitions": [{
+                "pbrMetallicRoughness": { "baseColorFactor": [0.5, 0.5, 0.5, 1.0] }
+            }]
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let geometry = serde_json::json!({
+            "definition": 0, "resource": 0, "vertexCount": 3
+        });
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                // Interior node with no mesh at all.
+                { "index": 0, "obb": obb, "children": [1, 2, 3] },
+                // Geometry but no material reference.
+                { "index": 1, "obb": obb, "mesh": { "geometry": geometry } },
+                // Geometry with a material.
+                { "index": 2, "obb": obb, "mesh": {
+                    "geometry": geometry,
+                    "material": { "definition": 0, "resource": 2 }
+                } },
+                // Geometry with a dangling material definition.
+                { "index": 3, "obb": obb, "mesh": {
+                    "geometry": geometry,
+                    "material": { "definition": 9, "resource": 3 }
+                } }
+            ]
+        }))
+        .unwrap();
+        let triangle: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        for index in 1..=3 {
+            writer.write_geometry(index, 0, &triangle).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let nodes = layer.nodes().unwrap();
+
+        let content = layer.node_content(&nodes.get(0).unwrap()).unwrap();
+        assert!(matches!(content, crate::layer::NodeContent::Empty));
+        assert!(content.geometry().is_none());
+
+        let content = layer.node_content(&nodes.get(1).unwrap()).unwrap();
+        assert!(matches!(content, crate::layer::NodeContent::GeometryOnly(_)));
+        assert_eq!(content.geometry().unwrap().vertex_count, 3);
+
+        match layer.node_content(&nodes.get(2).unwrap()).unwrap() {
+            crate::layer::NodeContent::Full {
+                material,
+                material_resource,
+                ..
+            } => {
+                assert_eq!(material.base_color_factor, [0.5, 0.5, 0.5, 1.0]);
+                assert_eq!(material_resource, 2);
+            }
+            other => panic!("expected Full, got {other:?}"),
+        }
+
+        // The dangling definition costs only the material.
+        let content = layer.node_content(&nodes.get(3).unwrap()).unwrap();
+        assert!(matches!(content, crate::layer::NodeContent::GeometryOnly(_)));
+    }
+
+    #[test]
+    fn raw_node_pages_bypass_the_cache() {
+        let dir = crate::testutil::TestDir::new("raw-page");
+        let path = dir.join("layer.slpk");
+        write_layer(&path, 2, b"geo");
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let (bytes, page) = layer.node_page_raw(1).unwrap();
+        assert!(std::str::from_utf8(&bytes).unwrap().contains("\"nodes\""));
+        assert_eq!(page.nodes.len(), 1);
+        assert_eq!(page.nodes[0].index, 1);
+        assert!(!layer.nodes().unwrap().page_cached(1));
+
+        assert!(layer.node_page_raw(7).is_err());
+    }
+
+    #[test]
+    fn fingerprints_are_stable_and_sensitive() {
+        let dir = crate::testutil::TestDir::new("fingerprint");
+
+        let a = dir.join("a.slpk");
+        write_layer(&a, 3, b"geo");
+        let first = crate::layer::SceneLayer::open_slpk(&a)
+            .unwrap()
+            .fingerprint()
+            .unwrap();
+        // A byte-identical rewrite keeps the fingerprint.
+        write_layer(&a, 3, b"geo");
+        let again = crate::layer::SceneLayer::open_slpk(&a)
+            .unwrap()
+            .fingerprint()
+            .unwrap();
+        assert_eq!(first, again);
+
+        // More node pages move it.
+        let b = dir.join("b.slpk");
+        write_layer(&b, 5, b"geo");
+        let grown = crate::layer::SceneLayer::open_slpk(&b)
+            .unwrap()
+            .fingerprint()
+            .unwrap();
+        assert_ne!(first, grown);
+    }
+}
@@ -0,0 +1,117 @@
+//! Read and decode [Esri I3S](https://github.com/Esri/i3s-spec) scene layers.
+//!
+//! The crate opens layers from local `.slpk` packages (feature `slpk`) or from
+//! hosted SceneServer REST endpoints (feature `http`), exposes the scene
+//! definition and node tree as typed structs, and decodes node geometry,
+//! material, and attribute resources.
+//!
+//! ```no_run
+//! use i3s::SceneLayer;
+//!
+//! let layer = SceneLayer::from_uri("mesh.slpk").unwrap();
+//! println!("{:?}", layer.name());
+//! ```
+
+pub mod attrs;
+pub mod budget;
+pub mod building;
+pub mod cache;
+#[cfg(feature = "cloud")]
+pub mod cloud;
+pub mod collection;
+pub mod config;
+pub mod crs;
+pub mod decode;
+pub mod defn;
+pub mod diag;
+pub mod edit;
+pub mod err;
+pub mod export;
+pub mod flatten;
+pub mod folder;
+#[cfg(feature = "slpk")]
+pub mod import;
+pub mod labels;
+mod layer;
+pub mod link;
+pub mod measure;
+pub mod node;
+pub mod obb;
+pub mod overview;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod pick;
+pub mod pointcloud;
+pub mod prefetch;
+pub mod profiles;
+pub mod quality;
+pub mod rm;
+pub mod sched;
+#[cfg(feature = "rstar")]
+pub mod spatial;
+pub mod stream;
+#[cfg(all(test, any(feature = "slpk", feature = "http")))]
+mod testutil;
+pub mod texel;
+pub mod validate;
+
+#[cfg(feature = "http")]
+pub mod service;
+#[cfg(feature = "serve")]
+pub mod serve;
+#[cfg(feature = "slpk")]
+pub mod slpk;
+
+#[cfg(feature = "async")]
+pub mod aio;
+
+pub use building::BuildingSceneLayer;
+pub use collection::SceneLayerCollection;
+pub use err::I3SError;
+pub use layer::SceneLayer;
+pub use profiles::{BuildingLayer, IntegratedMeshLayer, ObjectLayer3D, PointCloudLayer};
+pub use rm::{resource_manager_factory, Accessor, I3SFormat, ResourceManager, UriBuilder};
+
+/// The set of optional features this build of the crate was compiled with.
+///
+/// Lets applications adapt behavior up front (or produce actionable errors
+/// such as "rebuild with the `proj` feature") instead of failing deep inside
+/// a decode. Compressed-geometry and texture codecs are not features:
+/// register them at runtime on the
+/// [`GeometryCodecRegistry`](decode::GeometryCodecRegistry) and
+/// [`TextureDecoderRegistry`](decode::TextureDecoderRegistry).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// SceneServer REST backends (`http` feature).
+    pub http: bool,
+    /// Local `.slpk` packages (`slpk` feature).
+    pub slpk: bool,
+    /// Coordinate reprojection (`proj` feature).
+    pub proj: bool,
+    /// Async SceneServer client (`async` feature).
+    pub r#async: bool,
+}
+
+/// Report which optional features were compiled into this build.
+pub const fn capabilities() -> Capabilities {
+    Capabilities {
+        http: cfg!(feature = "http"),
+        slpk: cfg!(feature = "slpk"),
+        proj: cfg!(feature = "proj"),
+        r#async: cfg!(feature = "async"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_reflect_default_features() {
+        let caps = capabilities();
+        assert_eq!(caps.http, cfg!(feature = "http"));
+        assert_eq!(caps.slpk, cfg!(feature = "slpk"));
+        assert_eq!(caps.proj, cfg!(feature = "proj"));
+    }
+}
@@ -0,0 +1,141 @@
+//! Bookmarkable deep links into a layer.
+//!
+//! A [`DeepLink`] pins a location inside a layer — the source the layer
+//! was opened from, a node index and optionally a feature id — in a form
+//! that survives serialization, so applications can bookmark and share
+//! exact spots and re-resolve them later with [`DeepLink::resolve`].
+//! [`SceneLayer::root_path`] complements it with breadcrumbs: the chain
+//! of node indices from the root down to any node.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::err::{I3SError, Result};
+use crate::layer::SceneLayer;
+use crate::node::Node;
+
+/// A stable, shareable pointer to a location inside a layer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeepLink {
+    /// The layer source: an `.slpk` path, an exploded directory or a
+    /// SceneServer layer URL.
+    pub source: String,
+    pub node_index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_id: Option<u64>,
+}
+
+impl DeepLink {
+    /// The compact single-string form: the source with a `#node={index}`
+    /// fragment, plus `&feature={id}` when a feature is pinned.
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!("{}#node={}", self.source, self.node_index);
+        if let Some(feature) = self.feature_id {
+            uri.push_str(&format!("&feature={feature}"));
+        }
+        uri
+    }
+
+    /// Parse the form produced by [`to_uri`](Self::to_uri).
+    pub fn parse(uri: &str) -> Result<Self> {
+        let invalid = || I3SError::InvalidUri(format!("not a deep link: {uri}"));
+        let (source, fragment) = uri.rsplit_once('#').ok_or_else(invalid)?;
+        let mut node_index = None;
+        let mut feature_id = None;
+        for pair in fragment.split('&') {
+            match pair.split_once('=') {
+                Some(("node", value)) => {
+                    node_index = Some(value.parse().map_err(|_| invalid())?);
+                }
+                Some(("feature", value)) => {
+                    feature_id = Some(value.parse().map_err(|_| invalid())?);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(Self {
+            source: source.to_string(),
+            node_index: node_index.ok_or_else(invalid)?,
+            feature_id,
+        })
+    }
+
+    /// Reopen the linked layer and fetch the linked node.
+    ///
+    /// Fails with [`I3SError::MissingResource`] when the node no longer
+    /// exists, e.g. after the layer was republished with a smaller tree.
+    pub fn resolve(&self) -> Result<(SceneLayer, Arc<Node>)> {
+        let layer = SceneLayer::from_uri(&self.source)?;
+        let node = layer.nodes()?.get(self.node_index)?;
+        Ok((layer, node))
+    }
+}
+
+impl SceneLayer {
+    /// A deep link to `node_index`, optionally pinning one of its
+    /// features. The node is verified to exist before the link is built.
+    ///
+    /// Fails for backends without a reopenable source, such as in-memory
+    /// archive readers or custom backends.
+    pub fn deep_link(&self, node_index: usize, feature_id: Option<u64>) -> Result<DeepLink> {
+        let source = self.resource_manager().source_uri().ok_or_else(|| {
+            I3SError::InvalidUri("layer backend has no reopenable source".to_string())
+        })?;
+        self.nodes()?.get(node_index)?;
+        Ok(DeepLink {
+            source,
+            node_index,
+            feature_id,
+        })
+    }
+
+    /// Breadcrumbs for a node: the node indices from the root down to
+    /// `node_index`, inclusive.
+    pub fn root_path(&self, node_index: usize) -> Result<Vec<usize>> {
+        let nodes = self.nodes()?;
+        let mut node = nodes.get(node_index)?;
+        let mut path = vec![node.index];
+        while let Some(parent) = nodes.get_parent(&node)? {
+            path.push(parent.index);
+            node = parent;
+        }
+        path.reverse();
+        Ok(path)
+    }
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::testutil::{write_pyramid_layer, TestDir};
+
+    #[test]
+    fn deep_links_round_trip_and_resolve() {
+        let dir = TestDir::new("deep-link");
+        let path = dir.join("layer.slpk");
+        write_pyramid_layer(&path, false);
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let link = layer.deep_link(3, Some(42)).unwrap();
+        assert_eq!(link.node_index, 3);
+        assert_eq!(link.feature_id, Some(42));
+
+        // The string form survives a round trip.
+        let uri = link.to_uri();
+        assert!(uri.ends_with("#node=3&feature=42"));
+        assert_eq!(DeepLink::parse(&uri).unwrap(), link);
+        assert!(DeepLink::parse("layer.slpk").is_err());
+        assert!(DeepLink::parse("layer.slpk#node=x").is_err());
+
+        // Resolving reopens the layer and lands on the same node.
+        let (reopened, node) = link.resolve().unwrap();
+        assert_eq!(node.index, 3);
+        assert_eq!(reopened.root_path(3).unwrap(), vec![0, 1, 3]);
+        assert_eq!(reopened.root_path(0).unwrap(), vec![0]);
+
+        // Links to nodes that do not exist are refused up front.
+        assert!(layer.deep_link(9, None).is_err());
+    }
+}
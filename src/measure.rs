@@ -0,0 +1,293 @@
+//! Surface area and volume measurement in real-world units.
+//!
+//! Quantity take-offs from 3D object layers need areas and volumes in
+//! meters, but stored coordinates are whatever the layer CRS uses:
+//! degrees in geographic layers, distorted meters in Web Mercator. The
+//! measurement functions scale each node's vertices into local meters at
+//! the node's location before integrating, so results are comparable
+//! across layers. Projected CRSs other than Web Mercator are taken at
+//! face value (their scale error is below measurement noise for
+//! building-sized features).
+
+use crate::decode::DecodedGeometry;
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::node::Node;
+
+/// Meters per degree of longitude at the equator (and of latitude, in
+/// the spherical approximation used here).
+const METERS_PER_DEGREE: f64 = std::f64::consts::PI * 6_378_137.0 / 180.0;
+
+/// Web Mercator sphere radius.
+const WEB_MERCATOR_RADIUS: f64 = 6_378_137.0;
+
+/// Area and volume of one measured mesh, in square and cubic meters.
+///
+/// The volume integrates signed tetrahedra over the triangles and is
+/// only meaningful for closed, consistently wound meshes; for open
+/// surfaces (a feature's facade, a terrain skirt) use the area alone.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Measurement {
+    pub surface_area: f64,
+    pub volume: f64,
+    pub triangles: usize,
+}
+
+/// Measure a node's whole mesh, or `Ok(None)` when it has no geometry.
+pub fn measure_node(layer: &SceneLayer, node: &Node) -> Result<Option<Measurement>> {
+    let Some(geometry) = layer.node_geometry(node)? else {
+        return Ok(None);
+    };
+    let scale = unit_scale(layer, node);
+    let triangles = geometry.positions.len() / 9;
+    Ok(Some(measure_triangles(&geometry, 0..triangles, scale)))
+}
+
+/// Measure the triangles of one feature inside a node's mesh, resolved
+/// through the geometry's `faceRange` data. `Ok(None)` when the node has
+/// no geometry or does not contain the feature.
+pub fn measure_feature(
+    layer: &SceneLayer,
+    node: &Node,
+    feature_id: u64,
+) -> Result<Option<Measurement>> {
+    let Some(geometry) = layer.node_geometry(node)? else {
+        return Ok(None);
+    };
+    let Some(feature) = geometry.feature_ids.iter().position(|&id| id == feature_id) else {
+        return Ok(None);
+    };
+    let Some(range) = geometry.face_ranges.chunks_exact(2).nth(feature) else {
+        return Ok(None);
+    };
+    let scale = unit_scale(layer, node);
+    Ok(Some(measure_triangles(
+        &geometry,
+        range[0] as usize..range[1] as usize + 1,
+        scale,
+    )))
+}
+
+/// Per-axis factors from stored units to meters at the node's location.
+fn unit_scale(layer: &SceneLayer, node: &Node) -> [f64; 3] {
+    match crate::crs::layer_epsg(layer.definition()) {
+        // Geographic layers store degrees horizontally, meters in z;
+        // longitude degrees shrink with latitude.
+        Some(4326 | 4490 | 4258 | 4269 | 4979) => {
+            let latitude = node.obb.center[1].to_radians();
+            [
+                METERS_PER_DEGREE * latitude.cos(),
+                METERS_PER_DEGREE,
+                1.0,
+            ]
+        }
+        // Web Mercator overstates horizontal lengths by 1/cos(latitude).
+        Some(3857 | 102_100 | 900_913) => {
+            let latitude = (node.obb.center[1] / WEB_MERCATOR_RADIUS).sinh().atan();
+            [latitude.cos(), latitude.cos(), 1.0]
+        }
+        _ => [1.0; 3],
+    }
+}
+
+/// Integrate area and signed volume over a triangle range, scaling each
+/// vertex into local meters. Positions are node-relative, so the scale
+/// applies to small offsets and the latitude of the node center is a
+/// valid approximation for every vertex.
+fn measure_triangles(
+    geometry: &DecodedGeometry,
+    triangles: std::ops::Range<usize>,
+    scale: [f64; 3],
+) -> Measurement {
+    let corner = |triangle: usize, i: usize| {
+        let base = (triangle * 3 + i) * 3;
+        [
+            f64::from(geometry.positions[base]) * scale[0],
+            f64::from(geometry.positions[base + 1]) * scale[1],
+            f64::from(geometry.positions[base + 2]) * scale[2],
+        ]
+    };
+    let mut out = Measurement::default();
+    let mut signed_volume = 0.0;
+    for triangle in triangles {
+        if (triangle * 3 + 3) * 3 > geometry.positions.len() {
+            break;
+        }
+        let (a, b, c) = (
+            corner(triangle, 0),
+            corner(triangle, 1),
+            corner(triangle, 2),
+        );
+        let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let cross = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+        out.surface_area +=
+            0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt();
+        // Divergence theorem: one signed tetrahedron per triangle against
+        // the node center.
+        signed_volume += (a[0] * (b[1] * c[2] - b[2] * c[1])
+            + a[1] * (b[2] * c[0] - b[0] * c[2])
+            + a[2] * (b[0] * c[1] - b[1] * c[0]))
+            / 6.0;
+        out.triangles += 1;
+    }
+    out.volume = signed_volume.abs();
+    out
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::slpk::writer::SlpkWriter;
+
+    /// Twelve outward-wound triangles of the cube spanning `[-1, 1]^3`.
+    fn cube_vertices() -> Vec<f32> {
+        let quads: [[[f32; 3]; 4]; 6] = [
+            // +z, -z
+            [[-1., -1., 1.], [1., -1., 1.], [1., 1., 1.], [-1., 1., 1.]],
+            [[-1., -1., -1.], [-1., 1., -1.], [1., 1., -1.], [1., -1., -1.]],
+            // +x, -x
+            [[1., -1., -1.], [1., 1., -1.], [1., 1., 1.], [1., -1., 1.]],
+            [[-1., -1., -1.], [-1., -1., 1.], [-1., 1., 1.], [-1., 1., -1.]],
+            // +y, -y
+            [[-1., 1., -1.], [-1., 1., 1.], [1., 1., 1.], [1., 1., -1.]],
+            [[-1., -1., -1.], [1., -1., -1.], [1., -1., 1.], [-1., -1., 1.]],
+        ];
+        let mut out = Vec::new();
+        for quad in quads {
+            for corner in [quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]] {
+                out.extend_from_slice(&corner);
+            }
+        }
+        out
+    }
+
+    fn write_layer(path: &std::path::Path, spatial_reference: serde_json::Value, center: [f64; 3]) {
+        let mut doc = serde_json::json!({
+            "id": 0,
+            "layerType": "3DObject",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 8 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }]
+        });
+        if !spatial_reference.is_null() {
+            doc["spatialReference"] = spatial_reference;
+        }
+        let node = serde_json::json!({
+            "index": 0,
+            "obb": crate::testutil::obb(center, [1.0; 3]),
+            "mesh": { "geometry": {
+                "definition": 0, "resource": 0, "vertexCount": 36
+            } }
+        });
+        let bytes: Vec<u8> = cube_vertices()
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        crate::testutil::write_test_layer(path, doc, &[node], &[(0, 0, bytes)]);
+    }
+
+    #[test]
+    fn cube_measurements_respect_the_layer_crs() {
+        let dir = crate::testutil::TestDir::new("measure");
+
+        // Plain projected meters: a 2 m cube measures as written.
+        let path = dir.join("meters.slpk");
+        write_layer(&path, serde_json::Value::Null, [10.0, 20.0, 0.0]);
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let node = layer.root().unwrap();
+        let cube = measure_node(&layer, &node).unwrap().unwrap();
+        assert_eq!(cube.triangles, 12);
+        assert!((cube.surface_area - 24.0).abs() < 1e-9);
+        assert!((cube.volume - 8.0).abs() < 1e-9);
+
+        // Web Mercator at 60 degrees north: horizontal lengths shrink by
+        // cos(60) = 0.5, so the top/bottom faces scale by 0.25 and the
+        // four side faces by 0.5.
+        let path = dir.join("mercator.slpk");
+        let y = WEB_MERCATOR_RADIUS
+            * (std::f64::consts::FRAC_PI_4 + 60f64.to_radians() / 2.0).tan().ln();
+        write_layer(
+            &path,
+            serde_json::json!({ "wkid": 102100, "latestWkid": 3857 }),
+            [0.0, y, 0.0],
+        );
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let node = layer.root().unwrap();
+        let scaled = measure_node(&layer, &node).unwrap().unwrap();
+        assert!((scaled.surface_area - 10.0).abs() < 1e-6);
+        assert!((scaled.volume - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn feature_measurements_follow_face_ranges() {
+        let dir = crate::testutil::TestDir::new("measure-feature");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "3DObject",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 8 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 },
+                    "featureId": { "type": "UInt64", "component": 1, "binding": "per-feature" },
+                    "faceRange": { "type": "UInt32", "component": 2, "binding": "per-feature" }
+                }]
+            }]
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [4.0, 4.0, 4.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                },
+                "mesh": { "geometry": {
+                    "definition": 0, "resource": 0, "vertexCount": 6, "featureCount": 2
+                } }
+            }]
+        }))
+        .unwrap();
+        // Feature 10: a unit right triangle (area 0.5). Feature 20: the
+        // same shape scaled by two (area 2).
+        let vertices = [
+            0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, //
+            0.0, 0.0, 2.0, 2.0, 0.0, 2.0, 0.0, 2.0, 2.0,
+        ];
+        let mut bytes: Vec<u8> = vertices.iter().flat_map(|v| v.to_le_bytes()).collect();
+        for feature in [10u64, 20] {
+            bytes.extend_from_slice(&feature.to_le_bytes());
+        }
+        for value in [0u32, 0, 1, 1] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, &bytes).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let node = layer.root().unwrap();
+        let small = measure_feature(&layer, &node, 10).unwrap().unwrap();
+        assert_eq!(small.triangles, 1);
+        assert!((small.surface_area - 0.5).abs() < 1e-9);
+        let large = measure_feature(&layer, &node, 20).unwrap().unwrap();
+        assert!((large.surface_area - 2.0).abs() < 1e-9);
+        assert!(measure_feature(&layer, &node, 99).unwrap().is_none());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,1588 @@
+//! Node pages and tree navigation.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::defn::{Extent, ImageFormat, NodePageDefinition};
+use crate::err::{I3SError, Result};
+use crate::obb::OrientedBoundingBox;
+use crate::rm::{Accessor, ResourceManager, UriBuilder};
+
+/// Reference from a node to its material resource.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshMaterial {
+    pub definition: usize,
+    pub resource: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub texel_count_hint: Option<usize>,
+}
+
+/// Reference from a node to its geometry resource.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshGeometry {
+    pub definition: usize,
+    pub resource: usize,
+    pub vertex_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_count: Option<usize>,
+}
+
+/// Reference from a node to its attribute resources.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshAttribute {
+    pub resource: usize,
+}
+
+/// The `mesh` object of a node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mesh {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub material: Option<MeshMaterial>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<MeshGeometry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<MeshAttribute>,
+}
+
+/// One node of the layer tree, as stored in a node page.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Node {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<usize>,
+    pub obb: OrientedBoundingBox,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lod_threshold: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<Mesh>,
+    /// Per-node state attached by applications.
+    #[serde(skip)]
+    pub extras: Extras,
+}
+
+/// A thread-safe typed store for application state attached to a node.
+///
+/// Holds at most one value per Rust type, so renderers can attach GPU
+/// handles, visibility flags or culling results directly to the shared
+/// `Arc<Node>` instead of maintaining external maps keyed by node index.
+/// Values are never serialized.
+#[derive(Default)]
+pub struct Extras {
+    values: RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl Extras {
+    /// Attach a value, replacing any previous value of the same type.
+    pub fn insert<T: Any + Send + Sync>(&self, value: T) {
+        self.values
+            .write()
+            .expect("extras lock poisoned")
+            .insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// The attached value of type `T`, if any.
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.values
+            .read()
+            .expect("extras lock poisoned")
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast().ok())
+    }
+
+    /// Detach and return the value of type `T`, if any.
+    pub fn remove<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.values
+            .write()
+            .expect("extras lock poisoned")
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+    }
+
+    /// Drop all attached values.
+    pub fn clear(&self) {
+        self.values.write().expect("extras lock poisoned").clear();
+    }
+}
+
+impl std::fmt::Debug for Extras {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.values.read().expect("extras lock poisoned").len();
+        f.debug_struct("Extras").field("values", &count).finish()
+    }
+}
+
+impl Node {
+    /// Whether this node has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Accumulated stored size of this node's resources, computed from
+    /// archive entry sizes or `Content-Length` without downloading the
+    /// payloads themselves.
+    ///
+    /// Texture formats a set declares but the backend does not hold (a
+    /// server usually stores one encoding per set) are skipped; resources
+    /// whose size the backend cannot report are counted in
+    /// [`PayloadSize::unknown_resources`].
+    pub fn payload_size(&self, layer: &crate::layer::SceneLayer) -> Result<PayloadSize> {
+        let rm = layer.resource_manager();
+        let defn = layer.definition();
+        let mut out = PayloadSize::default();
+        let Some(mesh) = &self.mesh else {
+            return Ok(out);
+        };
+
+        let record = |uri: &str, slot: &mut u64, unknown: &mut usize| -> Result<()> {
+            match rm.size(uri) {
+                Ok(Some(bytes)) => *slot += bytes,
+                Ok(None) => *unknown += 1,
+                Err(I3SError::MissingResource(_)) => {}
+                Err(e) => return Err(e),
+            }
+            Ok(())
+        };
+
+        if let Some(geometry) = &mesh.geometry {
+            let uri = rm.geometry_uri(self.index, geometry.resource);
+            record(&uri, &mut out.geometry_bytes, &mut out.unknown_resources)?;
+        }
+        if let Some(material) = &mesh.material {
+            let sets = defn
+                .material_definitions
+                .get(material.definition)
+                .map(|def| {
+                    let pbr = def.pbr_metallic_roughness.as_ref();
+                    [
+                        pbr.and_then(|p| p.base_color_texture.as_ref()),
+                        def.normal_texture.as_ref(),
+                    ]
+                })
+                .unwrap_or_default();
+            let mut seen = Vec::new();
+            for texture in sets.into_iter().flatten() {
+                let id = texture.texture_set_definition_id;
+                if seen.contains(&id) {
+                    continue;
+                }
+                seen.push(id);
+                let Some(set) = defn.texture_set_definitions.get(id) else {
+                    continue;
+                };
+                for format in &set.formats {
+                    let uri = rm.texture_uri(material.resource, &format.name, format.format);
+                    let mut bytes = 0;
+                    record(&uri, &mut bytes, &mut out.unknown_resources)?;
+                    if bytes > 0 {
+                        match out
+                            .texture_bytes
+                            .iter_mut()
+                            .find(|(f, _)| *f == format.format)
+                        {
+                            Some((_, total)) => *total += bytes,
+                            None => out.texture_bytes.push((format.format, bytes)),
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(attribute) = &mesh.attribute {
+            for info in &defn.attribute_storage_info {
+                let uri = rm.attribute_uri(attribute.resource, &info.key);
+                record(&uri, &mut out.attribute_bytes, &mut out.unknown_resources)?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// The parent chain of this node, nearest first, ending at the root.
+    /// Empty for the root itself.
+    pub fn ancestors(&self, nodes: &NodeArray) -> Result<Vec<Arc<Node>>> {
+        let mut chain = Vec::new();
+        let mut parent_index = self.parent_index;
+        while let Some(index) = parent_index {
+            let parent = nodes.get(index)?;
+            parent_index = parent.parent_index;
+            chain.push(parent);
+        }
+        Ok(chain)
+    }
+
+    /// Every node beneath this one, in depth-first order, excluding the
+    /// node itself.
+    pub fn descendants(&self, nodes: &NodeArray) -> Result<Vec<Arc<Node>>> {
+        let mut out = Vec::new();
+        let mut stack: Vec<usize> = self.children.iter().rev().copied().collect();
+        while let Some(index) = stack.pop() {
+            let node = nodes.get(index)?;
+            stack.extend(node.children.iter().rev());
+            out.push(node);
+        }
+        Ok(out)
+    }
+
+    /// Edges between this node and the root; 0 for the root.
+    pub fn depth(&self, nodes: &NodeArray) -> Result<usize> {
+        Ok(self.ancestors(nodes)?.len())
+    }
+
+    /// This node followed by its ancestors up to and including the root.
+    pub fn path_to_root(self: &Arc<Node>, nodes: &NodeArray) -> Result<Vec<Arc<Node>>> {
+        let mut path = vec![Arc::clone(self)];
+        path.extend(self.ancestors(nodes)?);
+        Ok(path)
+    }
+}
+
+/// Accumulated stored size of one node's resources.
+///
+/// Sizes are the bytes at rest (gzipped entries, encoded textures), i.e.
+/// what a client would download, not decoded sizes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PayloadSize {
+    pub geometry_bytes: u64,
+    /// Stored texture bytes per encoded format, in texture set order.
+    pub texture_bytes: Vec<(ImageFormat, u64)>,
+    pub attribute_bytes: u64,
+    /// Resources whose size the backend could not report.
+    pub unknown_resources: usize,
+}
+
+impl PayloadSize {
+    /// Sum over all resource classes.
+    pub fn total(&self) -> u64 {
+        self.geometry_bytes
+            + self
+                .texture_bytes
+                .iter()
+                .map(|(_, bytes)| bytes)
+                .sum::<u64>()
+            + self.attribute_bytes
+    }
+}
+
+/// One page of nodes.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodePage {
+    pub nodes: Vec<Arc<Node>>,
+}
+
+/// The view that [`NodeArray::select_lod`] evaluates nodes against.
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    /// Eye position in layer coordinates.
+    pub position: [f64; 3],
+    /// Vertical field of view, in radians.
+    pub fov_y: f64,
+    /// Viewport height, in pixels.
+    pub screen_height: f64,
+}
+
+impl Camera {
+    /// Screen pixels covered by one world unit at `distance` in front of
+    /// the eye.
+    fn pixels_per_unit(&self, distance: f64) -> f64 {
+        self.screen_height / (2.0 * distance * (self.fov_y / 2.0).tan())
+    }
+}
+
+/// Whether a node's detail is insufficient for the view, per the layer's
+/// `lodSelectionMetricType`.
+fn too_coarse(node: &Node, metric: &str, camera: &Camera, max_screen_error: f64) -> bool {
+    // Group nodes without a threshold only exist to be descended through.
+    let Some(threshold) = node.lod_threshold else {
+        return true;
+    };
+    let sphere = node.obb.bounding_sphere();
+    let distance = camera
+        .position
+        .iter()
+        .zip(&sphere.center)
+        .map(|(eye, center)| (eye - center) * (eye - center))
+        .sum::<f64>()
+        .sqrt();
+    // A camera inside the bounding sphere always wants full detail.
+    if distance <= sphere.radius {
+        return true;
+    }
+    let pixels_per_unit = camera.pixels_per_unit(distance - sphere.radius);
+    match metric {
+        // Threshold is the projected sphere diameter in pixels.
+        "maxScreenThreshold" => 2.0 * sphere.radius * pixels_per_unit > threshold * max_screen_error,
+        // Threshold is the vertex density the node was built for; refine
+        // once the on-screen vertex spacing grows past the tolerated error.
+        "densityThreshold" => {
+            threshold > 0.0 && pixels_per_unit / threshold.sqrt() > max_screen_error
+        }
+        // `maxScreenThresholdSQ`, the spec default: threshold is the
+        // projected sphere area in pixels squared. Unknown metric names
+        // get the same treatment.
+        _ => {
+            let radius_px = sphere.radius * pixels_per_unit;
+            std::f64::consts::PI * radius_px * radius_px > threshold * max_screen_error
+        }
+    }
+}
+
+/// A lazily-populated view over the node tree of a layer.
+///
+/// Views are cheap: the page cache is a concurrent map shared with the
+/// [`SceneLayer`](crate::layer::SceneLayer) that created the view (and
+/// with every other view of it), so pages fetched through one view are
+/// visible to all and survive the view being dropped.
+#[derive(Clone)]
+pub struct NodeArray {
+    rm: Arc<ResourceManager>,
+    defn: NodePageDefinition,
+    root_index: usize,
+    pages: Arc<crate::cache::BoundedCache<usize, NodePage>>,
+}
+
+impl NodeArray {
+    pub(crate) fn new(
+        rm: Arc<ResourceManager>,
+        defn: &NodePageDefinition,
+        pages: Arc<crate::cache::BoundedCache<usize, NodePage>>,
+    ) -> Self {
+        Self {
+            rm,
+            root_index: defn.root_index.unwrap_or(0),
+            defn: defn.clone(),
+            pages,
+        }
+    }
+
+    /// The layer's node page layout.
+    pub fn page_definition(&self) -> &NodePageDefinition {
+        &self.defn
+    }
+
+    /// The layer's node page size.
+    pub fn nodes_per_page(&self) -> usize {
+        self.defn.nodes_per_page
+    }
+
+    /// Whether the node page with the given page index is already cached.
+    pub fn page_cached(&self, page_index: usize) -> bool {
+        self.pages.contains_key(&page_index)
+    }
+
+    /// Fetch (and cache) the node page with the given page index.
+    pub fn get_node_page(&self, page_index: usize) -> Result<Arc<NodePage>> {
+        if let Some(page) = self.pages.get(&page_index) {
+            return Ok(Arc::clone(&page));
+        }
+        let uri = self.rm.node_page_uri(page_index);
+        let bytes = self.rm.get(&uri).map_err(|e| {
+            e.with_context(crate::err::ErrorContext {
+                resource: Some("node page"),
+                uri: Some(uri.clone()),
+                ..Default::default()
+            })
+        })?;
+        let page: NodePage = serde_json::from_slice(&bytes).map_err(|e| I3SError::json(&uri, e))?;
+        // Two views racing on the same page keep the first parse.
+        Ok(self
+            .pages
+            .get_or_insert_with(page_index, 1, || Arc::new(page)))
+    }
+
+    /// Fetch the node with the given index.
+    pub fn get(&self, node_index: usize) -> Result<Arc<Node>> {
+        let page_index = self.defn.page_index(node_index)?;
+        let in_page = self.defn.index_in_page(node_index)?;
+        let page = self.get_node_page(page_index)?;
+        page.nodes.get(in_page).map(Arc::clone).ok_or_else(|| {
+            I3SError::MissingResource(format!("node {node_index} in page {page_index}"))
+        })
+    }
+
+    /// The root node of the layer.
+    pub fn root(&self) -> Result<Arc<Node>> {
+        self.get(self.root_index)
+    }
+
+    /// Fetch several nodes in one batch, returned in the requested order.
+    ///
+    /// The indices are grouped by node page first, and pages not already
+    /// cached are fetched concurrently, so a batch spanning two or three
+    /// pages costs one round trip instead of one per node.
+    pub fn get_many(&self, node_indices: &[usize]) -> Result<Vec<Arc<Node>>> {
+        let mut missing: Vec<usize> = Vec::new();
+        for &node_index in node_indices {
+            let page_index = self.defn.page_index(node_index)?;
+            if !self.pages.contains_key(&page_index) && !missing.contains(&page_index) {
+                missing.push(page_index);
+            }
+        }
+        if missing.len() > 1 {
+            let rm = &self.rm;
+            let fetched = std::thread::scope(|scope| {
+                let handles: Vec<_> = missing
+                    .iter()
+                    .map(|&page_index| {
+                        scope.spawn(move || -> Result<(usize, NodePage)> {
+                            let uri = rm.node_page_uri(page_index);
+                            let bytes = rm.get(&uri)?;
+                            let page: NodePage = serde_json::from_slice(&bytes)
+                                .map_err(|e| I3SError::json(&uri, e))?;
+                            Ok((page_index, page))
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("page fetch panicked"))
+                    .collect::<Result<Vec<_>>>()
+            })?;
+            for (page_index, page) in fetched {
+                self.pages.insert(page_index, 1, Arc::new(page));
+            }
+        }
+        node_indices.iter().map(|&index| self.get(index)).collect()
+    }
+
+    /// Fetch all children of a node, in declaration order.
+    pub fn get_children(&self, node: &Node) -> Result<Vec<Arc<Node>>> {
+        self.get_many(&node.children)
+    }
+
+    /// Fetch the parent of a node, if it has one.
+    pub fn get_parent(&self, node: &Node) -> Result<Option<Arc<Node>>> {
+        match node.parent_index {
+            Some(parent) => Ok(Some(self.get(parent)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The nodes whose bounding volumes touch `extent` (x/y only), in
+    /// depth-first order.
+    ///
+    /// Subtrees are pruned on the way down: children of a node whose box
+    /// misses the extent are never fetched, so clipping a city model to an
+    /// area of interest only touches the node pages along the overlap.
+    pub fn query_extent(&self, extent: &Extent) -> Result<Vec<Arc<Node>>> {
+        let mut out = Vec::new();
+        let mut stack = vec![self.root()?];
+        while let Some(node) = stack.pop() {
+            let aabb = node.obb.to_aabb();
+            if aabb.max[0] < extent.xmin
+                || aabb.min[0] > extent.xmax
+                || aabb.max[1] < extent.ymin
+                || aabb.min[1] > extent.ymax
+            {
+                continue;
+            }
+            for &child in node.children.iter().rev() {
+                stack.push(self.get(child)?);
+            }
+            out.push(node);
+        }
+        Ok(out)
+    }
+
+    /// The nodes a renderer should display for the given view: the LOD
+    /// cut where every node's detail satisfies the layer's
+    /// `lodSelectionMetricType` for the camera.
+    ///
+    /// Starting at the root, a node whose metric says it is too coarse is
+    /// replaced by its children; otherwise it joins the selection (nodes
+    /// without a mesh are descended through). `max_screen_error` tunes
+    /// quality: for the screen-threshold metrics it scales each node's
+    /// threshold (`1.0` is spec behavior, larger is coarser), for
+    /// `densityThreshold` it is the tolerated on-screen vertex spacing in
+    /// pixels.
+    pub fn select_lod(&self, camera: &Camera, max_screen_error: f64) -> Result<Vec<Arc<Node>>> {
+        self.select(camera, None, max_screen_error)
+    }
+
+    /// [`select_lod`](Self::select_lod) restricted to a view frustum:
+    /// subtrees whose OBBs fall entirely outside `frustum` are skipped
+    /// before their node pages are ever fetched, so a streaming renderer
+    /// only touches the pages along the visible cut.
+    pub fn select_visible(
+        &self,
+        camera: &Camera,
+        frustum: &crate::obb::Frustum,
+        max_screen_error: f64,
+    ) -> Result<Vec<Arc<Node>>> {
+        self.select(camera, Some(frustum), max_screen_error)
+    }
+
+    fn select(
+        &self,
+        camera: &Camera,
+        frustum: Option<&crate::obb::Frustum>,
+        max_screen_error: f64,
+    ) -> Result<Vec<Arc<Node>>> {
+        let metric = self
+            .defn
+            .lod_selection_metric_type
+            .clone()
+            .unwrap_or_else(|| "maxScreenThresholdSQ".to_string());
+        let mut selected = Vec::new();
+        let mut stack = vec![self.root()?];
+        while let Some(node) = stack.pop() {
+            if frustum.is_some_and(|frustum| !node.obb.intersects_frustum(frustum)) {
+                continue;
+            }
+            if !node.is_leaf()
+                && (node.mesh.is_none() || too_coarse(&node, &metric, camera, max_screen_error))
+            {
+                for &child in node.children.iter().rev() {
+                    stack.push(self.get(child)?);
+                }
+                continue;
+            }
+            if node.mesh.is_some() {
+                selected.push(node);
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Depth-first walk of the tree from the root. The callback steers
+    /// the walk per node: descend, prune the subtree, or stop.
+    pub fn traverse<F>(&self, mut callback: F) -> Result<()>
+    where
+        F: FnMut(&Arc<Node>) -> TraversalControl,
+    {
+        let mut stack = vec![self.root()?];
+        while let Some(node) = stack.pop() {
+            match callback(&node) {
+                TraversalControl::Continue => {}
+                TraversalControl::SkipSubtree => continue,
+                TraversalControl::Stop => return Ok(()),
+            }
+            for &child in node.children.iter().rev() {
+                stack.push(self.get(child)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Walk the tree in a chosen order, optionally bounded in depth.
+    ///
+    /// The callback receives each node with its depth (the root is 0) and
+    /// steers the walk like [`traverse`](Self::traverse). `max_depth`
+    /// stops descending below that depth — `Some(0)` visits the root
+    /// alone. Breadth-first visits level by level, the coarse-to-fine
+    /// order streaming consumers want; post-order depth-first visits
+    /// children before their parent, which suits bottom-up summaries
+    /// (there [`TraversalControl::SkipSubtree`] comes too late to prune
+    /// and acts like `Continue`).
+    pub fn traverse_with<F>(
+        &self,
+        order: TraversalOrder,
+        max_depth: Option<usize>,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&Arc<Node>, usize) -> TraversalControl,
+    {
+        let descend = |depth: usize| max_depth.is_none_or(|max| depth < max);
+        match order {
+            TraversalOrder::BreadthFirst => {
+                let mut queue = std::collections::VecDeque::new();
+                queue.push_back((self.root_index, 0usize));
+                while let Some((index, depth)) = queue.pop_front() {
+                    let node = self.get(index)?;
+                    match callback(&node, depth) {
+                        TraversalControl::Continue if descend(depth) => {
+                            queue.extend(node.children.iter().map(|&child| (child, depth + 1)));
+                        }
+                        TraversalControl::Continue | TraversalControl::SkipSubtree => {}
+                        TraversalControl::Stop => return Ok(()),
+                    }
+                }
+            }
+            TraversalOrder::DepthFirst { post_order: false } => {
+                let mut stack = vec![(self.root_index, 0usize)];
+                while let Some((index, depth)) = stack.pop() {
+                    let node = self.get(index)?;
+                    match callback(&node, depth) {
+                        TraversalControl::Continue if descend(depth) => {
+                            for &child in node.children.iter().rev() {
+                                stack.push((child, depth + 1));
+                            }
+                        }
+                        TraversalControl::Continue | TraversalControl::SkipSubtree => {}
+                        TraversalControl::Stop => return Ok(()),
+                    }
+                }
+            }
+            TraversalOrder::DepthFirst { post_order: true } => {
+                enum Frame {
+                    Visit(usize, usize),
+                    Emit(Arc<Node>, usize),
+                }
+                let mut stack = vec![Frame::Visit(self.root_index, 0)];
+                while let Some(frame) = stack.pop() {
+                    match frame {
+                        Frame::Visit(index, depth) => {
+                            let node = self.get(index)?;
+                            let children = node.children.clone();
+                            stack.push(Frame::Emit(node, depth));
+                            if descend(depth) {
+                                for &child in children.iter().rev() {
+                                    stack.push(Frame::Visit(child, depth + 1));
+                                }
+                            }
+                        }
+                        Frame::Emit(node, depth) => {
+                            if callback(&node, depth) == TraversalControl::Stop {
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Depth-first walk limited to a per-call time slice.
+    ///
+    /// Processes nodes in the same order as [`traverse`](Self::traverse)
+    /// until `time_budget` elapses, then returns a cursor that
+    /// [`resume_budgeted`](Self::resume_budgeted) picks up on a later frame.
+    /// Returns `Ok(None)` when the walk finished (or the callback stopped
+    /// it) within the slice. The budget is checked between nodes, so one
+    /// slow callback can still overshoot it.
+    pub fn traverse_budgeted<F>(
+        &self,
+        callback: F,
+        time_budget: Duration,
+    ) -> Result<Option<TraversalCursor>>
+    where
+        F: FnMut(&Arc<Node>) -> TraversalControl,
+    {
+        let cursor = TraversalCursor {
+            stack: vec![self.root_index],
+        };
+        self.resume_budgeted(cursor, callback, time_budget)
+    }
+
+    /// Continue a budgeted walk from where an earlier slice left off.
+    pub fn resume_budgeted<F>(
+        &self,
+        mut cursor: TraversalCursor,
+        mut callback: F,
+        time_budget: Duration,
+    ) -> Result<Option<TraversalCursor>>
+    where
+        F: FnMut(&Arc<Node>) -> TraversalControl,
+    {
+        let deadline = Instant::now() + time_budget;
+        while let Some(index) = cursor.stack.pop() {
+            let node = self.get(index)?;
+            match callback(&node) {
+                TraversalControl::Continue => {
+                    for &child in node.children.iter().rev() {
+                        cursor.stack.push(child);
+                    }
+                }
+                TraversalControl::SkipSubtree => {}
+                TraversalControl::Stop => return Ok(None),
+            }
+            if !cursor.stack.is_empty() && Instant::now() >= deadline {
+                return Ok(Some(cursor));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Lazily iterate the leaf nodes of the tree, in depth-first order.
+    ///
+    /// Node pages are pulled (and cached) as the iterator descends, so
+    /// exporters that only want leaves touch exactly the pages on the
+    /// way down instead of hand-rolling a filtering traversal. A fetch
+    /// or parse failure is yielded as the final item.
+    pub fn leaves(&self) -> Leaves<'_> {
+        Leaves {
+            nodes: self,
+            stack: vec![self.root_index],
+            failed: false,
+        }
+    }
+
+    /// Lazily iterate the nodes `level` edges below the root, in
+    /// depth-first order; level 0 is the root alone.
+    ///
+    /// The walk never descends past the requested level, so pages of
+    /// deeper nodes are not fetched.
+    pub fn at_level(&self, level: usize) -> AtLevel<'_> {
+        AtLevel {
+            nodes: self,
+            level,
+            stack: vec![(self.root_index, 0)],
+            failed: false,
+        }
+    }
+}
+
+/// Lazy iterator over leaf nodes; see [`NodeArray::leaves`].
+pub struct Leaves<'a> {
+    nodes: &'a NodeArray,
+    stack: Vec<usize>,
+    failed: bool,
+}
+
+impl Iterator for Leaves<'_> {
+    type Item = Result<Arc<Node>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        while let Some(index) = self.stack.pop() {
+            let node = match self.nodes.get(index) {
+                Ok(node) => node,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            };
+            if node.is_leaf() {
+                return Some(Ok(node));
+            }
+            for &child in node.children.iter().rev() {
+                self.stack.push(child);
+            }
+        }
+        None
+    }
+}
+
+/// Lazy iterator over the nodes of one level; see [`NodeArray::at_level`].
+pub struct AtLevel<'a> {
+    nodes: &'a NodeArray,
+    level: usize,
+    stack: Vec<(usize, usize)>,
+    failed: bool,
+}
+
+impl Iterator for AtLevel<'_> {
+    type Item = Result<Arc<Node>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        while let Some((index, depth)) = self.stack.pop() {
+            let node = match self.nodes.get(index) {
+                Ok(node) => node,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            };
+            if depth == self.level {
+                return Some(Ok(node));
+            }
+            for &child in node.children.iter().rev() {
+                self.stack.push((child, depth + 1));
+            }
+        }
+        None
+    }
+}
+
+/// Visit order for [`NodeArray::traverse_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalOrder {
+    /// Level by level from the root.
+    BreadthFirst,
+    /// The rootward-first order of [`NodeArray::traverse`], or its
+    /// children-first mirror.
+    DepthFirst { post_order: bool },
+}
+
+/// What a traversal callback wants to happen next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalControl {
+    /// Descend into this node's children.
+    Continue,
+    /// Skip this node's children but keep walking the rest of the tree.
+    SkipSubtree,
+    /// Abort the whole walk.
+    Stop,
+}
+
+/// Resumable position of a budgeted traversal, holding the node indexes
+/// still to visit.
+#[derive(Debug, Clone)]
+pub struct TraversalCursor {
+    stack: Vec<usize>,
+}
+
+impl TraversalCursor {
+    /// Number of frontier nodes still queued.
+    pub fn pending(&self) -> usize {
+        self.stack.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaf_detection() {
+        let node: Node = serde_json::from_value(serde_json::json!({
+            "index": 0,
+            "obb": {
+                "center": [0.0, 0.0, 0.0],
+                "halfSize": [1.0, 1.0, 1.0],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            }
+        }))
+        .unwrap();
+        assert!(node.is_leaf());
+    }
+
+    #[test]
+    fn extras_store_one_value_per_type() {
+        let node: Node = serde_json::from_value(serde_json::json!({
+            "index": 0,
+            "obb": {
+                "center": [0.0, 0.0, 0.0],
+                "halfSize": [1.0, 1.0, 1.0],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            }
+        }))
+        .unwrap();
+
+        #[derive(Debug, PartialEq)]
+        struct GpuHandle(u32);
+
+        assert!(node.extras.get::<GpuHandle>().is_none());
+        node.extras.insert(GpuHandle(7));
+        node.extras.insert(true);
+        assert_eq!(*node.extras.get::<GpuHandle>().unwrap(), GpuHandle(7));
+        node.extras.insert(GpuHandle(9));
+        assert_eq!(node.extras.get::<GpuHandle>().unwrap().0, 9);
+        assert!(*node.extras.remove::<bool>().unwrap());
+        assert!(node.extras.get::<bool>().is_none());
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn get_many_spans_pages_and_preserves_order() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("get-many");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 2 }
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        for (page_index, indices) in [[0usize, 1], [2, 3], [4, 5]].iter().enumerate() {
+            let page: NodePage = serde_json::from_value(serde_json::json!({
+                "nodes": indices
+                    .iter()
+                    .map(|&index| serde_json::json!({ "index": index, "obb": obb }))
+                    .collect::<Vec<_>>()
+            }))
+            .unwrap();
+            writer.write_node_page(page_index, &page).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(path.to_str().unwrap()).unwrap();
+        let nodes = layer.nodes().unwrap();
+        let batch = nodes.get_many(&[5, 0, 3, 1]).unwrap();
+        let indices: Vec<usize> = batch.iter().map(|node| node.index).collect();
+        assert_eq!(indices, vec![5, 0, 3, 1]);
+
+        // Views share the layer's page cache: a second view (and the
+        // layer itself) sees the pages the batch above fetched.
+        let second = layer.nodes().unwrap();
+        assert!(second.pages.contains_key(&0));
+        assert!(second.pages.contains_key(&1));
+        assert!(second.pages.contains_key(&2));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn leaf_and_level_iterators_pull_pages_lazily() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("leaves");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 2 }
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let node = |index: usize, children: Vec<usize>| {
+            serde_json::json!({ "index": index, "obb": obb, "children": children })
+        };
+        let nodes = [
+            node(0, vec![1, 2]),
+            node(1, vec![3, 4]),
+            node(2, vec![5, 6]),
+            node(3, vec![]),
+            node(4, vec![]),
+            node(5, vec![]),
+            node(6, vec![]),
+        ];
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        for (page_index, page_nodes) in nodes.chunks(2).enumerate() {
+            let page: NodePage =
+                serde_json::from_value(serde_json::json!({ "nodes": page_nodes })).unwrap();
+            writer.write_node_page(page_index, &page).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(path.to_str().unwrap()).unwrap();
+        let nodes = layer.nodes().unwrap();
+
+        // Level 1 never descends to the leaves, so their pages stay cold.
+        let level: Vec<usize> = nodes
+            .at_level(1)
+            .map(|node| node.unwrap().index)
+            .collect();
+        assert_eq!(level, vec![1, 2]);
+        assert!(!nodes.pages.contains_key(&2));
+        assert!(!nodes.pages.contains_key(&3));
+
+        let leaves: Vec<usize> = nodes.leaves().map(|node| node.unwrap().index).collect();
+        assert_eq!(leaves, vec![3, 4, 5, 6]);
+
+        assert_eq!(nodes.at_level(0).count(), 1);
+        assert_eq!(nodes.at_level(9).count(), 0);
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn ancestor_and_descendant_navigation() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("navigation");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 8 }
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let node = |index: usize, parent: Option<usize>, children: Vec<usize>| {
+            let mut value = serde_json::json!({
+                "index": index, "obb": obb, "children": children
+            });
+            if let Some(parent) = parent {
+                value["parentIndex"] = parent.into();
+            }
+            value
+        };
+        let page: NodePage = serde_json::from_value(serde_json::json!({ "nodes": [
+            node(0, None, vec![1, 2]),
+            node(1, Some(0), vec![3]),
+            node(2, Some(0), vec![]),
+            node(3, Some(1), vec![]),
+        ]}))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(path.to_str().unwrap()).unwrap();
+        let nodes = layer.nodes().unwrap();
+        let root = nodes.root().unwrap();
+        let deep = nodes.get(3).unwrap();
+
+        let up: Vec<usize> = deep
+            .ancestors(&nodes)
+            .unwrap()
+            .iter()
+            .map(|n| n.index)
+            .collect();
+        assert_eq!(up, vec![1, 0]);
+        assert!(root.ancestors(&nodes).unwrap().is_empty());
+
+        assert_eq!(deep.depth(&nodes).unwrap(), 2);
+        assert_eq!(root.depth(&nodes).unwrap(), 0);
+
+        let path_up: Vec<usize> = deep
+            .path_to_root(&nodes)
+            .unwrap()
+            .iter()
+            .map(|n| n.index)
+            .collect();
+        assert_eq!(path_up, vec![3, 1, 0]);
+
+        let down: Vec<usize> = root
+            .descendants(&nodes)
+            .unwrap()
+            .iter()
+            .map(|n| n.index)
+            .collect();
+        assert_eq!(down, vec![1, 3, 2]);
+        assert!(deep.descendants(&nodes).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn extent_queries_prune_subtrees() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("query-extent");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 2 }
+        }))
+        .unwrap();
+        let obb = |x: f64, half: f64| {
+            serde_json::json!({
+                "center": [x, 0.0, 0.0],
+                "halfSize": [half, half, half],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            })
+        };
+        // Root spans both wings; the west wing (node 1 with leaf 3) is far
+        // from the east wing (node 2 with leaf 4).
+        let pages = [
+            serde_json::json!({ "nodes": [
+                { "index": 0, "obb": obb(0.0, 120.0), "children": [1, 2] },
+                { "index": 1, "obb": obb(-100.0, 10.0), "parentIndex": 0, "children": [3] }
+            ]}),
+            serde_json::json!({ "nodes": [
+                { "index": 2, "obb": obb(100.0, 10.0), "parentIndex": 0, "children": [4] },
+                { "index": 3, "obb": obb(-100.0, 5.0), "parentIndex": 1 }
+            ]}),
+            serde_json::json!({ "nodes": [
+                { "index": 4, "obb": obb(100.0, 5.0), "parentIndex": 2 }
+            ]}),
+        ];
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        for (index, page) in pages.iter().enumerate() {
+            let page: NodePage = serde_json::from_value(page.clone()).unwrap();
+            writer.write_node_page(index, &page).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(path.to_str().unwrap()).unwrap();
+        let nodes = layer.nodes().unwrap();
+        let west = nodes
+            .query_extent(&Extent {
+                xmin: -110.0,
+                ymin: -5.0,
+                xmax: -90.0,
+                ymax: 5.0,
+            })
+            .unwrap();
+        let indices: Vec<usize> = west.iter().map(|node| node.index).collect();
+        assert_eq!(indices, vec![0, 1, 3]);
+        // The east leaf's page was pruned, not fetched.
+        assert!(!nodes.pages.contains_key(&2));
+
+        let nothing = nodes
+            .query_extent(&Extent {
+                xmin: 500.0,
+                ymin: 500.0,
+                xmax: 600.0,
+                ymax: 600.0,
+            })
+            .unwrap();
+        assert!(nothing.is_empty());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn lod_selection_follows_camera_distance() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("select-lod");
+
+        let write = |path: &std::path::Path, metric: &str, threshold: f64| {
+            let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+                "id": 0,
+                "layerType": "IntegratedMesh",
+                "store": { "profile": "meshpyramids" },
+                "nodePages": { "nodesPerPage": 8, "lodSelectionMetricType": metric }
+            }))
+            .unwrap();
+            let obb = |x: f64| {
+                serde_json::json!({
+                    "center": [x, 0.0, 0.0],
+                    "halfSize": [10.0, 10.0, 10.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                })
+            };
+            let mesh = |resource: usize| {
+                serde_json::json!({ "geometry": {
+                    "definition": 0, "resource": resource, "vertexCount": 3
+                } })
+            };
+            let page: NodePage = serde_json::from_value(serde_json::json!({
+                "nodes": [
+                    {
+                        "index": 0, "obb": obb(0.0), "children": [1, 2],
+                        "lodThreshold": threshold, "mesh": mesh(0)
+                    },
+                    { "index": 1, "obb": obb(-5.0), "parentIndex": 0, "mesh": mesh(1) },
+                    { "index": 2, "obb": obb(5.0), "parentIndex": 0, "mesh": mesh(2) }
+                ]
+            }))
+            .unwrap();
+            let mut writer = SlpkWriter::create(path).unwrap();
+            writer.write_scene_definition(&defn).unwrap();
+            writer.write_node_page(0, &page).unwrap();
+            writer.finish().unwrap();
+        };
+        let camera = |distance: f64| Camera {
+            position: [0.0, 0.0, distance],
+            fov_y: std::f64::consts::FRAC_PI_3,
+            screen_height: 1080.0,
+        };
+        let indices = |nodes: &[Arc<Node>]| -> Vec<usize> {
+            let mut indices: Vec<usize> = nodes.iter().map(|node| node.index).collect();
+            indices.sort_unstable();
+            indices
+        };
+
+        // Screen-area metric: the root's projected sphere covers far more
+        // than 10000 px^2 up close, far less from a distance.
+        let path = dir.join("area.slpk");
+        write(&path, "maxScreenThresholdSQ", 10_000.0);
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let nodes = layer.nodes().unwrap();
+        let near = nodes.select_lod(&camera(100.0), 1.0).unwrap();
+        assert_eq!(indices(&near), vec![1, 2]);
+        let far = nodes.select_lod(&camera(2000.0), 1.0).unwrap();
+        assert_eq!(indices(&far), vec![0]);
+        // A generous error tolerance keeps the coarse root even up close.
+        let coarse = nodes.select_lod(&camera(100.0), 20.0).unwrap();
+        assert_eq!(indices(&coarse), vec![0]);
+
+        // Density metric: one vertex per square unit projects to ~11 px
+        // spacing up close and well under 2 px from a distance.
+        let path = dir.join("density.slpk");
+        write(&path, "densityThreshold", 1.0);
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let nodes = layer.nodes().unwrap();
+        let near = nodes.select_lod(&camera(100.0), 2.0).unwrap();
+        assert_eq!(indices(&near), vec![1, 2]);
+        let far = nodes.select_lod(&camera(2000.0), 2.0).unwrap();
+        assert_eq!(indices(&far), vec![0]);
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn frustum_culled_selection_skips_invisible_pages() {
+        use crate::obb::{Frustum, Plane};
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("select-visible");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 2, "lodSelectionMetricType": "maxScreenThresholdSQ" }
+        }))
+        .unwrap();
+        let obb = |x: f64, half: f64| {
+            serde_json::json!({
+                "center": [x, 0.0, 0.0],
+                "halfSize": [half, half, half],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            })
+        };
+        let mesh = |resource: usize| {
+            serde_json::json!({ "geometry": {
+                "definition": 0, "resource": resource, "vertexCount": 3
+            } })
+        };
+        // Same two-wing layout as the extent test, with thresholds low
+        // enough that a nearby camera always refines to the leaves.
+        let pages = [
+            serde_json::json!({ "nodes": [
+                {
+                    "index": 0, "obb": obb(0.0, 120.0), "children": [1, 2],
+                    "lodThreshold": 1.0, "mesh": mesh(0)
+                },
+                {
+                    "index": 1, "obb": obb(-100.0, 10.0), "parentIndex": 0, "children": [3],
+                    "lodThreshold": 1.0, "mesh": mesh(1)
+                }
+            ]}),
+            serde_json::json!({ "nodes": [
+                {
+                    "index": 2, "obb": obb(100.0, 10.0), "parentIndex": 0, "children": [4],
+                    "lodThreshold": 1.0, "mesh": mesh(2)
+                },
+                { "index": 3, "obb": obb(-100.0, 5.0), "parentIndex": 1, "mesh": mesh(3) }
+            ]}),
+            serde_json::json!({ "nodes": [
+                { "index": 4, "obb": obb(100.0, 5.0), "parentIndex": 2, "mesh": mesh(4) }
+            ]}),
+        ];
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        for (index, page) in pages.iter().enumerate() {
+            let page: NodePage = serde_json::from_value(page.clone()).unwrap();
+            writer.write_node_page(index, &page).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let nodes = layer.nodes().unwrap();
+        // An axis-aligned frustum enclosing only the west wing.
+        let west_view = Frustum::new(vec![
+            Plane { normal: [1.0, 0.0, 0.0], distance: 120.0 },
+            Plane { normal: [-1.0, 0.0, 0.0], distance: -80.0 },
+            Plane { normal: [0.0, 1.0, 0.0], distance: 50.0 },
+            Plane { normal: [0.0, -1.0, 0.0], distance: 50.0 },
+        ]);
+        let camera = Camera {
+            position: [-100.0, 0.0, 50.0],
+            fov_y: std::f64::consts::FRAC_PI_3,
+            screen_height: 1080.0,
+        };
+
+        let visible = nodes.select_visible(&camera, &west_view, 1.0).unwrap();
+        let indices: Vec<usize> = visible.iter().map(|node| node.index).collect();
+        assert_eq!(indices, vec![3]);
+        // The east subtree was culled before its leaf page was fetched.
+        assert!(!nodes.pages.contains_key(&2));
+
+        // Without the frustum the same camera refines both wings.
+        let mut all = nodes
+            .select_lod(&camera, 1.0)
+            .unwrap()
+            .iter()
+            .map(|node| node.index)
+            .collect::<Vec<_>>();
+        all.sort_unstable();
+        assert_eq!(all, vec![3, 4]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn traversal_control_prunes_and_stops() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("traversal-control");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 8 }
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let page: NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "obb": obb, "children": [1, 2] },
+                { "index": 1, "obb": obb, "parentIndex": 0, "children": [3] },
+                { "index": 2, "obb": obb, "parentIndex": 0, "children": [4] },
+                { "index": 3, "obb": obb, "parentIndex": 1 },
+                { "index": 4, "obb": obb, "parentIndex": 2 }
+            ]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let nodes = layer.nodes().unwrap();
+
+        // Pruning node 1 skips its subtree but the walk carries on.
+        let mut pruned = Vec::new();
+        nodes
+            .traverse(|node| {
+                pruned.push(node.index);
+                if node.index == 1 {
+                    TraversalControl::SkipSubtree
+                } else {
+                    TraversalControl::Continue
+                }
+            })
+            .unwrap();
+        assert_eq!(pruned, vec![0, 1, 2, 4]);
+
+        // Stopping aborts mid-walk.
+        let mut stopped = Vec::new();
+        nodes
+            .traverse(|node| {
+                stopped.push(node.index);
+                if node.index == 3 {
+                    TraversalControl::Stop
+                } else {
+                    TraversalControl::Continue
+                }
+            })
+            .unwrap();
+        assert_eq!(stopped, vec![0, 1, 3]);
+
+        // Budgeted walks honor the same control flow across slices.
+        let mut sliced = Vec::new();
+        let mut cursor = nodes
+            .traverse_budgeted(
+                |node| {
+                    sliced.push(node.index);
+                    if node.index == 1 {
+                        TraversalControl::SkipSubtree
+                    } else {
+                        TraversalControl::Continue
+                    }
+                },
+                Duration::ZERO,
+            )
+            .unwrap();
+        while let Some(pending) = cursor {
+            cursor = nodes
+                .resume_budgeted(
+                    pending,
+                    |node| {
+                        sliced.push(node.index);
+                        if node.index == 1 {
+                            TraversalControl::SkipSubtree
+                        } else {
+                            TraversalControl::Continue
+                        }
+                    },
+                    Duration::ZERO,
+                )
+                .unwrap();
+        }
+        assert_eq!(sliced, vec![0, 1, 2, 4]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn traversal_orders_and_depth_limits() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("traversal-order");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 8 }
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let page: NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "obb": obb, "children": [1, 2] },
+                { "index": 1, "obb": obb, "parentIndex": 0, "children": [3] },
+                { "index": 2, "obb": obb, "parentIndex": 0, "children": [4] },
+                { "index": 3, "obb": obb, "parentIndex": 1 },
+                { "index": 4, "obb": obb, "parentIndex": 2 }
+            ]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let mut nodes = layer.nodes().unwrap();
+        let order_of = |nodes: &mut NodeArray, order, max_depth| {
+            let mut visited = Vec::new();
+            nodes
+                .traverse_with(order, max_depth, |node, depth| {
+                    visited.push((node.index, depth));
+                    TraversalControl::Continue
+                })
+                .unwrap();
+            visited
+        };
+
+        assert_eq!(
+            order_of(&mut nodes, TraversalOrder::BreadthFirst, None),
+            vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+        );
+        assert_eq!(
+            order_of(
+                &mut nodes,
+                TraversalOrder::DepthFirst { post_order: false },
+                None
+            ),
+            vec![(0, 0), (1, 1), (3, 2), (2, 1), (4, 2)]
+        );
+        assert_eq!(
+            order_of(
+                &mut nodes,
+                TraversalOrder::DepthFirst { post_order: true },
+                None
+            ),
+            vec![(3, 2), (1, 1), (4, 2), (2, 1), (0, 0)]
+        );
+        // Depth limits bound every order the same way.
+        assert_eq!(
+            order_of(&mut nodes, TraversalOrder::BreadthFirst, Some(1)),
+            vec![(0, 0), (1, 1), (2, 1)]
+        );
+        assert_eq!(
+            order_of(
+                &mut nodes,
+                TraversalOrder::DepthFirst { post_order: true },
+                Some(0)
+            ),
+            vec![(0, 0)]
+        );
+
+        // Pruning and stopping work level by level too.
+        let mut pruned = Vec::new();
+        nodes
+            .traverse_with(TraversalOrder::BreadthFirst, None, |node, _| {
+                pruned.push(node.index);
+                if node.index == 1 {
+                    TraversalControl::SkipSubtree
+                } else {
+                    TraversalControl::Continue
+                }
+            })
+            .unwrap();
+        assert_eq!(pruned, vec![0, 1, 2, 4]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn budgeted_traversal_resumes_in_order() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("budget");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let page: NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "children": [1, 2], "obb": obb },
+                { "index": 1, "parentIndex": 0, "obb": obb },
+                { "index": 2, "parentIndex": 0, "obb": obb }
+            ]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(path.to_str().unwrap()).unwrap();
+        let nodes = layer.nodes().unwrap();
+
+        let mut full_order = Vec::new();
+        nodes
+            .traverse(|node| {
+                full_order.push(node.index);
+                TraversalControl::Continue
+            })
+            .unwrap();
+
+        let mut sliced_order = Vec::new();
+        let mut slices = 0;
+        let mut cursor = nodes
+            .traverse_budgeted(
+                |node| {
+                    sliced_order.push(node.index);
+                    TraversalControl::Continue
+                },
+                Duration::ZERO,
+            )
+            .unwrap();
+        while let Some(pending) = cursor {
+            slices += 1;
+            assert!(pending.pending() > 0);
+            cursor = nodes
+                .resume_budgeted(
+                    pending,
+                    |node| {
+                        sliced_order.push(node.index);
+                        TraversalControl::Continue
+                    },
+                    Duration::ZERO,
+                )
+                .unwrap();
+        }
+        assert_eq!(sliced_order, full_order);
+        assert!(slices >= 1);
+    }
+}
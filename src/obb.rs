@@ -0,0 +1,647 @@
+//! Oriented bounding boxes and minimum bounding spheres.
+
+use serde::{Deserialize, Serialize};
+
+use crate::err::{I3SError, Result};
+
+/// Interpretation of bounding-volume centers, from the layer CRS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Centers are Cartesian coordinates in a projected CRS.
+    Local,
+    /// Centers are lon/lat/height in a geographic CRS.
+    Global,
+}
+
+/// An oriented bounding box as stored in node pages: center, half sizes and
+/// an x/y/z/w rotation quaternion.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrientedBoundingBox {
+    pub center: [f64; 3],
+    pub half_size: [f32; 3],
+    pub quaternion: [f64; 4],
+}
+
+/// A minimum bounding sphere: center and radius.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Mbs {
+    pub center: [f64; 3],
+    pub radius: f64,
+}
+
+/// Rotate `v` by the unit quaternion `q` (x, y, z, w).
+pub(crate) fn rotate(q: [f64; 4], v: [f64; 3]) -> [f64; 3] {
+    let [qx, qy, qz, qw] = q;
+    // t = 2 * cross(q.xyz, v); v' = v + qw * t + cross(q.xyz, t)
+    let tx = 2.0 * (qy * v[2] - qz * v[1]);
+    let ty = 2.0 * (qz * v[0] - qx * v[2]);
+    let tz = 2.0 * (qx * v[1] - qy * v[0]);
+    [
+        v[0] + qw * tx + qy * tz - qz * ty,
+        v[1] + qw * ty + qz * tx - qx * tz,
+        v[2] + qw * tz + qx * ty - qy * tx,
+    ]
+}
+
+/// WGS84 semi-major axis, meters.
+const WGS84_A: f64 = 6_378_137.0;
+/// WGS84 first eccentricity squared.
+const WGS84_E2: f64 = 6.694_379_990_141_316e-3;
+
+/// Convert geodetic lon/lat (degrees) and ellipsoidal height (meters) on
+/// WGS84 into ECEF coordinates.
+pub fn geodetic_to_ecef(lon: f64, lat: f64, height: f64) -> [f64; 3] {
+    let lon = lon.to_radians();
+    let lat = lat.to_radians();
+    let n = WGS84_A / (1.0 - WGS84_E2 * lat.sin() * lat.sin()).sqrt();
+    [
+        (n + height) * lat.cos() * lon.cos(),
+        (n + height) * lat.cos() * lon.sin(),
+        (n * (1.0 - WGS84_E2) + height) * lat.sin(),
+    ]
+}
+
+/// The east/north/up unit vectors of the local tangent frame at geodetic
+/// lon/lat (degrees), in ECEF axes.
+fn enu_frame(lon: f64, lat: f64) -> [[f64; 3]; 3] {
+    let lon = lon.to_radians();
+    let lat = lat.to_radians();
+    [
+        [-lon.sin(), lon.cos(), 0.0],
+        [-lat.sin() * lon.cos(), -lat.sin() * lon.sin(), lat.cos()],
+        [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()],
+    ]
+}
+
+impl OrientedBoundingBox {
+    /// The rotated offsets of the eight corners from the center, in the
+    /// box's own meter-scaled frame.
+    fn corner_offsets(&self) -> [[f64; 3]; 8] {
+        let mut out = [[0.0; 3]; 8];
+        for (i, offset) in out.iter_mut().enumerate() {
+            let signs = [
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            ];
+            let local = [
+                signs[0] * self.half_size[0] as f64,
+                signs[1] * self.half_size[1] as f64,
+                signs[2] * self.half_size[2] as f64,
+            ];
+            *offset = rotate(self.quaternion, local);
+        }
+        out
+    }
+
+    /// The eight corner vertices of the box in world space.
+    ///
+    /// For `Mode::Local` the corners are in the layer's Cartesian CRS. For
+    /// `Mode::Global` the center is lon/lat/height on WGS84, the box's
+    /// frame is the east-north-up tangent frame at the center, and the
+    /// returned corners are ECEF coordinates, usable for culling against
+    /// globe-space frusta.
+    pub fn vertices(&self, mode: Mode) -> Result<[[f64; 3]; 8]> {
+        let mut out = self.corner_offsets();
+        match mode {
+            Mode::Local => {
+                for corner in &mut out {
+                    for (value, center) in corner.iter_mut().zip(&self.center) {
+                        *value += center;
+                    }
+                }
+            }
+            Mode::Global => {
+                let [lon, lat, height] = self.center;
+                if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+                    return Err(I3SError::Decode(format!(
+                        "global-mode OBB center ({lon}, {lat}) is not a lon/lat position"
+                    )));
+                }
+                let origin = geodetic_to_ecef(lon, lat, height);
+                let [east, north, up] = enu_frame(lon, lat);
+                for corner in &mut out {
+                    let [x, y, z] = *corner;
+                    for axis in 0..3 {
+                        corner[axis] =
+                            origin[axis] + x * east[axis] + y * north[axis] + z * up[axis];
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// A plane in Hessian form: the points `p` with
+/// `dot(normal, p) + distance = 0`. The normal points toward the inside
+/// half-space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: [f64; 3],
+    pub distance: f64,
+}
+
+impl Plane {
+    /// Signed distance of `point` from the plane; positive inside.
+    pub fn signed_distance(&self, point: [f64; 3]) -> f64 {
+        dot(self.normal, point) + self.distance
+    }
+}
+
+/// A convex culling volume as inward-facing planes — six for a camera
+/// frustum, but any plane set works.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frustum {
+    pub planes: Vec<Plane>,
+}
+
+impl Frustum {
+    pub fn new(planes: Vec<Plane>) -> Self {
+        Self { planes }
+    }
+
+    /// Extract the six frustum planes from a row-major combined
+    /// view-projection matrix (Gribb/Hartmann), normals normalized.
+    pub fn from_view_projection(matrix: &[[f64; 4]; 4]) -> Self {
+        let row = |i: usize| matrix[i];
+        let mut planes = Vec::with_capacity(6);
+        for (base, sign) in [(0, 1.0), (0, -1.0), (1, 1.0), (1, -1.0), (2, 1.0), (2, -1.0)] {
+            let r = row(base);
+            let w = row(3);
+            let raw = [
+                w[0] + sign * r[0],
+                w[1] + sign * r[1],
+                w[2] + sign * r[2],
+                w[3] + sign * r[3],
+            ];
+            let norm = dot([raw[0], raw[1], raw[2]], [raw[0], raw[1], raw[2]]).sqrt();
+            if norm > 0.0 {
+                planes.push(Plane {
+                    normal: [raw[0] / norm, raw[1] / norm, raw[2] / norm],
+                    distance: raw[3] / norm,
+                });
+            }
+        }
+        Self { planes }
+    }
+}
+
+/// A ray for picking: origin plus (not necessarily unit) direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: [f64; 3],
+    pub direction: [f64; 3],
+}
+
+impl OrientedBoundingBox {
+    /// The box's three axes in world space.
+    fn axes(&self) -> [[f64; 3]; 3] {
+        [
+            rotate(self.quaternion, [1.0, 0.0, 0.0]),
+            rotate(self.quaternion, [0.0, 1.0, 0.0]),
+            rotate(self.quaternion, [0.0, 0.0, 1.0]),
+        ]
+    }
+
+    /// Whether the box touches the volume inside `frustum`.
+    ///
+    /// Tests the box's projected radius against every plane, so it never
+    /// culls a visible box; a box outside the volume but not fully outside
+    /// any single plane can slip through, which only costs a traversal
+    /// visit.
+    pub fn intersects_frustum(&self, frustum: &Frustum) -> bool {
+        let axes = self.axes();
+        for plane in &frustum.planes {
+            let radius: f64 = axes
+                .iter()
+                .zip(&self.half_size)
+                .map(|(axis, half)| (dot(plane.normal, *axis) * f64::from(*half)).abs())
+                .sum();
+            if plane.signed_distance(self.center) < -radius {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Distance along `ray` (in units of its direction length) to where it
+    /// enters the box — `0.0` when the origin is inside, `None` on a miss.
+    pub fn ray_intersection(&self, ray: &Ray) -> Option<f64> {
+        // Slab test in the box frame.
+        let [qx, qy, qz, qw] = self.quaternion;
+        let inverse = [-qx, -qy, -qz, qw];
+        let origin = rotate(
+            inverse,
+            [
+                ray.origin[0] - self.center[0],
+                ray.origin[1] - self.center[1],
+                ray.origin[2] - self.center[2],
+            ],
+        );
+        let direction = rotate(inverse, ray.direction);
+
+        let mut enter = f64::NEG_INFINITY;
+        let mut exit = f64::INFINITY;
+        for axis in 0..3 {
+            let half = f64::from(self.half_size[axis]);
+            if direction[axis].abs() < f64::EPSILON {
+                if origin[axis].abs() > half {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (-half - origin[axis]) / direction[axis];
+            let t1 = (half - origin[axis]) / direction[axis];
+            enter = enter.max(t0.min(t1));
+            exit = exit.min(t0.max(t1));
+        }
+        (exit >= enter.max(0.0)).then(|| enter.max(0.0))
+    }
+
+    /// Whether `ray` hits the box.
+    pub fn intersects_ray(&self, ray: &Ray) -> bool {
+        self.ray_intersection(ray).is_some()
+    }
+}
+
+/// An axis-aligned bounding box in the same coordinates as its source
+/// volume.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+
+impl OrientedBoundingBox {
+    /// The smallest axis-aligned box containing this one.
+    pub fn to_aabb(&self) -> Aabb {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for offset in self.corner_offsets() {
+            for axis in 0..3 {
+                let value = self.center[axis] + offset[axis];
+                min[axis] = min[axis].min(value);
+                max[axis] = max[axis].max(value);
+            }
+        }
+        Aabb { min, max }
+    }
+
+    /// The bounding sphere around this box — center plus the half-diagonal
+    /// as radius, the same shape I3S stores as a node MBS.
+    pub fn bounding_sphere(&self) -> Mbs {
+        let [hx, hy, hz] = self.half_size.map(f64::from);
+        Mbs {
+            center: self.center,
+            radius: (hx * hx + hy * hy + hz * hz).sqrt(),
+        }
+    }
+
+    /// An axis-aligned box containing both `self` and `other`.
+    ///
+    /// The minimal oriented union is neither unique nor cheap, so the
+    /// result is conservative: identity orientation around the combined
+    /// AABB of the two inputs.
+    pub fn union(&self, other: &Self) -> Self {
+        let (a, b) = (self.to_aabb(), other.to_aabb());
+        let mut center = [0.0; 3];
+        let mut half_size = [0.0f32; 3];
+        for axis in 0..3 {
+            let min = a.min[axis].min(b.min[axis]);
+            let max = a.max[axis].max(b.max[axis]);
+            center[axis] = (min + max) / 2.0;
+            // Round the f32 narrowing up so the inputs stay covered.
+            let half = (max - min) / 2.0;
+            let mut narrowed = half as f32;
+            if f64::from(narrowed) < half {
+                narrowed = narrowed.next_up();
+            }
+            half_size[axis] = narrowed;
+        }
+        Self {
+            center,
+            half_size,
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    /// Whether `point` lies inside or on the box.
+    pub fn contains_point(&self, point: [f64; 3]) -> bool {
+        let offset = [
+            point[0] - self.center[0],
+            point[1] - self.center[1],
+            point[2] - self.center[2],
+        ];
+        // Into the box frame via the inverse (conjugate) rotation.
+        let [qx, qy, qz, qw] = self.quaternion;
+        let local = rotate([-qx, -qy, -qz, qw], offset);
+        local
+            .iter()
+            .zip(&self.half_size)
+            .all(|(value, half)| value.abs() <= f64::from(*half))
+    }
+}
+
+/// Spherical interpolation between two unit quaternions (x, y, z, w).
+///
+/// Takes the short arc; nearly-parallel inputs fall back to normalized
+/// linear interpolation to avoid dividing by a vanishing angle.
+pub fn slerp(a: [f64; 4], b: [f64; 4], t: f64) -> [f64; 4] {
+    let mut dot: f64 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+    let b = if dot < 0.0 {
+        dot = -dot;
+        [-b[0], -b[1], -b[2], -b[3]]
+    } else {
+        b
+    };
+    let (wa, wb) = if dot > 0.9995 {
+        (1.0 - t, t)
+    } else {
+        let theta = dot.clamp(-1.0, 1.0).acos();
+        let sin = theta.sin();
+        (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+    };
+    let mut out = [0.0; 4];
+    for i in 0..4 {
+        out[i] = wa * a[i] + wb * b[i];
+    }
+    let norm = out.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        for v in &mut out {
+            *v /= norm;
+        }
+    }
+    out
+}
+
+impl OrientedBoundingBox {
+    /// Interpolate between two boxes: centers and half sizes linearly,
+    /// orientations spherically. `t = 0` is `self`, `t = 1` is `other`.
+    ///
+    /// Used by renderers that blend a parent's bounding volume into a
+    /// child's during an LOD switch instead of popping.
+    pub fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let mut center = [0.0; 3];
+        let mut half_size = [0.0f32; 3];
+        for i in 0..3 {
+            center[i] = self.center[i] + (other.center[i] - self.center[i]) * t;
+            half_size[i] = self.half_size[i] + (other.half_size[i] - self.half_size[i]) * t as f32;
+        }
+        Self {
+            center,
+            half_size,
+            quaternion: slerp(self.quaternion, other.quaternion, t),
+        }
+    }
+}
+
+/// Opacity weights for cross-fading a parent and its children during an
+/// LOD switch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LodTransition {
+    pub parent_weight: f64,
+    pub child_weight: f64,
+}
+
+/// Cross-fade weights for a node whose switch point is `lod_threshold`.
+///
+/// The fade runs over a band of width `fade_band` centered on the
+/// threshold: while the measured screen-space error is below the band the
+/// parent renders alone, above it the children render alone, and inside
+/// it the two are blended so the switch never pops. The weights always
+/// sum to one; a zero or negative band gives the usual hard switch.
+pub fn cross_fade(screen_space_error: f64, lod_threshold: f64, fade_band: f64) -> LodTransition {
+    let child_weight = if fade_band > 0.0 {
+        ((screen_space_error - (lod_threshold - fade_band / 2.0)) / fade_band).clamp(0.0, 1.0)
+    } else if screen_space_error >= lod_threshold {
+        1.0
+    } else {
+        0.0
+    };
+    LodTransition {
+        parent_weight: 1.0 - child_weight,
+        child_weight,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_quaternion_vertices() {
+        let obb = OrientedBoundingBox {
+            center: [10.0, 20.0, 30.0],
+            half_size: [1.0, 2.0, 3.0],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        };
+        let verts = obb.vertices(Mode::Local).unwrap();
+        assert_eq!(verts[0], [9.0, 18.0, 27.0]);
+        assert_eq!(verts[7], [11.0, 22.0, 33.0]);
+    }
+
+    #[test]
+    fn global_mode_vertices_are_ecef() {
+        // At lon 0 / lat 0 the ENU frame lines up with the ECEF axes:
+        // east is +Y, north is +Z and up is +X.
+        let obb = OrientedBoundingBox {
+            center: [0.0, 0.0, 0.0],
+            half_size: [1.0, 2.0, 3.0],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        };
+        let verts = obb.vertices(Mode::Global).unwrap();
+        let expected_low = [WGS84_A - 3.0, -1.0, -2.0];
+        let expected_high = [WGS84_A + 3.0, 1.0, 2.0];
+        for axis in 0..3 {
+            assert!((verts[0][axis] - expected_low[axis]).abs() < 1e-6);
+            assert!((verts[7][axis] - expected_high[axis]).abs() < 1e-6);
+        }
+
+        // The north pole exercises the polar-radius branch of the
+        // ellipsoid: up is +Z and the corners sit around b = a * (1 - f).
+        let pole = OrientedBoundingBox {
+            center: [0.0, 90.0, 0.0],
+            half_size: [1.0, 1.0, 1.0],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        };
+        let b = WGS84_A * (1.0 - WGS84_E2).sqrt();
+        let verts = pole.vertices(Mode::Global).unwrap();
+        for corner in verts {
+            assert!((corner[2] - b).abs() < 1.5);
+        }
+
+        let projected = OrientedBoundingBox {
+            center: [500_000.0, 4_000_000.0, 0.0],
+            half_size: [1.0; 3],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        };
+        assert!(projected.vertices(Mode::Global).is_err());
+    }
+
+    /// Inward-facing planes of the axis-aligned cube `[-extent, extent]^3`.
+    fn cube_frustum(extent: f64) -> Frustum {
+        let mut planes = Vec::new();
+        for axis in 0..3 {
+            for sign in [1.0, -1.0] {
+                let mut normal = [0.0; 3];
+                normal[axis] = sign;
+                planes.push(Plane {
+                    normal,
+                    distance: extent,
+                });
+            }
+        }
+        Frustum::new(planes)
+    }
+
+    #[test]
+    fn frustum_and_ray_intersections() {
+        let frustum = cube_frustum(10.0);
+        let half = std::f64::consts::FRAC_PI_4;
+        let obb = OrientedBoundingBox {
+            center: [0.0, 11.0, 0.0],
+            half_size: [2.0, 0.5, 0.5],
+            quaternion: [0.0, 0.0, half.sin(), half.cos()],
+        };
+        // Rotated 90 degrees about z, the long x half-extent points along
+        // y and reaches back inside the cube.
+        assert!(obb.intersects_frustum(&frustum));
+        let unrotated = OrientedBoundingBox {
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+            ..obb
+        };
+        assert!(!unrotated.intersects_frustum(&frustum));
+
+        let hit = Ray {
+            origin: [0.0, 0.0, 0.0],
+            direction: [0.0, 1.0, 0.0],
+        };
+        // Entry at y = 11 - 2 = 9 through the rotated long axis.
+        assert!((obb.ray_intersection(&hit).unwrap() - 9.0).abs() < 1e-9);
+        assert!(obb.intersects_ray(&hit));
+        // Same line, pointing away: no hit behind the origin.
+        assert!(!obb.intersects_ray(&Ray {
+            direction: [0.0, -1.0, 0.0],
+            ..hit
+        }));
+        // Origin inside reports distance zero.
+        assert_eq!(
+            obb.ray_intersection(&Ray {
+                origin: obb.center,
+                ..hit
+            }),
+            Some(0.0)
+        );
+
+        // A perspective view-projection looking down -z keeps the origin's
+        // surroundings and culls what is behind the camera.
+        let near = 0.1;
+        let far = 100.0;
+        let matrix = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [
+                0.0,
+                0.0,
+                (far + near) / (near - far),
+                2.0 * far * near / (near - far),
+            ],
+            [0.0, 0.0, -1.0, 0.0],
+        ];
+        let view = Frustum::from_view_projection(&matrix);
+        let visible = OrientedBoundingBox {
+            center: [0.0, 0.0, -5.0],
+            half_size: [1.0; 3],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        };
+        let behind = OrientedBoundingBox {
+            center: [0.0, 0.0, 5.0],
+            ..visible
+        };
+        assert!(visible.intersects_frustum(&view));
+        assert!(!behind.intersects_frustum(&view));
+    }
+
+    #[test]
+    fn aabb_sphere_union_and_containment() {
+        // 90 degrees about z swaps the x and y extents.
+        let half = std::f64::consts::FRAC_PI_4;
+        let rotated = OrientedBoundingBox {
+            center: [10.0, 0.0, 0.0],
+            half_size: [1.0, 2.0, 3.0],
+            quaternion: [0.0, 0.0, half.sin(), half.cos()],
+        };
+        let aabb = rotated.to_aabb();
+        for (axis, extent) in [2.0, 1.0, 3.0].into_iter().enumerate() {
+            assert!((aabb.max[axis] - rotated.center[axis] - extent).abs() < 1e-9);
+            assert!((rotated.center[axis] - aabb.min[axis] - extent).abs() < 1e-9);
+        }
+
+        let sphere = rotated.bounding_sphere();
+        assert_eq!(sphere.center, rotated.center);
+        assert!((sphere.radius - 14.0_f64.sqrt()).abs() < 1e-12);
+
+        // The rotated box spans y in [-1, 1]: a point at y = 1.5 is out.
+        assert!(rotated.contains_point([10.0, 0.9, 2.9]));
+        assert!(!rotated.contains_point([10.0, 1.5, 0.0]));
+        assert!(!rotated.contains_point([12.5, 0.0, 0.0]));
+
+        let other = OrientedBoundingBox {
+            center: [-10.0, 0.0, 0.0],
+            half_size: [1.0, 1.0, 1.0],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        };
+        let union = rotated.union(&other);
+        for obb in [&rotated, &other] {
+            for corner in obb.vertices(Mode::Local).unwrap() {
+                assert!(union.contains_point(corner));
+            }
+        }
+    }
+
+    #[test]
+    fn interpolation_endpoints_and_midpoint() {
+        let a = OrientedBoundingBox {
+            center: [0.0, 0.0, 0.0],
+            half_size: [1.0, 1.0, 1.0],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        };
+        // 90 degrees about z.
+        let half = std::f64::consts::FRAC_PI_4;
+        let b = OrientedBoundingBox {
+            center: [10.0, 0.0, 0.0],
+            half_size: [3.0, 1.0, 1.0],
+            quaternion: [0.0, 0.0, half.sin(), half.cos()],
+        };
+        assert_eq!(a.interpolate(&b, 0.0), a);
+        assert_eq!(a.interpolate(&b, 1.0), b);
+
+        let mid = a.interpolate(&b, 0.5);
+        assert_eq!(mid.center, [5.0, 0.0, 0.0]);
+        assert_eq!(mid.half_size, [2.0, 1.0, 1.0]);
+        // Halfway to 90 degrees is 45 degrees about z.
+        let quarter = std::f64::consts::FRAC_PI_8;
+        assert!((mid.quaternion[2] - quarter.sin()).abs() < 1e-9);
+        assert!((mid.quaternion[3] - quarter.cos()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cross_fade_band() {
+        let below = cross_fade(5.0, 10.0, 4.0);
+        assert_eq!(below.parent_weight, 1.0);
+        let above = cross_fade(15.0, 10.0, 4.0);
+        assert_eq!(above.child_weight, 1.0);
+        let mid = cross_fade(10.0, 10.0, 4.0);
+        assert!((mid.child_weight - 0.5).abs() < 1e-12);
+        assert!((mid.parent_weight + mid.child_weight - 1.0).abs() < 1e-12);
+
+        let hard = cross_fade(10.0, 10.0, 0.0);
+        assert_eq!(hard.child_weight, 1.0);
+    }
+}
@@ -0,0 +1,138 @@
+//! Bounding-volume-only layer overviews.
+//!
+//! For 2D coverage maps and tile debugging it is enough to know where the
+//! nodes of a given LOD level sit; none of the geometry needs to be decoded.
+//! [`level_bounds`] collects just the OBBs of one tree level, and the
+//! conversion helpers turn them into simple box meshes or GeoJSON polygons.
+
+use serde_json::{json, Value};
+
+use crate::err::Result;
+use crate::node::NodeArray;
+use crate::obb::{Mode, OrientedBoundingBox};
+
+/// The bounding volume of one node at the requested level.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeBounds {
+    pub node_index: usize,
+    pub obb: OrientedBoundingBox,
+}
+
+/// A node OBB expanded into a renderable box mesh.
+#[derive(Debug, Clone)]
+pub struct BoxMesh {
+    /// The eight box corners in layer coordinates.
+    pub positions: [[f64; 3]; 8],
+    /// Triangle list over [`Self::positions`], two triangles per face.
+    pub indices: [u16; 36],
+}
+
+/// Triangle indices for the corner ordering produced by
+/// [`OrientedBoundingBox::vertices`].
+const BOX_INDICES: [u16; 36] = [
+    0, 1, 3, 0, 3, 2, // -z
+    4, 6, 7, 4, 7, 5, // +z
+    0, 4, 5, 0, 5, 1, // -y
+    2, 3, 7, 2, 7, 6, // +y
+    0, 2, 6, 0, 6, 4, // -x
+    1, 5, 7, 1, 7, 3, // +x
+];
+
+/// Collect the bounding volumes of every node `level` steps below the root.
+///
+/// Leaves shallower than `level` are included, so the result always covers
+/// the full layer extent. Only node pages are fetched; no geometry is read.
+pub fn level_bounds(nodes: &mut NodeArray, level: usize) -> Result<Vec<NodeBounds>> {
+    let mut out = Vec::new();
+    let mut current = vec![nodes.root()?];
+    for _ in 0..level {
+        let mut next = Vec::new();
+        for node in current {
+            if node.is_leaf() {
+                out.push(NodeBounds {
+                    node_index: node.index,
+                    obb: node.obb,
+                });
+            } else {
+                next.extend(nodes.get_children(&node)?);
+            }
+        }
+        current = next;
+    }
+    for node in current {
+        out.push(NodeBounds {
+            node_index: node.index,
+            obb: node.obb,
+        });
+    }
+    Ok(out)
+}
+
+/// Expand an OBB into a box mesh for overview rendering.
+pub fn box_mesh(obb: &OrientedBoundingBox, mode: Mode) -> Result<BoxMesh> {
+    Ok(BoxMesh {
+        positions: obb.vertices(mode)?,
+        indices: BOX_INDICES,
+    })
+}
+
+/// Build a GeoJSON `FeatureCollection` of the axis-aligned footprints of the
+/// given bounds, one polygon feature per node.
+pub fn footprints_geojson(bounds: &[NodeBounds], mode: Mode) -> Result<Value> {
+    let mut features = Vec::with_capacity(bounds.len());
+    for b in bounds {
+        let corners = b.obb.vertices(mode)?;
+        let (mut xmin, mut ymin) = (f64::INFINITY, f64::INFINITY);
+        let (mut xmax, mut ymax) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for c in corners {
+            xmin = xmin.min(c[0]);
+            ymin = ymin.min(c[1]);
+            xmax = xmax.max(c[0]);
+            ymax = ymax.max(c[1]);
+        }
+        features.push(json!({
+            "type": "Feature",
+            "properties": { "nodeIndex": b.node_index },
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[
+                    [xmin, ymin], [xmax, ymin], [xmax, ymax], [xmin, ymax], [xmin, ymin]
+                ]]
+            }
+        }));
+    }
+    Ok(json!({ "type": "FeatureCollection", "features": features }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn footprint_covers_obb() {
+        let bounds = [NodeBounds {
+            node_index: 0,
+            obb: OrientedBoundingBox {
+                center: [5.0, 5.0, 0.0],
+                half_size: [1.0, 2.0, 3.0],
+                quaternion: [0.0, 0.0, 0.0, 1.0],
+            },
+        }];
+        let geojson = footprints_geojson(&bounds, Mode::Local).unwrap();
+        let ring = &geojson["features"][0]["geometry"]["coordinates"][0];
+        assert_eq!(ring[0][0], 4.0);
+        assert_eq!(ring[2][1], 7.0);
+    }
+
+    #[test]
+    fn box_mesh_has_36_indices() {
+        let obb = OrientedBoundingBox {
+            center: [0.0; 3],
+            half_size: [1.0; 3],
+            quaternion: [0.0, 0.0, 0.0, 1.0],
+        };
+        let mesh = box_mesh(&obb, Mode::Local).unwrap();
+        assert_eq!(mesh.indices.len(), 36);
+        assert!(mesh.indices.iter().all(|&i| i < 8));
+    }
+}
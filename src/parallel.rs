@@ -0,0 +1,186 @@
+//! Parallel traversal and bulk geometry decode (feature `rayon`).
+//!
+//! [`NodeArray::traverse`](crate::node::NodeArray::traverse) fetches node
+//! pages one at a time and visits nodes on the calling thread; on a
+//! 100k-node package that serializes both the I/O and the JSON parsing.
+//! [`traverse_par`] walks the tree level by level instead, fetching each
+//! level's missing node pages across the rayon pool — they land in the
+//! layer's shared page cache, so later sequential walks are warm too —
+//! and [`decode_nodes_par`] fans a batch of geometry decodes out over the
+//! same pool.
+
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+use crate::decode::{DecodedGeometry, ResourceDecoder};
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::node::{Node, NodeArray, TraversalControl};
+
+/// Visit every node of the layer, fetching node pages and running the
+/// callback across the rayon pool. Returns the number of nodes visited.
+///
+/// The tree is walked breadth-first one level at a time: the whole level
+/// is visited in parallel, then the node pages its children live on are
+/// fetched in parallel, then the next level starts. Within a level the
+/// visit order is unspecified, so the callback must be `Sync` and any
+/// state it collects must tolerate concurrent writes.
+///
+/// [`TraversalControl`] steers the walk as in the sequential API, with
+/// one caveat: `Stop` cannot preempt callbacks already running, so the
+/// current level is always visited to completion before the walk ends.
+pub fn traverse_par<F>(layer: &SceneLayer, callback: F) -> Result<usize>
+where
+    F: Fn(&Arc<Node>) -> TraversalControl + Send + Sync,
+{
+    traverse_par_with(&layer.nodes()?, callback)
+}
+
+/// [`traverse_par`] over a caller-owned [`NodeArray`] view.
+pub fn traverse_par_with<F>(nodes: &NodeArray, callback: F) -> Result<usize>
+where
+    F: Fn(&Arc<Node>) -> TraversalControl + Send + Sync,
+{
+    let mut frontier = vec![nodes.root()?];
+    let mut visited = 0usize;
+    while !frontier.is_empty() {
+        visited += frontier.len();
+        let controls: Vec<TraversalControl> = frontier.par_iter().map(&callback).collect();
+        if controls.contains(&TraversalControl::Stop) {
+            return Ok(visited);
+        }
+
+        let mut child_indices = Vec::new();
+        for (node, control) in frontier.iter().zip(&controls) {
+            if *control == TraversalControl::Continue {
+                child_indices.extend_from_slice(&node.children);
+            }
+        }
+
+        // One fetch per distinct page; already-cached pages return
+        // immediately, so no cached-or-not bookkeeping is needed here.
+        let mut pages = Vec::new();
+        for &child in &child_indices {
+            let page_index = nodes.page_definition().page_index(child)?;
+            if !pages.contains(&page_index) {
+                pages.push(page_index);
+            }
+        }
+        pages
+            .par_iter()
+            .try_for_each(|&page_index| nodes.get_node_page(page_index).map(|_| ()))?;
+
+        frontier = child_indices
+            .iter()
+            .map(|&child| nodes.get(child))
+            .collect::<Result<Vec<_>>>()?;
+    }
+    Ok(visited)
+}
+
+/// Decode the geometries of a batch of nodes across the rayon pool.
+///
+/// Returns `(node index, geometry)` pairs in the input order; nodes
+/// without a geometry resource are skipped. The first decode or fetch
+/// error aborts the batch.
+pub fn decode_nodes_par(
+    layer: &SceneLayer,
+    nodes: &[Arc<Node>],
+) -> Result<Vec<(usize, DecodedGeometry)>> {
+    decode_nodes_par_with(layer, nodes, &ResourceDecoder::new(layer.profile()))
+}
+
+/// [`decode_nodes_par`] with a caller-configured decoder (e.g. one
+/// carrying a [`VertexTransform`](crate::decode::VertexTransform)).
+pub fn decode_nodes_par_with(
+    layer: &SceneLayer,
+    nodes: &[Arc<Node>],
+    decoder: &ResourceDecoder,
+) -> Result<Vec<(usize, DecodedGeometry)>> {
+    let decoded = nodes
+        .par_iter()
+        .map(|node| {
+            let geometry = layer.node_geometry_with(node, decoder)?;
+            Ok(geometry.map(|geometry| (node.index, geometry)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(decoded.into_iter().flatten().collect())
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::testutil::{write_pyramid_layer, TestDir};
+
+    #[test]
+    fn parallel_traversal_matches_sequential_and_prunes() {
+        let dir = TestDir::new("parallel");
+        let path = dir.join("traverse.slpk");
+        write_pyramid_layer(&path, false);
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let mut sequential = Vec::new();
+        layer
+            .nodes()
+            .unwrap()
+            .traverse(|node| {
+                sequential.push(node.index);
+                TraversalControl::Continue
+            })
+            .unwrap();
+
+        let seen = std::sync::Mutex::new(Vec::new());
+        let visited = traverse_par(&layer, |node| {
+            seen.lock().unwrap().push(node.index);
+            TraversalControl::Continue
+        })
+        .unwrap();
+        let mut seen = seen.into_inner().unwrap();
+        seen.sort_unstable();
+        let mut expected = sequential.clone();
+        expected.sort_unstable();
+        assert_eq!(visited, 7);
+        assert_eq!(seen, expected);
+
+        // Pruning node 1 keeps its subtree (and its node pages) untouched.
+        let visited = traverse_par_with(&layer.nodes().unwrap(), |node| {
+            if node.index == 1 {
+                TraversalControl::SkipSubtree
+            } else {
+                TraversalControl::Continue
+            }
+        })
+        .unwrap();
+        assert_eq!(visited, 5);
+
+        // Stop ends the walk once the current level has been visited.
+        let visited = traverse_par(&layer, |node| {
+            if node.index == 0 {
+                TraversalControl::Stop
+            } else {
+                TraversalControl::Continue
+            }
+        })
+        .unwrap();
+        assert_eq!(visited, 1);
+    }
+
+    #[test]
+    fn bulk_decode_returns_leaf_geometries_in_order() {
+        let dir = TestDir::new("parallel");
+        let path = dir.join("decode.slpk");
+        write_pyramid_layer(&path, false);
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let nodes = layer.nodes().unwrap();
+        let batch = nodes.get_many(&[0, 3, 4, 5, 6]).unwrap();
+        let decoded = decode_nodes_par(&layer, &batch).unwrap();
+        // Node 0 has no geometry and is skipped; the rest keep input order.
+        let indices: Vec<usize> = decoded.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, vec![3, 4, 5, 6]);
+        for (_, geometry) in &decoded {
+            assert_eq!(geometry.positions.len(), 9);
+        }
+    }
+}
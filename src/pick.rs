@@ -0,0 +1,262 @@
+//! Ray picking against a layer's node tree.
+//!
+//! [`SceneLayer::pick`] descends the tree, culling subtrees whose OBBs
+//! the ray misses, decodes the geometry of candidate leaves and
+//! intersects every triangle, keeping the closest hit. Coordinates are
+//! the layer's local Cartesian CRS; mesh positions are node-relative and
+//! are shifted by the node's OBB center before testing.
+
+use crate::decode::{DecodedGeometry, ResourceDecoder};
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::obb::Ray;
+
+/// The closest intersection found by [`SceneLayer::pick`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickHit {
+    pub node_index: usize,
+    /// Triangle index within the node's geometry.
+    pub triangle: usize,
+    /// The feature the triangle belongs to, resolved through the
+    /// geometry's `faceRange` data when present.
+    pub feature_id: Option<u64>,
+    /// Distance along the ray, in units of its direction length.
+    pub distance: f64,
+    /// The hit position in layer coordinates.
+    pub point: [f64; 3],
+}
+
+impl SceneLayer {
+    /// Cast `ray` through the node tree and return the closest triangle
+    /// hit among leaf geometries, or `None` when nothing is struck.
+    ///
+    /// Subtrees are pruned by OBB ray tests and by the best hit found so
+    /// far, so only leaves the ray can actually improve on are decoded.
+    pub fn pick(&self, ray: &Ray) -> Result<Option<PickHit>> {
+        let decoder = ResourceDecoder::new(self.profile());
+        let nodes = self.nodes()?;
+        let mut best: Option<PickHit> = None;
+        let mut stack = vec![nodes.root()?.index];
+        while let Some(index) = stack.pop() {
+            let node = nodes.get(index)?;
+            let Some(entry) = node.obb.ray_intersection(ray) else {
+                continue;
+            };
+            if let Some(hit) = &best {
+                if entry > hit.distance {
+                    continue;
+                }
+            }
+            if !node.is_leaf() {
+                stack.extend(node.children.iter().copied());
+                continue;
+            }
+            let Some(geometry) = self.node_geometry_with(&node, &decoder)? else {
+                continue;
+            };
+            if let Some(hit) = closest_triangle_hit(&geometry, node.obb.center, ray, node.index) {
+                if best.as_ref().is_none_or(|b| hit.distance < b.distance) {
+                    best = Some(hit);
+                }
+            }
+        }
+        Ok(best)
+    }
+}
+
+fn closest_triangle_hit(
+    geometry: &DecodedGeometry,
+    center: [f64; 3],
+    ray: &Ray,
+    node_index: usize,
+) -> Option<PickHit> {
+    let mut best: Option<PickHit> = None;
+    for triangle in 0..geometry.positions.len() / 9 {
+        let corner = |i: usize| {
+            let base = (triangle * 3 + i) * 3;
+            [
+                f64::from(geometry.positions[base]) + center[0],
+                f64::from(geometry.positions[base + 1]) + center[1],
+                f64::from(geometry.positions[base + 2]) + center[2],
+            ]
+        };
+        let Some(distance) = ray_triangle(ray, corner(0), corner(1), corner(2)) else {
+            continue;
+        };
+        if best.as_ref().is_some_and(|b| distance >= b.distance) {
+            continue;
+        }
+        best = Some(PickHit {
+            node_index,
+            triangle,
+            feature_id: feature_of_triangle(geometry, triangle),
+            distance,
+            point: [
+                ray.origin[0] + distance * ray.direction[0],
+                ray.origin[1] + distance * ray.direction[1],
+                ray.origin[2] + distance * ray.direction[2],
+            ],
+        });
+    }
+    best
+}
+
+/// Resolve a triangle to its feature through the interleaved first/last
+/// `faceRange` pairs.
+fn feature_of_triangle(geometry: &DecodedGeometry, triangle: usize) -> Option<u64> {
+    geometry
+        .face_ranges
+        .chunks_exact(2)
+        .position(|range| {
+            (range[0] as usize..=range[1] as usize).contains(&triangle)
+        })
+        .and_then(|feature| geometry.feature_ids.get(feature).copied())
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Möller–Trumbore, double-sided; `Some(t)` for forward hits.
+fn ray_triangle(ray: &Ray, a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> Option<f64> {
+    let e1 = sub(b, a);
+    let e2 = sub(c, a);
+    let p = cross(ray.direction, e2);
+    let det = dot(e1, p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv = 1.0 / det;
+    let s = sub(ray.origin, a);
+    let u = dot(s, p) * inv;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = cross(s, e1);
+    let v = dot(ray.direction, q) * inv;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = dot(e2, q) * inv;
+    (t >= 0.0).then_some(t)
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::slpk::writer::SlpkWriter;
+
+    fn triangle_bytes(vertices: &[f32]) -> Vec<u8> {
+        vertices.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn picking_returns_the_closest_feature() {
+        let dir = crate::testutil::TestDir::new("pick");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 8 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 },
+                    "featureId": { "type": "UInt64", "component": 1, "binding": "per-feature" },
+                    "faceRange": { "type": "UInt32", "component": 2, "binding": "per-feature" }
+                }]
+            }]
+        }))
+        .unwrap();
+        let obb = |center: [f64; 3]| {
+            serde_json::json!({
+                "center": center,
+                "halfSize": [2.0, 2.0, 2.0],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            })
+        };
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "obb": obb([0.0, 0.0, 5.0]), "children": [1, 2] },
+                {
+                    "index": 1, "obb": obb([0.0, 0.0, 2.0]), "parentIndex": 0,
+                    "mesh": { "geometry": {
+                        "definition": 0, "resource": 1, "vertexCount": 6, "featureCount": 2
+                    } }
+                },
+                {
+                    "index": 2, "obb": obb([0.0, 0.0, 9.0]), "parentIndex": 0,
+                    "mesh": { "geometry": {
+                        "definition": 0, "resource": 2, "vertexCount": 3, "featureCount": 1
+                    } }
+                }
+            ]
+        }))
+        .unwrap();
+
+        // Node 1: two stacked triangles facing -z, node-relative to
+        // center z = 2, so world planes z = 2 and z = 3.
+        let near = [
+            -1.0, -1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0, // feature 10
+            -1.0, -1.0, 1.0, 1.0, -1.0, 1.0, 0.0, 1.0, 1.0, // feature 20
+        ];
+        let mut bytes = triangle_bytes(&near);
+        for feature in [10u64, 20] {
+            bytes.extend_from_slice(&feature.to_le_bytes());
+        }
+        for range in [[0u32, 0], [1, 1]] {
+            bytes.extend_from_slice(&range[0].to_le_bytes());
+            bytes.extend_from_slice(&range[1].to_le_bytes());
+        }
+        // Node 2: one triangle further along, world plane z = 9.
+        let far = [-1.0, -1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0];
+        let mut far_bytes = triangle_bytes(&far);
+        far_bytes.extend_from_slice(&30u64.to_le_bytes());
+        for value in [0u32, 0] {
+            far_bytes.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(1, 1, &bytes).unwrap();
+        writer.write_geometry(2, 2, &far_bytes).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let hit = layer
+            .pick(&Ray {
+                origin: [0.0, 0.0, 0.0],
+                direction: [0.0, 0.0, 1.0],
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(hit.node_index, 1);
+        assert_eq!(hit.triangle, 0);
+        assert_eq!(hit.feature_id, Some(10));
+        assert!((hit.distance - 2.0).abs() < 1e-9);
+        assert!((hit.point[2] - 2.0).abs() < 1e-9);
+
+        // A ray that misses every OBB picks nothing.
+        let miss = layer
+            .pick(&Ray {
+                origin: [50.0, 0.0, 0.0],
+                direction: [0.0, 0.0, 1.0],
+            })
+            .unwrap();
+        assert!(miss.is_none());
+    }
+}
@@ -0,0 +1,510 @@
+//! Point cloud scene layer (PCSL) node decoding.
+//!
+//! PCSL nodes store one resource per attribute: positions as a LEPCC-XYZ
+//! blob, colors as a ClusterRGB blob, intensity as a LEPCC intensity blob,
+//! plus plain typed arrays for class codes and flags. Every LEPCC blob starts
+//! with a 10-byte identifier key that this module uses for dispatch, followed
+//! by the common 18-byte header parsed by [`lepcc_header`].
+//!
+//! After the header each blob carries its payload:
+//!
+//! * **LEPCC-XYZ** — point count, origin and per-axis scale, then one
+//!   bit-packed stream of quantized grid offsets per axis. A position decodes
+//!   as `origin + offset * scale`, so the scale is the quantization
+//!   tolerance.
+//! * **ClusterRGB** — point count, then either an up-to-256-color palette
+//!   with a bit-packed index stream, or raw `r g b` triples when the node
+//!   has more distinct colors than a palette can hold.
+//! * **Intensity** — point count, then one bit-packed stream of the 16-bit
+//!   values.
+//! * **FlagBytes** — point count, then either a single byte shared by every
+//!   point or the raw bytes.
+//!
+//! Bit-packed streams store a bits-per-value byte followed by the values
+//! packed least-significant-bit first. The `encode_*` functions write the
+//! same layout; they exist for building test layers and for repacking tools,
+//! and round-trip exactly except for positions, which quantize to the given
+//! scale.
+
+use crate::err::{I3SError, Result};
+
+/// Points of a PCSL node decoded into typed arrays.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodedPoints {
+    pub count: usize,
+    /// Interleaved x/y/z positions in the layer CRS.
+    pub positions: Vec<f64>,
+    /// Interleaved r/g/b colors, one triple per point.
+    pub rgb: Vec<u8>,
+    pub intensity: Vec<u16>,
+    pub class_codes: Vec<u8>,
+    pub flags: Vec<u8>,
+}
+
+/// The kind of a LEPCC blob, from its 10-byte identifier key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LepccBlobType {
+    Xyz,
+    Rgb,
+    Intensity,
+    FlagBytes,
+}
+
+const KEY_XYZ: &[u8; 10] = b"LEPCC     ";
+const KEY_RGB: &[u8; 10] = b"ClusterRGB";
+const KEY_INTENSITY: &[u8; 10] = b"Intensity ";
+const KEY_FLAG_BYTES: &[u8; 10] = b"FlagBytes ";
+
+/// Size of the common header in front of every LEPCC payload.
+const HEADER_SIZE: usize = 18;
+
+/// Identify a LEPCC blob from its identifier key, if it has one.
+pub fn lepcc_blob_type(bytes: &[u8]) -> Option<LepccBlobType> {
+    let key: &[u8; 10] = bytes.get(..10)?.try_into().ok()?;
+    match key {
+        KEY_XYZ => Some(LepccBlobType::Xyz),
+        KEY_RGB => Some(LepccBlobType::Rgb),
+        KEY_INTENSITY => Some(LepccBlobType::Intensity),
+        KEY_FLAG_BYTES => Some(LepccBlobType::FlagBytes),
+        _ => None,
+    }
+}
+
+/// The common header of a LEPCC blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LepccHeader {
+    pub blob_type: LepccBlobType,
+    pub version: u16,
+    /// Total blob size in bytes as declared by the header.
+    pub blob_size: u32,
+}
+
+/// Parse the common header of a LEPCC blob.
+pub fn lepcc_header(bytes: &[u8]) -> Result<LepccHeader> {
+    let blob_type = lepcc_blob_type(bytes)
+        .ok_or_else(|| I3SError::Decode("buffer is not a lepcc blob".to_string()))?;
+    if bytes.len() < HEADER_SIZE {
+        return Err(I3SError::Decode("lepcc header truncated".to_string()));
+    }
+    let version = u16::from_le_bytes([bytes[10], bytes[11]]);
+    // Bytes 12..14 are the checksum; 14..18 the declared blob size in v1.
+    let blob_size = u32::from_le_bytes([bytes[14], bytes[15], bytes[16], bytes[17]]);
+    Ok(LepccHeader {
+        blob_type,
+        version,
+        blob_size,
+    })
+}
+
+/// Check the header and hand back a cursor over the payload of `wanted`.
+fn lepcc_payload(bytes: &[u8], wanted: LepccBlobType) -> Result<Cursor<'_>> {
+    let header = lepcc_header(bytes)?;
+    if header.blob_type != wanted {
+        return Err(I3SError::Decode(format!(
+            "expected a {wanted:?} blob, found {:?}",
+            header.blob_type
+        )));
+    }
+    if header.version != 1 {
+        return Err(I3SError::Decode(format!(
+            "unsupported lepcc version {} for {wanted:?} blob",
+            header.version
+        )));
+    }
+    if bytes.len() < header.blob_size as usize {
+        return Err(I3SError::Decode(format!(
+            "{wanted:?} blob truncated: header declares {} bytes, have {}",
+            header.blob_size,
+            bytes.len()
+        )));
+    }
+    Ok(Cursor {
+        bytes,
+        offset: HEADER_SIZE,
+    })
+}
+
+/// Check a decoded point count against what the node declares.
+fn check_count(blob_type: LepccBlobType, count: usize, point_count: usize) -> Result<()> {
+    if count == point_count {
+        Ok(())
+    } else {
+        Err(I3SError::Decode(format!(
+            "{blob_type:?} blob holds {count} points, node declares {point_count}"
+        )))
+    }
+}
+
+/// Decode a PCSL position resource into interleaved x/y/z doubles.
+///
+/// Uncompressed buffers are `Float64` triples; LEPCC-XYZ blobs are detected
+/// by key and decompressed.
+pub fn decode_positions(bytes: &[u8], point_count: usize) -> Result<Vec<f64>> {
+    if lepcc_blob_type(bytes).is_some() {
+        let mut cursor = lepcc_payload(bytes, LepccBlobType::Xyz)?;
+        let count = cursor.read_u32()? as usize;
+        check_count(LepccBlobType::Xyz, count, point_count)?;
+        let origin = [cursor.read_f64()?, cursor.read_f64()?, cursor.read_f64()?];
+        let scale = [cursor.read_f64()?, cursor.read_f64()?, cursor.read_f64()?];
+        let mut positions = vec![0.0; count * 3];
+        for axis in 0..3 {
+            let offsets = cursor.read_packed(count)?;
+            for (point, offset) in offsets.into_iter().enumerate() {
+                positions[point * 3 + axis] = origin[axis] + f64::from(offset) * scale[axis];
+            }
+        }
+        return Ok(positions);
+    }
+    let expected = point_count * 3 * 8;
+    if bytes.len() < expected {
+        return Err(I3SError::Decode(format!(
+            "position buffer too short: need {expected}, have {}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes[..expected]
+        .chunks_exact(8)
+        .map(|c| f64::from_le_bytes(c.try_into().unwrap()))
+        .collect())
+}
+
+/// Decode a PCSL color resource into interleaved r/g/b bytes.
+pub fn decode_rgb(bytes: &[u8], point_count: usize) -> Result<Vec<u8>> {
+    if lepcc_blob_type(bytes).is_some() {
+        let mut cursor = lepcc_payload(bytes, LepccBlobType::Rgb)?;
+        let count = cursor.read_u32()? as usize;
+        check_count(LepccBlobType::Rgb, count, point_count)?;
+        let palette_len = u16::from_le_bytes(cursor.read_array()?) as usize;
+        if palette_len == 0 {
+            return Ok(cursor.read_slice(count * 3)?.to_vec());
+        }
+        let palette = cursor.read_slice(palette_len * 3)?.to_vec();
+        let mut rgb = Vec::with_capacity(count * 3);
+        for index in cursor.read_packed(count)? {
+            let start = index as usize * 3;
+            let color = palette.get(start..start + 3).ok_or_else(|| {
+                I3SError::Decode(format!(
+                    "rgb index {index} outside the {palette_len}-color palette"
+                ))
+            })?;
+            rgb.extend_from_slice(color);
+        }
+        return Ok(rgb);
+    }
+    let expected = point_count * 3;
+    if bytes.len() < expected {
+        return Err(I3SError::Decode(format!(
+            "rgb buffer too short: need {expected}, have {}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes[..expected].to_vec())
+}
+
+/// Decode a PCSL intensity resource.
+pub fn decode_intensity(bytes: &[u8], point_count: usize) -> Result<Vec<u16>> {
+    if lepcc_blob_type(bytes).is_some() {
+        let mut cursor = lepcc_payload(bytes, LepccBlobType::Intensity)?;
+        let count = cursor.read_u32()? as usize;
+        check_count(LepccBlobType::Intensity, count, point_count)?;
+        let values = cursor.read_packed(count)?;
+        return values
+            .into_iter()
+            .map(|v| {
+                u16::try_from(v).map_err(|_| {
+                    I3SError::Decode(format!("intensity value {v} exceeds 16 bits"))
+                })
+            })
+            .collect();
+    }
+    let expected = point_count * 2;
+    if bytes.len() < expected {
+        return Err(I3SError::Decode(format!(
+            "intensity buffer too short: need {expected}, have {}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes[..expected]
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect())
+}
+
+/// Decode a plain one-byte-per-point resource (class codes, flags).
+pub fn decode_bytes(bytes: &[u8], point_count: usize) -> Result<Vec<u8>> {
+    if lepcc_blob_type(bytes).is_some() {
+        let mut cursor = lepcc_payload(bytes, LepccBlobType::FlagBytes)?;
+        let count = cursor.read_u32()? as usize;
+        check_count(LepccBlobType::FlagBytes, count, point_count)?;
+        return match cursor.read_array::<1>()?[0] {
+            0 => Ok(vec![cursor.read_array::<1>()?[0]; count]),
+            1 => Ok(cursor.read_slice(count)?.to_vec()),
+            mode => Err(I3SError::Decode(format!(
+                "unknown FlagBytes mode {mode}"
+            ))),
+        };
+    }
+    if bytes.len() < point_count {
+        return Err(I3SError::Decode(format!(
+            "buffer too short: need {point_count}, have {}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes[..point_count].to_vec())
+}
+
+/// Encode interleaved x/y/z doubles as a LEPCC-XYZ blob.
+///
+/// Positions are quantized to a grid of `scale` cells anchored at the
+/// per-axis minimum, so decoded values land within half a cell of the
+/// input. Fails if an axis spans more than `u32::MAX` cells.
+pub fn encode_positions(positions: &[f64], scale: [f64; 3]) -> Result<Vec<u8>> {
+    let count = positions.len() / 3;
+    let mut origin = [f64::INFINITY; 3];
+    for point in positions.chunks_exact(3) {
+        for axis in 0..3 {
+            origin[axis] = origin[axis].min(point[axis]);
+        }
+    }
+    if count == 0 {
+        origin = [0.0; 3];
+    }
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(count as u32).to_le_bytes());
+    for value in origin.iter().chain(&scale) {
+        payload.extend_from_slice(&value.to_le_bytes());
+    }
+    for axis in 0..3 {
+        let mut offsets = Vec::with_capacity(count);
+        for point in positions.chunks_exact(3) {
+            let cells = ((point[axis] - origin[axis]) / scale[axis]).round();
+            if !(0.0..=f64::from(u32::MAX)).contains(&cells) {
+                return Err(I3SError::Decode(format!(
+                    "axis {axis} spans more than a 32-bit grid at scale {}",
+                    scale[axis]
+                )));
+            }
+            offsets.push(cells as u32);
+        }
+        pack_values(&offsets, &mut payload);
+    }
+    Ok(lepcc_blob(KEY_XYZ, &payload))
+}
+
+/// Encode interleaved r/g/b bytes as a ClusterRGB blob.
+///
+/// Colors are palettized when at most 256 distinct ones occur, raw
+/// otherwise.
+pub fn encode_rgb(rgb: &[u8]) -> Vec<u8> {
+    let count = rgb.len() / 3;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut indices = Vec::with_capacity(count);
+    for color in rgb.chunks_exact(3) {
+        let color: [u8; 3] = color.try_into().expect("chunks_exact");
+        let index = palette.iter().position(|c| *c == color).unwrap_or_else(|| {
+            palette.push(color);
+            palette.len() - 1
+        });
+        indices.push(index as u32);
+    }
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(count as u32).to_le_bytes());
+    if palette.len() > 256 {
+        payload.extend_from_slice(&0u16.to_le_bytes());
+        payload.extend_from_slice(&rgb[..count * 3]);
+    } else {
+        payload.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+        for color in &palette {
+            payload.extend_from_slice(color);
+        }
+        pack_values(&indices, &mut payload);
+    }
+    lepcc_blob(KEY_RGB, &payload)
+}
+
+/// Encode 16-bit intensities as a LEPCC intensity blob.
+pub fn encode_intensity(values: &[u16]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    let wide: Vec<u32> = values.iter().map(|&v| u32::from(v)).collect();
+    pack_values(&wide, &mut payload);
+    lepcc_blob(KEY_INTENSITY, &payload)
+}
+
+/// Encode one-byte-per-point flags as a FlagBytes blob.
+pub fn encode_flags(flags: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(flags.len() as u32).to_le_bytes());
+    match flags {
+        [first, rest @ ..] if rest.iter().all(|b| b == first) => {
+            payload.push(0);
+            payload.push(*first);
+        }
+        _ => {
+            payload.push(1);
+            payload.extend_from_slice(flags);
+        }
+    }
+    lepcc_blob(KEY_FLAG_BYTES, &payload)
+}
+
+/// Prefix `payload` with the common header for `key`.
+fn lepcc_blob(key: &[u8; 10], payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_SIZE + payload.len());
+    out.extend_from_slice(key);
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&((HEADER_SIZE + payload.len()) as u32).to_le_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Append a bit-packed stream: the widest value's bit count, then every
+/// value packed least-significant-bit first.
+fn pack_values(values: &[u32], out: &mut Vec<u8>) {
+    let bits = values
+        .iter()
+        .map(|v| 32 - v.leading_zeros() as usize)
+        .max()
+        .unwrap_or(0);
+    out.push(bits as u8);
+    let start = out.len();
+    out.resize(start + (values.len() * bits).div_ceil(8), 0);
+    for (index, value) in values.iter().enumerate() {
+        for bit in 0..bits {
+            if value >> bit & 1 == 1 {
+                let position = index * bits + bit;
+                out[start + position / 8] |= 1 << (position % 8);
+            }
+        }
+    }
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Cursor<'_> {
+    fn read_slice(&mut self, len: usize) -> Result<&[u8]> {
+        let end = self.offset.checked_add(len).filter(|&e| e <= self.bytes.len());
+        let end = end.ok_or_else(|| {
+            I3SError::Decode(format!("lepcc blob truncated at byte {}", self.offset))
+        })?;
+        let out = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(out)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self
+            .read_slice(N)?
+            .try_into()
+            .expect("slice length checked"))
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_f64(&mut self) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.read_array()?))
+    }
+
+    /// Read a bit-packed stream of `count` values.
+    fn read_packed(&mut self, count: usize) -> Result<Vec<u32>> {
+        let bits = self.read_array::<1>()?[0] as usize;
+        if bits > 32 {
+            return Err(I3SError::Decode(format!(
+                "bit-packed stream declares {bits} bits per value"
+            )));
+        }
+        let packed = self.read_slice((count * bits).div_ceil(8))?;
+        let mut values = Vec::with_capacity(count);
+        for index in 0..count {
+            let mut value = 0u32;
+            for bit in 0..bits {
+                let position = index * bits + bit;
+                if packed[position / 8] >> (position % 8) & 1 == 1 {
+                    value |= 1 << bit;
+                }
+            }
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifies_lepcc_blobs() {
+        let mut blob = Vec::new();
+        blob.extend_from_slice(KEY_XYZ);
+        blob.extend_from_slice(&1u16.to_le_bytes());
+        blob.extend_from_slice(&0u16.to_le_bytes());
+        blob.extend_from_slice(&64u32.to_le_bytes());
+        assert_eq!(lepcc_blob_type(&blob), Some(LepccBlobType::Xyz));
+        let header = lepcc_header(&blob).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.blob_type, LepccBlobType::Xyz);
+    }
+
+    #[test]
+    fn decodes_uncompressed_positions() {
+        let points = [1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let bytes: Vec<u8> = points.iter().flat_map(|p| p.to_le_bytes()).collect();
+        assert_eq!(decode_positions(&bytes, 2).unwrap(), points);
+    }
+
+    #[test]
+    fn lepcc_positions_round_trip_within_the_scale() {
+        let points = [100.25, -20.5, 3.125, 101.75, -19.0, 3.0, 100.0, -21.0, 2.875];
+        let scale = [0.01, 0.01, 0.01];
+        let blob = encode_positions(&points, scale).unwrap();
+        assert_eq!(lepcc_blob_type(&blob), Some(LepccBlobType::Xyz));
+        let decoded = decode_positions(&blob, 3).unwrap();
+        for (got, want) in decoded.iter().zip(&points) {
+            assert!((got - want).abs() <= 0.005, "{got} vs {want}");
+        }
+    }
+
+    #[test]
+    fn cluster_rgb_round_trips_palettized_and_raw() {
+        let few: Vec<u8> = [[10, 20, 30], [40, 50, 60], [10, 20, 30]].concat();
+        assert_eq!(decode_rgb(&encode_rgb(&few), 3).unwrap(), few);
+
+        // 512 distinct colors overflow the palette and fall back to raw.
+        let many: Vec<u8> = (0..512u32)
+            .flat_map(|c| [(c % 256) as u8, (c / 256) as u8, 7])
+            .collect();
+        assert_eq!(decode_rgb(&encode_rgb(&many), 512).unwrap(), many);
+    }
+
+    #[test]
+    fn intensity_and_flags_round_trip() {
+        let intensity = [0u16, 1, 999, 65535];
+        let blob = encode_intensity(&intensity);
+        assert_eq!(decode_intensity(&blob, 4).unwrap(), intensity);
+
+        let constant = [3u8; 5];
+        let blob = encode_flags(&constant);
+        // Constant runs collapse to a single stored byte.
+        assert_eq!(blob.len(), HEADER_SIZE + 4 + 2);
+        assert_eq!(decode_bytes(&blob, 5).unwrap(), constant);
+
+        let mixed = [1u8, 2, 3];
+        assert_eq!(decode_bytes(&encode_flags(&mixed), 3).unwrap(), mixed);
+    }
+
+    #[test]
+    fn count_mismatches_and_wrong_blob_types_are_rejected() {
+        let blob = encode_intensity(&[1, 2, 3]);
+        let err = decode_intensity(&blob, 4).unwrap_err();
+        assert!(err.to_string().contains("declares 4"));
+        let err = decode_positions(&blob, 3).unwrap_err();
+        assert!(err.to_string().contains("Intensity"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Background prefetching of node pages.
+//!
+//! A traversal that descends one node at a time pays a full round trip per
+//! node page, which dominates latency against a remote SceneServer. The
+//! [`Prefetcher`] owns a small pool of worker threads and a clone of the
+//! layer's [`NodeArray`] view; after visiting a node, hand it to
+//! [`prefetch_around`](Prefetcher::prefetch_around) and the pages holding
+//! its children and siblings are fetched in the background. Because every
+//! view shares the layer's page cache, the foreground traversal finds the
+//! pages already parsed when it gets there.
+//!
+//! The same pool works for SLPK archives (it parallelizes decompression)
+//! and REST services (it overlaps round trips). Pages already cached or
+//! already queued are skipped, so calling it on every visited node is
+//! cheap.
+
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+
+use crate::node::{Node, NodeArray};
+
+/// Pages queued or being fetched, shared between callers and workers.
+#[derive(Default)]
+struct InFlight {
+    pages: Mutex<HashSet<usize>>,
+    idle: Condvar,
+}
+
+/// Fetches node pages ahead of a traversal on background threads.
+///
+/// Dropping the prefetcher drains the queue and joins the workers; pages
+/// fetched so far stay in the shared cache. Fetch errors are swallowed —
+/// a failed prefetch simply leaves the page to the foreground request,
+/// which reports the error in context.
+pub struct Prefetcher {
+    nodes: NodeArray,
+    queue: Option<mpsc::Sender<usize>>,
+    workers: Vec<std::thread::JoinHandle<()>>,
+    in_flight: Arc<InFlight>,
+}
+
+impl Prefetcher {
+    /// Start `workers` background threads fetching through `nodes`.
+    pub fn new(nodes: NodeArray, workers: usize) -> Self {
+        let (queue, receiver) = mpsc::channel::<usize>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let in_flight = Arc::new(InFlight::default());
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let nodes = nodes.clone();
+                let receiver = Arc::clone(&receiver);
+                let in_flight = Arc::clone(&in_flight);
+                std::thread::spawn(move || loop {
+                    let page_index = {
+                        let receiver = receiver.lock().expect("prefetch lock poisoned");
+                        match receiver.recv() {
+                            Ok(page_index) => page_index,
+                            Err(_) => return,
+                        }
+                    };
+                    let _ = nodes.get_node_page(page_index);
+                    let mut pages = in_flight.pages.lock().expect("prefetch lock poisoned");
+                    pages.remove(&page_index);
+                    if pages.is_empty() {
+                        in_flight.idle.notify_all();
+                    }
+                })
+            })
+            .collect();
+        Self {
+            nodes,
+            queue: Some(queue),
+            workers,
+            in_flight,
+        }
+    }
+
+    /// Queue the pages holding `node`'s children and siblings.
+    ///
+    /// Returns the number of pages newly queued; pages already cached or
+    /// already in flight are not counted.
+    pub fn prefetch_around(&self, node: &Node) -> usize {
+        let mut wanted = Vec::new();
+        for &child in &node.children {
+            if let Ok(page) = self.nodes.page_definition().page_index(child) {
+                wanted.push(page);
+            }
+        }
+        // Siblings live next to the node, which the caller already has, so
+        // their pages are usually cached — but a node straddling a page
+        // boundary still benefits.
+        if let Some(parent) = node.parent_index {
+            if let Ok(parent) = self.nodes.get(parent) {
+                for &sibling in &parent.children {
+                    if let Ok(page) = self.nodes.page_definition().page_index(sibling) {
+                        wanted.push(page);
+                    }
+                }
+            }
+        }
+        self.prefetch_pages(wanted)
+    }
+
+    /// Queue explicit page indices; returns the number newly queued.
+    pub fn prefetch_pages(&self, pages: impl IntoIterator<Item = usize>) -> usize {
+        let Some(queue) = &self.queue else { return 0 };
+        let mut in_flight = self.in_flight.pages.lock().expect("prefetch lock poisoned");
+        let mut queued = 0;
+        for page_index in pages {
+            if self.nodes.page_cached(page_index) || !in_flight.insert(page_index) {
+                continue;
+            }
+            if queue.send(page_index).is_ok() {
+                queued += 1;
+            }
+        }
+        queued
+    }
+
+    /// Number of pages queued or currently being fetched.
+    pub fn pending(&self) -> usize {
+        self.in_flight.pages.lock().expect("prefetch lock poisoned").len()
+    }
+
+    /// Block until every queued page has been fetched.
+    pub fn wait_idle(&self) {
+        let mut pages = self.in_flight.pages.lock().expect("prefetch lock poisoned");
+        while !pages.is_empty() {
+            pages = self
+                .in_flight
+                .idle
+                .wait(pages)
+                .expect("prefetch lock poisoned");
+        }
+    }
+}
+
+impl Drop for Prefetcher {
+    fn drop(&mut self) {
+        // Closing the channel ends the worker loops once drained.
+        self.queue.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::testutil::{write_pyramid_layer, TestDir};
+
+    #[test]
+    fn prefetching_warms_the_shared_page_cache() {
+        let dir = TestDir::new("prefetch");
+        let path = dir.join("layer.slpk");
+        write_pyramid_layer(&path, false);
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let nodes = layer.nodes().unwrap();
+        let prefetcher = Prefetcher::new(nodes.clone(), 2);
+
+        // Visiting the root queues the pages of nodes 1 and 2; node 2's
+        // page is distinct from the root's.
+        let root = nodes.root().unwrap();
+        prefetcher.prefetch_around(&root);
+        prefetcher.wait_idle();
+        assert!(nodes.page_cached(1));
+
+        // Around node 1: children 3 and 4 span pages 1 and 2, and the
+        // sibling pass re-queues nothing new.
+        let node = nodes.get(1).unwrap();
+        prefetcher.prefetch_around(&node);
+        prefetcher.wait_idle();
+        assert!(nodes.page_cached(2));
+        assert_eq!(prefetcher.pending(), 0);
+
+        // Cached pages are not re-queued; the one uncached page is.
+        assert_eq!(prefetcher.prefetch_pages([0, 1, 2]), 0);
+        assert_eq!(prefetcher.prefetch_pages([3]), 1);
+        prefetcher.wait_idle();
+        assert!(nodes.page_cached(3));
+    }
+}
@@ -0,0 +1,145 @@
+//! Profile-specific layer facades.
+//!
+//! [`SceneLayer`] accepts any layer type, but most operations are only
+//! meaningful for particular profiles (feature queries need 3D Object,
+//! point access needs point clouds, ...). These wrappers are constructed
+//! from an open `SceneLayer` after checking its [`LayerType`], so that
+//! profile-specific code paths are checked once at construction instead of
+//! panicking later in a decoder.
+
+use std::sync::Arc;
+
+use crate::defn::{Extent, Field, LayerType, SceneDefinition};
+use crate::err::{I3SError, Result};
+use crate::layer::SceneLayer;
+use crate::node::{Node, NodeArray};
+
+fn expect_layer_type(layer: &SceneLayer, wanted: LayerType) -> Result<()> {
+    let got = layer.layer_type();
+    if got == wanted {
+        Ok(())
+    } else {
+        Err(I3SError::UnsupportedLayerType {
+            got,
+            expected: wanted,
+        })
+    }
+}
+
+macro_rules! common_accessors {
+    () => {
+        /// The parsed `3dSceneLayer` document.
+        pub fn definition(&self) -> &SceneDefinition {
+            self.layer.definition()
+        }
+
+        /// The layer name, if declared.
+        pub fn name(&self) -> Option<&str> {
+            self.layer.name()
+        }
+
+        /// The layer extent, if declared.
+        pub fn extent(&self) -> Option<Extent> {
+            self.layer.extent()
+        }
+
+        /// The underlying untyped layer.
+        pub fn as_scene_layer(&self) -> &SceneLayer {
+            &self.layer
+        }
+    };
+}
+
+/// An integrated mesh layer: one continuous textured mesh, no per-feature
+/// attributes.
+pub struct IntegratedMeshLayer {
+    layer: SceneLayer,
+}
+
+impl IntegratedMeshLayer {
+    /// Wrap a layer after checking it is an integrated mesh.
+    pub fn new(layer: SceneLayer) -> Result<Self> {
+        expect_layer_type(&layer, LayerType::IntegratedMesh)?;
+        Ok(Self { layer })
+    }
+
+    common_accessors!();
+
+    /// A fresh view over the node tree.
+    pub fn nodes(&self) -> Result<NodeArray> {
+        self.layer.nodes()
+    }
+
+    /// Fetch the root node.
+    pub fn root(&self) -> Result<Arc<Node>> {
+        self.layer.root()
+    }
+}
+
+/// A 3D Object layer: discrete features with attributes.
+pub struct ObjectLayer3D {
+    layer: SceneLayer,
+}
+
+impl ObjectLayer3D {
+    /// Wrap a layer after checking it is a 3D Object layer.
+    pub fn new(layer: SceneLayer) -> Result<Self> {
+        expect_layer_type(&layer, LayerType::Object3D)?;
+        Ok(Self { layer })
+    }
+
+    common_accessors!();
+
+    /// A fresh view over the node tree.
+    pub fn nodes(&self) -> Result<NodeArray> {
+        self.layer.nodes()
+    }
+
+    /// Fetch the root node.
+    pub fn root(&self) -> Result<Arc<Node>> {
+        self.layer.root()
+    }
+
+    /// The attribute fields of the feature schema.
+    pub fn fields(&self) -> &[Field] {
+        &self.layer.definition().fields
+    }
+}
+
+/// A point cloud layer.
+///
+/// Decode PCSL point resources with
+/// [`ResourceDecoder::decode_points`](crate::decode::ResourceDecoder::decode_points);
+/// the per-buffer formats (LEPCC positions, color, intensity) live in
+/// [`crate::pointcloud`].
+pub struct PointCloudLayer {
+    layer: SceneLayer,
+}
+
+impl PointCloudLayer {
+    /// Wrap a layer after checking it is a point cloud.
+    pub fn new(layer: SceneLayer) -> Result<Self> {
+        expect_layer_type(&layer, LayerType::PointCloud)?;
+        Ok(Self { layer })
+    }
+
+    common_accessors!();
+}
+
+/// A building scene layer opened through the generic [`SceneLayer`] path.
+///
+/// Most building workflows want [`crate::building::BuildingSceneLayer`]
+/// instead, which parses the sublayer tree and opens sublayers.
+pub struct BuildingLayer {
+    layer: SceneLayer,
+}
+
+impl BuildingLayer {
+    /// Wrap a layer after checking it is a building scene layer.
+    pub fn new(layer: SceneLayer) -> Result<Self> {
+        expect_layer_type(&layer, LayerType::Building)?;
+        Ok(Self { layer })
+    }
+
+    common_accessors!();
+}
@@ -0,0 +1,307 @@
+//! Mesh quality statistics for content QA.
+//!
+//! Decodes the geometry of every mesh node and counts the defects that
+//! crash or visually break downstream engines: vertices with non-finite
+//! components, triangles that repeat a corner, zero-area faces, and
+//! normals far from unit length. The per-node numbers point at the
+//! resources to rebuild; the layer totals give a pass/fail signal for a
+//! publishing pipeline.
+
+use crate::decode::{DecodedGeometry, ResourceDecoder};
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::node::TraversalControl;
+
+/// Distribution of vertex normal lengths in one geometry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalLengthStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Normals whose length is off unit by more than one percent.
+    pub non_unit: usize,
+}
+
+/// Quality measurements for one node's decoded geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeGeometryStats {
+    pub node_index: usize,
+    pub triangles: usize,
+    /// Triangles with two or more identical corner positions.
+    pub degenerate_triangles: usize,
+    /// Non-degenerate triangles whose area is (numerically) zero.
+    pub zero_area_triangles: usize,
+    /// Vertices with a NaN or infinite position component.
+    pub nan_vertices: usize,
+    /// Normal length distribution; `None` when the geometry has none.
+    pub normals: Option<NormalLengthStats>,
+}
+
+impl NodeGeometryStats {
+    /// Whether any counted defect is present.
+    pub fn has_defects(&self) -> bool {
+        self.degenerate_triangles > 0
+            || self.zero_area_triangles > 0
+            || self.nan_vertices > 0
+            || self.normals.is_some_and(|normals| normals.non_unit > 0)
+    }
+}
+
+/// What [`analyze_geometry_quality`] measured.
+#[derive(Debug, Clone, Default)]
+pub struct GeometryQualityReport {
+    /// One entry per decoded mesh node, in traversal order.
+    pub stats: Vec<NodeGeometryStats>,
+    /// Layer-wide totals over all decoded nodes.
+    pub triangles: usize,
+    pub degenerate_triangles: usize,
+    pub zero_area_triangles: usize,
+    pub nan_vertices: usize,
+    /// Mesh nodes whose geometry failed to decode.
+    pub skipped: usize,
+}
+
+impl GeometryQualityReport {
+    /// The nodes that carry at least one defect.
+    pub fn defective_nodes(&self) -> Vec<usize> {
+        self.stats
+            .iter()
+            .filter(|stats| stats.has_defects())
+            .map(|stats| stats.node_index)
+            .collect()
+    }
+}
+
+/// Decode every mesh node and collect its quality statistics.
+pub fn analyze_geometry_quality(layer: &SceneLayer) -> Result<GeometryQualityReport> {
+    let decoder = ResourceDecoder::new(layer.profile());
+    let nodes = layer.nodes()?;
+    let mut indices = Vec::new();
+    nodes.traverse(|node| {
+        if node.mesh.as_ref().is_some_and(|mesh| mesh.geometry.is_some()) {
+            indices.push(node.index);
+        }
+        TraversalControl::Continue
+    })?;
+
+    let mut report = GeometryQualityReport::default();
+    for index in indices {
+        let node = nodes.get(index)?;
+        let Ok(Some(geometry)) = layer.node_geometry_with(&node, &decoder) else {
+            report.skipped += 1;
+            continue;
+        };
+        let stats = node_geometry_stats(index, &geometry);
+        report.triangles += stats.triangles;
+        report.degenerate_triangles += stats.degenerate_triangles;
+        report.zero_area_triangles += stats.zero_area_triangles;
+        report.nan_vertices += stats.nan_vertices;
+        report.stats.push(stats);
+    }
+    Ok(report)
+}
+
+/// Statistics of one decoded geometry, independent of where it came from.
+pub fn node_geometry_stats(node_index: usize, geometry: &DecodedGeometry) -> NodeGeometryStats {
+    let nan_vertices = geometry
+        .positions
+        .chunks_exact(3)
+        .filter(|vertex| vertex.iter().any(|value| !value.is_finite()))
+        .count();
+
+    let mut triangles = 0;
+    let mut degenerate = 0;
+    let mut zero_area = 0;
+    for triangle in geometry.positions.chunks_exact(9) {
+        triangles += 1;
+        let corner = |i: usize| {
+            [
+                f64::from(triangle[i * 3]),
+                f64::from(triangle[i * 3 + 1]),
+                f64::from(triangle[i * 3 + 2]),
+            ]
+        };
+        let (a, b, c) = (corner(0), corner(1), corner(2));
+        if a.iter().chain(&b).chain(&c).any(|value| !value.is_finite()) {
+            continue;
+        }
+        if a == b || b == c || a == c {
+            degenerate += 1;
+            continue;
+        }
+        let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let cross = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+        let area = 0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt();
+        if area == 0.0 {
+            zero_area += 1;
+        }
+    }
+
+    let normals = (!geometry.normals.is_empty()).then(|| {
+        let mut stats = NormalLengthStats {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            non_unit: 0,
+        };
+        let mut count = 0usize;
+        for normal in geometry.normals.chunks_exact(3) {
+            let length = normal
+                .iter()
+                .map(|&value| f64::from(value) * f64::from(value))
+                .sum::<f64>()
+                .sqrt();
+            stats.min = stats.min.min(length);
+            stats.max = stats.max.max(length);
+            stats.mean += length;
+            if (length - 1.0).abs() > 0.01 {
+                stats.non_unit += 1;
+            }
+            count += 1;
+        }
+        stats.mean /= count.max(1) as f64;
+        stats
+    });
+
+    NodeGeometryStats {
+        node_index,
+        triangles,
+        degenerate_triangles: degenerate,
+        zero_area_triangles: zero_area,
+        nan_vertices,
+        normals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics_count_each_defect_class() {
+        let geometry = DecodedGeometry {
+            vertex_count: 9,
+            positions: vec![
+                // A healthy triangle.
+                0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+                // Degenerate: first two corners coincide.
+                2.0, 0.0, 0.0, 2.0, 0.0, 0.0, 3.0, 1.0, 0.0,
+                // Zero area: three distinct collinear corners.
+                0.0, 2.0, 0.0, 1.0, 2.0, 0.0, 2.0, 2.0, 0.0,
+            ],
+            normals: vec![
+                0.0, 0.0, 1.0, //
+                0.0, 0.0, 2.0, // double length
+                0.0, 0.0, 1.0,
+            ],
+            uvs: Vec::new(),
+            colors: Vec::new(),
+            feature_ids: Vec::new(),
+            face_ranges: Vec::new(),
+            uv_regions: Vec::new(),
+        };
+        let stats = node_geometry_stats(7, &geometry);
+        assert_eq!(stats.node_index, 7);
+        assert_eq!(stats.triangles, 3);
+        assert_eq!(stats.degenerate_triangles, 1);
+        assert_eq!(stats.zero_area_triangles, 1);
+        assert_eq!(stats.nan_vertices, 0);
+        let normals = stats.normals.unwrap();
+        assert_eq!(normals.non_unit, 1);
+        assert!((normals.min - 1.0).abs() < 1e-12);
+        assert!((normals.max - 2.0).abs() < 1e-12);
+        assert!(stats.has_defects());
+
+        // NaN positions are counted per vertex and keep the triangle out
+        // of the area classes.
+        let broken = DecodedGeometry {
+            vertex_count: 3,
+            positions: vec![f32::NAN, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            colors: Vec::new(),
+            feature_ids: Vec::new(),
+            face_ranges: Vec::new(),
+            uv_regions: Vec::new(),
+        };
+        let stats = node_geometry_stats(0, &broken);
+        assert_eq!(stats.nan_vertices, 1);
+        assert_eq!(stats.degenerate_triangles, 0);
+        assert_eq!(stats.zero_area_triangles, 0);
+        assert!(stats.normals.is_none());
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn layer_analysis_aggregates_node_stats() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("quality");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 8 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }]
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [2.0, 2.0, 2.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "obb": obb, "children": [1, 2] },
+                {
+                    "index": 1, "obb": obb, "parentIndex": 0,
+                    "mesh": { "geometry": {
+                        "definition": 0, "resource": 1, "vertexCount": 3
+                    } }
+                },
+                {
+                    "index": 2, "obb": obb, "parentIndex": 0,
+                    "mesh": { "geometry": {
+                        "definition": 0, "resource": 2, "vertexCount": 3
+                    } }
+                }
+            ]
+        }))
+        .unwrap();
+        let healthy: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let collapsed: Vec<u8> = [1.0f32, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(1, 1, &healthy).unwrap();
+        writer.write_geometry(2, 2, &collapsed).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let report = analyze_geometry_quality(&layer).unwrap();
+        assert_eq!(report.stats.len(), 2);
+        assert_eq!(report.triangles, 2);
+        assert_eq!(report.degenerate_triangles, 1);
+        assert_eq!(report.skipped, 0);
+        assert_eq!(report.defective_nodes(), vec![2]);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
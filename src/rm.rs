@@ -0,0 +1,579 @@
+//! Resource managers: the abstraction over where layer resources come from.
+
+use std::sync::Arc;
+
+#[cfg(feature = "http")]
+use crate::service::Service;
+
+#[cfg(feature = "cloud")]
+use crate::cloud::CloudStore;
+use crate::folder::ExplodedFolder;
+#[cfg(feature = "slpk")]
+use crate::slpk::SceneLayerPackage;
+
+use crate::budget::DownloadBudget;
+use crate::defn::ImageFormat;
+use crate::diag::SessionStats;
+use crate::err::{I3SError, Result};
+
+/// Fetches raw resource bytes by URI.
+pub trait Accessor {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>>;
+
+    /// Size in bytes of a resource, without fetching its content, when the
+    /// backend can tell cheaply (archive entry size, `Content-Length`).
+    /// `Ok(None)` when the backend cannot report sizes.
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        let _ = uri;
+        Ok(None)
+    }
+}
+
+/// A user-provided storage backend: anything that can fetch resource bytes
+/// and describe the URI layout it serves them under. Implemented
+/// automatically for any `Accessor + UriBuilder` that is thread-safe; plug
+/// one in with [`ResourceManager::custom`] or
+/// [`SceneLayer::from_backend`](crate::SceneLayer::from_backend).
+pub trait Backend: Accessor + UriBuilder + Send + Sync {}
+
+impl<T: Accessor + UriBuilder + Send + Sync> Backend for T {}
+
+/// Builds resource URIs in the layout of a particular backend.
+pub trait UriBuilder {
+    fn scene_definition_uri(&self) -> String;
+    fn node_page_uri(&self, page_index: usize) -> String;
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String;
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String;
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String;
+}
+
+/// The source format of a layer URI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum I3SFormat {
+    Slpk,
+    Rest,
+    /// An exploded layer directory on disk.
+    Folder,
+    /// An exploded layer in a cloud object store.
+    Cloud,
+}
+
+impl I3SFormat {
+    /// Guess the source format from a URI.
+    ///
+    /// Accepts SceneServer URLs (with or without a `/layers/{id}` suffix),
+    /// cloud object store URLs, and local content in any spelling —
+    /// absolute, relative, `file://` URLs, UNC paths, uppercase `.SLPK`,
+    /// trailing slashes.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        if uri.starts_with("http://") || uri.starts_with("https://") {
+            return Ok(Self::Rest);
+        }
+        if ["s3://", "gs://", "az://", "azure://", "adl://", "abfs://", "abfss://"]
+            .iter()
+            .any(|scheme| uri.starts_with(scheme))
+        {
+            return Ok(Self::Cloud);
+        }
+        let path = local_path(uri);
+        if path.to_ascii_lowercase().ends_with(".slpk") {
+            Ok(Self::Slpk)
+        } else if std::path::Path::new(path).is_dir() {
+            Ok(Self::Folder)
+        } else {
+            Err(I3SError::InvalidUri(uri.to_string()))
+        }
+    }
+}
+
+/// The filesystem path of a local URI: strips a `file://` scheme (including
+/// the Windows `file:///C:/...` form) and trailing separators, and leaves
+/// plain, relative, and UNC paths alone.
+pub(crate) fn local_path(uri: &str) -> &str {
+    let mut path = uri.strip_prefix("file://").unwrap_or(uri);
+    // `file:///C:/...` keeps a leading slash before the drive letter.
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3
+        && bytes[0] == b'/'
+        && bytes[1].is_ascii_alphabetic()
+        && bytes[2] == b':'
+    {
+        path = &path[1..];
+    }
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    // A bare root path must keep its separator.
+    if trimmed.is_empty() {
+        path
+    } else {
+        trimmed
+    }
+}
+
+/// The backend a `SceneLayer` reads from.
+pub enum ResourceManager {
+    #[cfg(feature = "slpk")]
+    Slpk(SceneLayerPackage),
+    #[cfg(feature = "http")]
+    Service(Service),
+    /// An exploded layer directory on disk.
+    Folder(ExplodedFolder),
+    /// An exploded layer in a cloud object store.
+    #[cfg(feature = "cloud")]
+    Cloud(CloudStore),
+    /// A view over another backend scoped to a building scene sublayer.
+    Sublayer(SublayerRouter),
+    /// A backend whose fetches draw from a download budget.
+    Budgeted(BudgetedRouter),
+    /// A backend whose fetches are counted into session statistics.
+    Recorded(RecordedRouter),
+    /// A user-provided backend (database, proprietary cache, ...).
+    Custom(Box<dyn Backend>),
+}
+
+impl ResourceManager {
+    /// Scope a backend to the resources of a building scene sublayer.
+    pub fn sublayer(inner: Arc<ResourceManager>, id: u32) -> Self {
+        Self::Sublayer(SublayerRouter { inner, id })
+    }
+
+    /// Meter a backend against a download budget.
+    pub fn budgeted(inner: Arc<ResourceManager>, budget: Arc<DownloadBudget>) -> Self {
+        Self::Budgeted(BudgetedRouter { inner, budget })
+    }
+
+    /// Count every fetch of a backend into session statistics.
+    pub fn recorded(inner: Arc<ResourceManager>, stats: Arc<SessionStats>) -> Self {
+        Self::Recorded(RecordedRouter { inner, stats })
+    }
+
+    /// Wrap a user-provided backend.
+    pub fn custom(backend: impl Backend + 'static) -> Self {
+        Self::Custom(Box::new(backend))
+    }
+
+    /// The file-backed SLPK at the bottom of this backend, if any,
+    /// unwrapping scoping and budgeting layers.
+    #[cfg(feature = "slpk")]
+    pub(crate) fn slpk_package(&self) -> Option<&SceneLayerPackage> {
+        match self {
+            Self::Slpk(package) => Some(package),
+            Self::Sublayer(router) => router.inner.slpk_package(),
+            Self::Budgeted(router) => router.inner.slpk_package(),
+            Self::Recorded(router) => router.inner.slpk_package(),
+            _ => None,
+        }
+    }
+
+    /// The URI this backend was opened from — an `.slpk` path, exploded
+    /// directory or SceneServer layer URL — in a form
+    /// [`SceneLayer::from_uri`](crate::SceneLayer::from_uri) accepts.
+    ///
+    /// `None` for backends without a reopenable source: in-memory archive
+    /// readers, sublayer views and custom backends.
+    pub fn source_uri(&self) -> Option<String> {
+        match self {
+            #[cfg(feature = "slpk")]
+            Self::Slpk(package) => package
+                .path()
+                .map(|path| path.to_string_lossy().into_owned()),
+            #[cfg(feature = "http")]
+            Self::Service(service) => Some(service.layer_url()),
+            Self::Folder(folder) => Some(folder.path().to_string_lossy().into_owned()),
+            #[cfg(feature = "cloud")]
+            Self::Cloud(_) => None,
+            // A sublayer view cannot be reopened from a bare URI.
+            Self::Sublayer(_) => None,
+            Self::Budgeted(router) => router.inner.source_uri(),
+            Self::Recorded(router) => router.inner.source_uri(),
+            Self::Custom(_) => None,
+        }
+    }
+
+    /// Bound (or unbound) the raw resource byte cache of the innermost
+    /// backend; backends without an in-memory byte cache ignore this.
+    pub(crate) fn set_resource_cache_budget(&self, budget: Option<u64>) {
+        match self {
+            #[cfg(feature = "slpk")]
+            Self::Slpk(package) => package.set_cache_budget(budget),
+            #[cfg(feature = "http")]
+            Self::Service(service) => service.set_cache_budget(budget),
+            Self::Folder(_) => {}
+            #[cfg(feature = "cloud")]
+            Self::Cloud(_) => {}
+            Self::Sublayer(router) => router.inner.set_resource_cache_budget(budget),
+            Self::Budgeted(router) => router.inner.set_resource_cache_budget(budget),
+            Self::Recorded(router) => router.inner.set_resource_cache_budget(budget),
+            Self::Custom(_) => {}
+        }
+    }
+
+    /// Whether the innermost backend is a REST service (as opposed to an
+    /// archive), which decides how scoped URIs are rewritten.
+    fn is_service_backed(&self) -> bool {
+        match self {
+            #[cfg(feature = "slpk")]
+            Self::Slpk(_) => false,
+            #[cfg(feature = "http")]
+            Self::Service(_) => true,
+            Self::Folder(_) => false,
+            #[cfg(feature = "cloud")]
+            Self::Cloud(_) => false,
+            Self::Sublayer(router) => router.inner.is_service_backed(),
+            Self::Budgeted(router) => router.inner.is_service_backed(),
+            Self::Recorded(router) => router.inner.is_service_backed(),
+            // Custom backends get the archive-style sublayer prefix; a
+            // service-like backend can fold it into its own URI scheme.
+            Self::Custom(_) => false,
+        }
+    }
+}
+
+/// Charges a [`DownloadBudget`] for every fetch of the wrapped backend.
+pub struct BudgetedRouter {
+    inner: Arc<ResourceManager>,
+    budget: Arc<DownloadBudget>,
+}
+
+impl Accessor for BudgetedRouter {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        self.budget.admit_request()?;
+        let bytes = self.inner.get(uri)?;
+        self.budget.charge_bytes(bytes.len() as u64);
+        Ok(bytes)
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        // Size probes do not transfer payloads; they are free.
+        self.inner.size(uri)
+    }
+}
+
+impl UriBuilder for BudgetedRouter {
+    fn scene_definition_uri(&self) -> String {
+        self.inner.scene_definition_uri()
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        self.inner.node_page_uri(page_index)
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        self.inner.geometry_uri(node_index, resource)
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String {
+        self.inner.texture_uri(node_index, name, format)
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        self.inner.attribute_uri(node_index, key)
+    }
+}
+
+/// Counts fetches, bytes, failures and timings of the wrapped backend into
+/// shared [`SessionStats`].
+pub struct RecordedRouter {
+    inner: Arc<ResourceManager>,
+    stats: Arc<SessionStats>,
+}
+
+impl Accessor for RecordedRouter {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        let start = std::time::Instant::now();
+        let outcome = self.inner.get(uri);
+        self.stats.record(uri, start.elapsed(), &outcome);
+        outcome
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        // Size probes do not transfer payloads; they are not counted.
+        self.inner.size(uri)
+    }
+}
+
+impl UriBuilder for RecordedRouter {
+    fn scene_definition_uri(&self) -> String {
+        self.inner.scene_definition_uri()
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        self.inner.node_page_uri(page_index)
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        self.inner.geometry_uri(node_index, resource)
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String {
+        self.inner.texture_uri(node_index, name, format)
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        self.inner.attribute_uri(node_index, key)
+    }
+}
+
+/// Rewrites URIs so a sublayer's resources resolve inside the parent
+/// backend: `sublayers/{id}/...` entries in an SLPK, `layers/{id}/...`
+/// routes on a SceneServer.
+pub struct SublayerRouter {
+    inner: Arc<ResourceManager>,
+    id: u32,
+}
+
+impl SublayerRouter {
+    fn route(&self, uri: String) -> String {
+        if self.inner.is_service_backed() {
+            // Replace whatever layer id the service put in the URI.
+            if let Some(start) = uri.find("/layers/") {
+                let digits = start + "/layers/".len();
+                let end = uri[digits..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .map_or(uri.len(), |i| digits + i);
+                return format!("{}{}{}", &uri[..digits], self.id, &uri[end..]);
+            }
+            uri
+        } else {
+            format!("sublayers/{}/{uri}", self.id)
+        }
+    }
+}
+
+impl Accessor for SublayerRouter {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        self.inner.get(uri)
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        self.inner.size(uri)
+    }
+}
+
+impl UriBuilder for SublayerRouter {
+    fn scene_definition_uri(&self) -> String {
+        self.route(self.inner.scene_definition_uri())
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        self.route(self.inner.node_page_uri(page_index))
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        self.route(self.inner.geometry_uri(node_index, resource))
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String {
+        self.route(self.inner.texture_uri(node_index, name, format))
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        self.route(self.inner.attribute_uri(node_index, key))
+    }
+}
+
+/// Construct the resource manager for a URI of a known format.
+///
+/// Open and connect failures (missing file, malformed archive, unreachable
+/// server) are returned, not panicked; a backend that was not compiled in
+/// surfaces as [`I3SError::InvalidUri`].
+pub fn resource_manager_factory(format: I3SFormat, uri: &str) -> Result<ResourceManager> {
+    match format {
+        #[cfg(feature = "slpk")]
+        I3SFormat::Slpk => Ok(ResourceManager::Slpk(SceneLayerPackage::open(local_path(
+            uri,
+        ))?)),
+        #[cfg(feature = "http")]
+        I3SFormat::Rest => Ok(ResourceManager::Service(Service::connect(uri)?)),
+        I3SFormat::Folder => Ok(ResourceManager::Folder(ExplodedFolder::open(local_path(
+            uri,
+        ))?)),
+        #[cfg(feature = "cloud")]
+        I3SFormat::Cloud => Ok(ResourceManager::Cloud(CloudStore::from_url(uri)?)),
+        #[allow(unreachable_patterns)]
+        _ => Err(I3SError::InvalidUri(format!(
+            "no backend compiled in for {format:?} ({uri})"
+        ))),
+    }
+}
+
+impl Accessor for ResourceManager {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        match self {
+            #[cfg(feature = "slpk")]
+            Self::Slpk(slpk) => slpk.get(uri),
+            #[cfg(feature = "http")]
+            Self::Service(service) => service.get(uri),
+            Self::Folder(folder) => folder.get(uri),
+            #[cfg(feature = "cloud")]
+            Self::Cloud(cloud) => cloud.get(uri),
+            Self::Sublayer(router) => router.get(uri),
+            Self::Budgeted(router) => router.get(uri),
+            Self::Recorded(router) => router.get(uri),
+            Self::Custom(backend) => backend.get(uri),
+        }
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        match self {
+            #[cfg(feature = "slpk")]
+            Self::Slpk(slpk) => slpk.size(uri),
+            #[cfg(feature = "http")]
+            Self::Service(service) => service.size(uri),
+            Self::Folder(folder) => folder.size(uri),
+            #[cfg(feature = "cloud")]
+            Self::Cloud(cloud) => cloud.size(uri),
+            Self::Sublayer(router) => router.size(uri),
+            Self::Budgeted(router) => router.size(uri),
+            Self::Recorded(router) => router.size(uri),
+            Self::Custom(backend) => backend.size(uri),
+        }
+    }
+}
+
+macro_rules! delegate_uri {
+    ($self:ident, $method:ident($($arg:expr),*)) => {
+        match $self {
+            #[cfg(feature = "slpk")]
+            Self::Slpk(slpk) => slpk.$method($($arg),*),
+            #[cfg(feature = "http")]
+            Self::Service(service) => service.$method($($arg),*),
+            Self::Folder(folder) => folder.$method($($arg),*),
+            #[cfg(feature = "cloud")]
+            Self::Cloud(cloud) => cloud.$method($($arg),*),
+            Self::Sublayer(router) => router.$method($($arg),*),
+            Self::Budgeted(router) => router.$method($($arg),*),
+            Self::Recorded(router) => router.$method($($arg),*),
+            Self::Custom(backend) => backend.$method($($arg),*),
+        }
+    };
+}
+
+impl UriBuilder for ResourceManager {
+    fn scene_definition_uri(&self) -> String {
+        delegate_uri!(self, scene_definition_uri())
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        delegate_uri!(self, node_page_uri(page_index))
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        delegate_uri!(self, geometry_uri(node_index, resource))
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String {
+        delegate_uri!(self, texture_uri(node_index, name, format))
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        delegate_uri!(self, attribute_uri(node_index, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MemoryBackend {
+        resources: std::collections::HashMap<String, Vec<u8>>,
+    }
+
+    impl Accessor for MemoryBackend {
+        fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+            self.resources
+                .get(uri)
+                .map(|bytes| Arc::new(bytes.clone()))
+                .ok_or_else(|| I3SError::MissingResource(uri.to_string()))
+        }
+    }
+
+    impl UriBuilder for MemoryBackend {
+        fn scene_definition_uri(&self) -> String {
+            "layer".to_string()
+        }
+
+        fn node_page_uri(&self, page_index: usize) -> String {
+            format!("page/{page_index}")
+        }
+
+        fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+            format!("geometry/{node_index}/{resource}")
+        }
+
+        fn texture_uri(&self, node_index: usize, name: &str, _format: ImageFormat) -> String {
+            format!("texture/{node_index}/{name}")
+        }
+
+        fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+            format!("attribute/{node_index}/{key}")
+        }
+    }
+
+    #[test]
+    fn uri_formats_are_recognized_in_any_spelling() {
+        assert_eq!(
+            I3SFormat::from_uri("https://host/SceneServer/layers/3").unwrap(),
+            I3SFormat::Rest
+        );
+        assert_eq!(
+            I3SFormat::from_uri("http://host/SceneServer/").unwrap(),
+            I3SFormat::Rest
+        );
+        assert_eq!(I3SFormat::from_uri("data/Layer.SLPK").unwrap(), I3SFormat::Slpk);
+        assert_eq!(
+            I3SFormat::from_uri("file:///tmp/layer.slpk").unwrap(),
+            I3SFormat::Slpk
+        );
+        assert_eq!(
+            I3SFormat::from_uri(r"\\server\share\layer.slpk").unwrap(),
+            I3SFormat::Slpk
+        );
+        assert_eq!(
+            I3SFormat::from_uri("/tmp/layer.slpk/").unwrap(),
+            I3SFormat::Slpk
+        );
+        assert_eq!(
+            I3SFormat::from_uri("s3://bucket/layer").unwrap(),
+            I3SFormat::Cloud
+        );
+
+        let dir = crate::testutil::TestDir::new("from-uri");
+        let with_slash = format!("{}/", dir.path().display());
+        assert_eq!(I3SFormat::from_uri(&with_slash).unwrap(), I3SFormat::Folder);
+
+        assert!(I3SFormat::from_uri("not-a-layer").is_err());
+
+        assert_eq!(local_path("file:///C:/data/layer.slpk"), "C:/data/layer.slpk");
+        assert_eq!(local_path("file:///tmp/layer.slpk"), "/tmp/layer.slpk");
+        assert_eq!(local_path("layer.slpk"), "layer.slpk");
+    }
+
+    #[test]
+    fn custom_backend_serves_a_layer() {
+        let defn = serde_json::json!({
+            "id": 0,
+            "name": "in-memory",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        });
+        let page = serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        });
+        let resources = std::collections::HashMap::from([
+            ("layer".to_string(), serde_json::to_vec(&defn).unwrap()),
+            ("page/0".to_string(), serde_json::to_vec(&page).unwrap()),
+        ]);
+
+        let layer = crate::layer::SceneLayer::from_backend(MemoryBackend { resources }).unwrap();
+        assert_eq!(layer.name(), Some("in-memory"));
+        assert_eq!(layer.root().unwrap().index, 0);
+    }
+}
@@ -0,0 +1,238 @@
+//! Priority scheduling for resource access.
+//!
+//! Long-running bulk work (exports, deep validation) and interactive
+//! rendering often share one `SceneLayer`. The [`Scheduler`] sits in front
+//! of the network/ZIP access layer and admits requests by [`Priority`], so a
+//! burst of background fetches cannot starve the resources needed for
+//! visible nodes.
+
+use std::sync::{Condvar, Mutex};
+
+use crate::err::Result;
+use crate::rm::Accessor;
+
+/// Priority class of a resource request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Resources for currently visible nodes.
+    Interactive = 0,
+    /// Speculative fetches ahead of the camera.
+    Prefetch = 1,
+    /// Bulk exports, validation, cache warming.
+    Background = 2,
+}
+
+#[derive(Default)]
+struct Counts {
+    active: usize,
+    waiting: [usize; 3],
+}
+
+/// Admits requests in priority order with a bound on concurrency.
+///
+/// A waiting request is admitted only when a slot is free and no
+/// higher-priority request is waiting; within one class admission order is
+/// unspecified.
+pub struct Scheduler {
+    max_concurrent: usize,
+    counts: Mutex<Counts>,
+    cv: Condvar,
+}
+
+impl Scheduler {
+    /// A scheduler admitting at most `max_concurrent` requests at once.
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            counts: Mutex::new(Counts::default()),
+            cv: Condvar::new(),
+        }
+    }
+
+    fn acquire(&self, priority: Priority) {
+        let class = priority as usize;
+        let mut counts = self.counts.lock().expect("scheduler lock poisoned");
+        counts.waiting[class] += 1;
+        while counts.active >= self.max_concurrent
+            || counts.waiting[..class].iter().sum::<usize>() > 0
+        {
+            counts = self.cv.wait(counts).expect("scheduler lock poisoned");
+        }
+        counts.waiting[class] -= 1;
+        counts.active += 1;
+    }
+
+    fn release(&self) {
+        let mut counts = self.counts.lock().expect("scheduler lock poisoned");
+        counts.active -= 1;
+        drop(counts);
+        self.cv.notify_all();
+    }
+
+    /// Number of requests currently waiting in the given class.
+    pub fn queued(&self, priority: Priority) -> usize {
+        self.counts.lock().expect("scheduler lock poisoned").waiting[priority as usize]
+    }
+
+    /// Run `work` once a slot is available for `priority`.
+    pub fn execute<T>(&self, priority: Priority, work: impl FnOnce() -> T) -> T {
+        self.acquire(priority);
+        let out = work();
+        self.release();
+        out
+    }
+
+    /// Fetch a resource through the scheduler at the given priority.
+    pub fn get<A: Accessor>(
+        &self,
+        accessor: &A,
+        uri: &str,
+        priority: Priority,
+    ) -> Result<std::sync::Arc<Vec<u8>>> {
+        self.execute(priority, || accessor.get(uri))
+    }
+}
+
+/// The difference between two successive frame selections.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionDiff {
+    /// Newly selected nodes, in selection order — typically front-to-back
+    /// or by screen-space error, so loads can start with the most visible.
+    pub load: Vec<usize>,
+    /// Nodes no longer selected, ascending.
+    pub release: Vec<usize>,
+}
+
+/// Tracks the node set a streaming renderer has resident and diffs each
+/// frame's selection against it.
+///
+/// As the camera moves, successive SSE-based selections overlap heavily;
+/// feeding each one through [`advance`](Self::advance) yields only the
+/// nodes to load and to release, so GPU residency is managed
+/// incrementally instead of re-uploading the whole set.
+#[derive(Debug, Default)]
+pub struct ResidencyTracker {
+    resident: std::collections::BTreeSet<usize>,
+}
+
+impl ResidencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Diff `selection` — the node indices the current frame wants, in
+    /// priority order — against the resident set and make it the new
+    /// resident set. Duplicate indices in the selection are ignored.
+    pub fn advance(&mut self, selection: &[usize]) -> SelectionDiff {
+        let wanted: std::collections::BTreeSet<usize> = selection.iter().copied().collect();
+        let mut diff = SelectionDiff {
+            release: self.resident.difference(&wanted).copied().collect(),
+            ..Default::default()
+        };
+        let mut seen = std::collections::HashSet::new();
+        for &index in selection {
+            if !self.resident.contains(&index) && seen.insert(index) {
+                diff.load.push(index);
+            }
+        }
+        self.resident = wanted;
+        diff
+    }
+
+    /// The node indices currently resident, ascending.
+    pub fn resident(&self) -> Vec<usize> {
+        self.resident.iter().copied().collect()
+    }
+
+    /// Drop everything; the next [`advance`](Self::advance) reloads the
+    /// full selection. For renderers recovering from a device loss.
+    pub fn clear(&mut self) -> SelectionDiff {
+        SelectionDiff {
+            release: std::mem::take(&mut self.resident).into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Stateless diff of two selections, for callers keeping their own
+/// resident set.
+pub fn diff_selections(previous: &[usize], current: &[usize]) -> SelectionDiff {
+    let mut tracker = ResidencyTracker::new();
+    tracker.advance(previous);
+    tracker.advance(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn residency_diffs_are_incremental() {
+        let mut tracker = ResidencyTracker::new();
+        let first = tracker.advance(&[3, 1, 2]);
+        assert_eq!(first.load, vec![3, 1, 2]);
+        assert!(first.release.is_empty());
+
+        // Camera moved: 1 stays, 4 appears, 2 and 3 go away.
+        let second = tracker.advance(&[4, 1, 4]);
+        assert_eq!(second.load, vec![4]);
+        assert_eq!(second.release, vec![2, 3]);
+        assert_eq!(tracker.resident(), vec![1, 4]);
+
+        let dropped = tracker.clear();
+        assert_eq!(dropped.release, vec![1, 4]);
+        assert_eq!(tracker.advance(&[1]).load, vec![1]);
+
+        assert_eq!(
+            diff_selections(&[1, 2], &[2, 5]),
+            SelectionDiff {
+                load: vec![5],
+                release: vec![1],
+            }
+        );
+    }
+
+    #[test]
+    fn interactive_preempts_background() {
+        let scheduler = Arc::new(Scheduler::new(1));
+        let (tx, rx) = mpsc::channel();
+
+        // Occupy the only slot.
+        scheduler.acquire(Priority::Interactive);
+
+        let bg = {
+            let scheduler = Arc::clone(&scheduler);
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                scheduler.execute(Priority::Background, || tx.send("background").unwrap())
+            })
+        };
+        while scheduler.queued(Priority::Background) == 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let fg = {
+            let scheduler = Arc::clone(&scheduler);
+            std::thread::spawn(move || {
+                scheduler.execute(Priority::Interactive, || tx.send("interactive").unwrap())
+            })
+        };
+        while scheduler.queued(Priority::Interactive) == 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        scheduler.release();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "interactive"
+        );
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "background"
+        );
+        fg.join().unwrap();
+        bg.join().unwrap();
+    }
+}
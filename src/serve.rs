@@ -0,0 +1,261 @@
+//! A built-in HTTP server that exposes an SLPK as a SceneServer.
+//!
+//! Lets viewers that only speak the REST protocol (ArcGIS JS API,
+//! loaders.gl) preview a local package without uploading it anywhere:
+//! requests under `/SceneServer/layers/0/...` are mapped to archive
+//! entries, gz resources are decompressed on the fly, and responses carry
+//! the matching content type plus a permissive CORS header.
+//!
+//! The server is deliberately small — a blocking accept loop on one
+//! thread, one request per connection — which is plenty for local preview
+//! and keeps the crate free of an async-runtime dependency.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::err::Result;
+use crate::rm::Accessor;
+use crate::slpk::SceneLayerPackage;
+
+/// A running local SceneServer.
+pub struct SlpkServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl SlpkServer {
+    /// Serve `package` on `addr` (e.g. `"127.0.0.1:0"` for an ephemeral
+    /// port). Returns once the listener is bound; requests are handled on
+    /// a background thread.
+    pub fn bind(package: SceneLayerPackage, addr: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            let package = Arc::new(package);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let _ = handle_connection(&package, stream);
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                            std::thread::sleep(Duration::from_millis(10));
+                        }
+                        Err(_) => break,
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// The bound address, including the actual port when an ephemeral one
+    /// was requested.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The URL viewers should be pointed at.
+    pub fn scene_server_url(&self) -> String {
+        format!("http://{}/SceneServer", self.addr)
+    }
+
+    /// Stop accepting requests and join the server thread.
+    pub fn shutdown(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for SlpkServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn handle_connection(package: &SceneLayerPackage, mut stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    if method != "GET" {
+        return respond(&mut stream, 405, "text/plain", b"method not allowed");
+    }
+    let path = path.split('?').next().unwrap_or(path);
+    match resolve(package, path) {
+        Some((bytes, content_type)) => respond(&mut stream, 200, content_type, &bytes),
+        None => respond(&mut stream, 404, "text/plain", b"not found"),
+    }
+}
+
+/// Map a REST path to archive bytes and a content type.
+fn resolve(package: &SceneLayerPackage, path: &str) -> Option<(Vec<u8>, &'static str)> {
+    let rest = path
+        .trim_end_matches('/')
+        .strip_prefix("/SceneServer")?
+        .trim_start_matches('/');
+    let json = |entry: &str| {
+        package
+            .get(entry)
+            .ok()
+            .map(|bytes| (bytes.to_vec(), "application/json"))
+    };
+
+    if rest.is_empty() || rest == "layers/0" {
+        return json("3dSceneLayer.json.gz");
+    }
+    let rest = rest.strip_prefix("layers/0/")?;
+    let segments: Vec<&str> = rest.split('/').collect();
+    match segments.as_slice() {
+        ["nodepages", page] => json(&format!("nodepages/{page}.json.gz")),
+        ["nodes", node, "geometries", resource] => package
+            .get(&format!("nodes/{node}/geometries/{resource}.bin.gz"))
+            .ok()
+            .map(|bytes| (bytes.to_vec(), "application/octet-stream")),
+        ["nodes", node, "textures", name] => {
+            // The REST route carries no extension; probe the formats the
+            // SLPK layout allows.
+            let candidates = [
+                (format!("nodes/{node}/textures/{name}.jpg"), "image/jpeg"),
+                (format!("nodes/{node}/textures/{name}.png"), "image/png"),
+                (
+                    format!("nodes/{node}/textures/{name}.bin.dds.gz"),
+                    "application/octet-stream",
+                ),
+                (format!("nodes/{node}/textures/{name}.ktx2"), "image/ktx2"),
+                (
+                    format!("nodes/{node}/textures/{name}.basis"),
+                    "application/octet-stream",
+                ),
+                (format!("nodes/{node}/textures/{name}.ktx"), "image/ktx"),
+            ];
+            candidates.iter().find_map(|(entry, content_type)| {
+                package
+                    .get(entry)
+                    .ok()
+                    .map(|bytes| (bytes.to_vec(), *content_type))
+            })
+        }
+        ["nodes", node, "attributes", key, "0"] => package
+            .get(&format!("nodes/{node}/attributes/{key}/0.bin.gz"))
+            .ok()
+            .map(|bytes| (bytes.to_vec(), "application/octet-stream")),
+        _ => None,
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slpk::writer::SlpkWriter;
+    use std::io::Read;
+
+    fn request(addr: SocketAddr, path: &str) -> (u16, Vec<u8>) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let header_end = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .expect("complete response")
+            + 4;
+        let head = String::from_utf8_lossy(&response[..header_end]).into_owned();
+        let status: u16 = head
+            .split_whitespace()
+            .nth(1)
+            .expect("status code")
+            .parse()
+            .unwrap();
+        (status, response[header_end..].to_vec())
+    }
+
+    #[test]
+    fn serves_layer_document_and_resources() {
+        let dir = crate::testutil::TestDir::new("serve");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": "served",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        let package = SceneLayerPackage::open(&path).unwrap();
+        let server = SlpkServer::bind(package, "127.0.0.1:0").unwrap();
+        let addr = server.addr();
+
+        let (status, body) = request(addr, "/SceneServer/layers/0");
+        assert_eq!(status, 200);
+        let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(doc["name"], "served");
+
+        let (status, body) = request(addr, "/SceneServer/layers/0/nodes/0/geometries/0");
+        assert_eq!(status, 200);
+        assert_eq!(body, vec![1, 2, 3]);
+
+        let (status, _) = request(addr, "/SceneServer/layers/0/nodes/9/geometries/0");
+        assert_eq!(status, 404);
+
+        server.shutdown();
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,953 @@
+//! Reading hosted scene layers from a SceneServer REST endpoint.
+
+use std::sync::Arc;
+
+
+use serde::{Deserialize, Serialize};
+
+use crate::defn::{Extent, ImageFormat, LayerType};
+use crate::err::{I3SError, Result};
+use crate::node::NodePage;
+use crate::rm::{Accessor, UriBuilder};
+
+/// Lightweight description of one layer of a multi-layer SceneServer, from
+/// the root service document — no `SceneLayer` is constructed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerSummary {
+    pub id: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Absent when the root document omits the type.
+    #[serde(default)]
+    pub layer_type: Option<LayerType>,
+    #[serde(default)]
+    pub full_extent: Option<Extent>,
+}
+
+#[derive(Deserialize)]
+struct ServiceRootDocument {
+    #[serde(default)]
+    layers: Vec<LayerSummary>,
+}
+
+/// A callback that produces a fresh token on demand.
+pub type TokenProvider = Box<dyn Fn() -> Result<String> + Send + Sync>;
+
+/// How requests to a hosted service authenticate.
+#[derive(Default)]
+pub enum Auth {
+    /// Anonymous access.
+    #[default]
+    None,
+    /// A static Esri token, appended to every request as `token=`.
+    Token(String),
+    /// A static OAuth access token, sent as an `Authorization: Bearer`
+    /// header.
+    Bearer(String),
+    /// Username and password, exchanged for a token at `token_url`
+    /// (a `generateToken` endpoint) and renewed when the service rejects
+    /// the current one.
+    Credentials {
+        token_url: String,
+        username: String,
+        password: String,
+    },
+    /// A callback producing tokens (e.g. an application-managed OAuth
+    /// refresh), invoked lazily and again whenever the service rejects the
+    /// current token. Tokens are applied as `token=`.
+    Refresh(TokenProvider),
+}
+
+impl std::fmt::Debug for Auth {
+    // Manual so credentials and tokens never end up in logs.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "Auth::None",
+            Self::Token(_) => "Auth::Token",
+            Self::Bearer(_) => "Auth::Bearer",
+            Self::Credentials { .. } => "Auth::Credentials",
+            Self::Refresh(_) => "Auth::Refresh",
+        })
+    }
+}
+
+impl Auth {
+    /// Whether a rejected token can be replaced with a fresh one.
+    fn is_renewable(&self) -> bool {
+        matches!(self, Self::Credentials { .. } | Self::Refresh(_))
+    }
+}
+
+/// Split a `.../SceneServer/layers/{id}` URL into the service base URL and
+/// the layer id. URLs without a trailing layer path address layer 0.
+pub(crate) fn split_layer_url(url: &str) -> (String, u32) {
+    let trimmed = url.trim_end_matches('/');
+    if let Some((base, id)) = trimmed.rsplit_once("/layers/") {
+        if let Ok(id) = id.parse() {
+            return (base.to_string(), id);
+        }
+    }
+    (trimmed.to_string(), 0)
+}
+
+/// Append a token query parameter to a URI.
+fn with_token(uri: &str, token: &str) -> String {
+    let separator = if uri.contains('?') { '&' } else { '?' };
+    format!("{uri}{separator}token={token}")
+}
+
+/// The Esri error code of a JSON error body, if this is one. Hosted
+/// services report expired or missing tokens as HTTP 200 with an error
+/// payload, so status checks alone are not enough.
+fn esri_error_code(bytes: &[u8]) -> Option<u16> {
+    if !bytes.starts_with(b"{\"error\"") {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_slice(bytes).ok()?;
+    value["error"]["code"].as_u64().map(|code| code as u16)
+}
+
+/// Transport tuning for a hosted service connection.
+#[derive(Debug, Clone)]
+pub struct ServiceOptions {
+    /// Per-request timeout.
+    pub timeout: std::time::Duration,
+    /// Extra attempts after a retryable failure (429/502/503 responses,
+    /// timeouts, connection errors).
+    pub retries: u32,
+    /// Delay before the first retry; doubled after every further attempt.
+    pub backoff: std::time::Duration,
+    /// Directory for an on-disk response cache with `ETag`/`Last-Modified`
+    /// revalidation; `None` disables persistent caching.
+    pub disk_cache: Option<std::path::PathBuf>,
+}
+
+impl Default for ServiceOptions {
+    fn default() -> Self {
+        Self {
+            timeout: std::time::Duration::from_secs(30),
+            retries: 3,
+            backoff: std::time::Duration::from_millis(250),
+            disk_cache: None,
+        }
+    }
+}
+
+impl ServiceOptions {
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn backoff(mut self, backoff: std::time::Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn disk_cache(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.disk_cache = Some(dir.into());
+        self
+    }
+}
+
+/// Validators of a cached response.
+#[derive(Serialize, Deserialize)]
+struct CacheMeta {
+    /// Full URL, kept to guard against key collisions.
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// An on-disk cache of service responses keyed by URL.
+///
+/// Responses carrying an `ETag` or `Last-Modified` header are persisted;
+/// later runs revalidate with a conditional request, so unchanged node
+/// pages and textures cost one `304 Not Modified` instead of a refetch.
+struct HttpCache {
+    dir: std::path::PathBuf,
+}
+
+impl HttpCache {
+    fn open(dir: std::path::PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// A filesystem-safe key for a URL.
+    fn key(url: &str) -> String {
+        crate::cache::content_hash(url.as_bytes())
+    }
+
+    fn load(&self, url: &str) -> Option<(Vec<u8>, CacheMeta)> {
+        let key = Self::key(url);
+        let meta_bytes = std::fs::read(self.dir.join(format!("{key}.meta"))).ok()?;
+        let meta: CacheMeta = serde_json::from_slice(&meta_bytes).ok()?;
+        if meta.url != url {
+            return None;
+        }
+        let body = std::fs::read(self.dir.join(format!("{key}.body"))).ok()?;
+        Some((body, meta))
+    }
+
+    fn store(&self, meta: &CacheMeta, body: &[u8]) -> Result<()> {
+        let key = Self::key(&meta.url);
+        let meta_bytes =
+            serde_json::to_vec(meta).map_err(|e| I3SError::json("cache metadata", e))?;
+        std::fs::write(self.dir.join(format!("{key}.body")), body)?;
+        std::fs::write(self.dir.join(format!("{key}.meta")), meta_bytes)?;
+        Ok(())
+    }
+}
+
+/// A connection to a SceneServer REST endpoint.
+pub struct Service {
+    base_url: String,
+    layer_id: u32,
+    client: reqwest::blocking::Client,
+    auth: Auth,
+    options: ServiceOptions,
+    disk_cache: Option<HttpCache>,
+    /// The current token of a renewable [`Auth`] mode.
+    token: std::sync::RwLock<Option<String>>,
+    cache: crate::cache::BoundedCache<String, Vec<u8>>,
+}
+
+impl Service {
+    /// Connect to a SceneServer URL (e.g. `https://.../SceneServer`).
+    ///
+    /// A URL ending in `/layers/{id}` selects that layer of a multi-layer
+    /// service; a bare SceneServer URL addresses layer 0.
+    pub fn connect(url: &str) -> Result<Self> {
+        Self::connect_with(url, Auth::None)
+    }
+
+    /// Connect with credentials applied to every request.
+    pub fn connect_with(url: &str, auth: Auth) -> Result<Self> {
+        Self::connect_with_options(url, auth, ServiceOptions::default())
+    }
+
+    /// Connect with credentials and transport tuning.
+    pub fn connect_with_options(url: &str, auth: Auth, options: ServiceOptions) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(options.timeout)
+            .build()?;
+        Self::from_parts(url, client, auth, options, None)
+    }
+
+    /// Start building a connection with full client configuration
+    /// (proxy, extra headers, user agent, TLS roots).
+    pub fn builder(url: &str) -> ServiceBuilder {
+        ServiceBuilder::new(url)
+    }
+
+    fn from_parts(
+        url: &str,
+        client: reqwest::blocking::Client,
+        auth: Auth,
+        options: ServiceOptions,
+        layer_id: Option<u32>,
+    ) -> Result<Self> {
+        let (base_url, parsed_id) = split_layer_url(url);
+        let disk_cache = options
+            .disk_cache
+            .clone()
+            .map(HttpCache::open)
+            .transpose()?;
+        let service = Self {
+            base_url,
+            layer_id: layer_id.unwrap_or(parsed_id),
+            client,
+            auth,
+            options,
+            disk_cache,
+            token: std::sync::RwLock::new(None),
+            cache: crate::cache::BoundedCache::new(),
+        };
+        // Probe the layer document so connection and credential failures
+        // surface here.
+        service.get(&service.scene_definition_uri())?;
+        Ok(service)
+    }
+
+    /// Send a request, retrying transient failures with exponential
+    /// backoff. `make` builds a fresh request for each attempt.
+    fn send_with_retry(
+        &self,
+        make: impl Fn() -> reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response> {
+        let mut attempt = 0;
+        loop {
+            let result = make().send();
+            let transient = match &result {
+                Ok(response) => matches!(response.status().as_u16(), 429 | 502 | 503),
+                Err(error) => error.is_timeout() || error.is_connect(),
+            };
+            if !transient || attempt >= self.options.retries {
+                return Ok(result?);
+            }
+            // Cap the exponent: 2^attempt overflows u32 past 31 retries,
+            // and the multiplied Duration can overflow even earlier.
+            let factor = 2u32.pow(attempt.min(16));
+            std::thread::sleep(self.options.backoff.saturating_mul(factor));
+            attempt += 1;
+        }
+    }
+
+    /// The token to send, acquiring one for renewable auth modes.
+    fn current_token(&self) -> Result<Option<String>> {
+        match &self.auth {
+            Auth::None | Auth::Bearer(_) => Ok(None),
+            Auth::Token(token) => Ok(Some(token.clone())),
+            Auth::Credentials { .. } | Auth::Refresh(_) => {
+                if let Some(token) = self.token.read().expect("token lock poisoned").clone() {
+                    return Ok(Some(token));
+                }
+                let fresh = match &self.auth {
+                    Auth::Credentials {
+                        token_url,
+                        username,
+                        password,
+                    } => self.generate_token(token_url, username, password)?,
+                    Auth::Refresh(provider) => provider()?,
+                    _ => unreachable!(),
+                };
+                *self.token.write().expect("token lock poisoned") = Some(fresh.clone());
+                Ok(Some(fresh))
+            }
+        }
+    }
+
+    /// Exchange username and password at a `generateToken` endpoint.
+    fn generate_token(&self, token_url: &str, username: &str, password: &str) -> Result<String> {
+        let response = self
+            .client
+            .post(token_url)
+            .form(&[
+                ("username", username),
+                ("password", password),
+                ("client", "requestip"),
+                ("f", "json"),
+            ])
+            .send()?;
+        let status = response.status();
+        let bytes = response.bytes()?;
+        let value: serde_json::Value =
+            serde_json::from_slice(&bytes).map_err(|e| I3SError::json(token_url, e))?;
+        if let Some(token) = value["token"].as_str() {
+            return Ok(token.to_string());
+        }
+        Err(I3SError::Http {
+            status: value["error"]["code"]
+                .as_u64()
+                .map(|code| code as u16)
+                .unwrap_or(status.as_u16()),
+            uri: token_url.to_string(),
+        })
+    }
+
+    /// Run one authenticated GET; `Err` carries the status for auth
+    /// failures so the caller can decide to renew.
+    fn fetch(&self, uri: &str) -> Result<Vec<u8>> {
+        let token = self.current_token()?;
+        let cached = self.disk_cache.as_ref().and_then(|cache| cache.load(uri));
+        let response = self.send_with_retry(|| {
+            let mut request = match &token {
+                Some(token) => self.client.get(with_token(uri, token)),
+                None => self.client.get(uri),
+            };
+            if let Auth::Bearer(token) = &self.auth {
+                request = request.bearer_auth(token);
+            }
+            if let Some((_, meta)) = &cached {
+                if let Some(etag) = &meta.etag {
+                    request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &meta.last_modified {
+                    request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+            request
+        })?;
+        let status = response.status();
+        if status.as_u16() == 304 {
+            if let Some((body, _)) = cached {
+                return Ok(body);
+            }
+        }
+        if !status.is_success() {
+            return Err(I3SError::Http {
+                status: status.as_u16(),
+                uri: uri.to_string(),
+            });
+        }
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        let bytes = response.bytes()?.to_vec();
+        if let Some(code) = esri_error_code(&bytes) {
+            return Err(I3SError::Http {
+                status: code,
+                uri: uri.to_string(),
+            });
+        }
+        // Only responses with validators are persisted; anything else
+        // would have to be refetched in full anyway.
+        if let Some(cache) = &self.disk_cache {
+            if etag.is_some() || last_modified.is_some() {
+                let _ = cache.store(
+                    &CacheMeta {
+                        url: uri.to_string(),
+                        etag,
+                        last_modified,
+                    },
+                    &bytes,
+                );
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// The SceneServer base URL, without any layer path.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The id of the layer this connection addresses.
+    pub fn layer_id(&self) -> u32 {
+        self.layer_id
+    }
+
+    /// The URL of the addressed layer.
+    pub(crate) fn layer_url(&self) -> String {
+        format!("{}/layers/{}", self.base_url, self.layer_id)
+    }
+
+    /// Bound the in-memory response cache to `budget` bytes with LRU
+    /// eviction, or lift the bound with `None`.
+    pub fn set_cache_budget(&self, budget: Option<u64>) {
+        self.cache.set_budget(budget);
+    }
+
+    /// Summaries of every layer the service declares.
+    pub fn list_layers(&self) -> Result<Vec<LayerSummary>> {
+        let uri = format!("{}?f=json", self.base_url);
+        let bytes = self.get(&uri)?;
+        let root: ServiceRootDocument =
+            serde_json::from_slice(&bytes).map_err(|e| I3SError::json(&uri, e))?;
+        Ok(root.layers)
+    }
+
+    /// Summaries of only the layers of the given type.
+    pub fn layers_of_type(&self, layer_type: LayerType) -> Result<Vec<LayerSummary>> {
+        Ok(self
+            .list_layers()?
+            .into_iter()
+            .filter(|layer| layer.layer_type == Some(layer_type))
+            .collect())
+    }
+
+    /// Fetch and parse a node page by page index.
+    pub fn get_node_page(&self, page_index: usize) -> Result<NodePage> {
+        let uri = self.node_page_uri(page_index);
+        let bytes = self.get(&uri)?;
+        serde_json::from_slice(&bytes).map_err(|e| I3SError::json(&uri, e))
+    }
+}
+
+/// Fluent construction of a [`Service`] with full client configuration.
+///
+/// Covers what [`Service::connect_with_options`] cannot: corporate
+/// proxies, extra headers sent with every request (API keys, tracing
+/// ids), a custom user agent, and additional TLS root certificates for
+/// servers behind private CAs.
+pub struct ServiceBuilder {
+    url: String,
+    auth: Auth,
+    options: ServiceOptions,
+    proxy: Option<String>,
+    headers: Vec<(String, String)>,
+    user_agent: Option<String>,
+    root_certificates: Vec<reqwest::Certificate>,
+    layer_id: Option<u32>,
+}
+
+impl ServiceBuilder {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            auth: Auth::None,
+            options: ServiceOptions::default(),
+            proxy: None,
+            headers: Vec::new(),
+            user_agent: None,
+            root_certificates: Vec::new(),
+            layer_id: None,
+        }
+    }
+
+    /// Address a specific layer of a multi-layer service, overriding any
+    /// `/layers/{id}` suffix of the URL.
+    pub fn layer_id(mut self, id: u32) -> Self {
+        self.layer_id = Some(id);
+        self
+    }
+
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    pub fn options(mut self, options: ServiceOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Route all requests through an HTTP(S) proxy URL.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Send an extra header with every request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn user_agent(mut self, agent: impl Into<String>) -> Self {
+        self.user_agent = Some(agent.into());
+        self
+    }
+
+    /// Trust an additional PEM-encoded root certificate.
+    pub fn root_certificate_pem(mut self, pem: &[u8]) -> Result<Self> {
+        self.root_certificates.push(reqwest::Certificate::from_pem(pem)?);
+        Ok(self)
+    }
+
+    /// Build the client and connect, probing the layer document.
+    pub fn connect(self) -> Result<Service> {
+        let mut builder = reqwest::blocking::Client::builder().timeout(self.options.timeout);
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(agent) = &self.user_agent {
+            builder = builder.user_agent(agent.clone());
+        }
+        for certificate in self.root_certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if !self.headers.is_empty() {
+            let mut headers = reqwest::header::HeaderMap::new();
+            for (name, value) in &self.headers {
+                let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| I3SError::Validation(format!("invalid header name {name:?}: {e}")))?;
+                let value = reqwest::header::HeaderValue::from_str(value).map_err(|e| {
+                    I3SError::Validation(format!("invalid header value for {name:?}: {e}"))
+                })?;
+                headers.insert(name, value);
+            }
+            builder = builder.default_headers(headers);
+        }
+        Service::from_parts(
+            &self.url,
+            builder.build()?,
+            self.auth,
+            self.options,
+            self.layer_id,
+        )
+    }
+}
+
+impl Accessor for Service {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        if let Some(hit) = self.cache.get(&uri.to_string()) {
+            return Ok(hit);
+        }
+        let bytes = match self.fetch(uri) {
+            // An expired or invalid token gets renewed once before the
+            // failure is surfaced.
+            Err(I3SError::Http { status, .. })
+                if matches!(status, 401 | 403 | 498 | 499) && self.auth.is_renewable() =>
+            {
+                *self.token.write().expect("token lock poisoned") = None;
+                self.fetch(uri).map_err(|e| e.with_uri(uri))?
+            }
+            other => other.map_err(|e| e.with_uri(uri))?,
+        };
+        let bytes = Arc::new(bytes);
+        self.cache
+            .insert(uri.to_string(), bytes.len() as u64, Arc::clone(&bytes));
+        Ok(bytes)
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        let token = self.current_token()?;
+        let response = self.send_with_retry(|| {
+            let mut request = match &token {
+                Some(token) => self.client.head(with_token(uri, token)),
+                None => self.client.head(uri),
+            };
+            if let Auth::Bearer(token) = &self.auth {
+                request = request.bearer_auth(token);
+            }
+            request
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(I3SError::Http {
+                status: status.as_u16(),
+                uri: uri.to_string(),
+            });
+        }
+        Ok(response.content_length())
+    }
+}
+
+impl UriBuilder for Service {
+    fn scene_definition_uri(&self) -> String {
+        self.layer_url()
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        format!("{}/nodepages/{page_index}", self.layer_url())
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        format!(
+            "{}/nodes/{node_index}/geometries/{resource}",
+            self.layer_url()
+        )
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, _format: ImageFormat) -> String {
+        format!("{}/nodes/{node_index}/textures/{name}", self.layer_url())
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        format!(
+            "{}/nodes/{node_index}/attributes/{key}/0",
+            self.layer_url()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// A tiny SceneServer stub that only answers requests carrying
+    /// `token=valid`, rejecting everything else the way hosted services
+    /// do: HTTP 200 with an Esri error payload.
+    fn spawn_stub(connections: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for _ in 0..connections {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut request_line = String::new();
+                let _ = BufReader::new(&stream).read_line(&mut request_line);
+                let body = if request_line.contains("token=valid") {
+                    r#"{"id": 0, "layerType": "IntegratedMesh",
+                        "store": {"profile": "meshpyramids"}}"#
+                } else {
+                    r#"{"error": {"code": 498, "message": "Invalid token"}}"#
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        format!("http://{addr}/SceneServer")
+    }
+
+    #[test]
+    fn layer_urls_select_non_zero_layers() {
+        assert_eq!(
+            split_layer_url("https://host/SceneServer/layers/3/"),
+            ("https://host/SceneServer".to_string(), 3)
+        );
+        assert_eq!(
+            split_layer_url("https://host/SceneServer"),
+            ("https://host/SceneServer".to_string(), 0)
+        );
+        // A path segment that is not a number is not a layer id.
+        assert_eq!(
+            split_layer_url("https://host/layers/all/SceneServer"),
+            ("https://host/layers/all/SceneServer".to_string(), 0)
+        );
+
+        let url = spawn_stub(1);
+        let service =
+            Service::connect_with(&format!("{url}/layers/3"), Auth::Token("valid".to_string()))
+                .unwrap();
+        assert_eq!(service.base_url(), url);
+        assert_eq!(service.layer_id(), 3);
+        assert_eq!(
+            service.node_page_uri(2),
+            format!("{url}/layers/3/nodepages/2")
+        );
+    }
+
+    #[test]
+    fn layers_can_be_enumerated_and_opened() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for _ in 0..3 {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut request_line = String::new();
+                let _ = BufReader::new(&stream).read_line(&mut request_line);
+                let body = if request_line.contains("/SceneServer?f=json") {
+                    r#"{"layers": [
+                        {"id": 0, "name": "Overview", "layerType": "IntegratedMesh"},
+                        {"id": 1, "name": "Detail", "layerType": "IntegratedMesh"}
+                    ]}"#
+                } else if request_line.contains("/layers/1") {
+                    r#"{"id": 1, "name": "Detail", "layerType": "IntegratedMesh",
+                        "store": {"profile": "meshpyramids"}}"#
+                } else {
+                    r#"{"id": 0, "name": "Overview", "layerType": "IntegratedMesh",
+                        "store": {"profile": "meshpyramids"}}"#
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        let url = format!("http://{addr}/SceneServer");
+        let service = Service::connect(&url).unwrap();
+        let layers = service.list_layers().unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[1].id, 1);
+        assert_eq!(layers[1].name.as_deref(), Some("Detail"));
+
+        let layer = crate::layer::SceneLayer::open_layer(&url, 1).unwrap();
+        assert_eq!(layer.name(), Some("Detail"));
+    }
+
+    #[test]
+    fn static_token_is_appended_to_requests() {
+        let url = spawn_stub(1);
+        let service = Service::connect_with(&url, Auth::Token("valid".to_string())).unwrap();
+        assert_eq!(service.base_url(), url);
+    }
+
+    #[test]
+    fn rejected_tokens_are_renewed_once() {
+        let url = spawn_stub(3);
+        // Anonymous access fails with the Esri error code.
+        let Err(err) = Service::connect(&url) else {
+            panic!("anonymous access should be rejected");
+        };
+        assert!(matches!(err, I3SError::Http { status: 498, .. }));
+
+        // The first provided token is stale; the retry path renews it.
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let provider: TokenProvider = Box::new(move || {
+            let call = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(if call == 0 { "stale" } else { "valid" }.to_string())
+        });
+        Service::connect_with(&url, Auth::Refresh(provider)).unwrap();
+    }
+
+    #[test]
+    fn transient_statuses_are_retried_with_backoff() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            // Two 503s, then success.
+            for attempt in 0..3 {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut request_line = String::new();
+                let _ = BufReader::new(&stream).read_line(&mut request_line);
+                let (status, body) = if attempt < 2 {
+                    ("503 Service Unavailable", "busy")
+                } else {
+                    (
+                        "200 OK",
+                        r#"{"id": 0, "layerType": "IntegratedMesh",
+                            "store": {"profile": "meshpyramids"}}"#,
+                    )
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        let options = ServiceOptions::default()
+            .retries(2)
+            .backoff(std::time::Duration::from_millis(5));
+        let url = format!("http://{addr}/SceneServer");
+        Service::connect_with_options(&url, Auth::None, options).unwrap();
+    }
+
+    #[test]
+    fn exhausted_retries_surface_the_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for _ in 0..2 {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut request_line = String::new();
+                let _ = BufReader::new(&stream).read_line(&mut request_line);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 429 Too Many Requests\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+            }
+        });
+
+        let options = ServiceOptions::default()
+            .retries(1)
+            .backoff(std::time::Duration::from_millis(5));
+        let url = format!("http://{addr}/SceneServer");
+        let Err(err) = Service::connect_with_options(&url, Auth::None, options) else {
+            panic!("all attempts return 429");
+        };
+        assert!(matches!(err, I3SError::Http { status: 429, .. }));
+    }
+
+    #[test]
+    fn disk_cache_revalidates_with_etag() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for _ in 0..2 {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(&stream);
+                let mut revalidation = false;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).is_err() || line.trim().is_empty() {
+                        break;
+                    }
+                    if line.to_ascii_lowercase().starts_with("if-none-match:")
+                        && line.contains("v1")
+                    {
+                        revalidation = true;
+                    }
+                }
+                if revalidation {
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 304 Not Modified\r\nETag: v1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    );
+                } else {
+                    let body = r#"{"id": 0, "layerType": "IntegratedMesh",
+                        "store": {"profile": "meshpyramids"}}"#;
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nETag: v1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                }
+            }
+        });
+
+        let cache_dir = crate::testutil::TestDir::new("http-cache");
+        let url = format!("http://{addr}/SceneServer");
+        let options = ServiceOptions::default().disk_cache(cache_dir.path());
+
+        // First run populates the cache; the second revalidates and gets
+        // the stub's empty 304, so a successful parse proves the cached
+        // body was served.
+        Service::connect_with_options(&url, Auth::None, options.clone()).unwrap();
+        Service::connect_with_options(&url, Auth::None, options).unwrap();
+    }
+
+    #[test]
+    fn builder_headers_and_user_agent_reach_the_wire() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen_by_server = std::sync::Arc::clone(&seen);
+        std::thread::spawn(move || {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut reader = BufReader::new(&stream);
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).is_err() || line.trim().is_empty() {
+                    break;
+                }
+                seen_by_server
+                    .lock()
+                    .unwrap()
+                    .push(line.trim().to_ascii_lowercase());
+            }
+            let body = r#"{"id": 0, "layerType": "IntegratedMesh",
+                "store": {"profile": "meshpyramids"}}"#;
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        });
+
+        let url = format!("http://{addr}/SceneServer");
+        Service::builder(&url)
+            .header("x-api-key", "abc123")
+            .user_agent("i3s-test/1.0")
+            .connect()
+            .unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert!(seen.iter().any(|line| line == "x-api-key: abc123"));
+        assert!(seen.iter().any(|line| line == "user-agent: i3s-test/1.0"));
+    }
+
+    #[test]
+    fn invalid_extra_headers_fail_at_connect() {
+        let err = Service::builder("http://127.0.0.1:1/SceneServer")
+            .header("bad header", "x")
+            .connect();
+        assert!(matches!(err, Err(I3SError::Validation(_))));
+    }
+
+    #[test]
+    fn token_query_parameter_placement() {
+        assert_eq!(with_token("http://h/a", "t"), "http://h/a?token=t");
+        assert_eq!(with_token("http://h/a?f=json", "t"), "http://h/a?f=json&token=t");
+    }
+}
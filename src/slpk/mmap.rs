@@ -0,0 +1,208 @@
+//! Memory-mapped SLPK access for very large archives.
+//!
+//! [`SceneLayerPackage`](super::SceneLayerPackage) funnels every read
+//! through one `RwLock<ZipArchive<File>>`, which serializes readers and
+//! pays a seek per entry. [`MmapPackage`] instead maps the archive once,
+//! indexes the central directory up front, and serves STOREd entries
+//! straight out of the mapping with no locking and no inflate; deflated
+//! entries are inflated from the mapping and cached. Plug one into a layer
+//! with [`SceneLayer::from_backend`](crate::SceneLayer::from_backend).
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::decode::maybe_ungzip;
+use crate::defn::ImageFormat;
+use crate::err::{I3SError, Result};
+use crate::rm::{Accessor, UriBuilder};
+
+struct EntryInfo {
+    data_start: u64,
+    compressed_size: u64,
+    method: CompressionMethod,
+}
+
+/// An SLPK archive served from a memory mapping.
+pub struct MmapPackage {
+    path: PathBuf,
+    map: memmap2::Mmap,
+    entries: HashMap<String, EntryInfo>,
+    cache: crate::cache::BoundedCache<String, Vec<u8>>,
+}
+
+impl MmapPackage {
+    /// Map an SLPK file and index its entries.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let mut archive = ZipArchive::new(&file)?;
+        let mut entries = HashMap::with_capacity(archive.len());
+        for index in 0..archive.len() {
+            let entry = archive.by_index_raw(index)?;
+            if entry.is_dir() {
+                continue;
+            }
+            entries.insert(
+                entry.name().to_string(),
+                EntryInfo {
+                    data_start: entry.data_start(),
+                    compressed_size: entry.compressed_size(),
+                    method: entry.compression(),
+                },
+            );
+        }
+        drop(archive);
+        // Safety: the mapping is read-only; mutating the archive while it
+        // is mapped is undefined behavior, as with any mmap-backed file.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self {
+            path,
+            map,
+            entries,
+            cache: crate::cache::BoundedCache::new(),
+        })
+    }
+
+    /// Path of the underlying archive.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Entry names inside the archive.
+    pub fn entries(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    fn raw(&self, info: &EntryInfo) -> Result<&[u8]> {
+        let start = info.data_start as usize;
+        let end = start + info.compressed_size as usize;
+        self.map.get(start..end).ok_or_else(|| {
+            I3SError::Decode(format!(
+                "archive entry extends past the mapping: {start}..{end} of {}",
+                self.map.len()
+            ))
+        })
+    }
+}
+
+impl Accessor for MmapPackage {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        if let Some(hit) = self.cache.get(&uri.to_string()) {
+            return Ok(hit);
+        }
+        let info = self
+            .entries
+            .get(uri)
+            .ok_or_else(|| I3SError::MissingResource(uri.to_string()))?;
+        let raw = self.raw(info)?;
+        let bytes = match info.method {
+            CompressionMethod::Stored => raw.to_vec(),
+            CompressionMethod::Deflated => {
+                let mut out = Vec::new();
+                std::io::Read::read_to_end(
+                    &mut flate2::read::DeflateDecoder::new(raw),
+                    &mut out,
+                )?;
+                out
+            }
+            other => {
+                return Err(I3SError::Decode(format!(
+                    "unsupported compression method {other} for {uri}"
+                )))
+            }
+        };
+        let bytes = Arc::new(maybe_ungzip(bytes)?);
+        self.cache
+            .insert(uri.to_string(), bytes.len() as u64, Arc::clone(&bytes));
+        Ok(bytes)
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        self.entries
+            .get(uri)
+            .map(|info| Some(info.compressed_size))
+            .ok_or_else(|| I3SError::MissingResource(uri.to_string()))
+    }
+}
+
+impl UriBuilder for MmapPackage {
+    fn scene_definition_uri(&self) -> String {
+        "3dSceneLayer.json.gz".to_string()
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        format!("nodepages/{page_index}.json.gz")
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        format!("nodes/{node_index}/geometries/{resource}.bin.gz")
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String {
+        let ext = match format {
+            ImageFormat::Jpg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Dds => "bin.dds.gz",
+            ImageFormat::Ktx2 => "ktx2",
+            ImageFormat::Basis => "basis",
+            ImageFormat::KtxEtc2 => "ktx",
+        };
+        format!("nodes/{node_index}/textures/{name}.{ext}")
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        format!("nodes/{node_index}/attributes/{key}/0.bin.gz")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slpk::writer::SlpkWriter;
+
+    #[test]
+    fn mmap_package_matches_file_backed_reads() {
+        let dir = crate::testutil::TestDir::new("mmap");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": "mapped",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        let package = MmapPackage::open(&path).unwrap();
+        let geometry = package.get(&package.geometry_uri(0, 0)).unwrap();
+        assert_eq!(&*geometry, &vec![1u8, 2, 3]);
+
+        let layer = crate::layer::SceneLayer::from_backend(MmapPackage::open(&path).unwrap())
+            .unwrap();
+        assert_eq!(layer.name(), Some("mapped"));
+        assert_eq!(layer.root().unwrap().index, 0);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,983 @@
+//! Reading and writing scene layer packages (`.slpk` archives).
+
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod repair;
+pub mod split;
+pub mod writer;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use zip::ZipArchive;
+
+use crate::decode::maybe_ungzip;
+use crate::defn::ImageFormat;
+use crate::err::{I3SError, Result};
+use crate::rm::{Accessor, UriBuilder};
+
+/// Name of the optional Esri hash-index entry: MD5 of each entry path plus
+/// the offset of its local file header, for lookup without scanning the
+/// central directory.
+const HASH_INDEX_ENTRY: &str = "@specialIndexFileUSERDATA@";
+
+/// A parsed `@specialIndexFileUSERDATA@` entry.
+struct HashIndex {
+    offsets: std::collections::HashMap<[u8; 16], u64>,
+}
+
+impl HashIndex {
+    /// Parse the fixed 24-byte records (16-byte path MD5, 8-byte offset).
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.is_empty() || !bytes.len().is_multiple_of(24) {
+            return None;
+        }
+        let offsets = bytes
+            .chunks_exact(24)
+            .map(|record| {
+                let mut hash = [0u8; 16];
+                hash.copy_from_slice(&record[..16]);
+                let offset = u64::from_le_bytes(record[16..24].try_into().unwrap());
+                (hash, offset)
+            })
+            .collect();
+        Some(Self { offsets })
+    }
+
+    /// The local-header offset of an entry, if indexed.
+    fn offset(&self, name: &str) -> Option<u64> {
+        self.offsets.get(&md5::compute(name.as_bytes()).0).copied()
+    }
+}
+
+/// A type-erased `Read + Seek` source for archives that do not live on
+/// disk (in-memory bytes, readers embedded in other containers).
+trait ArchiveRead: std::io::Read + std::io::Seek + Send {}
+impl<T: std::io::Read + std::io::Seek + Send> ArchiveRead for T {}
+
+/// Where an opened package reads its bytes from.
+enum Source {
+    /// An on-disk file; fetches go through a pool of independent readers.
+    File { path: PathBuf, pool: ReaderPool },
+    /// An arbitrary reader; fetches serialize on one archive.
+    Reader(Mutex<ZipArchive<Box<dyn ArchiveRead>>>),
+}
+
+impl Source {
+    /// Stream an entry into `sink`; bytes written, 0 for directories.
+    fn stream_entry(&self, name: &str, sink: &mut dyn std::io::Write) -> Result<u64> {
+        match self {
+            Self::File { pool, .. } => {
+                pool.with(|archive| stream_archive_entry(archive, name, sink))
+            }
+            Self::Reader(archive) => {
+                stream_archive_entry(&mut archive.lock().expect("archive poisoned"), name, sink)
+            }
+        }
+    }
+
+    /// Read an entry in full; `Ok(None)` when the entry is a directory.
+    fn read_entry(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::File { pool, .. } => pool.with(|archive| read_archive_entry(archive, name)),
+            Self::Reader(archive) => {
+                read_archive_entry(&mut archive.lock().expect("archive poisoned"), name)
+            }
+        }
+    }
+
+    /// Compressed size of an entry.
+    fn entry_size(&self, name: &str) -> Result<u64> {
+        match self {
+            Self::File { pool, .. } => pool.with(|archive| archive_entry_size(archive, name)),
+            Self::Reader(archive) => {
+                archive_entry_size(&mut archive.lock().expect("archive poisoned"), name)
+            }
+        }
+    }
+
+    /// Entry names inside the archive.
+    fn entry_names(&self) -> Vec<String> {
+        match self {
+            Self::File { pool, .. } => pool
+                .with(|archive| Ok(archive.file_names().map(str::to_string).collect()))
+                .unwrap_or_default(),
+            Self::Reader(archive) => archive
+                .lock()
+                .expect("archive poisoned")
+                .file_names()
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+fn read_archive_entry<R: std::io::Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| I3SError::MissingResource(name.to_string()))?;
+    if entry.is_dir() {
+        return Ok(None);
+    }
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    std::io::Read::read_to_end(&mut entry, &mut bytes)?;
+    Ok(Some(bytes))
+}
+
+fn stream_archive_entry<R: std::io::Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+    sink: &mut dyn std::io::Write,
+) -> Result<u64> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| I3SError::MissingResource(name.to_string()))?;
+    if entry.is_dir() {
+        return Ok(0);
+    }
+    stream_maybe_ungzip(&mut entry, sink)
+}
+
+/// Copy `reader` into `sink`, transparently inflating a gzip wrapper, with
+/// bounded memory regardless of entry size.
+fn stream_maybe_ungzip(reader: &mut dyn std::io::Read, sink: &mut dyn std::io::Write) -> Result<u64> {
+    use std::io::Read;
+    let mut magic = [0u8; 2];
+    let mut have = 0;
+    while have < 2 {
+        let read = reader.read(&mut magic[have..])?;
+        if read == 0 {
+            break;
+        }
+        have += read;
+    }
+    if have == 2 && magic == [0x1f, 0x8b] {
+        let chained = std::io::Cursor::new(magic).chain(reader);
+        let mut decoder = flate2::read::GzDecoder::new(chained);
+        Ok(std::io::copy(&mut decoder, sink)?)
+    } else {
+        sink.write_all(&magic[..have])?;
+        Ok(have as u64 + std::io::copy(reader, sink)?)
+    }
+}
+
+/// The compressed size recorded in a local header's Zip64 extended
+/// information extra field (header id `0x0001`); local headers carry the
+/// original size followed by the compressed size.
+fn zip64_compressed_size(extra: &[u8]) -> Option<u64> {
+    let mut rest = extra;
+    while rest.len() >= 4 {
+        let id = u16::from_le_bytes([rest[0], rest[1]]);
+        let size = usize::from(u16::from_le_bytes([rest[2], rest[3]]));
+        let data = rest.get(4..4 + size)?;
+        if id == 0x0001 {
+            if data.len() >= 16 {
+                return Some(u64::from_le_bytes(data[8..16].try_into().unwrap()));
+            }
+            return None;
+        }
+        rest = &rest[4 + size..];
+    }
+    None
+}
+
+fn archive_entry_size<R: std::io::Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<u64> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|_| I3SError::MissingResource(name.to_string()))?;
+    Ok(entry.size())
+}
+
+/// Identity of the archive file at open time. An editor commits by
+/// writing a new archive next to the original and atomically renaming it
+/// over the path, so a package opened earlier must not mix readers of the
+/// two generations: lazily-opened readers are admitted only while the path
+/// still names the original file.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct FileId {
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+    #[cfg(unix)]
+    dev: u64,
+    #[cfg(unix)]
+    ino: u64,
+}
+
+impl FileId {
+    fn of(metadata: &std::fs::Metadata) -> Self {
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            dev: metadata.dev(),
+            #[cfg(unix)]
+            ino: metadata.ino(),
+        }
+    }
+}
+
+/// A pool of independent archive readers, so resource fetches from
+/// several threads do not serialize on one file handle. Readers are opened
+/// lazily (each one re-reads the central directory) and a bounded number
+/// of idle readers is kept for reuse.
+///
+/// The pool pins the archive generation it was opened against: once the
+/// path has been swapped by an editor commit, no new readers are opened
+/// and fetches wait for one of the original handles instead, so an open
+/// package keeps serving a consistent snapshot.
+struct ReaderPool {
+    path: PathBuf,
+    id: FileId,
+    idle: Mutex<Vec<ZipArchive<File>>>,
+    returned: std::sync::Condvar,
+}
+
+impl ReaderPool {
+    /// Idle readers kept around between fetches.
+    const MAX_IDLE: usize = 8;
+
+    fn new(path: PathBuf, id: FileId, first: ZipArchive<File>) -> Self {
+        Self {
+            path,
+            id,
+            idle: Mutex::new(vec![first]),
+            returned: std::sync::Condvar::new(),
+        }
+    }
+
+    /// Whether the path still names the file this pool was opened against.
+    fn path_is_current(&self) -> bool {
+        std::fs::metadata(&self.path)
+            .map(|metadata| FileId::of(&metadata) == self.id)
+            .unwrap_or(false)
+    }
+
+    /// Run `work` with a pooled reader. When all are busy, a fresh one is
+    /// opened — unless the file has been swapped out under the path, in
+    /// which case the call waits for a reader of the original file.
+    fn with<R>(&self, work: impl FnOnce(&mut ZipArchive<File>) -> Result<R>) -> Result<R> {
+        let mut reader = {
+            let mut idle = self.idle.lock().expect("reader pool poisoned");
+            loop {
+                if let Some(reader) = idle.pop() {
+                    break reader;
+                }
+                if self.path_is_current() {
+                    drop(idle);
+                    break ZipArchive::new(File::open(&self.path)?)?;
+                }
+                idle = self
+                    .returned
+                    .wait(idle)
+                    .expect("reader pool poisoned");
+            }
+        };
+        let out = work(&mut reader);
+        let mut idle = self.idle.lock().expect("reader pool poisoned");
+        if idle.len() < Self::MAX_IDLE {
+            idle.push(reader);
+        }
+        drop(idle);
+        self.returned.notify_one();
+        out
+    }
+}
+
+/// An SLPK archive opened for reading.
+pub struct SceneLayerPackage {
+    source: Source,
+    index: Option<HashIndex>,
+    cache: crate::cache::BoundedCache<String, Vec<u8>>,
+}
+
+impl SceneLayerPackage {
+    /// Open an SLPK file from disk.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let id = FileId::of(&file.metadata()?);
+        let mut archive = ZipArchive::new(file)?;
+        let index = match archive.by_name(HASH_INDEX_ENTRY) {
+            Ok(mut entry) => {
+                let mut bytes = Vec::with_capacity(entry.size() as usize);
+                std::io::Read::read_to_end(&mut entry, &mut bytes)?;
+                HashIndex::parse(&bytes)
+            }
+            Err(_) => None,
+        };
+        Ok(Self {
+            source: Source::File {
+                pool: ReaderPool::new(path.clone(), id, archive),
+                path,
+            },
+            index,
+            cache: crate::cache::BoundedCache::new(),
+        })
+    }
+
+    /// Open an SLPK from any seekable reader — a layer embedded in another
+    /// container, a download, a test fixture. Reads serialize on the one
+    /// reader, so prefer [`open`](Self::open) for files on disk. The result
+    /// still implements [`Backend`](crate::rm::Backend), so it plugs into
+    /// [`SceneLayer::from_backend`](crate::SceneLayer::from_backend).
+    pub fn from_reader<R>(reader: R) -> Result<Self>
+    where
+        R: std::io::Read + std::io::Seek + Send + 'static,
+    {
+        let boxed: Box<dyn ArchiveRead> = Box::new(reader);
+        let archive = ZipArchive::new(boxed)?;
+        Ok(Self {
+            source: Source::Reader(Mutex::new(archive)),
+            index: None,
+            cache: crate::cache::BoundedCache::new(),
+        })
+    }
+
+    /// Open an SLPK held entirely in memory.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        Self::from_reader(std::io::Cursor::new(bytes))
+    }
+
+    /// Bound the in-memory entry cache to `budget` bytes with LRU
+    /// eviction, or lift the bound with `None`.
+    pub fn set_cache_budget(&self, budget: Option<u64>) {
+        self.cache.set_budget(budget);
+    }
+
+    /// Whether the package carries a usable hash index.
+    pub fn has_hash_index(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Serve a STOREd entry through the hash index: seek straight to its
+    /// local header without touching the shared archive lock. `Ok(None)`
+    /// when the entry is not indexed or needs the regular inflate path.
+    fn get_indexed(&self, uri: &str) -> Result<Option<Vec<u8>>> {
+        let Some(offset) = self.index.as_ref().and_then(|index| index.offset(uri)) else {
+            return Ok(None);
+        };
+        let Source::File { path, pool } = &self.source else {
+            return Ok(None);
+        };
+        // After an editor commit swapped the file, by-path reads would see
+        // the new archive; fall back to the pooled snapshot readers.
+        if !pool.path_is_current() {
+            return Ok(None);
+        }
+        use std::io::{Read, Seek, SeekFrom};
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut header = [0u8; 30];
+        file.read_exact(&mut header)?;
+        if header[..4] != [0x50, 0x4b, 0x03, 0x04] {
+            return Ok(None);
+        }
+        let flags = u16::from_le_bytes([header[6], header[7]]);
+        let method = u16::from_le_bytes([header[8], header[9]]);
+        let compressed_size = u32::from_le_bytes(header[18..22].try_into().unwrap());
+        // Only plain STOREd entries with sizes in the header qualify;
+        // everything else goes through the central-directory path.
+        if method != 0 || flags & 0x08 != 0 {
+            return Ok(None);
+        }
+        let name_len = u16::from_le_bytes([header[26], header[27]]) as i64;
+        let extra_len = usize::from(u16::from_le_bytes([header[28], header[29]]));
+        file.seek(SeekFrom::Current(name_len))?;
+        let mut extra = vec![0u8; extra_len];
+        file.read_exact(&mut extra)?;
+        // A saturated size field means the real size lives in the Zip64
+        // extra field.
+        let compressed_size = if compressed_size == u32::MAX {
+            match zip64_compressed_size(&extra) {
+                Some(size) => size,
+                None => return Ok(None),
+            }
+        } else {
+            u64::from(compressed_size)
+        };
+        let mut bytes = vec![0u8; compressed_size as usize];
+        file.read_exact(&mut bytes)?;
+        Ok(Some(bytes))
+    }
+
+    /// Read an entry without stripping its gzip wrapper, for byte-exact
+    /// copies into another package. `Ok(None)` for directories.
+    pub(crate) fn raw_entry(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.source.read_entry(name)
+    }
+
+    /// Stream a resource into `sink` without buffering it whole: the zip
+    /// and gzip layers are decompressed on the fly, so multi-gigabyte
+    /// attribute or geometry entries need only a bounded working set.
+    /// Returns the number of decompressed bytes written; the resource
+    /// cache is bypassed.
+    pub fn stream_to(&self, uri: &str, sink: &mut dyn std::io::Write) -> Result<u64> {
+        self.source.stream_entry(uri, sink)
+    }
+
+    /// Path of the underlying archive; `None` for reader-backed packages.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.source {
+            Source::File { path, .. } => Some(path),
+            Source::Reader(_) => None,
+        }
+    }
+
+    /// Entry names inside the archive.
+    pub fn entries(&self) -> Vec<String> {
+        self.source.entry_names()
+    }
+
+    /// Catalog metadata from the embedded `esriinfo/iteminfo.xml`, or
+    /// `Ok(None)` when the package carries none. Parsed without opening
+    /// the layer, so catalog software can list title, tags and credits
+    /// from the archive alone.
+    pub fn item_info(&self) -> Result<Option<ItemInfo>> {
+        let Ok(Some(bytes)) = self.source.read_entry("esriinfo/iteminfo.xml") else {
+            return Ok(None);
+        };
+        let xml = String::from_utf8(maybe_ungzip(bytes)?)
+            .map_err(|e| I3SError::Decode(format!("iteminfo.xml is not UTF-8: {e}")))?;
+        Ok(Some(ItemInfo {
+            title: xml_element_text(&xml, "title"),
+            snippet: xml_element_text(&xml, "snippet"),
+            description: xml_element_text(&xml, "description"),
+            tags: xml_element_text(&xml, "tags")
+                .map(|tags| {
+                    tags.split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            credits: xml_element_text(&xml, "accessinformation"),
+            license_info: xml_element_text(&xml, "licenseinfo"),
+        }))
+    }
+
+    /// The preview image under `esriinfo/thumbnail/`, or `Ok(None)` when
+    /// the package has none (or only a format this crate does not name).
+    pub fn thumbnail(&self) -> Result<Option<Thumbnail>> {
+        for name in self.entries() {
+            let Some(file) = name.strip_prefix("esriinfo/thumbnail/") else {
+                continue;
+            };
+            let format = match file.rsplit_once('.').map(|(_, ext)| ext) {
+                Some("jpg") | Some("jpeg") => ImageFormat::Jpg,
+                Some("png") => ImageFormat::Png,
+                _ => continue,
+            };
+            let Some(bytes) = self.source.read_entry(&name)? else {
+                continue;
+            };
+            return Ok(Some(Thumbnail { format, bytes }));
+        }
+        Ok(None)
+    }
+
+    /// Unpack the archive into the static "exploded" folder layout under
+    /// `dir`: every entry is written to the path it has inside the archive,
+    /// with gzipped resources decompressed and their `.gz` suffix dropped.
+    /// The result can be hosted as-is on any static file server or CDN.
+    pub fn explode_to(&self, dir: impl AsRef<Path>) -> Result<ExplodeReport> {
+        let dir = dir.as_ref();
+        let mut report = ExplodeReport::default();
+        for name in self.entries() {
+            if name.ends_with('/') {
+                continue;
+            }
+            let target = dir.join(name.strip_suffix(".gz").unwrap_or(&name));
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = std::io::BufWriter::new(File::create(&target)?);
+            let written = self.source.stream_entry(&name, &mut file)?;
+            std::io::Write::flush(&mut file)?;
+            report.files_written += 1;
+            report.bytes_written += written;
+        }
+        Ok(report)
+    }
+}
+
+/// Catalog metadata embedded in a package's `esriinfo/iteminfo.xml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemInfo {
+    pub title: Option<String>,
+    /// The short summary shown in item galleries.
+    pub snippet: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// Credits, from `accessinformation`.
+    pub credits: Option<String>,
+    pub license_info: Option<String>,
+}
+
+/// The preview image embedded under `esriinfo/thumbnail/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub format: ImageFormat,
+    pub bytes: Vec<u8>,
+}
+
+/// The text of the first `<tag>...</tag>` element, with the common XML
+/// entities unescaped. Good enough for the flat elements of an
+/// `iteminfo.xml`; attributes and nesting are not interpreted.
+fn xml_element_text(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    let text = xml[start..end]
+        .trim()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    (!text.is_empty()).then_some(text)
+}
+
+/// What [`SceneLayerPackage::explode_to`] wrote.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExplodeReport {
+    /// Files created under the output directory.
+    pub files_written: usize,
+    /// Total decompressed bytes written.
+    pub bytes_written: u64,
+}
+
+impl Accessor for SceneLayerPackage {
+    fn get(&self, uri: &str) -> Result<Arc<Vec<u8>>> {
+        if let Some(hit) = self.cache.get(&uri.to_string()) {
+            return Ok(hit);
+        }
+        // Archive and inflate failures name the entry they belong to.
+        let fetched: Result<Arc<Vec<u8>>> = (|| {
+            if let Some(raw) = self.get_indexed(uri)? {
+                return Ok(Arc::new(maybe_ungzip(raw)?));
+            }
+            let bytes = self
+                .source
+                .read_entry(uri)?
+                .ok_or_else(|| I3SError::MissingResource(uri.to_string()))?;
+            Ok(Arc::new(maybe_ungzip(bytes)?))
+        })();
+        let bytes = fetched.map_err(|e| e.with_uri(uri))?;
+        self.cache
+            .insert(uri.to_string(), bytes.len() as u64, Arc::clone(&bytes));
+        Ok(bytes)
+    }
+
+    fn size(&self, uri: &str) -> Result<Option<u64>> {
+        self.source.entry_size(uri).map(Some)
+    }
+}
+
+impl UriBuilder for SceneLayerPackage {
+    fn scene_definition_uri(&self) -> String {
+        "3dSceneLayer.json.gz".to_string()
+    }
+
+    fn node_page_uri(&self, page_index: usize) -> String {
+        format!("nodepages/{page_index}.json.gz")
+    }
+
+    fn geometry_uri(&self, node_index: usize, resource: usize) -> String {
+        format!("nodes/{node_index}/geometries/{resource}.bin.gz")
+    }
+
+    fn texture_uri(&self, node_index: usize, name: &str, format: ImageFormat) -> String {
+        let ext = match format {
+            ImageFormat::Jpg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Dds => "bin.dds.gz",
+            ImageFormat::Ktx2 => "ktx2",
+            ImageFormat::Basis => "basis",
+            ImageFormat::KtxEtc2 => "ktx",
+        };
+        format!("nodes/{node_index}/textures/{name}.{ext}")
+    }
+
+    fn attribute_uri(&self, node_index: usize, key: &str) -> String {
+        format!("nodes/{node_index}/attributes/{key}/0.bin.gz")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slpk::writer::SlpkWriter;
+
+    #[test]
+    fn pooled_readers_serve_parallel_fetches() {
+        let dir = crate::testutil::TestDir::new("pool");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        for node in 0..16usize {
+            writer
+                .write_geometry(node, 0, &[node as u8; 16])
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        let package = Arc::new(SceneLayerPackage::open(&path).unwrap());
+        std::thread::scope(|scope| {
+            for thread in 0..4usize {
+                let package = Arc::clone(&package);
+                scope.spawn(move || {
+                    for node in 0..16usize {
+                        let uri = package.geometry_uri(node, 0);
+                        let bytes = package.get(&uri).unwrap();
+                        assert_eq!(&*bytes, &vec![node as u8; 16], "thread {thread}");
+                    }
+                });
+            }
+        });
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn hash_index_serves_stored_entries() {
+        let dir = crate::testutil::TestDir::new("hash-index");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": "indexed",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        // Append the hash index the way packaging tools do: one 24-byte
+        // record per entry, MD5 of the path plus the local-header offset.
+        let mut records = Vec::new();
+        {
+            let file = File::open(&path).unwrap();
+            let mut archive = ZipArchive::new(file).unwrap();
+            for i in 0..archive.len() {
+                let entry = archive.by_index_raw(i).unwrap();
+                records.extend_from_slice(&md5::compute(entry.name().as_bytes()).0);
+                records.extend_from_slice(&entry.header_start().to_le_bytes());
+            }
+        }
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        let mut appender = zip::ZipWriter::new_append(file).unwrap();
+        appender
+            .start_file::<_, ()>(
+                HASH_INDEX_ENTRY,
+                zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored),
+            )
+            .unwrap();
+        std::io::Write::write_all(&mut appender, &records).unwrap();
+        appender.finish().unwrap();
+
+        let package = SceneLayerPackage::open(&path).unwrap();
+        assert!(package.has_hash_index());
+        let raw = package.get_indexed(&package.geometry_uri(0, 0)).unwrap();
+        assert!(raw.is_some());
+        let geometry = package.get(&package.geometry_uri(0, 0)).unwrap();
+        assert_eq!(&*geometry, &vec![1u8, 2, 3]);
+
+        // Entries outside the index still resolve through the archive.
+        let mut plain = SceneLayerPackage::open(&path).unwrap();
+        plain.index = None;
+        let geometry = plain.get(&plain.geometry_uri(0, 0)).unwrap();
+        assert_eq!(&*geometry, &vec![1u8, 2, 3]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn zip64_entries_read_through_the_hash_index() {
+        let dir = crate::testutil::TestDir::new("zip64");
+        let path = dir.join("large.slpk");
+
+        // Force the Zip64 format for one STOREd entry, the layout huge
+        // archives use.
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .large_file(true);
+        writer.start_file("nodes/0/geometries/0.bin", options).unwrap();
+        std::io::Write::write_all(&mut writer, &[7u8; 64]).unwrap();
+        writer.finish().unwrap();
+
+        let mut records = Vec::new();
+        {
+            let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+            let entry = archive.by_index_raw(0).unwrap();
+            records.extend_from_slice(&md5::compute(entry.name().as_bytes()).0);
+            records.extend_from_slice(&entry.header_start().to_le_bytes());
+        }
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        let mut appender = zip::ZipWriter::new_append(file).unwrap();
+        appender
+            .start_file::<_, ()>(
+                HASH_INDEX_ENTRY,
+                zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored),
+            )
+            .unwrap();
+        std::io::Write::write_all(&mut appender, &records).unwrap();
+        appender.finish().unwrap();
+
+        let package = SceneLayerPackage::open(&path).unwrap();
+        assert!(package.has_hash_index());
+        let indexed = package.get_indexed("nodes/0/geometries/0.bin").unwrap();
+        assert_eq!(indexed, Some(vec![7u8; 64]));
+        let bytes = package.get("nodes/0/geometries/0.bin").unwrap();
+        assert_eq!(&*bytes, &vec![7u8; 64]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn streaming_matches_buffered_reads() {
+        let dir = crate::testutil::TestDir::new("stream");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let payload: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_geometry(0, 0, &payload).unwrap();
+        writer.write_raw("esriinfo/iteminfo.xml", b"<item/>").unwrap();
+        writer.finish().unwrap();
+
+        let package = SceneLayerPackage::open(&path).unwrap();
+        // Gzipped entry: streamed bytes are the inflated resource.
+        let mut streamed = Vec::new();
+        let written = package
+            .stream_to(&package.geometry_uri(0, 0), &mut streamed)
+            .unwrap();
+        assert_eq!(written, payload.len() as u64);
+        assert_eq!(streamed, payload);
+        // Raw entry: passed through untouched.
+        let mut streamed = Vec::new();
+        package
+            .stream_to("esriinfo/iteminfo.xml", &mut streamed)
+            .unwrap();
+        assert_eq!(streamed, b"<item/>");
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn open_packages_snapshot_across_atomic_swaps() {
+        let dir = crate::testutil::TestDir::new("snapshot");
+        let path = dir.join("layer.slpk");
+        let staged = dir.join("staged.slpk");
+
+        let write = |path: &std::path::Path, name: &str, payload: &[u8]| {
+            let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+                "id": 0,
+                "name": name,
+                "layerType": "IntegratedMesh",
+                "store": { "profile": "meshpyramids" },
+                "nodePages": { "nodesPerPage": 64 }
+            }))
+            .unwrap();
+            let mut writer = SlpkWriter::create(path).unwrap();
+            writer.write_scene_definition(&defn).unwrap();
+            writer.write_geometry(0, 0, payload).unwrap();
+            writer.finish().unwrap();
+        };
+        write(&path, "original", b"\x01\x02\x03");
+        write(&staged, "edited", b"\x09\x09\x09");
+
+        let package = SceneLayerPackage::open(&path).unwrap();
+        // Commit an edit the way the editor does: atomic rename over the
+        // open path.
+        std::fs::rename(&staged, &path).unwrap();
+
+        // The package opened before the swap keeps serving the original.
+        let defn = package.get(&package.scene_definition_uri()).unwrap();
+        let defn: crate::defn::SceneDefinition =
+            crate::defn::SceneDefinition::from_slice(&defn).unwrap();
+        assert_eq!(defn.name.as_deref(), Some("original"));
+        let geometry = package.get(&package.geometry_uri(0, 0)).unwrap();
+        assert_eq!(&*geometry, &vec![1u8, 2, 3]);
+
+        // Reopening the path observes the committed edit.
+        let reopened = SceneLayerPackage::open(&path).unwrap();
+        let defn = reopened.get(&reopened.scene_definition_uri()).unwrap();
+        let defn: crate::defn::SceneDefinition =
+            crate::defn::SceneDefinition::from_slice(&defn).unwrap();
+        assert_eq!(defn.name.as_deref(), Some("edited"));
+    }
+
+    #[test]
+    fn in_memory_package_opens_without_a_file() {
+        let dir = crate::testutil::TestDir::new("bytes");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": "in-memory",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let package = SceneLayerPackage::from_bytes(bytes).unwrap();
+        assert!(package.path().is_none());
+        let geometry = package.get(&package.geometry_uri(0, 0)).unwrap();
+        assert_eq!(&*geometry, &vec![1u8, 2, 3]);
+
+        let layer = crate::layer::SceneLayer::from_backend(package).unwrap();
+        assert_eq!(layer.name(), Some("in-memory"));
+        assert_eq!(layer.root().unwrap().index, 0);
+    }
+
+    #[test]
+    fn item_info_and_thumbnail_read_without_a_layer_open() {
+        let dir = crate::testutil::TestDir::new("iteminfo");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer
+            .write_raw(
+                "esriinfo/iteminfo.xml",
+                br#"<ESRI_ItemInformation>
+                  <title>Downtown &amp; Harbour</title>
+                  <snippet>Textured mesh of the waterfront</snippet>
+                  <tags>mesh, i3s, survey</tags>
+                  <accessinformation>City survey office</accessinformation>
+                </ESRI_ItemInformation>"#,
+            )
+            .unwrap();
+        writer
+            .write_raw("esriinfo/thumbnail/thumbnail.png", b"\x89PNG-ish")
+            .unwrap();
+        writer.finish().unwrap();
+
+        let package = SceneLayerPackage::open(&path).unwrap();
+        let info = package.item_info().unwrap().unwrap();
+        assert_eq!(info.title.as_deref(), Some("Downtown & Harbour"));
+        assert_eq!(
+            info.snippet.as_deref(),
+            Some("Textured mesh of the waterfront")
+        );
+        assert_eq!(info.tags, vec!["mesh", "i3s", "survey"]);
+        assert_eq!(info.credits.as_deref(), Some("City survey office"));
+        assert_eq!(info.description, None);
+
+        let thumbnail = package.thumbnail().unwrap().unwrap();
+        assert_eq!(thumbnail.format, ImageFormat::Png);
+        assert_eq!(thumbnail.bytes, b"\x89PNG-ish");
+
+        // A package without esriinfo reports neither.
+        let bare = dir.join("bare.slpk");
+        let mut writer = SlpkWriter::create(&bare).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.finish().unwrap();
+        let bare = SceneLayerPackage::open(&bare).unwrap();
+        assert_eq!(bare.item_info().unwrap(), None);
+        assert_eq!(bare.thumbnail().unwrap(), None);
+    }
+
+    #[test]
+    fn explode_decompresses_and_strips_gz_suffix() {
+        let dir = crate::testutil::TestDir::new("explode");
+        let path = dir.join("layer.slpk");
+        let out = dir.join("exploded");
+        std::fs::remove_dir_all(&out).ok();
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": "exploded",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        let package = SceneLayerPackage::open(&path).unwrap();
+        let report = package.explode_to(&out).unwrap();
+        // Scene definition, geometry and the writer's metadata.json.
+        assert_eq!(report.files_written, 3);
+
+        let doc = std::fs::read(out.join("3dSceneLayer.json")).unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&doc).unwrap();
+        assert_eq!(parsed["name"], "exploded");
+        let geometry = std::fs::read(out.join("nodes/0/geometries/0.bin")).unwrap();
+        assert_eq!(geometry, vec![1, 2, 3]);
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_dir_all(&out).ok();
+    }
+}
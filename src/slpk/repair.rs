@@ -0,0 +1,344 @@
+//! Best-effort repair of damaged scene layer packages.
+//!
+//! Interrupted copies and flaky media leave SLPKs with truncated tails,
+//! corrupt payloads or an unreadable central directory. [`repair_slpk`]
+//! ignores the central directory entirely and scans the file for local
+//! entry headers instead, salvages every entry whose payload survives its
+//! CRC check, and writes a fresh package: entries reordered per the SLPK
+//! spec (scene definition first, hash index last), resources re-stored,
+//! `metadata.json` regenerated when it was lost, and the hash index
+//! rebuilt from scratch. The report names what could not be saved.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use super::{zip64_compressed_size, HASH_INDEX_ENTRY};
+use crate::err::{I3SError, Result};
+
+/// Local file header signature `PK\x03\x04`.
+const LOCAL_HEADER: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+
+/// What [`repair_slpk`] salvaged and what it had to give up on.
+#[derive(Debug, Default, Clone)]
+pub struct RepairReport {
+    /// Entries salvaged intact from the damaged package.
+    pub salvaged: usize,
+    /// Entries that were found but failed their CRC check or were cut
+    /// off by truncation.
+    pub lost: Vec<String>,
+    /// Bytes of the damaged file that held no recognizable entry.
+    pub skipped_bytes: u64,
+    /// Whether `metadata.json` had to be regenerated.
+    pub rebuilt_metadata: bool,
+    /// Whether the repaired package carries a scene definition; without
+    /// one it will not open as a layer.
+    pub has_scene_definition: bool,
+}
+
+/// One salvaged entry: its path and STOREd payload bytes.
+struct Salvaged {
+    name: String,
+    payload: Vec<u8>,
+}
+
+/// Rebuild a readable package at `out` from the damaged one at `damaged`.
+///
+/// The output always gets a fresh hash index and spec ordering; damage is
+/// reflected only in the report, so callers decide whether a package that
+/// lost entries is still worth serving.
+pub fn repair_slpk(damaged: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<RepairReport> {
+    let mut report = RepairReport::default();
+    let salvaged = scan_entries(damaged.as_ref(), &mut report)?;
+    write_repaired(out.as_ref(), salvaged, &mut report)?;
+    Ok(report)
+}
+
+/// Scan `path` for local entry headers and salvage every verifiable
+/// payload, tolerating garbage between entries and a missing central
+/// directory. When a name occurs more than once the last occurrence wins,
+/// matching ZIP semantics.
+fn scan_entries(path: &Path, report: &mut RepairReport) -> Result<Vec<Salvaged>> {
+    let mut file = std::io::BufReader::new(File::open(path)?);
+    let len = file.seek(SeekFrom::End(0))?;
+    let mut entries: Vec<Salvaged> = Vec::new();
+    let mut pos = 0u64;
+    while pos + 4 <= len {
+        file.seek(SeekFrom::Start(pos))?;
+        let mut signature = [0u8; 4];
+        file.read_exact(&mut signature)?;
+        if signature != LOCAL_HEADER {
+            // Central directory or end-of-archive records mean no further
+            // local entries follow.
+            if signature[..2] == [0x50, 0x4b] && (signature[3] == 0x02 || signature[3] == 0x06) {
+                break;
+            }
+            pos += 1;
+            report.skipped_bytes += 1;
+            continue;
+        }
+        match salvage_entry(&mut file, len, pos) {
+            Ok((entry, next)) => {
+                if let Some(entry) = entry {
+                    if let Some(previous) =
+                        entries.iter_mut().find(|have| have.name == entry.name)
+                    {
+                        *previous = entry;
+                    } else {
+                        entries.push(entry);
+                    }
+                }
+                pos = next;
+            }
+            Err(name) => {
+                // Truncated or corrupt: record it, then resume the scan
+                // right after the header so a later intact entry is still
+                // found.
+                if let Some(name) = name {
+                    report.lost.push(name);
+                }
+                pos += 4;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Parse and verify one entry whose local header starts at `pos`; the
+/// 4-byte signature has already been consumed. Returns the salvaged entry
+/// (`None` for directories and data-descriptor entries we cannot trust)
+/// and the offset to continue scanning from, or the entry name on damage.
+fn salvage_entry(
+    file: &mut (impl Read + Seek),
+    len: u64,
+    pos: u64,
+) -> std::result::Result<(Option<Salvaged>, u64), Option<String>> {
+    let mut header = [0u8; 26];
+    file.read_exact(&mut header).map_err(|_| None)?;
+    let flags = u16::from_le_bytes([header[2], header[3]]);
+    let method = u16::from_le_bytes([header[4], header[5]]);
+    let crc = u32::from_le_bytes(header[10..14].try_into().unwrap());
+    let compressed_size = u32::from_le_bytes(header[14..18].try_into().unwrap());
+    let name_len = usize::from(u16::from_le_bytes([header[22], header[23]]));
+    let extra_len = usize::from(u16::from_le_bytes([header[24], header[25]]));
+
+    let mut name = vec![0u8; name_len];
+    file.read_exact(&mut name).map_err(|_| None)?;
+    let name = String::from_utf8(name).map_err(|_| None)?;
+    let mut extra = vec![0u8; extra_len];
+    file.read_exact(&mut extra).map_err(|_| Some(name.clone()))?;
+
+    // Without sizes in the header the payload boundary is unknowable
+    // here; skip past the header and let the scan find the next entry.
+    if flags & 0x08 != 0 {
+        return Ok((None, pos + 30 + name_len as u64 + extra_len as u64));
+    }
+    let compressed_size = if compressed_size == u32::MAX {
+        zip64_compressed_size(&extra).ok_or_else(|| Some(name.clone()))?
+    } else {
+        u64::from(compressed_size)
+    };
+    let data_start = pos + 30 + name_len as u64 + extra_len as u64;
+    if data_start + compressed_size > len {
+        return Err(Some(name));
+    }
+    let mut payload = vec![0u8; compressed_size as usize];
+    file.read_exact(&mut payload).map_err(|_| Some(name.clone()))?;
+    if name.ends_with('/') {
+        return Ok((None, data_start + compressed_size));
+    }
+    // Re-store the payload: inflate DEFLATEd entries (the spec wants
+    // STORE), refuse methods we cannot verify.
+    let payload = match method {
+        0 => payload,
+        8 => {
+            let mut inflated = Vec::new();
+            flate2::read::DeflateDecoder::new(payload.as_slice())
+                .read_to_end(&mut inflated)
+                .map_err(|_| Some(name.clone()))?;
+            inflated
+        }
+        _ => return Err(Some(name)),
+    };
+    if crc32fast::hash(&payload) != crc {
+        return Err(Some(name));
+    }
+    Ok((
+        Some(Salvaged { name, payload }),
+        data_start + compressed_size,
+    ))
+}
+
+/// Spec position of an entry: scene definition first, then metadata and
+/// node pages, then per-node resources, extras at the back and the hash
+/// index last.
+fn spec_rank(name: &str) -> u8 {
+    if name.starts_with("3dSceneLayer.json") {
+        0
+    } else if name == "metadata.json" {
+        1
+    } else if name.starts_with("nodepages/") {
+        2
+    } else if name.starts_with("nodes/") {
+        3
+    } else if name == HASH_INDEX_ENTRY {
+        5
+    } else {
+        4
+    }
+}
+
+/// Write the salvaged entries to `out` in spec order with a regenerated
+/// `metadata.json` and a freshly built hash index.
+fn write_repaired(out: &Path, mut entries: Vec<Salvaged>, report: &mut RepairReport) -> Result<()> {
+    // The old index is stale by construction; metadata survives only when
+    // it still parses.
+    entries.retain(|entry| entry.name != HASH_INDEX_ENTRY);
+    entries.retain(|entry| {
+        entry.name != "metadata.json"
+            || serde_json::from_slice::<serde_json::Value>(&entry.payload).is_ok()
+    });
+    report.salvaged = entries.len();
+    if !entries.iter().any(|entry| entry.name == "metadata.json") {
+        let metadata = serde_json::json!({
+            "folderPattern": "BASIC",
+            "archiveCompressionType": "STORE",
+            "resourceCompressionType": "GZIP",
+            "I3SVersion": "1.8",
+        });
+        entries.push(Salvaged {
+            name: "metadata.json".to_string(),
+            payload: serde_json::to_vec(&metadata)
+                .map_err(|e| I3SError::json("metadata.json", e))?,
+        });
+        report.rebuilt_metadata = true;
+    }
+    entries.sort_by(|a, b| {
+        spec_rank(&a.name)
+            .cmp(&spec_rank(&b.name))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    report.has_scene_definition = entries
+        .iter()
+        .any(|entry| entry.name.starts_with("3dSceneLayer.json"));
+
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored);
+    let mut writer = zip::ZipWriter::new(File::create(out)?);
+    for entry in &entries {
+        writer.start_file(&*entry.name, options)?;
+        writer.write_all(&entry.payload)?;
+    }
+    writer.finish()?;
+
+    // Rebuild the hash index from the archive just written, so the
+    // recorded offsets match the new layout.
+    let mut records = Vec::new();
+    {
+        let mut archive = ZipArchive::new(File::open(out)?)?;
+        for i in 0..archive.len() {
+            let entry = archive.by_index_raw(i)?;
+            records.extend_from_slice(&md5::compute(entry.name().as_bytes()).0);
+            records.extend_from_slice(&entry.header_start().to_le_bytes());
+        }
+    }
+    let file = std::fs::OpenOptions::new().read(true).write(true).open(out)?;
+    let mut appender = zip::ZipWriter::new_append(file)?;
+    appender.start_file(HASH_INDEX_ENTRY, options)?;
+    appender.write_all(&records)?;
+    appender.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rm::{Accessor, UriBuilder};
+    use crate::slpk::SceneLayerPackage;
+
+    fn write_layer(path: &std::path::Path) {
+        crate::testutil::write_test_layer(
+            path,
+            serde_json::json!({
+                "id": 0,
+                "name": "repaired",
+                "layerType": "IntegratedMesh",
+                "store": { "profile": "meshpyramids" },
+                "nodePages": { "nodesPerPage": 64 }
+            }),
+            &[serde_json::json!({ "index": 0, "obb": crate::testutil::unit_obb() })],
+            &[(0, 0, b"\x01\x02\x03".to_vec()), (0, 1, b"\x04\x05\x06".to_vec())],
+        );
+    }
+
+    #[test]
+    fn repair_salvages_a_truncated_package() {
+        let dir = crate::testutil::TestDir::new("repair");
+        let damaged = dir.join("damaged.slpk");
+        let repaired = dir.join("repaired.slpk");
+        write_layer(&damaged);
+
+        // Cut the copy off mid-way through the last entry's payload: the
+        // central directory and metadata.json are gone, so the archive no
+        // longer opens at all.
+        let bytes = std::fs::read(&damaged).unwrap();
+        let cut = bytes
+            .windows(13)
+            .position(|window| window == b"metadata.json")
+            .unwrap()
+            + 20;
+        std::fs::write(&damaged, &bytes[..cut]).unwrap();
+        assert!(SceneLayerPackage::open(&damaged).is_err());
+
+        let report = repair_slpk(&damaged, &repaired).unwrap();
+        assert!(report.has_scene_definition);
+        assert!(report.rebuilt_metadata);
+        assert_eq!(report.lost, vec!["metadata.json".to_string()]);
+        // Definition, node page and both geometries made it out.
+        assert_eq!(report.salvaged, 4);
+
+        let package = SceneLayerPackage::open(&repaired).unwrap();
+        assert!(package.has_hash_index());
+        let layer = crate::layer::SceneLayer::open_slpk(&repaired).unwrap();
+        assert_eq!(layer.name(), Some("repaired"));
+        let geometry = package.get(&package.geometry_uri(0, 1)).unwrap();
+        assert_eq!(&*geometry, &vec![4u8, 5, 6]);
+    }
+
+    #[test]
+    fn repair_drops_corrupt_payloads_and_keeps_the_rest() {
+        let dir = crate::testutil::TestDir::new("repair-corrupt");
+        let damaged = dir.join("damaged.slpk");
+        let repaired = dir.join("repaired.slpk");
+        write_layer(&damaged);
+
+        // Flip bytes inside the first geometry's gzip payload so its CRC
+        // no longer matches.
+        let mut bytes = std::fs::read(&damaged).unwrap();
+        let name = b"nodes/0/geometries/0.bin.gz";
+        let at = bytes
+            .windows(name.len())
+            .position(|window| window == name)
+            .unwrap()
+            + name.len();
+        for byte in &mut bytes[at + 8..at + 12] {
+            *byte ^= 0xff;
+        }
+        std::fs::write(&damaged, &bytes).unwrap();
+
+        let report = repair_slpk(&damaged, &repaired).unwrap();
+        assert_eq!(report.lost, vec!["nodes/0/geometries/0.bin.gz".to_string()]);
+        assert!(!report.rebuilt_metadata);
+
+        // The intact sibling survives; the corrupt entry is gone.
+        let package = SceneLayerPackage::open(&repaired).unwrap();
+        let geometry = package.get(&package.geometry_uri(0, 1)).unwrap();
+        assert_eq!(&*geometry, &vec![4u8, 5, 6]);
+        assert!(matches!(
+            package.get(&package.geometry_uri(0, 0)),
+            Err(I3SError::MissingResource(_))
+        ));
+    }
+}
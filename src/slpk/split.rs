@@ -0,0 +1,568 @@
+//! Splitting a large SLPK into regional packages.
+//!
+//! Partitions the top-level subtrees of a layer by where their bounding
+//! boxes fall — either into a regular grid over the layer extent or into
+//! caller-provided polygons — and writes one self-contained `.slpk` per
+//! region plus an `index.json` describing the coverage of each package.
+//! Node indices are remapped per package, so every output opens as an
+//! ordinary layer. [`extract_extent`] is the single-region variant:
+//! clip one subtree touching an extent into one package.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::err::{I3SError, Result};
+use crate::layer::SceneLayer;
+use crate::node::{Node, NodeArray, NodePage};
+use crate::rm::{Accessor, UriBuilder};
+use crate::slpk::writer::SlpkWriter;
+
+/// How subtrees are assigned to output packages.
+pub enum SplitScheme {
+    /// A regular `columns x rows` grid over the layer extent.
+    Grid { columns: usize, rows: usize },
+    /// Caller-provided named polygons in layer coordinates.
+    Regions(Vec<SplitRegion>),
+}
+
+/// One named polygon of a [`SplitScheme::Regions`] split.
+pub struct SplitRegion {
+    pub name: String,
+    /// Polygon ring as x/y pairs; the last point closes to the first.
+    pub polygon: Vec<[f64; 2]>,
+}
+
+/// Coverage of one output package.
+#[derive(Debug, Clone, Serialize)]
+pub struct RegionSummary {
+    pub name: String,
+    /// File name of the package, relative to the output directory.
+    pub package: String,
+    /// Nodes in the package, including its root.
+    pub nodes: usize,
+    /// Covered x/y bounds as `[xmin, ymin, xmax, ymax]`.
+    pub extent: [f64; 4],
+}
+
+/// What [`split_slpk`] produced.
+#[derive(Debug, Clone)]
+pub struct SplitReport {
+    pub regions: Vec<RegionSummary>,
+    /// Top-level subtrees whose center fell outside every region.
+    pub unassigned_subtrees: usize,
+}
+
+/// Split the package at `slpk_path` into regional packages under `out_dir`.
+///
+/// Each top-level subtree (a child of the root) goes to the region holding
+/// its bounding-box center; subtrees outside every region are counted but
+/// not written. Alongside the packages, `index.json` lists each region's
+/// package name, node count and covered extent.
+pub fn split_slpk(
+    slpk_path: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+    scheme: &SplitScheme,
+) -> Result<SplitReport> {
+    let out_dir = out_dir.as_ref();
+    std::fs::create_dir_all(out_dir)?;
+    let layer = SceneLayer::open_slpk(slpk_path)?;
+    let mut nodes = layer.nodes()?;
+    let root = nodes.root()?;
+    let subtrees = nodes.get_many(&root.children)?;
+
+    let names = region_names(scheme)?;
+    let bounds = match scheme {
+        SplitScheme::Grid { .. } => Some(grid_bounds(&layer, &subtrees)?),
+        SplitScheme::Regions(_) => None,
+    };
+    let mut assigned: Vec<Vec<Arc<Node>>> = vec![Vec::new(); names.len()];
+    let mut unassigned_subtrees = 0;
+    for subtree in subtrees {
+        match assign(scheme, bounds, &subtree) {
+            Some(region) => assigned[region].push(subtree),
+            None => unassigned_subtrees += 1,
+        }
+    }
+
+    let mut regions = Vec::new();
+    for (name, roots) in names.into_iter().zip(assigned) {
+        if roots.is_empty() {
+            continue;
+        }
+        let package = format!("{name}.slpk");
+        let summary = write_region(&layer, &mut nodes, &root, roots, out_dir.join(&package))?;
+        regions.push(RegionSummary {
+            name,
+            package,
+            nodes: summary.0,
+            extent: summary.1,
+        });
+    }
+
+    let index = serde_json::json!({ "regions": regions });
+    std::fs::write(
+        out_dir.join("index.json"),
+        serde_json::to_vec_pretty(&index).map_err(|e| I3SError::json("index.json", e))?,
+    )?;
+    Ok(SplitReport {
+        regions,
+        unassigned_subtrees,
+    })
+}
+
+fn region_names(scheme: &SplitScheme) -> Result<Vec<String>> {
+    match scheme {
+        SplitScheme::Grid { columns, rows } => {
+            if *columns == 0 || *rows == 0 {
+                return Err(I3SError::Validation(
+                    "split grid needs at least one column and one row".to_string(),
+                ));
+            }
+            Ok((0..*rows)
+                .flat_map(|row| (0..*columns).map(move |column| format!("r{row}c{column}")))
+                .collect())
+        }
+        SplitScheme::Regions(regions) => {
+            for region in regions {
+                if region.polygon.len() < 3 {
+                    return Err(I3SError::Validation(format!(
+                        "split region {} has fewer than 3 polygon points",
+                        region.name
+                    )));
+                }
+            }
+            Ok(regions.iter().map(|region| region.name.clone()).collect())
+        }
+    }
+}
+
+/// The x/y bounds the grid is laid over: the layer extent when declared,
+/// otherwise the bounds of the top-level subtree centers.
+fn grid_bounds(layer: &SceneLayer, subtrees: &[Arc<Node>]) -> Result<[f64; 4]> {
+    if let Some(extent) = layer.extent() {
+        return Ok([extent.xmin, extent.ymin, extent.xmax, extent.ymax]);
+    }
+    let mut bounds = [f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY];
+    for subtree in subtrees {
+        bounds[0] = bounds[0].min(subtree.obb.center[0]);
+        bounds[1] = bounds[1].min(subtree.obb.center[1]);
+        bounds[2] = bounds[2].max(subtree.obb.center[0]);
+        bounds[3] = bounds[3].max(subtree.obb.center[1]);
+    }
+    if bounds[0] > bounds[2] {
+        return Err(I3SError::Validation(
+            "layer has no extent and no top-level subtrees to split".to_string(),
+        ));
+    }
+    Ok(bounds)
+}
+
+fn assign(scheme: &SplitScheme, bounds: Option<[f64; 4]>, subtree: &Node) -> Option<usize> {
+    let [x, y] = [subtree.obb.center[0], subtree.obb.center[1]];
+    match scheme {
+        SplitScheme::Grid { columns, rows } => {
+            let [xmin, ymin, xmax, ymax] = bounds.expect("grid split without bounds");
+            if x < xmin || x > xmax || y < ymin || y > ymax {
+                return None;
+            }
+            let column = cell(x, xmin, xmax, *columns);
+            let row = cell(y, ymin, ymax, *rows);
+            Some(row * columns + column)
+        }
+        SplitScheme::Regions(regions) => regions
+            .iter()
+            .position(|region| point_in_polygon([x, y], &region.polygon)),
+    }
+}
+
+fn cell(value: f64, min: f64, max: f64, cells: usize) -> usize {
+    if max <= min {
+        return 0;
+    }
+    let t = (value - min) / (max - min);
+    ((t * cells as f64) as usize).min(cells - 1)
+}
+
+/// Even-odd ray-casting point-in-polygon test.
+fn point_in_polygon(point: [f64; 2], polygon: &[[f64; 2]]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+        if (a[1] > point[1]) != (b[1] > point[1])
+            && point[0] < (b[0] - a[0]) * (point[1] - a[1]) / (b[1] - a[1]) + a[0]
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Write one regional package and return its node count and x/y extent.
+fn write_region(
+    layer: &SceneLayer,
+    nodes: &mut NodeArray,
+    original_root: &Node,
+    roots: Vec<Arc<Node>>,
+    path: PathBuf,
+) -> Result<(usize, [f64; 4])> {
+    // Collect the region's nodes in pre-order, starting with a copy of the
+    // original root so the package keeps a single tree.
+    let mut included: Vec<Arc<Node>> = Vec::new();
+    let mut stack: Vec<Arc<Node>> = roots.iter().rev().cloned().collect();
+    while let Some(node) = stack.pop() {
+        included.push(Arc::clone(&node));
+        for child in nodes.get_many(&node.children)?.into_iter().rev() {
+            stack.push(child);
+        }
+    }
+
+    let mut remap = std::collections::HashMap::new();
+    remap.insert(original_root.index, 0usize);
+    for (position, node) in included.iter().enumerate() {
+        remap.insert(node.index, position + 1);
+    }
+
+    let mut defn = layer.definition().clone();
+    if let Some(pages) = &mut defn.node_pages {
+        pages.root_index = Some(0);
+    }
+    let per_page = nodes.nodes_per_page();
+
+    let new_root = Node {
+        index: 0,
+        parent_index: None,
+        children: roots.iter().map(|node| remap[&node.index]).collect(),
+        obb: original_root.obb,
+        lod_threshold: original_root.lod_threshold,
+        mesh: None,
+        extras: Default::default(),
+    };
+    let mut renumbered = vec![Arc::new(new_root)];
+    let mut extent = [f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY];
+    for node in &included {
+        let mut mesh = node.mesh.clone();
+        if let Some(mesh) = &mut mesh {
+            // Material/attribute resources live in per-node folders, so
+            // they follow the node to its new index.
+            if let Some(material) = &mut mesh.material {
+                material.resource = *remap.get(&material.resource).unwrap_or(&remap[&node.index]);
+            }
+            if let Some(attribute) = &mut mesh.attribute {
+                attribute.resource =
+                    *remap.get(&attribute.resource).unwrap_or(&remap[&node.index]);
+            }
+        }
+        renumbered.push(Arc::new(Node {
+            index: remap[&node.index],
+            parent_index: node.parent_index.map(|parent| remap[&parent]),
+            children: node.children.iter().map(|child| remap[child]).collect(),
+            obb: node.obb,
+            lod_threshold: node.lod_threshold,
+            mesh,
+            extras: Default::default(),
+        }));
+        let half = [node.obb.half_size[0] as f64, node.obb.half_size[1] as f64];
+        extent[0] = extent[0].min(node.obb.center[0] - half[0]);
+        extent[1] = extent[1].min(node.obb.center[1] - half[1]);
+        extent[2] = extent[2].max(node.obb.center[0] + half[0]);
+        extent[3] = extent[3].max(node.obb.center[1] + half[1]);
+    }
+
+    let mut writer = SlpkWriter::create(&path)?;
+    writer.write_scene_definition(&defn)?;
+    for (page_index, chunk) in renumbered.chunks(per_page).enumerate() {
+        writer.write_node_page(
+            page_index,
+            &NodePage {
+                nodes: chunk.to_vec(),
+            },
+        )?;
+    }
+
+    for node in &included {
+        copy_node_resources(layer, &mut writer, node, remap[&node.index])?;
+    }
+    writer.finish()?;
+    Ok((renumbered.len(), extent))
+}
+
+/// Copy the referenced geometry/texture/attribute resources of `node` to
+/// `new_index` in the output package. Texture formats the backend does
+/// not hold (a package usually stores one encoding per set) are skipped.
+fn copy_node_resources(
+    layer: &SceneLayer,
+    writer: &mut SlpkWriter<std::fs::File>,
+    node: &Node,
+    new_index: usize,
+) -> Result<()> {
+    let rm = layer.resource_manager();
+    let defn = layer.definition();
+    let Some(mesh) = &node.mesh else {
+        return Ok(());
+    };
+    if let Some(geometry) = &mesh.geometry {
+        let bytes = rm.get(&rm.geometry_uri(node.index, geometry.resource))?;
+        writer.write_geometry(new_index, geometry.resource, &bytes)?;
+    }
+    if let Some(material) = &mesh.material {
+        for set in &defn.texture_set_definitions {
+            for format in &set.formats {
+                match rm.get(&rm.texture_uri(material.resource, &format.name, format.format)) {
+                    Ok(bytes) => {
+                        writer.write_texture(new_index, &format.name, format.format, &bytes)?;
+                    }
+                    Err(I3SError::MissingResource(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+    if let Some(attribute) = &mesh.attribute {
+        for info in &defn.attribute_storage_info {
+            match rm.get(&rm.attribute_uri(attribute.resource, &info.key)) {
+                Ok(bytes) => writer.write_attribute(new_index, &info.key, &bytes)?,
+                Err(I3SError::MissingResource(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write the subtree of nodes touching `extent` as a self-contained
+/// package at `path`; returns the number of nodes written.
+///
+/// The kept set comes from [`NodeArray::query_extent`], so it is a
+/// connected subtree under the original root. Node indices are remapped
+/// to be dense, parent/child links and per-node resource references are
+/// rewritten, and only the resources of kept nodes are copied — cutting
+/// a city model down to a project area without re-encoding anything.
+pub fn extract_extent(
+    layer: &SceneLayer,
+    extent: &crate::defn::Extent,
+    path: impl AsRef<Path>,
+) -> Result<usize> {
+    let nodes = layer.nodes()?;
+    let included = nodes.query_extent(extent)?;
+    if included.is_empty() {
+        return Err(I3SError::Validation(
+            "extent intersects no nodes of the layer".to_string(),
+        ));
+    }
+
+    let mut remap = std::collections::HashMap::new();
+    for (position, node) in included.iter().enumerate() {
+        remap.insert(node.index, position);
+    }
+
+    let mut defn = layer.definition().clone();
+    defn.full_extent = Some(*extent);
+    if let Some(pages) = &mut defn.node_pages {
+        pages.root_index = Some(0);
+    }
+    let per_page = nodes.nodes_per_page();
+
+    let renumbered: Vec<Arc<Node>> = included
+        .iter()
+        .map(|node| {
+            let mut mesh = node.mesh.clone();
+            if let Some(mesh) = &mut mesh {
+                if let Some(material) = &mut mesh.material {
+                    material.resource =
+                        *remap.get(&material.resource).unwrap_or(&remap[&node.index]);
+                }
+                if let Some(attribute) = &mut mesh.attribute {
+                    attribute.resource =
+                        *remap.get(&attribute.resource).unwrap_or(&remap[&node.index]);
+                }
+            }
+            Arc::new(Node {
+                index: remap[&node.index],
+                parent_index: node
+                    .parent_index
+                    .and_then(|parent| remap.get(&parent).copied()),
+                children: node
+                    .children
+                    .iter()
+                    .filter_map(|child| remap.get(child).copied())
+                    .collect(),
+                obb: node.obb,
+                lod_threshold: node.lod_threshold,
+                mesh,
+                extras: Default::default(),
+            })
+        })
+        .collect();
+
+    let mut writer = SlpkWriter::create(path)?;
+    writer.write_scene_definition(&defn)?;
+    for (page_index, chunk) in renumbered.chunks(per_page).enumerate() {
+        writer.write_node_page(
+            page_index,
+            &NodePage {
+                nodes: chunk.to_vec(),
+            },
+        )?;
+    }
+    for node in &included {
+        copy_node_resources(layer, &mut writer, node, remap[&node.index])?;
+    }
+    writer.finish()?;
+    Ok(renumbered.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_split_writes_openable_regional_packages() {
+        let dir = crate::testutil::TestDir::new("split");
+        let path = dir.join("layer.slpk");
+        let out = dir.join("regions");
+        std::fs::remove_dir_all(&out).ok();
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "fullExtent": { "xmin": 0.0, "ymin": 0.0, "xmax": 100.0, "ymax": 100.0 },
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let obb = |x: f64| {
+            serde_json::json!({
+                "center": [x, 50.0, 0.0],
+                "halfSize": [5.0, 5.0, 5.0],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            })
+        };
+        let page: NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "children": [1, 2], "obb": obb(50.0) },
+                { "index": 1, "parentIndex": 0, "children": [3], "obb": obb(10.0) },
+                { "index": 2, "parentIndex": 0, "obb": obb(90.0) },
+                { "index": 3, "parentIndex": 1, "obb": obb(12.0) }
+            ]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.finish().unwrap();
+
+        let report = split_slpk(
+            &path,
+            &out,
+            &SplitScheme::Grid {
+                columns: 2,
+                rows: 1,
+            },
+        )
+        .unwrap();
+        assert_eq!(report.unassigned_subtrees, 0);
+        assert_eq!(report.regions.len(), 2);
+        assert!(out.join("index.json").is_file());
+
+        // West region: new root plus the two-node subtree under node 1.
+        let west = SceneLayer::open_slpk(out.join("r0c0.slpk")).unwrap();
+        let west_nodes = west.nodes().unwrap();
+        let west_root = west_nodes.root().unwrap();
+        assert_eq!(west_root.children, vec![1]);
+        let subtree = west_nodes.get(1).unwrap();
+        assert_eq!(subtree.children, vec![2]);
+        assert_eq!(west_nodes.get(2).unwrap().parent_index, Some(1));
+
+        let east = SceneLayer::open_slpk(out.join("r0c1.slpk")).unwrap();
+        assert_eq!(east.root().unwrap().children, vec![1]);
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_dir_all(&out).ok();
+    }
+
+    #[test]
+    fn extent_extraction_clips_and_renumbers() {
+        let dir = crate::testutil::TestDir::new("extract");
+        let path = dir.join("city.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "fullExtent": { "xmin": 0.0, "ymin": 0.0, "xmax": 100.0, "ymax": 100.0 },
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }]
+        }))
+        .unwrap();
+        let obb = |x: f64, half: f64| {
+            serde_json::json!({
+                "center": [x, 50.0, 0.0],
+                "halfSize": [half, half, half],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            })
+        };
+        let mesh = |index: usize| {
+            serde_json::json!({ "geometry": {
+                "definition": 0, "resource": index, "vertexCount": 3
+            } })
+        };
+        let page: NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "children": [1, 2], "obb": obb(50.0, 60.0) },
+                { "index": 1, "parentIndex": 0, "children": [3], "obb": obb(10.0, 8.0) },
+                { "index": 2, "parentIndex": 0, "obb": obb(90.0, 8.0), "mesh": mesh(2) },
+                { "index": 3, "parentIndex": 1, "obb": obb(12.0, 4.0), "mesh": mesh(3) }
+            ]
+        }))
+        .unwrap();
+        let triangle: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(2, 2, &triangle).unwrap();
+        writer.write_geometry(3, 3, &triangle).unwrap();
+        writer.finish().unwrap();
+
+        let layer = SceneLayer::open_slpk(&path).unwrap();
+        let west = crate::defn::Extent {
+            xmin: 0.0,
+            ymin: 40.0,
+            xmax: 25.0,
+            ymax: 60.0,
+        };
+        let clipped = layer.extract(&west, dir.join("west.slpk")).unwrap();
+
+        // Root, node 1 and leaf 3 survive, renumbered 0..=2; the east
+        // subtree and its geometry are gone.
+        let nodes = clipped.nodes().unwrap();
+        let root = nodes.root().unwrap();
+        assert_eq!(root.children, vec![1]);
+        assert_eq!(nodes.get(1).unwrap().children, vec![2]);
+        let leaf = nodes.get(2).unwrap();
+        assert_eq!(leaf.parent_index, Some(1));
+        let decoded = clipped.node_geometry(&leaf).unwrap().unwrap();
+        assert_eq!(decoded.vertex_count, 3);
+        assert_eq!(clipped.extent().unwrap().xmax, 25.0);
+        assert!(nodes.get(3).is_err());
+
+        // An extent outside the layer is rejected.
+        let nowhere = crate::defn::Extent {
+            xmin: 500.0,
+            ymin: 500.0,
+            xmax: 600.0,
+            ymax: 600.0,
+        };
+        assert!(layer.extract(&nowhere, dir.join("nope.slpk")).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Authoring scene layer packages.
+//!
+//! Per the SLPK spec, individual resources are gzip-compressed while the ZIP
+//! container itself uses STORE entries, so readers can locate and stream
+//! entry bytes without inflating the archive. [`SlpkWriter`] takes care of
+//! both layers: JSON and binary resources are gzipped on write, textures are
+//! stored as-is, and `metadata.json` is emitted on finish.
+
+use std::fs::File;
+use std::io::{Seek, Write};
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::defn::{ImageFormat, SceneDefinition};
+use crate::err::Result;
+use crate::node::NodePage;
+
+/// Running totals of what a writer has produced so far.
+///
+/// `archive_bytes` estimates the final package size from the entry
+/// payloads plus the fixed ZIP bookkeeping per entry (local header,
+/// central directory record, end-of-directory record); on a [dry
+/// run](SlpkWriter::dry_run) it is the disk space the real write would
+/// need.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlpkWriterStats {
+    pub entries: usize,
+    /// Resource bytes handed to the writer, before gzip.
+    pub resource_bytes: u64,
+    /// Estimated size of the package on disk.
+    pub archive_bytes: u64,
+}
+
+/// A sink that discards everything written to it, for dry runs.
+#[derive(Debug, Default)]
+pub struct Discard {
+    position: u64,
+    length: u64,
+}
+
+impl Write for Discard {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.position += buf.len() as u64;
+        self.length = self.length.max(self.position);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for Discard {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            std::io::SeekFrom::Start(offset) => Some(offset),
+            std::io::SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            std::io::SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = target.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek before start")
+        })?;
+        Ok(self.position)
+    }
+}
+
+/// ZIP bookkeeping per entry: the local file header plus the central
+/// directory record, each followed by the entry name.
+const ZIP_ENTRY_OVERHEAD: u64 = 30 + 46;
+/// The end-of-central-directory record, written once on finish.
+const ZIP_EOCD_SIZE: u64 = 22;
+
+/// Writes a scene layer package entry by entry.
+pub struct SlpkWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    stats: SlpkWriterStats,
+}
+
+impl SlpkWriter<File> {
+    /// Create a new `.slpk` file, truncating any existing one.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(File::create(path)?))
+    }
+}
+
+impl SlpkWriter<Discard> {
+    /// A writer that runs the full pipeline — gzip, entry layout, hash
+    /// index — but discards the output. [`stats`](Self::stats) and the
+    /// value returned by [`finish`](Self::finish) report what a real
+    /// write would have produced, so disk needs can be estimated before
+    /// a long conversion.
+    pub fn dry_run() -> Self {
+        Self::new(Discard::default())
+    }
+}
+
+impl<W: Write + Seek> SlpkWriter<W> {
+    /// Write a package into any seekable sink.
+    pub fn new(sink: W) -> Self {
+        Self {
+            zip: ZipWriter::new(sink),
+            stats: SlpkWriterStats::default(),
+        }
+    }
+
+    /// What the writer has produced so far.
+    pub fn stats(&self) -> SlpkWriterStats {
+        self.stats
+    }
+
+    fn store_options() -> SimpleFileOptions {
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored)
+    }
+
+    fn write_stored(&mut self, name: &str, payload: &[u8], resource_len: usize) -> Result<()> {
+        self.zip.start_file(name, Self::store_options())?;
+        self.zip.write_all(payload)?;
+        self.stats.entries += 1;
+        self.stats.resource_bytes += resource_len as u64;
+        self.stats.archive_bytes +=
+            ZIP_ENTRY_OVERHEAD + 2 * name.len() as u64 + payload.len() as u64;
+        Ok(())
+    }
+
+    fn write_entry(&mut self, name: &str, bytes: &[u8]) -> Result<()> {
+        self.write_stored(name, bytes, bytes.len())
+    }
+
+    fn write_gz_entry(&mut self, name: &str, bytes: &[u8]) -> Result<()> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes)?;
+        self.write_stored(name, &encoder.finish()?, bytes.len())
+    }
+
+    /// Write the `3dSceneLayer.json.gz` document.
+    pub fn write_scene_definition(&mut self, defn: &SceneDefinition) -> Result<()> {
+        let json = serde_json::to_vec(defn)
+            .map_err(|e| crate::err::I3SError::json("3dSceneLayer.json.gz", e))?;
+        self.write_gz_entry("3dSceneLayer.json.gz", &json)
+    }
+
+    /// Write one node page.
+    pub fn write_node_page(&mut self, page_index: usize, page: &NodePage) -> Result<()> {
+        let json = serde_json::to_vec(page).map_err(|e| {
+            crate::err::I3SError::json(format!("nodepages/{page_index}.json.gz"), e)
+        })?;
+        self.write_gz_entry(&format!("nodepages/{page_index}.json.gz"), &json)
+    }
+
+    /// Write a raw geometry buffer for a node.
+    pub fn write_geometry(
+        &mut self,
+        node_index: usize,
+        resource: usize,
+        bytes: &[u8],
+    ) -> Result<()> {
+        self.write_gz_entry(
+            &format!("nodes/{node_index}/geometries/{resource}.bin.gz"),
+            bytes,
+        )
+    }
+
+    /// Write an encoded texture for a node. JPEG/PNG/KTX payloads are already
+    /// compressed and are stored without a gzip wrapper; DDS is gzipped per
+    /// the SLPK naming convention.
+    pub fn write_texture(
+        &mut self,
+        node_index: usize,
+        name: &str,
+        format: ImageFormat,
+        bytes: &[u8],
+    ) -> Result<()> {
+        match format {
+            ImageFormat::Dds => self.write_gz_entry(
+                &format!("nodes/{node_index}/textures/{name}.bin.dds.gz"),
+                bytes,
+            ),
+            _ => {
+                let ext = match format {
+                    ImageFormat::Jpg => "jpg",
+                    ImageFormat::Png => "png",
+                    ImageFormat::Ktx2 => "ktx2",
+                    ImageFormat::Basis => "basis",
+                    ImageFormat::KtxEtc2 => "ktx",
+                    ImageFormat::Dds => unreachable!(),
+                };
+                self.write_entry(&format!("nodes/{node_index}/textures/{name}.{ext}"), bytes)
+            }
+        }
+    }
+
+    /// Write an attribute resource for a node.
+    pub fn write_attribute(&mut self, node_index: usize, key: &str, bytes: &[u8]) -> Result<()> {
+        self.write_gz_entry(
+            &format!("nodes/{node_index}/attributes/{key}/0.bin.gz"),
+            bytes,
+        )
+    }
+
+    /// Write an arbitrary extra entry (e.g. `esriinfo/iteminfo.xml`).
+    pub fn write_raw(&mut self, name: &str, bytes: &[u8]) -> Result<()> {
+        self.write_entry(name, bytes)
+    }
+
+    /// Write `metadata.json` and finalize the archive, returning the
+    /// totals of everything written.
+    pub fn finish(mut self) -> Result<SlpkWriterStats> {
+        let metadata = serde_json::json!({
+            "folderPattern": "BASIC",
+            "archiveCompressionType": "STORE",
+            "resourceCompressionType": "GZIP",
+            "I3SVersion": "1.8",
+        });
+        let json = serde_json::to_vec(&metadata)
+            .map_err(|e| crate::err::I3SError::json("metadata.json", e))?;
+        self.write_entry("metadata.json", &json)?;
+        self.zip.finish()?;
+        self.stats.archive_bytes += ZIP_EOCD_SIZE;
+        Ok(self.stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rm::{Accessor, UriBuilder};
+    use crate::slpk::SceneLayerPackage;
+
+    #[test]
+    fn written_package_reads_back() {
+        let dir = crate::testutil::TestDir::new("writer");
+        let path = dir.join("roundtrip.slpk");
+
+        let defn: SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": "test",
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let page: NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                }
+            }]
+        }))
+        .unwrap();
+
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.write_geometry(0, 0, b"\x01\x02\x03").unwrap();
+        writer.finish().unwrap();
+
+        let package = SceneLayerPackage::open(&path).unwrap();
+        let bytes = package.get(&package.scene_definition_uri()).unwrap();
+        let read_back = SceneDefinition::from_slice(&bytes).unwrap();
+        assert_eq!(read_back.name.as_deref(), Some("test"));
+        let geometry = package.get(&package.geometry_uri(0, 0)).unwrap();
+        assert_eq!(&*geometry, &vec![1u8, 2, 3]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn dry_run_reports_the_real_package_size() {
+        let dir = crate::testutil::TestDir::new("writer-dry-run");
+        let path = dir.join("estimate.slpk");
+
+        let defn: SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 }
+        }))
+        .unwrap();
+        let geometry = vec![7u8; 4096];
+
+        fn write_all<W: Write + Seek>(
+            mut writer: SlpkWriter<W>,
+            defn: &SceneDefinition,
+            geometry: &[u8],
+        ) -> SlpkWriterStats {
+            writer.write_scene_definition(defn).unwrap();
+            writer.write_geometry(0, 0, geometry).unwrap();
+            writer
+                .write_texture(0, "0", ImageFormat::Jpg, b"jpeg-payload")
+                .unwrap();
+            writer.finish().unwrap()
+        }
+        let real = write_all(SlpkWriter::create(&path).unwrap(), &defn, &geometry);
+        let estimated = write_all(SlpkWriter::dry_run(), &defn, &geometry);
+
+        assert_eq!(estimated, real);
+        assert_eq!(estimated.entries, 4); // + metadata.json
+        assert_eq!(
+            estimated.archive_bytes,
+            std::fs::metadata(&path).unwrap().len()
+        );
+        assert!(estimated.resource_bytes >= 4096 + 12);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
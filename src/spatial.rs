@@ -0,0 +1,218 @@
+//! An opt-in R*-tree over leaf node bounding boxes (feature `rstar`).
+//!
+//! [`NodeArray::query_extent`](crate::node::NodeArray::query_extent)
+//! walks the I3S tree per query; when the same layer is queried many
+//! times — hit testing in an editor, tiling a model into jobs — it pays
+//! to walk once and index the leaf AABBs instead. [`SpatialIndex`] does
+//! that walk at build time and answers nearest-neighbor, envelope and
+//! ray queries from the index without touching node pages again.
+
+use rstar::{RTree, RTreeObject, SelectionFunction, AABB};
+
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::obb::{Aabb, Ray};
+
+/// One indexed leaf: its node index and world-space AABB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedLeaf {
+    pub node_index: usize,
+    pub aabb: Aabb,
+}
+
+impl RTreeObject for IndexedLeaf {
+    type Envelope = AABB<[f64; 3]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_corners(self.aabb.min, self.aabb.max)
+    }
+}
+
+impl rstar::PointDistance for IndexedLeaf {
+    fn distance_2(&self, point: &[f64; 3]) -> f64 {
+        self.envelope().distance_2(point)
+    }
+}
+
+/// An R*-tree of the layer's leaf bounding boxes.
+pub struct SpatialIndex {
+    tree: RTree<IndexedLeaf>,
+}
+
+impl std::fmt::Debug for SpatialIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpatialIndex")
+            .field("leaves", &self.tree.size())
+            .finish()
+    }
+}
+
+impl SpatialIndex {
+    /// Walk the node tree once and bulk-load every leaf's AABB.
+    pub fn build(layer: &SceneLayer) -> Result<Self> {
+        let mut leaves = Vec::new();
+        layer.nodes()?.traverse(|node| {
+            if node.is_leaf() {
+                leaves.push(IndexedLeaf {
+                    node_index: node.index,
+                    aabb: node.obb.to_aabb(),
+                });
+            }
+            crate::node::TraversalControl::Continue
+        })?;
+        Ok(Self {
+            tree: RTree::bulk_load(leaves),
+        })
+    }
+
+    /// Number of indexed leaves.
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 0
+    }
+
+    /// The leaf whose box is closest to `point` (the box itself for a
+    /// point inside one).
+    pub fn nearest(&self, point: [f64; 3]) -> Option<&IndexedLeaf> {
+        self.tree.nearest_neighbor(&point)
+    }
+
+    /// All leaves whose boxes intersect the axis-aligned envelope
+    /// spanned by `min` and `max`.
+    pub fn locate_in_envelope(&self, min: [f64; 3], max: [f64; 3]) -> Vec<&IndexedLeaf> {
+        self.tree
+            .locate_in_envelope_intersecting(&AABB::from_corners(min, max))
+            .collect()
+    }
+
+    /// The leaves whose boxes `ray` passes through, closest entry first.
+    pub fn ray(&self, ray: &Ray) -> Vec<&IndexedLeaf> {
+        let mut hits: Vec<(f64, &IndexedLeaf)> = self
+            .tree
+            .locate_with_selection_function(RaySelection { ray: *ray })
+            .filter_map(|leaf| ray_aabb(ray, &leaf.aabb).map(|enter| (enter, leaf)))
+            .collect();
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        hits.into_iter().map(|(_, leaf)| leaf).collect()
+    }
+}
+
+/// Prunes R-tree branches whose envelope the ray misses.
+struct RaySelection {
+    ray: Ray,
+}
+
+impl SelectionFunction<IndexedLeaf> for RaySelection {
+    fn should_unpack_parent(&self, envelope: &AABB<[f64; 3]>) -> bool {
+        ray_aabb(
+            &self.ray,
+            &Aabb {
+                min: envelope.lower(),
+                max: envelope.upper(),
+            },
+        )
+        .is_some()
+    }
+
+    fn should_unpack_leaf(&self, leaf: &IndexedLeaf) -> bool {
+        ray_aabb(&self.ray, &leaf.aabb).is_some()
+    }
+}
+
+/// Slab test; the forward entry distance in units of the direction
+/// length, `0.0` for an origin inside the box.
+fn ray_aabb(ray: &Ray, aabb: &Aabb) -> Option<f64> {
+    let mut enter = f64::NEG_INFINITY;
+    let mut exit = f64::INFINITY;
+    for axis in 0..3 {
+        if ray.direction[axis].abs() < f64::EPSILON {
+            if ray.origin[axis] < aabb.min[axis] || ray.origin[axis] > aabb.max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let t0 = (aabb.min[axis] - ray.origin[axis]) / ray.direction[axis];
+        let t1 = (aabb.max[axis] - ray.origin[axis]) / ray.direction[axis];
+        enter = enter.max(t0.min(t1));
+        exit = exit.min(t0.max(t1));
+    }
+    (exit >= enter.max(0.0)).then(|| enter.max(0.0))
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::slpk::writer::SlpkWriter;
+
+    #[test]
+    fn index_answers_nearest_envelope_and_ray_queries() {
+        let dir = crate::testutil::TestDir::new("spatial-index");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 8 }
+        }))
+        .unwrap();
+        let obb = |x: f64, half: f64| {
+            serde_json::json!({
+                "center": [x, 0.0, 0.0],
+                "halfSize": [half, half, half],
+                "quaternion": [0.0, 0.0, 0.0, 1.0]
+            })
+        };
+        // Three leaves strung out along x under one root.
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "index": 0, "obb": obb(0.0, 60.0), "children": [1, 2, 3] },
+                { "index": 1, "obb": obb(-50.0, 2.0), "parentIndex": 0 },
+                { "index": 2, "obb": obb(0.0, 2.0), "parentIndex": 0 },
+                { "index": 3, "obb": obb(50.0, 2.0), "parentIndex": 0 }
+            ]
+        }))
+        .unwrap();
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let index = SpatialIndex::build(&layer).unwrap();
+        assert_eq!(index.len(), 3);
+
+        assert_eq!(index.nearest([-40.0, 0.0, 0.0]).unwrap().node_index, 1);
+        assert_eq!(index.nearest([100.0, 0.0, 0.0]).unwrap().node_index, 3);
+
+        let mut enveloped: Vec<usize> = index
+            .locate_in_envelope([-5.0, -5.0, -5.0], [55.0, 5.0, 5.0])
+            .into_iter()
+            .map(|leaf| leaf.node_index)
+            .collect();
+        enveloped.sort_unstable();
+        assert_eq!(enveloped, vec![2, 3]);
+
+        // A ray along +x crosses all three leaves, nearest first.
+        let along_x: Vec<usize> = index
+            .ray(&Ray {
+                origin: [-100.0, 0.0, 0.0],
+                direction: [1.0, 0.0, 0.0],
+            })
+            .into_iter()
+            .map(|leaf| leaf.node_index)
+            .collect();
+        assert_eq!(along_x, vec![1, 2, 3]);
+        assert!(index
+            .ray(&Ray {
+                origin: [-100.0, 30.0, 0.0],
+                direction: [1.0, 0.0, 0.0],
+            })
+            .is_empty());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,174 @@
+//! A ready-to-use streaming driver for a region of interest.
+//!
+//! Renderers streaming a layer repeat the same loop every frame: select
+//! the LOD cut for the camera, diff it against what is resident, start
+//! loads and releases, and fetch ahead of the motion — all while keeping
+//! memory bounded. [`StreamingSession`] packages the pieces this crate
+//! already provides ([`NodeArray::select_visible`], the
+//! [`ResidencyTracker`](crate::sched::ResidencyTracker), the
+//! [`Prefetcher`](crate::prefetch::Prefetcher) and the layer's cache
+//! budgets) into one driver: hand it a camera each frame and act on the
+//! returned load/unload events.
+
+use std::sync::Arc;
+
+use crate::cache::CacheConfig;
+use crate::err::Result;
+use crate::layer::SceneLayer;
+use crate::node::{Camera, Node, NodeArray};
+use crate::obb::Frustum;
+use crate::prefetch::Prefetcher;
+use crate::sched::ResidencyTracker;
+
+/// Tuning for a [`StreamingSession`].
+#[derive(Debug, Clone, Copy)]
+pub struct StreamingOptions {
+    /// Quality knob forwarded to LOD selection; `1.0` is spec behavior,
+    /// larger is coarser.
+    pub max_screen_error: f64,
+    /// Background threads fetching node pages ahead of the cut.
+    pub prefetch_workers: usize,
+    /// Cache bounds applied to the layer for the session's lifetime.
+    pub cache: CacheConfig,
+}
+
+impl Default for StreamingOptions {
+    fn default() -> Self {
+        Self {
+            max_screen_error: 1.0,
+            prefetch_workers: 2,
+            cache: CacheConfig::default(),
+        }
+    }
+}
+
+/// What changed between the previous frame and this one.
+#[derive(Debug, Default)]
+pub struct FrameUpdate {
+    /// Nodes newly entering the cut, in selection order — load their
+    /// resources and upload.
+    pub load: Vec<Arc<Node>>,
+    /// Indices of nodes that left the cut, ascending — release their
+    /// GPU residency.
+    pub unload: Vec<usize>,
+    /// Size of the full cut this frame, resident nodes included.
+    pub selected: usize,
+}
+
+/// Drives selection, residency and prefetch for one camera/ROI.
+///
+/// One session per viewport; sessions over the same layer share its page
+/// and resource caches. Dropping the session stops the prefetch workers.
+pub struct StreamingSession {
+    nodes: NodeArray,
+    tracker: ResidencyTracker,
+    prefetcher: Prefetcher,
+    options: StreamingOptions,
+}
+
+impl StreamingSession {
+    /// Start a session over `layer`, applying the options' cache bounds.
+    pub fn new(layer: &SceneLayer, options: StreamingOptions) -> Result<Self> {
+        layer.apply_cache_config(options.cache);
+        let nodes = layer.nodes()?;
+        let prefetcher = Prefetcher::new(nodes.clone(), options.prefetch_workers);
+        Ok(Self {
+            nodes,
+            tracker: ResidencyTracker::new(),
+            prefetcher,
+            options,
+        })
+    }
+
+    /// Advance one frame: select for `camera` (culled to `frustum` when
+    /// given), diff against the resident set, and kick off prefetches
+    /// around the newly loaded nodes.
+    pub fn update(&mut self, camera: &Camera, frustum: Option<&Frustum>) -> Result<FrameUpdate> {
+        let selection = match frustum {
+            Some(frustum) => {
+                self.nodes
+                    .select_visible(camera, frustum, self.options.max_screen_error)?
+            }
+            None => self.nodes.select_lod(camera, self.options.max_screen_error)?,
+        };
+        let indices: Vec<usize> = selection.iter().map(|node| node.index).collect();
+        let diff = self.tracker.advance(&indices);
+
+        let mut update = FrameUpdate {
+            unload: diff.release,
+            selected: selection.len(),
+            ..Default::default()
+        };
+        for index in diff.load {
+            if let Some(node) = selection.iter().find(|node| node.index == index) {
+                // Speculate one level ahead of everything entering the
+                // cut, so zooming in finds the children's pages warm.
+                self.prefetcher.prefetch_around(node);
+                update.load.push(Arc::clone(node));
+            }
+        }
+        Ok(update)
+    }
+
+    /// Node pages queued or being fetched ahead of the cut.
+    pub fn pending_prefetches(&self) -> usize {
+        self.prefetcher.pending()
+    }
+
+    /// Block until the prefetch queue is drained; mainly for tests and
+    /// batch consumers that want a warm cache before proceeding.
+    pub fn wait_for_prefetches(&self) {
+        self.prefetcher.wait_idle();
+    }
+
+    /// The node view the session selects from.
+    pub fn nodes(&self) -> &NodeArray {
+        &self.nodes
+    }
+}
+
+#[cfg(all(test, feature = "slpk"))]
+mod tests {
+    use super::*;
+    use crate::testutil::{write_pyramid_layer, TestDir};
+
+    #[test]
+    fn sessions_emit_load_and_unload_events() {
+        let dir = TestDir::new("stream");
+        let path = dir.join("layer.slpk");
+        write_pyramid_layer(&path, true);
+
+        let layer = crate::layer::SceneLayer::open_slpk(&path).unwrap();
+        let mut session = StreamingSession::new(&layer, StreamingOptions::default()).unwrap();
+
+        // Far away: the root alone satisfies the error budget.
+        let far = Camera {
+            position: [0.0, 0.0, 1000.0],
+            fov_y: 1.0,
+            screen_height: 1080.0,
+        };
+        let update = session.update(&far, None).unwrap();
+        let loaded: Vec<usize> = update.load.iter().map(|n| n.index).collect();
+        assert_eq!(loaded, vec![0]);
+        assert!(update.unload.is_empty());
+
+        // Unchanged view: nothing to do.
+        let update = session.update(&far, None).unwrap();
+        assert!(update.load.is_empty());
+        assert!(update.unload.is_empty());
+        assert_eq!(update.selected, 1);
+
+        // Moving close refines into the leaves and releases the root.
+        let near = Camera {
+            position: [0.0, 0.0, 12.0],
+            ..far
+        };
+        let update = session.update(&near, None).unwrap();
+        let loaded: Vec<usize> = update.load.iter().map(|n| n.index).collect();
+        assert_eq!(loaded, vec![1, 2]);
+        assert_eq!(update.unload, vec![0]);
+
+        session.wait_for_prefetches();
+        assert_eq!(session.pending_prefetches(), 0);
+    }
+}
@@ -0,0 +1,160 @@
+//! Shared fixtures for the unit tests.
+//!
+//! Tests that need an SLPK on disk build it here instead of pasting the
+//! same scene-definition JSON into every module, and put it in a
+//! [`TestDir`] so concurrent test runs never collide on a path and failed
+//! runs leave nothing behind.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A scratch directory unique to one test invocation, removed on drop.
+pub(crate) struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    /// Create `i3s-{name}-{pid}-{n}` under the system temp directory.
+    pub(crate) fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "i3s-{name}-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path).expect("create test dir");
+        Self { path }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn join(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.path).ok();
+    }
+}
+
+/// A unit box around the origin, the OBB most fixtures use.
+#[cfg(feature = "slpk")]
+pub(crate) fn unit_obb() -> serde_json::Value {
+    obb([0.0; 3], [1.0; 3])
+}
+
+#[cfg(feature = "slpk")]
+pub(crate) fn obb(center: [f64; 3], half_size: [f64; 3]) -> serde_json::Value {
+    serde_json::json!({
+        "center": center,
+        "halfSize": half_size,
+        "quaternion": [0.0, 0.0, 0.0, 1.0]
+    })
+}
+
+/// One XY triangle as `Float32` position bytes.
+#[cfg(feature = "slpk")]
+pub(crate) fn triangle_bytes() -> Vec<u8> {
+    [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+/// Write an SLPK from a scene definition and node JSON values, splitting
+/// node pages by the definition's `nodesPerPage`, then the given
+/// `(definition, resource, bytes)` geometry blobs.
+#[cfg(feature = "slpk")]
+pub(crate) fn write_test_layer(
+    path: &Path,
+    defn: serde_json::Value,
+    nodes: &[serde_json::Value],
+    geometries: &[(usize, usize, Vec<u8>)],
+) {
+    use crate::slpk::writer::SlpkWriter;
+
+    let per_page = defn["nodePages"]["nodesPerPage"]
+        .as_u64()
+        .expect("definition declares nodesPerPage") as usize;
+    let defn: crate::defn::SceneDefinition = serde_json::from_value(defn).unwrap();
+    let mut writer = SlpkWriter::create(path).unwrap();
+    writer.write_scene_definition(&defn).unwrap();
+    for (page_index, page_nodes) in nodes.chunks(per_page).enumerate() {
+        let page: crate::node::NodePage =
+            serde_json::from_value(serde_json::json!({ "nodes": page_nodes })).unwrap();
+        writer.write_node_page(page_index, &page).unwrap();
+    }
+    for (definition, resource, bytes) in geometries {
+        writer.write_geometry(*definition, *resource, bytes).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+/// Write the tree most tests traverse: a three-level mesh pyramid (root,
+/// two interior nodes, four leaves) split two nodes per page.
+///
+/// With `lod` false only the leaves carry a one-triangle geometry, so
+/// interior nodes always refine. With `lod` true every node has a mesh
+/// and OBBs and thresholds are sized so a far camera keeps the root and
+/// a near one stops at the interior nodes.
+#[cfg(feature = "slpk")]
+pub(crate) fn write_pyramid_layer(path: &Path, lod: bool) {
+    let defn = serde_json::json!({
+        "id": 0,
+        "layerType": "IntegratedMesh",
+        "store": { "profile": "meshpyramids" },
+        "nodePages": {
+            "nodesPerPage": 2,
+            "lodSelectionMetricType": "maxScreenThresholdSQ"
+        },
+        "geometryDefinitions": [{
+            "geometryBuffers": [{
+                "position": { "type": "Float32", "component": 3 }
+            }]
+        }]
+    });
+    let node = |index: usize, parent: Option<usize>, children: Vec<usize>, half: f64| {
+        let mut value = serde_json::json!({
+            "index": index,
+            "obb": obb([0.0; 3], [half; 3]),
+            "children": children
+        });
+        if let Some(parent) = parent {
+            value["parentIndex"] = parent.into();
+        }
+        let meshed = lod || value["children"].as_array().unwrap().is_empty();
+        if meshed {
+            value["mesh"] = serde_json::json!({ "geometry": {
+                "definition": 0, "resource": index, "vertexCount": 3
+            } });
+        }
+        if lod {
+            // The root refines for any nearby camera; its children never do.
+            value["lodThreshold"] = if index == 0 { 1e5 } else { 1e12 }.into();
+        }
+        value
+    };
+    let (root_half, mid_half) = if lod { (10.0, 5.0) } else { (1.0, 1.0) };
+    let nodes = [
+        node(0, None, vec![1, 2], root_half),
+        node(1, Some(0), vec![3, 4], mid_half),
+        node(2, Some(0), vec![5, 6], mid_half),
+        node(3, Some(1), vec![], 1.0),
+        node(4, Some(1), vec![], 1.0),
+        node(5, Some(2), vec![], 1.0),
+        node(6, Some(2), vec![], 1.0),
+    ];
+    let meshed = if lod { 0..=6 } else { 3..=6 };
+    let geometries: Vec<_> = meshed.map(|index| (index, index, triangle_bytes())).collect();
+    write_test_layer(path, defn, &nodes, &geometries);
+}
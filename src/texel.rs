@@ -0,0 +1,315 @@
+//! Texel density and UV overdraw analysis for content QA.
+//!
+//! Walks the nodes that carry both geometry and a base-color texture,
+//! relates triangle areas in UV space (scaled by the texture resolution)
+//! to triangle areas in world space, and estimates how much of the used UV
+//! area is claimed by more than one triangle. Low texel density shows up
+//! on screen as blur; high overlap usually means wasted texture memory or
+//! an atlas packing problem. Dimensions are read from the JPEG/PNG headers
+//! directly, so no image decoder is pulled in.
+
+use crate::decode::{decode_material, TexturePreference};
+use crate::defn::ImageFormat;
+use crate::err::{I3SError, Result};
+use crate::layer::SceneLayer;
+use crate::rm::{Accessor, UriBuilder};
+
+/// Thresholds for flagging nodes in a [`TexelDensityReport`].
+#[derive(Debug, Clone, Copy)]
+pub struct TexelDensityOptions {
+    /// Nodes whose density falls below this many texels per world unit are
+    /// reported as blurry.
+    pub blurry_below: f64,
+    /// Nodes where more than this fraction of the used UV area is covered
+    /// by several triangles are reported as wasteful.
+    pub wasteful_overlap_above: f64,
+    /// Edge length of the coverage grid used for the overlap estimate.
+    pub overlap_grid: usize,
+}
+
+impl Default for TexelDensityOptions {
+    fn default() -> Self {
+        Self {
+            blurry_below: 16.0,
+            wasteful_overlap_above: 0.25,
+            overlap_grid: 64,
+        }
+    }
+}
+
+/// Per-node measurements of one analysis pass.
+#[derive(Debug, Clone)]
+pub struct NodeTexelStats {
+    pub node_index: usize,
+    /// Area-weighted texels per world unit (the square root of texel area
+    /// over world area, so it compares to on-screen sampling rates).
+    pub texels_per_unit: f64,
+    /// Fraction of the used UV area covered by more than one triangle.
+    pub uv_overlap: f64,
+    /// Decoded texture dimensions in pixels.
+    pub texture_size: (u32, u32),
+    pub triangles: usize,
+}
+
+/// What [`analyze_texel_density`] measured.
+#[derive(Debug, Clone, Default)]
+pub struct TexelDensityReport {
+    /// One entry per analyzed node, in traversal order.
+    pub stats: Vec<NodeTexelStats>,
+    /// Node indices flagged as blurry (density below the threshold).
+    pub blurry: Vec<usize>,
+    /// Node indices flagged as wasteful (overlap above the threshold).
+    pub wasteful: Vec<usize>,
+    /// Nodes skipped because their texture format carries no readable
+    /// dimensions (compressed formats) or the resource was absent.
+    pub skipped: usize,
+}
+
+/// Measure texel density and UV overlap for every textured node.
+pub fn analyze_texel_density(
+    layer: &SceneLayer,
+    options: &TexelDensityOptions,
+) -> Result<TexelDensityReport> {
+    if options.overlap_grid == 0 {
+        return Err(I3SError::Validation(
+            "overlap grid needs at least one cell".to_string(),
+        ));
+    }
+    let nodes = layer.nodes()?;
+    let mut indices = Vec::new();
+    nodes.traverse(|node| {
+        indices.push(node.index);
+        crate::node::TraversalControl::Continue
+    })?;
+
+    let mut report = TexelDensityReport::default();
+    let defn = layer.definition();
+    let rm = layer.resource_manager();
+    for index in indices {
+        let node = nodes.get(index)?;
+        let Some(mesh) = &node.mesh else { continue };
+        let (Some(material), Some(_)) = (&mesh.material, &mesh.geometry) else {
+            continue;
+        };
+        let Some(definition) = defn.material_definitions.get(material.definition) else {
+            continue;
+        };
+        let texture_set = definition
+            .pbr_metallic_roughness
+            .as_ref()
+            .and_then(|pbr| pbr.base_color_texture.as_ref())
+            .and_then(|t| defn.texture_set_definitions.get(t.texture_set_definition_id));
+        let Ok(decoded) = decode_material(definition, texture_set, &TexturePreference::default())
+        else {
+            report.skipped += 1;
+            continue;
+        };
+        let Some(texture) = &decoded.texture else {
+            continue;
+        };
+        let bytes = match rm.get(&rm.texture_uri(material.resource, &texture.name, texture.format))
+        {
+            Ok(bytes) => bytes,
+            Err(I3SError::MissingResource(_)) => {
+                report.skipped += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let Some(size) = image_dimensions(texture.format, &bytes) else {
+            report.skipped += 1;
+            continue;
+        };
+        let Some(geometry) = layer.node_geometry(&node)? else {
+            continue;
+        };
+        if geometry.uvs.is_empty() || geometry.positions.len() < 9 {
+            continue;
+        }
+        let Some(stats) = measure(&node, &geometry, size, options) else {
+            continue;
+        };
+        if stats.texels_per_unit < options.blurry_below {
+            report.blurry.push(stats.node_index);
+        }
+        if stats.uv_overlap > options.wasteful_overlap_above {
+            report.wasteful.push(stats.node_index);
+        }
+        report.stats.push(stats);
+    }
+    Ok(report)
+}
+
+fn measure(
+    node: &crate::node::Node,
+    geometry: &crate::decode::DecodedGeometry,
+    texture_size: (u32, u32),
+    options: &TexelDensityOptions,
+) -> Option<NodeTexelStats> {
+    let triangle_count = geometry.positions.len() / 9;
+    let (width, height) = (texture_size.0 as f64, texture_size.1 as f64);
+    let mut world_area = 0.0;
+    let mut texel_area = 0.0;
+    let mut coverage = vec![0u16; options.overlap_grid * options.overlap_grid];
+    for t in 0..triangle_count {
+        let p = |corner: usize| {
+            let base = (t * 3 + corner) * 3;
+            [
+                geometry.positions[base] as f64,
+                geometry.positions[base + 1] as f64,
+                geometry.positions[base + 2] as f64,
+            ]
+        };
+        let uv = |corner: usize| {
+            let base = (t * 3 + corner) * 2;
+            [
+                geometry.uvs.get(base).copied().unwrap_or(0.0) as f64,
+                geometry.uvs.get(base + 1).copied().unwrap_or(0.0) as f64,
+            ]
+        };
+        world_area += triangle_area_3d(p(0), p(1), p(2));
+        let (a, b, c) = (uv(0), uv(1), uv(2));
+        texel_area += triangle_area_2d(a, b, c) * width * height;
+        rasterize(&mut coverage, options.overlap_grid, a, b, c);
+    }
+    if world_area <= 0.0 {
+        return None;
+    }
+    let covered = coverage.iter().filter(|&&cells| cells > 0).count();
+    let overlapped = coverage.iter().filter(|&&cells| cells > 1).count();
+    Some(NodeTexelStats {
+        node_index: node.index,
+        texels_per_unit: (texel_area / world_area).sqrt(),
+        uv_overlap: if covered > 0 {
+            overlapped as f64 / covered as f64
+        } else {
+            0.0
+        },
+        texture_size,
+        triangles: triangle_count,
+    })
+}
+
+fn triangle_area_3d(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> f64 {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let cross = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt()
+}
+
+fn triangle_area_2d(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    0.5 * ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs()
+}
+
+/// Count triangle coverage on a unit-square grid by testing cell centers.
+fn rasterize(coverage: &mut [u16], grid: usize, a: [f64; 2], b: [f64; 2], c: [f64; 2]) {
+    let min_x = a[0].min(b[0]).min(c[0]).clamp(0.0, 1.0);
+    let max_x = a[0].max(b[0]).max(c[0]).clamp(0.0, 1.0);
+    let min_y = a[1].min(b[1]).min(c[1]).clamp(0.0, 1.0);
+    let max_y = a[1].max(b[1]).max(c[1]).clamp(0.0, 1.0);
+    let cell = |v: f64| ((v * grid as f64) as usize).min(grid - 1);
+    for y in cell(min_y)..=cell(max_y) {
+        for x in cell(min_x)..=cell(max_x) {
+            let center = [
+                (x as f64 + 0.5) / grid as f64,
+                (y as f64 + 0.5) / grid as f64,
+            ];
+            if point_in_triangle(center, a, b, c) {
+                let count = &mut coverage[y * grid + x];
+                *count = count.saturating_add(1);
+            }
+        }
+    }
+}
+
+fn point_in_triangle(p: [f64; 2], a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> bool {
+    let sign = |a: [f64; 2], b: [f64; 2], p: [f64; 2]| {
+        (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+    };
+    let (d1, d2, d3) = (sign(a, b, p), sign(b, c, p), sign(c, a, p));
+    let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_neg && has_pos)
+}
+
+/// Read the pixel dimensions from a JPEG or PNG header. `None` for the
+/// compressed GPU formats, whose containers this pass does not parse.
+pub fn image_dimensions(format: ImageFormat, bytes: &[u8]) -> Option<(u32, u32)> {
+    match format {
+        ImageFormat::Png => {
+            // Signature (8) + IHDR length/type (8), then width/height.
+            if bytes.len() < 24 || &bytes[..8] != b"\x89PNG\r\n\x1a\n" || &bytes[12..16] != b"IHDR"
+            {
+                return None;
+            }
+            let width = u32::from_be_bytes(bytes[16..20].try_into().ok()?);
+            let height = u32::from_be_bytes(bytes[20..24].try_into().ok()?);
+            Some((width, height))
+        }
+        ImageFormat::Jpg => {
+            if bytes.len() < 4 || bytes[0] != 0xFF || bytes[1] != 0xD8 {
+                return None;
+            }
+            // Walk the marker segments until a start-of-frame.
+            let mut offset = 2;
+            while offset + 4 <= bytes.len() {
+                if bytes[offset] != 0xFF {
+                    return None;
+                }
+                let marker = bytes[offset + 1];
+                if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+                    if offset + 9 > bytes.len() {
+                        return None;
+                    }
+                    let height = u16::from_be_bytes([bytes[offset + 5], bytes[offset + 6]]);
+                    let width = u16::from_be_bytes([bytes[offset + 7], bytes[offset + 8]]);
+                    return Some((u32::from(width), u32::from(height)));
+                }
+                let length = u16::from_be_bytes([bytes[offset + 2], bytes[offset + 3]]) as usize;
+                offset += 2 + length;
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_and_jpeg_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(&13u32.to_be_bytes());
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&256u32.to_be_bytes());
+        png.extend_from_slice(&128u32.to_be_bytes());
+        assert_eq!(image_dimensions(ImageFormat::Png, &png), Some((256, 128)));
+
+        // SOI, then a minimal SOF0 segment.
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xC0, 0x00, 0x0B, 0x08, 0x00, 0x40, 0x00, 0x20, 0x01, 0x00, 0x00,
+            0x00,
+        ];
+        assert_eq!(image_dimensions(ImageFormat::Jpg, &jpeg), Some((32, 64)));
+        assert_eq!(image_dimensions(ImageFormat::Dds, &png), None);
+    }
+
+    #[test]
+    fn overlap_grid_counts_double_coverage() {
+        let mut coverage = vec![0u16; 16 * 16];
+        // The same triangle twice: everything covered is covered twice.
+        let (a, b, c) = ([0.0, 0.0], [1.0, 0.0], [0.0, 1.0]);
+        rasterize(&mut coverage, 16, a, b, c);
+        rasterize(&mut coverage, 16, a, b, c);
+        let covered = coverage.iter().filter(|&&n| n > 0).count();
+        let overlapped = coverage.iter().filter(|&&n| n > 1).count();
+        assert!(covered > 0);
+        assert_eq!(covered, overlapped);
+    }
+}
@@ -0,0 +1,665 @@
+//! Spec-conformance validation.
+//!
+//! [`SceneLayerValidator`] checks an open layer against the I3S 1.8 / OGC
+//! SceneLayers spec and reports structured [`Finding`]s instead of failing
+//! on the first problem: definition-level issues (missing node pages,
+//! malformed texture names), node page consistency (indices, parent/child
+//! back-links), bounding-volume sanity, and — because a well-formed
+//! definition can still reference truncated data — declared geometry buffer
+//! layouts against the actual resource sizes.
+//! [`SceneLayerValidator::validate_deep`] goes further and fetches and
+//! decodes every geometry across a worker pool, which is what makes
+//! validating multi-gigabyte packages practical.
+
+use crate::decode::attribute_len;
+use crate::defn::GeometryBuffer;
+use crate::err::{I3SError, Result};
+use crate::layer::SceneLayer;
+use crate::node::Node;
+use crate::rm::{Accessor, UriBuilder};
+
+/// How bad a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// A spec violation; readers may fail or render wrongly.
+    Error,
+    /// Suspicious but readable.
+    Warning,
+}
+
+/// One validation finding.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Stable machine-readable code, e.g. `"node-page/bad-index"`.
+    pub code: &'static str,
+    /// Where the problem is (`"layer"`, `"node 17"`, ...).
+    pub location: String,
+    pub message: String,
+}
+
+/// Result of a validation run.
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+    pub nodes_checked: usize,
+}
+
+impl ValidationReport {
+    /// Whether no errors were found (warnings allowed).
+    pub fn is_conformant(&self) -> bool {
+        self.findings.iter().all(|f| f.severity != Severity::Error)
+    }
+
+    /// Only the error-severity findings.
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+    }
+}
+
+/// A callback reporting deep-validation progress as `(pages_done,
+/// pages_total)`.
+pub type ValidationProgress = Box<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Tuning for [`SceneLayerValidator::validate_deep`].
+pub struct DeepValidationOptions {
+    /// Worker threads fetching and decoding resources.
+    pub workers: usize,
+    /// Invoked after each completed node page, from worker threads.
+    pub progress: Option<ValidationProgress>,
+}
+
+impl Default for DeepValidationOptions {
+    fn default() -> Self {
+        Self {
+            workers: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4)
+                .min(8),
+            progress: None,
+        }
+    }
+}
+
+/// Validates a layer against the spec.
+#[derive(Debug, Clone, Default)]
+pub struct SceneLayerValidator {
+    /// Also fetch every geometry resource and compare its size against the
+    /// declared buffer layout. Costs one download per node.
+    pub check_buffer_sizes: bool,
+}
+
+impl SceneLayerValidator {
+    /// A validator with all cheap checks; buffer-size checking is opt-in
+    /// via [`check_buffer_sizes`](Self::check_buffer_sizes).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run all checks and collect findings.
+    pub fn validate(&self, layer: &SceneLayer) -> Result<ValidationReport> {
+        let mut report = ValidationReport::default();
+        self.check_definition(layer, &mut report);
+        let mut nodes = layer.nodes()?;
+        let indices = self.check_structure(&mut nodes, &mut report)?;
+        for &index in &indices {
+            let node = nodes.get(index)?;
+            self.check_node_resources(
+                layer,
+                &node,
+                self.check_buffer_sizes,
+                false,
+                &mut report.findings,
+            )?;
+        }
+        Ok(report)
+    }
+
+    /// Run all checks, additionally fetching and decoding every geometry
+    /// resource across a worker pool.
+    ///
+    /// Workers steal node pages from a shared queue, so one oversized page
+    /// does not serialize the run; findings come back in page order
+    /// regardless of which worker produced them.
+    pub fn validate_deep(
+        &self,
+        layer: &SceneLayer,
+        options: &DeepValidationOptions,
+    ) -> Result<ValidationReport> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Mutex;
+
+        let mut report = ValidationReport::default();
+        self.check_definition(layer, &mut report);
+        let mut nodes = layer.nodes()?;
+        let indices = self.check_structure(&mut nodes, &mut report)?;
+
+        // Bucket nodes by page so workers claim page-sized units of work.
+        let page_defn = nodes.page_definition().clone();
+        let mut pages: std::collections::BTreeMap<usize, Vec<usize>> =
+            std::collections::BTreeMap::new();
+        for &index in &indices {
+            pages
+                .entry(page_defn.page_index(index).unwrap_or(0))
+                .or_default()
+                .push(index);
+        }
+        let buckets: Vec<Vec<usize>> = pages.into_values().collect();
+        let total = buckets.len();
+        let workers = options.workers.max(1).min(total.max(1));
+
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let results: Mutex<Vec<(usize, Vec<Finding>)>> = Mutex::new(Vec::with_capacity(total));
+        let failure: Mutex<Option<I3SError>> = Mutex::new(None);
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    let fail = |e: I3SError| {
+                        failure
+                            .lock()
+                            .expect("validation lock poisoned")
+                            .get_or_insert(e);
+                    };
+                    let nodes = match layer.nodes() {
+                        Ok(nodes) => nodes,
+                        Err(e) => return fail(e),
+                    };
+                    loop {
+                        let bucket = next.fetch_add(1, Ordering::Relaxed);
+                        if bucket >= total {
+                            return;
+                        }
+                        let mut findings = Vec::new();
+                        for &index in &buckets[bucket] {
+                            let node = match nodes.get(index) {
+                                Ok(node) => node,
+                                Err(e) => return fail(e),
+                            };
+                            if let Err(e) =
+                                self.check_node_resources(layer, &node, true, true, &mut findings)
+                            {
+                                return fail(e);
+                            }
+                        }
+                        results
+                            .lock()
+                            .expect("validation lock poisoned")
+                            .push((bucket, findings));
+                        let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+                        if let Some(progress) = &options.progress {
+                            progress(finished, total);
+                        }
+                    }
+                });
+            }
+        });
+
+        if let Some(e) = failure.into_inner().expect("validation lock poisoned") {
+            return Err(e);
+        }
+        let mut results = results.into_inner().expect("validation lock poisoned");
+        results.sort_by_key(|(bucket, _)| *bucket);
+        for (_, findings) in results {
+            report.findings.extend(findings);
+        }
+        Ok(report)
+    }
+
+    fn check_definition(&self, layer: &SceneLayer, report: &mut ValidationReport) {
+        let defn = layer.definition();
+        if defn.node_pages.is_none() {
+            report.findings.push(Finding {
+                severity: Severity::Error,
+                code: "layer/missing-node-pages",
+                location: "layer".to_string(),
+                message: "layer has no nodePages definition".to_string(),
+            });
+        }
+        for (i, set) in defn.texture_set_definitions.iter().enumerate() {
+            if set.formats.is_empty() {
+                report.findings.push(Finding {
+                    severity: Severity::Error,
+                    code: "texture-set/empty",
+                    location: format!("textureSetDefinitions[{i}]"),
+                    message: "texture set declares no formats".to_string(),
+                });
+            }
+            for format in &set.formats {
+                if let Err(e) = format.validate() {
+                    report.findings.push(Finding {
+                        severity: Severity::Error,
+                        code: "texture-set/bad-name",
+                        location: format!("textureSetDefinitions[{i}]"),
+                        message: e.to_string(),
+                    });
+                }
+            }
+        }
+        for (i, definition) in defn.geometry_definitions.iter().enumerate() {
+            if definition.geometry_buffers.is_empty() {
+                report.findings.push(Finding {
+                    severity: Severity::Error,
+                    code: "geometry-definition/empty",
+                    location: format!("geometryDefinitions[{i}]"),
+                    message: "geometry definition declares no buffers".to_string(),
+                });
+            } else if definition.geometry_buffers[0].position.is_none() {
+                report.findings.push(Finding {
+                    severity: Severity::Error,
+                    code: "geometry-definition/no-position",
+                    location: format!("geometryDefinitions[{i}]"),
+                    message: "buffer 0 has no position attribute".to_string(),
+                });
+            }
+            for (b, buffer) in definition.geometry_buffers.iter().enumerate() {
+                let Some(compressed) = &buffer.compressed_attributes else {
+                    continue;
+                };
+                if compressed.encoding != "draco" {
+                    report.findings.push(Finding {
+                        severity: Severity::Warning,
+                        code: "geometry-definition/unknown-encoding",
+                        location: format!("geometryDefinitions[{i}].geometryBuffers[{b}]"),
+                        message: format!(
+                            "encoding {:?} is not in the spec; decoding it needs a \
+                             registered geometry codec",
+                            compressed.encoding
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Structural node page checks: indices, back-links, bounding volumes.
+    /// Returns the visited node indices for the resource passes.
+    fn check_structure(
+        &self,
+        nodes: &mut crate::node::NodeArray,
+        report: &mut ValidationReport,
+    ) -> Result<Vec<usize>> {
+        let page_defn = nodes.page_definition().clone();
+
+        let mut indices = Vec::new();
+        nodes.traverse(|node| {
+            indices.push(node.index);
+            crate::node::TraversalControl::Continue
+        })?;
+        report.nodes_checked = indices.len();
+
+        for &index in &indices {
+            let node = nodes.get(index)?;
+            let location = format!("node {index}");
+
+            if node.index != index {
+                report.findings.push(Finding {
+                    severity: Severity::Error,
+                    code: "node-page/bad-index",
+                    location: location.clone(),
+                    message: format!("node stored at index {index} declares index {}", node.index),
+                });
+            }
+            if page_defn.page_index(index).is_err() {
+                report.findings.push(Finding {
+                    severity: Severity::Error,
+                    code: "node-page/index-overflow",
+                    location: location.clone(),
+                    message: "node index does not map to a page".to_string(),
+                });
+            }
+            self.check_obb(&node, &location, report);
+
+            for &child in &node.children {
+                let child = nodes.get(child)?;
+                if child.parent_index != Some(node.index) {
+                    report.findings.push(Finding {
+                        severity: Severity::Error,
+                        code: "node-page/broken-back-link",
+                        location: location.clone(),
+                        message: format!("child {} does not link back to its parent", child.index),
+                    });
+                }
+            }
+        }
+        Ok(indices)
+    }
+
+    /// Resource checks of one node: presence (or, with `fetch`, size
+    /// against the declared layout; with `decode`, a full decode).
+    fn check_node_resources(
+        &self,
+        layer: &SceneLayer,
+        node: &Node,
+        fetch: bool,
+        decode: bool,
+        findings: &mut Vec<Finding>,
+    ) -> Result<()> {
+        let defn = layer.definition();
+        let location = format!("node {}", node.index);
+        let Some(geometry) = node.mesh.as_ref().and_then(|m| m.geometry.as_ref()) else {
+            return Ok(());
+        };
+        let Some(definition) = defn.geometry_definitions.get(geometry.definition) else {
+            findings.push(Finding {
+                severity: Severity::Error,
+                code: "node/bad-geometry-definition",
+                location,
+                message: format!("geometry definition {} out of range", geometry.definition),
+            });
+            return Ok(());
+        };
+        let rm = layer.resource_manager();
+        let uri = rm.geometry_uri(node.index, geometry.resource);
+        if !fetch {
+            // Presence only: a size probe avoids the download.
+            match rm.size(&uri) {
+                Ok(_) => {}
+                Err(I3SError::MissingResource(_)) => findings.push(Finding {
+                    severity: Severity::Error,
+                    code: "node/missing-geometry",
+                    location,
+                    message: format!("geometry resource {uri} is missing"),
+                }),
+                Err(e) => return Err(e),
+            }
+            return Ok(());
+        }
+        let bytes = match rm.get(&uri) {
+            Ok(bytes) => bytes,
+            Err(I3SError::MissingResource(_)) => {
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    code: "node/missing-geometry",
+                    location,
+                    message: format!("geometry resource {uri} is missing"),
+                });
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        if let Some(buffer) = definition.geometry_buffers.first() {
+            self.check_buffer_size(
+                buffer,
+                &bytes,
+                geometry.vertex_count,
+                geometry.feature_count.unwrap_or(0),
+                &location,
+                findings,
+            );
+        }
+        if decode {
+            let decoder = crate::decode::ResourceDecoder::new(defn.store.profile);
+            if let Err(e) = decoder.decode_geometry(
+                &bytes,
+                definition,
+                geometry.vertex_count,
+                geometry.feature_count.unwrap_or(0),
+            ) {
+                findings.push(Finding {
+                    severity: Severity::Error,
+                    code: "node/undecodable-geometry",
+                    location,
+                    message: e.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn check_obb(&self, node: &Node, location: &str, report: &mut ValidationReport) {
+        let obb = &node.obb;
+        let finite = obb.center.iter().all(|v| v.is_finite())
+            && obb.half_size.iter().all(|v| v.is_finite())
+            && obb.quaternion.iter().all(|v| v.is_finite());
+        if !finite {
+            report.findings.push(Finding {
+                severity: Severity::Error,
+                code: "obb/non-finite",
+                location: location.to_string(),
+                message: "bounding volume has non-finite components".to_string(),
+            });
+            return;
+        }
+        if obb.half_size.iter().any(|&v| v < 0.0) {
+            report.findings.push(Finding {
+                severity: Severity::Error,
+                code: "obb/negative-half-size",
+                location: location.to_string(),
+                message: format!("half size {:?} has a negative extent", obb.half_size),
+            });
+        }
+        let norm = obb.quaternion.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if (norm - 1.0).abs() > 1e-3 {
+            report.findings.push(Finding {
+                severity: Severity::Warning,
+                code: "obb/unnormalized-quaternion",
+                location: location.to_string(),
+                message: format!("quaternion norm is {norm:.6}, expected 1"),
+            });
+        }
+    }
+
+    fn check_buffer_size(
+        &self,
+        buffer: &GeometryBuffer,
+        bytes: &[u8],
+        vertex_count: usize,
+        feature_count: usize,
+        location: &str,
+        findings: &mut Vec<Finding>,
+    ) {
+        let mut expected = buffer.offset.unwrap_or(0);
+        let per_vertex = [
+            &buffer.position,
+            &buffer.normal,
+            &buffer.uv0,
+            &buffer.color,
+            &buffer.uv_region,
+        ];
+        let per_feature = [&buffer.feature_id, &buffer.face_range];
+        for attr in per_vertex.into_iter().flatten() {
+            match attribute_len(attr, vertex_count) {
+                Ok(len) => expected += len,
+                Err(e) => {
+                    findings.push(Finding {
+                        severity: Severity::Error,
+                        code: "geometry-buffer/bad-declaration",
+                        location: location.to_string(),
+                        message: e.to_string(),
+                    });
+                    return;
+                }
+            }
+        }
+        for attr in per_feature.into_iter().flatten() {
+            match attribute_len(attr, feature_count) {
+                Ok(len) => expected += len,
+                Err(e) => {
+                    findings.push(Finding {
+                        severity: Severity::Error,
+                        code: "geometry-buffer/bad-declaration",
+                        location: location.to_string(),
+                        message: e.to_string(),
+                    });
+                    return;
+                }
+            }
+        }
+        if bytes.len() < expected {
+            findings.push(Finding {
+                severity: Severity::Error,
+                code: "geometry-buffer/truncated",
+                location: location.to_string(),
+                message: format!(
+                    "declared layout needs {expected} bytes, resource has {}",
+                    bytes.len()
+                ),
+            });
+        } else if bytes.len() > expected {
+            findings.push(Finding {
+                severity: Severity::Warning,
+                code: "geometry-buffer/trailing-bytes",
+                location: location.to_string(),
+                message: format!(
+                    "resource has {} bytes beyond the declared layout",
+                    bytes.len() - expected
+                ),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn flags_truncated_geometry_and_bad_texture_names() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("validate");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 64 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }],
+            "textureSetDefinitions": [{
+                "formats": [{ "name": "not a name", "format": "jpg" }]
+            }]
+        }))
+        .unwrap();
+        let page: crate::node::NodePage = serde_json::from_value(serde_json::json!({
+            "nodes": [{
+                "index": 0,
+                "obb": {
+                    "center": [0.0, 0.0, 0.0],
+                    "halfSize": [1.0, 1.0, 1.0],
+                    "quaternion": [0.0, 0.0, 0.0, 1.0]
+                },
+                "mesh": {
+                    "geometry": { "definition": 0, "resource": 0, "vertexCount": 3 }
+                }
+            }]
+        }))
+        .unwrap();
+
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        writer.write_node_page(0, &page).unwrap();
+        // 3 vertices declared, bytes for only 2.
+        writer.write_geometry(0, 0, &[0u8; 24]).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(path.to_str().unwrap()).unwrap();
+        let report = SceneLayerValidator {
+            check_buffer_sizes: true,
+        }
+        .validate(&layer)
+        .unwrap();
+
+        assert!(!report.is_conformant());
+        let codes: Vec<&str> = report.findings.iter().map(|f| f.code).collect();
+        assert!(codes.contains(&"texture-set/bad-name"));
+        assert!(codes.contains(&"geometry-buffer/truncated"));
+        assert_eq!(report.nodes_checked, 1);
+    }
+
+    #[cfg(feature = "slpk")]
+    #[test]
+    fn deep_validation_runs_pages_across_workers() {
+        use crate::slpk::writer::SlpkWriter;
+
+        let dir = crate::testutil::TestDir::new("validate-deep");
+        let path = dir.join("layer.slpk");
+
+        let defn: crate::defn::SceneDefinition = serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "layerType": "IntegratedMesh",
+            "store": { "profile": "meshpyramids" },
+            "nodePages": { "nodesPerPage": 1 },
+            "geometryDefinitions": [{
+                "geometryBuffers": [{
+                    "position": { "type": "Float32", "component": 3 }
+                }]
+            }]
+        }))
+        .unwrap();
+        let obb = serde_json::json!({
+            "center": [0.0, 0.0, 0.0],
+            "halfSize": [1.0, 1.0, 1.0],
+            "quaternion": [0.0, 0.0, 0.0, 1.0]
+        });
+        let pages = [
+            serde_json::json!({ "nodes": [
+                { "index": 0, "obb": obb, "children": [1, 2] }
+            ]}),
+            serde_json::json!({ "nodes": [{
+                "index": 1, "obb": obb, "parentIndex": 0,
+                "mesh": { "geometry": { "definition": 0, "resource": 1, "vertexCount": 3 } }
+            }]}),
+            serde_json::json!({ "nodes": [{
+                "index": 2, "obb": obb, "parentIndex": 0,
+                "mesh": { "geometry": { "definition": 0, "resource": 2, "vertexCount": 3 } }
+            }]}),
+        ];
+
+        let mut writer = SlpkWriter::create(&path).unwrap();
+        writer.write_scene_definition(&defn).unwrap();
+        for (index, page) in pages.iter().enumerate() {
+            let page: crate::node::NodePage = serde_json::from_value(page.clone()).unwrap();
+            writer.write_node_page(index, &page).unwrap();
+        }
+        writer.write_geometry(1, 1, &[0u8; 36]).unwrap();
+        // 3 vertices declared, bytes for only 2.
+        writer.write_geometry(2, 2, &[0u8; 24]).unwrap();
+        writer.finish().unwrap();
+
+        let layer = crate::layer::SceneLayer::from_uri(path.to_str().unwrap()).unwrap();
+        let progress = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&progress);
+        let options = DeepValidationOptions {
+            workers: 2,
+            progress: Some(Box::new(move |done, total| {
+                seen.lock().unwrap().push((done, total));
+            })),
+        };
+        let report = SceneLayerValidator::new()
+            .validate_deep(&layer, &options)
+            .unwrap();
+
+        assert_eq!(report.nodes_checked, 3);
+        let codes: Vec<&str> = report.findings.iter().map(|f| f.code).collect();
+        assert!(codes.contains(&"geometry-buffer/truncated"));
+        assert!(!report.is_conformant());
+
+        let mut calls = progress.lock().unwrap().clone();
+        calls.sort_unstable();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[2], (3, 3));
+        assert!(calls.iter().all(|&(_, total)| total == 3));
+    }
+
+    #[test]
+    fn texture_name_convention() {
+        let good: crate::defn::TextureFormat =
+            serde_json::from_value(serde_json::json!({ "name": "0_0_1", "format": "jpg" }))
+                .unwrap();
+        assert!(good.validate().is_ok());
+        let bad: crate::defn::TextureFormat =
+            serde_json::from_value(serde_json::json!({ "name": "tex__1", "format": "jpg" }))
+                .unwrap();
+        assert!(bad.validate().is_err());
+    }
+}